@@ -31,6 +31,7 @@ pub mod hh;
 pub use configurable::*;
 pub mod output_selection;
 pub mod serde_helpers;
+pub mod source_unit;
 pub mod sourcemap;
 pub mod sources;
 use crate::output_selection::{ContractOutputSelection, OutputSelection};
@@ -42,6 +43,7 @@ use foundry_compilers_core::{
     },
 };
 pub use serde_helpers::{deserialize_bytes, deserialize_opt_bytes};
+pub use source_unit::SolcVfs;
 pub use sources::*;
 
 /// Solidity files are made up of multiple `source units`, a solidity contract is such a `source
@@ -172,6 +174,20 @@ impl SolcInput {
         self.settings.strip_prefix(base);
     }
 
+    /// Renames all source files to the source unit names solc's virtual file system assigns to
+    /// them, and converts the paths in the settings accordingly.
+    ///
+    /// This is an alternative to [`Self::strip_prefix`] which matches the names of a solc
+    /// invocation with the same `--base-path` and `--include-path`s, see [`SolcVfs`].
+    pub fn apply_vfs(&mut self, vfs: &SolcVfs) {
+        self.sources = std::mem::take(&mut self.sources)
+            .into_iter()
+            .map(|(path, s)| (PathBuf::from(vfs.source_unit_name(&path)), s))
+            .collect();
+
+        self.settings.apply_vfs(vfs);
+    }
+
     /// The flag indicating whether the current [SolcInput] is
     /// constructed for the yul sources
     pub fn is_yul(&self) -> bool {
@@ -541,6 +557,44 @@ impl Settings {
         self.strip_prefix(base);
         self
     }
+
+    /// Converts all paths to source unit names, see [`SolcInput::apply_vfs`].
+    pub fn apply_vfs(&mut self, vfs: &SolcVfs) {
+        let name = |file: &str| {
+            if Path::new(file).is_absolute() {
+                vfs.source_unit_name(Path::new(file))
+            } else {
+                file.to_string()
+            }
+        };
+
+        self.remappings = self.remappings.iter().map(|r| vfs.remapping(r)).collect();
+
+        self.libraries.libs = std::mem::take(&mut self.libraries.libs)
+            .into_iter()
+            .map(|(file, libs)| (PathBuf::from(name(&file.to_string_lossy())), libs))
+            .collect();
+
+        self.output_selection = OutputSelection(
+            std::mem::take(&mut self.output_selection.0)
+                .into_iter()
+                .map(|(file, selection)| (name(&file), selection))
+                .collect(),
+        );
+
+        if let Some(model_checker) = self.model_checker.as_mut() {
+            model_checker.contracts = std::mem::take(&mut model_checker.contracts)
+                .into_iter()
+                .map(|(file, contracts)| (name(&file), contracts))
+                .collect();
+        }
+    }
+
+    /// Converts all paths to source unit names, see [`SolcInput::apply_vfs`].
+    pub fn with_vfs(mut self, vfs: &SolcVfs) -> Self {
+        self.apply_vfs(vfs);
+        self
+    }
 }
 
 impl Default for Settings {
@@ -1,9 +1,13 @@
 use super::Remapping;
-use foundry_compilers_core::utils;
+use foundry_compilers_core::{
+    utils,
+    vfs::{DirEntry, FileKind, FileSystem, OsFileSystem},
+};
 use rayon::prelude::*;
+use semver::Version;
 use std::{
+    cmp::Reverse,
     collections::{BTreeMap, HashSet, btree_map::Entry},
-    fs::FileType,
     path::{Path, PathBuf},
     sync::Mutex,
 };
@@ -12,6 +16,7 @@ const DAPPTOOLS_CONTRACTS_DIR: &str = "src";
 const DAPPTOOLS_LIB_DIR: &str = "lib";
 const JS_CONTRACTS_DIR: &str = "contracts";
 const JS_LIB_DIR: &str = "node_modules";
+const PNPM_STORE_DIR: &str = ".pnpm";
 
 impl Remapping {
     /// Attempts to autodetect all remappings given a certain root path.
@@ -55,68 +60,230 @@ impl Remapping {
     /// which would be multiple rededications according to our rules ("governance", "protocol-v2"),
     /// are unified into `@aave` by looking at their common ancestor, the root of this subdirectory
     /// (`@aave`)
+    ///
+    /// Dependencies vendored into version-suffixed folders, like `forge-std-1.9.2/` or
+    /// `@openzeppelin-contracts@5.0.2/`, are remapped without the version: `forge-std/` and
+    /// `@openzeppelin-contracts/`. If multiple folders share a name once their version is stripped,
+    /// the top-level folder with the newest version gets the global remapping. Every other
+    /// top-level folder gets a remapping for the same name in its own context, so that imports
+    /// within the folder resolve to itself, and nested folders like `solmate-6.2.0/lib/forge-std/`
+    /// get one in the context of the dependency containing them, `solmate-6.2.0/`.
+    ///
+    /// If the directory is a `node_modules` folder installed by pnpm, the packages in the
+    /// `.pnpm` store are scanned as well. Dependencies of a store package which resolve to a
+    /// different version than the top-level package of the same name get a remapping in the context
+    /// of the depending package.
     #[instrument(level = "trace", name = "Remapping::find_many")]
     pub fn find_many(dir: &Path) -> Vec<Self> {
-        /// prioritize
-        ///   - ("a", "1/2") over ("a", "1/2/3")
-        ///   - if a path ends with `src`
-        fn insert_prioritized(
-            mappings: &mut BTreeMap<String, PathBuf>,
-            key: String,
-            path: PathBuf,
-        ) {
-            match mappings.entry(key) {
-                Entry::Occupied(mut e) => {
-                    if e.get().components().count() > path.components().count()
-                        || (path.ends_with(DAPPTOOLS_CONTRACTS_DIR)
-                            && !e.get().ends_with(DAPPTOOLS_CONTRACTS_DIR))
-                    {
-                        e.insert(path);
-                    }
-                }
-                Entry::Vacant(e) => {
-                    e.insert(path);
+        Self::find_many_in(&OsFileSystem, dir)
+    }
+
+    /// Same as [`Self::find_many`], but scans the given [`FileSystem`].
+    #[instrument(level = "trace", name = "Remapping::find_many_in", skip(fs))]
+    pub fn find_many_in(fs: &dyn FileSystem, dir: &Path) -> Vec<Self> {
+        let mut remappings = find_many_filtered(fs, dir, &|_| true);
+        if fs.is_dir(&dir.join(PNPM_STORE_DIR)) {
+            let pnpm_remappings = find_pnpm_remappings(fs, dir, &remappings);
+            remappings.extend(pnpm_remappings);
+            // context-specific remappings have to come before the global ones, because the first
+            // matching remapping is applied
+            remappings.sort_by(|a, b| {
+                (&a.name, a.context.is_none()).cmp(&(&b.name, b.context.is_none()))
+            });
+        }
+        remappings
+    }
+}
+
+/// Finds the remappings of the subdirectories of `dir` that match the `filter`.
+fn find_many_filtered(
+    fs: &dyn FileSystem,
+    dir: &Path,
+    filter: &(dyn Fn(&Path) -> bool + Sync),
+) -> Vec<Remapping> {
+    /// prioritize
+    ///   - ("a", "1/2") over ("a", "1/2/3")
+    ///   - if a path ends with `src`
+    fn insert_prioritized(
+        mappings: &mut BTreeMap<String, (PathBuf, PathBuf)>,
+        key: String,
+        path: PathBuf,
+        window_start: PathBuf,
+    ) {
+        match mappings.entry(key) {
+            Entry::Occupied(mut e) => {
+                let (current, _) = e.get();
+                if current.components().count() > path.components().count()
+                    || (path.ends_with(DAPPTOOLS_CONTRACTS_DIR)
+                        && !current.ends_with(DAPPTOOLS_CONTRACTS_DIR))
+                {
+                    e.insert((path, window_start));
                 }
             }
+            Entry::Vacant(e) => {
+                e.insert((path, window_start));
+            }
         }
+    }
 
-        let is_inside_node_modules = dir.ends_with("node_modules");
-        let visited_symlink_dirs = Mutex::new(HashSet::new());
+    let is_inside_node_modules = dir.ends_with("node_modules");
+    let visited_symlink_dirs = Mutex::new(HashSet::new());
 
-        // iterate over all dirs that are children of the root
-        let mut candidates = read_dir(dir)
-            .filter(|(_, file_type, _)| file_type.is_dir())
-            .collect::<Vec<_>>()
-            .par_iter()
-            .flat_map_iter(|(dir, _, _)| {
-                find_remapping_candidates(
-                    dir,
-                    dir,
-                    0,
-                    is_inside_node_modules,
-                    &visited_symlink_dirs,
-                )
+    // iterate over all dirs that are children of the root
+    let mut candidates = read_dir(fs, dir)
+        .filter(|entry| entry.kind == FileKind::Dir && filter(&entry.path))
+        .collect::<Vec<_>>()
+        .par_iter()
+        .flat_map_iter(|DirEntry { path: dir, .. }| {
+            find_remapping_candidates(
+                fs,
+                dir,
+                dir,
+                0,
+                is_inside_node_modules,
+                &visited_symlink_dirs,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // sort candidates so they are deterministic.
+    // this ensures that hashes do not change due to non deterministic mappings for paths with
+    // same number of components.
+    candidates.sort_by(|a, b| a.source_dir.cmp(&b.source_dir));
+
+    // all combined remappings from all subdirs
+    let mut all_remappings = BTreeMap::new();
+    for candidate in candidates {
+        if let Some(name) = candidate.window_start.file_name().and_then(|s| s.to_str()) {
+            insert_prioritized(
+                &mut all_remappings,
+                format!("{name}/"),
+                candidate.source_dir,
+                candidate.window_start,
+            );
+        }
+    }
+
+    // group the remappings by their name without version
+    let mut packages = BTreeMap::<_, Vec<_>>::new();
+    for (name, (path, window_start)) in all_remappings {
+        let (name, version) = split_version(name.trim_end_matches('/'));
+        let is_nested = window_start.parent() != Some(dir);
+        // nested dependencies are imported by the package whose library folder contains them
+        let context = window_start
+            .ancestors()
+            .skip(1)
+            .take_while(|ancestor| *ancestor != dir)
+            .find(|ancestor| {
+                ancestor.ends_with(DAPPTOOLS_LIB_DIR) || ancestor.ends_with(JS_LIB_DIR)
             })
-            .collect::<Vec<_>>();
-
-        // sort candidates so they are deterministic.
-        // this ensures that hashes do not change due to non deterministic mappings for paths with
-        // same number of components.
-        candidates.sort_by(|a, b| a.source_dir.cmp(&b.source_dir));
-
-        // all combined remappings from all subdirs
-        let mut all_remappings = BTreeMap::new();
-        for candidate in candidates {
-            if let Some(name) = candidate.window_start.file_name().and_then(|s| s.to_str()) {
-                insert_prioritized(&mut all_remappings, format!("{name}/"), candidate.source_dir);
-            }
+            .and_then(Path::parent)
+            .map_or_else(|| window_start.clone(), Path::to_path_buf);
+        packages.entry(format!("{name}/")).or_default().push((is_nested, version, path, context));
+    }
+
+    let mut remappings = Vec::new();
+    for (name, mut versions) in packages {
+        // the newest top-level version is remapped globally
+        versions.sort_by_key(|(is_nested, version, ..)| (*is_nested, Reverse(version.clone())));
+        let mut versions = versions.into_iter();
+        let Some((_, _, global, _)) = versions.next() else { continue };
+        remappings.extend(versions.map(|(_, _, path, context)| Remapping {
+            context: Some(format!("{}/", context.display())),
+            name: name.clone(),
+            path: format!("{}/", path.display()),
+        }));
+        remappings.push(Remapping { context: None, name, path: format!("{}/", global.display()) });
+    }
+    remappings
+}
+
+/// Splits a version suffix like `-1.9.2` or `@5.0.2` off the given folder name.
+///
+/// Versions need at least a major and a minor component, so that names like `erc-20` are kept.
+fn split_version(name: &str) -> (&str, Option<Version>) {
+    for (idx, sep) in name.char_indices().skip(1) {
+        if sep != '-' && sep != '@' {
+            continue;
+        }
+        let version = &name[idx + 1..];
+        if !version.starts_with(|c: char| c.is_ascii_digit()) {
+            continue;
+        }
+        let parsed = Version::parse(version).ok().or_else(|| {
+            // `1.2` without patch version
+            let (major, minor) = version.split_once('.')?;
+            Some(Version::new(major.parse().ok()?, minor.parse().ok()?, 0))
+        });
+        if let Some(version) = parsed {
+            return (&name[..idx], Some(version));
+        }
+    }
+    (name, None)
+}
+
+/// Finds the remappings of the dependencies of the packages in the pnpm store of the given
+/// `node_modules` folder which differ from the given top-level `remappings`.
+///
+/// Each store entry `.pnpm/<package>@<version>/node_modules/` contains the package itself next to
+/// links to the versions of its dependencies. The remappings point to the canonical paths of the
+/// dependencies in the store and apply in the context of the top-level link to the package, if
+/// any, and of the package in the store.
+fn find_pnpm_remappings(
+    fs: &dyn FileSystem,
+    node_modules: &Path,
+    remappings: &[Remapping],
+) -> Vec<Remapping> {
+    let canonicalize = |path: &Path| fs.canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let global = remappings
+        .iter()
+        .filter(|r| r.context.is_none())
+        .map(|r| (r.name.as_str(), canonicalize(Path::new(&r.path))))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut entries = fs.read_dir(&node_modules.join(PNPM_STORE_DIR)).unwrap_or_default();
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut pnpm_remappings = Vec::new();
+    for entry in entries.into_iter().filter(|entry| entry.kind == FileKind::Dir) {
+        let Some(package) = entry.path.file_name().and_then(|s| s.to_str()).and_then(pnpm_package)
+        else {
+            continue;
+        };
+        let modules = entry.path.join(JS_LIB_DIR);
+        let Some(scope) = Path::new(&package).components().next() else { continue };
+        let scope = modules.join(scope);
+
+        // only packages with solidity sources can import dependencies
+        let package_dir = modules.join(&package);
+        if find_many_filtered(fs, &modules, &|dir| dir == scope).is_empty() {
+            continue;
+        }
+        let mut contexts = vec![canonicalize(&package_dir)];
+        let linked = node_modules.join(&package);
+        if fs.is_dir(&linked) && canonicalize(&linked) == contexts[0] {
+            contexts.insert(0, linked);
         }
 
-        all_remappings
-            .into_iter()
-            .map(|(name, path)| Self { context: None, name, path: format!("{}/", path.display()) })
-            .collect()
+        for dependency in find_many_filtered(fs, &modules, &|dir| dir != scope) {
+            let path = canonicalize(Path::new(&dependency.path));
+            if global.get(dependency.name.as_str()) == Some(&path) {
+                continue;
+            }
+            pnpm_remappings.extend(contexts.iter().map(|context| Remapping {
+                context: Some(format!("{}/", context.display())),
+                name: dependency.name.clone(),
+                path: format!("{}/", path.display()),
+            }));
+        }
     }
+    pnpm_remappings
+}
+
+/// Returns the package name of a pnpm store entry like `@scope+name@1.0.0_peer@2.0.0`.
+fn pnpm_package(entry: &str) -> Option<String> {
+    let idx = entry.char_indices().skip(1).find(|(_, c)| *c == '@')?.0;
+    Some(entry[..idx].replace('+', "/"))
 }
 
 #[derive(Clone, Debug)]
@@ -299,6 +466,7 @@ fn is_hidden(path: &Path) -> bool {
 /// Note: this supports symlinks and will short-circuit if a symlink dir has already been visited,
 /// this can occur in pnpm setups: <https://github.com/foundry-rs/foundry/issues/7820>
 fn find_remapping_candidates(
+    fs: &dyn FileSystem,
     current_dir: &Path,
     open: &Path,
     current_level: usize,
@@ -312,11 +480,11 @@ fn find_remapping_candidates(
 
     // scan all entries in the current dir
     let mut search = Vec::new();
-    for (subdir, file_type, path_is_symlink) in read_dir(current_dir) {
+    for DirEntry { path: subdir, kind, is_symlink: path_is_symlink } in read_dir(fs, current_dir) {
         // found a solidity file directly the current dir
-        if !is_candidate && file_type.is_file() && subdir.extension() == Some("sol".as_ref()) {
+        if !is_candidate && kind == FileKind::File && subdir.extension() == Some("sol".as_ref()) {
             is_candidate = true;
-        } else if file_type.is_dir() {
+        } else if kind == FileKind::Dir {
             // if the dir is a symlink to a parent dir we short circuit here
             // `walkdir` will catch symlink loops, but this check prevents that we end up scanning a
             // workspace like
@@ -325,7 +493,7 @@ fn find_remapping_candidates(
             // ├── dep/node_modules
             //     ├── symlink to `my-package`
             // ```
-            if path_is_symlink && let Ok(target) = utils::canonicalize(&subdir) {
+            if path_is_symlink && let Ok(target) = fs.canonicalize(&subdir) {
                 if !visited_symlink_dirs.lock().unwrap().insert(target.clone()) {
                     // short-circuiting if we've already visited the symlink
                     return Vec::new();
@@ -360,6 +528,7 @@ fn find_remapping_candidates(
             // check if the subdir is a lib barrier, in which case we open a new window
             if is_lib_dir(subdir) {
                 find_remapping_candidates(
+                    fs,
                     subdir,
                     subdir,
                     current_level + 1,
@@ -369,6 +538,7 @@ fn find_remapping_candidates(
             } else {
                 // continue scanning with the current window
                 find_remapping_candidates(
+                    fs,
                     subdir,
                     open,
                     current_level,
@@ -415,26 +585,12 @@ fn find_remapping_candidates(
     candidates
 }
 
-/// Returns an iterator over the entries in the directory:
-/// `(path, real_file_type, path_is_symlink)`
+/// Returns an iterator over the non-hidden entries in the directory.
 ///
-/// File type is the file type of the link if it is a symlink. This mimics the behavior of
-/// `walkdir` with `follow_links` set to `true`.
-fn read_dir(dir: &Path) -> impl Iterator<Item = (PathBuf, FileType, bool)> {
-    std::fs::read_dir(dir)
-        .into_iter()
-        .flatten()
-        .filter_map(Result::ok)
-        .filter_map(|e| {
-            let path = e.path();
-            let mut ft = e.file_type().ok()?;
-            let path_is_symlink = ft.is_symlink();
-            if path_is_symlink {
-                ft = std::fs::metadata(&path).ok()?.file_type();
-            }
-            Some((path, ft, path_is_symlink))
-        })
-        .filter(|(p, _, _)| !is_hidden(p))
+/// The kind of a symlink entry is the kind of its target. This mimics the behavior of `walkdir`
+/// with `follow_links` set to `true`.
+fn read_dir(fs: &dyn FileSystem, dir: &Path) -> impl Iterator<Item = DirEntry> {
+    fs.read_dir(dir).into_iter().flatten().filter(|entry| !is_hidden(&entry.path))
 }
 
 fn no_recurse(dir: &Path) -> bool {
@@ -979,4 +1135,146 @@ mod tests {
         expected.sort_unstable();
         assert_eq!(remappings, expected);
     }
+
+    #[test]
+    fn can_find_remappings_in_memory() {
+        use foundry_compilers_core::vfs::MemoryFileSystem;
+
+        let root = Path::new("/project/lib");
+        let fs = MemoryFileSystem::from_iter(
+            ["ds-test/src/test.sol", "ds-test/demo/demo.sol", "standards/src/interfaces/iweth.sol"]
+                .map(|path| (root.join(path), "")),
+        );
+
+        let mut remappings = Remapping::find_many_in(&fs, root);
+        remappings.sort_unstable();
+        assert_eq!(
+            remappings,
+            vec![
+                Remapping {
+                    context: None,
+                    name: "ds-test/".to_string(),
+                    path: to_str(root.join("ds-test/src")),
+                },
+                Remapping {
+                    context: None,
+                    name: "standards/".to_string(),
+                    path: to_str(root.join("standards/src")),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn can_split_versions() {
+        assert_eq!(split_version("forge-std-1.9.2"), ("forge-std", Some(Version::new(1, 9, 2))));
+        assert_eq!(
+            split_version("@openzeppelin-contracts@5.0"),
+            ("@openzeppelin-contracts", Some(Version::new(5, 0, 0)))
+        );
+        assert_eq!(split_version("solady-0.0.245-rc.1").0, "solady");
+        assert_eq!(split_version("erc-20"), ("erc-20", None));
+        assert_eq!(split_version("v4-core"), ("v4-core", None));
+        assert_eq!(pnpm_package("@scope+pkg@1.0.0_peer@2.0.0"), Some("@scope/pkg".to_string()));
+        assert_eq!(pnpm_package("pkg@1.0.0"), Some("pkg".to_string()));
+    }
+
+    #[test]
+    fn can_find_versioned_remappings() {
+        use foundry_compilers_core::vfs::MemoryFileSystem;
+
+        let root = Path::new("/project/dependencies");
+        let fs = MemoryFileSystem::from_iter(
+            [
+                "forge-std-1.9.2/src/Test.sol",
+                "forge-std-1.8.0/src/Test.sol",
+                "@openzeppelin-contracts-5.0.2/token/ERC20.sol",
+                "solmate-6.2.0/src/tokens/ERC20.sol",
+                "solmate-6.2.0/lib/forge-std/src/Test.sol",
+            ]
+            .map(|path| (root.join(path), "")),
+        );
+
+        let remappings = Remapping::find_many_in(&fs, root);
+        assert_eq!(
+            remappings,
+            vec![
+                Remapping {
+                    context: None,
+                    name: "@openzeppelin-contracts/".to_string(),
+                    path: to_str(root.join("@openzeppelin-contracts-5.0.2")),
+                },
+                Remapping {
+                    context: Some(to_str(root.join("forge-std-1.8.0"))),
+                    name: "forge-std/".to_string(),
+                    path: to_str(root.join("forge-std-1.8.0/src")),
+                },
+                Remapping {
+                    context: Some(to_str(root.join("solmate-6.2.0"))),
+                    name: "forge-std/".to_string(),
+                    path: to_str(root.join("solmate-6.2.0/lib/forge-std/src")),
+                },
+                Remapping {
+                    context: None,
+                    name: "forge-std/".to_string(),
+                    path: to_str(root.join("forge-std-1.9.2/src")),
+                },
+                Remapping {
+                    context: None,
+                    name: "solmate/".to_string(),
+                    path: to_str(root.join("solmate-6.2.0/src")),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn can_find_pnpm_remappings() {
+        use std::os::unix::fs::symlink;
+
+        let tmp_dir = tempdir("pnpm").unwrap();
+        let root = tmp_dir.path().canonicalize().unwrap();
+        let node_modules = root.join("node_modules");
+        let store = node_modules.join(".pnpm");
+        mkdir_or_touch(
+            &store,
+            &[
+                "a@1.0.0/node_modules/a/contracts/A.sol",
+                "b@1.0.0/node_modules/b/contracts/B.sol",
+                "b@2.0.0/node_modules/b/contracts/B.sol",
+            ],
+        );
+        symlink("../../b@1.0.0/node_modules/b", store.join("a@1.0.0/node_modules/b")).unwrap();
+        symlink(".pnpm/a@1.0.0/node_modules/a", node_modules.join("a")).unwrap();
+        symlink(".pnpm/b@2.0.0/node_modules/b", node_modules.join("b")).unwrap();
+
+        let remappings = Remapping::find_many(&node_modules);
+        let b_1 = to_str(store.join("b@1.0.0/node_modules/b"));
+        assert_eq!(
+            remappings,
+            vec![
+                Remapping {
+                    context: None,
+                    name: "a/".to_string(),
+                    path: to_str(node_modules.join("a")),
+                },
+                Remapping {
+                    context: Some(to_str(node_modules.join("a"))),
+                    name: "b/".to_string(),
+                    path: b_1.clone(),
+                },
+                Remapping {
+                    context: Some(to_str(store.join("a@1.0.0/node_modules/a"))),
+                    name: "b/".to_string(),
+                    path: b_1,
+                },
+                Remapping {
+                    context: None,
+                    name: "b/".to_string(),
+                    path: to_str(node_modules.join("b")),
+                },
+            ]
+        );
+    }
 }
@@ -61,11 +61,20 @@ impl Remapping {
         RelativeRemapping::new(self, root)
     }
 
-    /// Removes the `base` path from the remapping
+    /// Removes the `base` path from the path and the context of the remapping
     pub fn strip_prefix(&mut self, base: &Path) -> &mut Self {
         if let Ok(stripped) = Path::new(&self.path).strip_prefix(base) {
             self.path = stripped.display().to_string();
         }
+        if let Some(context) = &mut self.context
+            && let Ok(stripped) = Path::new(context).strip_prefix(base)
+        {
+            let mut stripped = stripped.display().to_string();
+            if context.ends_with('/') && !stripped.is_empty() {
+                stripped.push('/');
+            }
+            *context = stripped;
+        }
         self
     }
 }
@@ -424,6 +433,15 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn can_strip_prefix_of_context() {
+        let mut remapping =
+            Remapping::from_str("/root/lib/a-1.0/:a/=/root/lib/a-1.0/src/").unwrap();
+        remapping.strip_prefix(Path::new("/root"));
+        assert_eq!(remapping.to_string(), "lib/a-1.0/:a/=lib/a-1.0/src/");
+    }
+
     #[test]
     fn can_resolve_global_contexts() {
         let remapping = ":oz=a/b/c/d/";
@@ -0,0 +1,306 @@
+//! Source unit names as assigned by solc's virtual file system.
+//!
+//! See also <https://docs.soliditylang.org/en/latest/path-resolution.html>
+
+use crate::remappings::Remapping;
+use std::path::{Component, Path, PathBuf};
+
+/// Computes source unit names the way solc's virtual file system (VFS) does when it's invoked
+/// with `--base-path` and `--include-path`.
+///
+/// Paths of files passed on the command line are normalized and then made relative to the base
+/// path, or else to the first include path that contains them. Imports are resolved relative to
+/// the source unit name of the importing file, and then remapped.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SolcVfs {
+    working_dir: PathBuf,
+    base_path: PathBuf,
+    include_paths: Vec<PathBuf>,
+}
+
+impl SolcVfs {
+    /// Creates a new VFS for a solc invocation in the given absolute working directory, with the
+    /// given base path.
+    ///
+    /// Relative paths are relative to the working directory, and an empty base path is treated as
+    /// the working directory, like solc does.
+    pub fn new(working_dir: impl Into<PathBuf>, base_path: impl AsRef<Path>) -> Self {
+        let working_dir = working_dir.into();
+        let base_path = normalize_cli_path(&working_dir, base_path.as_ref());
+        Self { working_dir, base_path, include_paths: Vec::new() }
+    }
+
+    /// Adds include paths, which are tried in order after the base path.
+    #[must_use]
+    pub fn with_include_paths(
+        mut self,
+        include_paths: impl IntoIterator<Item = impl AsRef<Path>>,
+    ) -> Self {
+        let include_paths = include_paths
+            .into_iter()
+            .map(|path| normalize_cli_path(&self.working_dir, path.as_ref()))
+            .collect::<Vec<_>>();
+        self.include_paths.extend(include_paths);
+        self
+    }
+
+    /// Returns the working directory.
+    pub fn working_dir(&self) -> &Path {
+        &self.working_dir
+    }
+
+    /// Returns the normalized base path.
+    pub fn base_path(&self) -> &Path {
+        &self.base_path
+    }
+
+    /// Returns the normalized include paths.
+    pub fn include_paths(&self) -> &[PathBuf] {
+        &self.include_paths
+    }
+
+    /// Returns the source unit name solc assigns to a file passed on the command line.
+    ///
+    /// Relative paths are relative to the working directory. The path is normalized without
+    /// resolving symlinks, and made relative to the base path or the first include path which
+    /// contains it. If neither contains it, the absolute path is used.
+    pub fn source_unit_name(&self, path: &Path) -> String {
+        let path = normalize_cli_path(&self.working_dir, path);
+        std::iter::once(&self.base_path)
+            .chain(&self.include_paths)
+            .find_map(|prefix| path.strip_prefix(prefix).ok())
+            .map_or_else(|| slash_path(&path), slash_path)
+    }
+
+    /// Converts the absolute paths in the context and target of the remapping to source unit
+    /// names.
+    ///
+    /// Relative paths are used as-is, since solc doesn't normalize remappings.
+    pub fn remapping(&self, remapping: &Remapping) -> Remapping {
+        let convert = |path: &str| {
+            if !Path::new(path).is_absolute() {
+                return path.to_string();
+            }
+            let mut name = self.source_unit_name(Path::new(path));
+            if path.ends_with('/') && !name.is_empty() && !name.ends_with('/') {
+                name.push('/');
+            }
+            name
+        };
+        Remapping {
+            context: remapping.context.as_deref().map(convert),
+            name: remapping.name.clone(),
+            path: convert(&remapping.path),
+        }
+    }
+
+    /// Returns the source unit name of the file imported with `import` by the source unit
+    /// `importer`.
+    ///
+    /// Relative imports, starting with `./` or `../`, are resolved against the name of the
+    /// importer, all other imports are used as-is. The `remappings` are applied to the resulting
+    /// name: of the remappings whose context is a prefix of `importer` and whose name is a prefix
+    /// of the import, the one with the highest [`Self::remapping_precedence`] wins, and the last
+    /// one if they are tied.
+    pub fn import_unit_name(importer: &str, import: &str, remappings: &[Remapping]) -> String {
+        let name = resolve_relative_import(importer, import);
+
+        let mut best: Option<((usize, usize), &Remapping)> = None;
+        for remapping in remappings {
+            let context = remapping.context.as_deref().unwrap_or_default();
+            if !importer.starts_with(context) || !name.starts_with(&remapping.name) {
+                continue;
+            }
+            let key = Self::remapping_precedence(remapping);
+            if best.is_none_or(|(best, _)| key >= best) {
+                best = Some((key, remapping));
+            }
+        }
+
+        match best {
+            Some((_, remapping)) => format!("{}{}", remapping.path, &name[remapping.name.len()..]),
+            None => name,
+        }
+    }
+
+    /// Returns the precedence of the remapping when multiple remappings match an import: the
+    /// length of its context, and then the length of its name.
+    pub fn remapping_precedence(remapping: &Remapping) -> (usize, usize) {
+        (remapping.context.as_deref().unwrap_or_default().len(), remapping.name.len())
+    }
+}
+
+/// Makes the path absolute and collapses `.` and `..` segments, without resolving symlinks.
+fn normalize_cli_path(working_dir: &Path, path: &Path) -> PathBuf {
+    let path = working_dir.join(path);
+
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Resolves a relative import against the source unit name of the importer.
+fn resolve_relative_import(importer: &str, import: &str) -> String {
+    let is_relative = matches!(import.split('/').next(), Some("." | ".."));
+    if !is_relative {
+        return import.to_string();
+    }
+
+    let mut name = importer.to_string();
+    remove_last_segment(&mut name);
+    for segment in import.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => remove_last_segment(&mut name),
+            segment => {
+                if !name.is_empty() {
+                    name.push('/');
+                }
+                name.push_str(segment);
+            }
+        }
+    }
+    name
+}
+
+/// Removes everything past the last slash, and then all trailing slashes.
+fn remove_last_segment(name: &mut String) {
+    name.truncate(name.rfind('/').map_or(0, |idx| idx + 1));
+    name.truncate(name.trim_end_matches('/').len());
+}
+
+fn slash_path(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    #[cfg(windows)]
+    {
+        path.replace('\\', "/")
+    }
+    #[cfg(not(windows))]
+    {
+        path.into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // <https://docs.soliditylang.org/en/latest/path-resolution.html#cli-path-normalization-and-stripping>
+    #[test]
+    #[cfg_attr(windows, ignore = "unix paths")]
+    fn can_name_cli_paths() {
+        let vfs = SolcVfs::new("/", "/home/user/project");
+        let table = [
+            ("/home/user/project/contract.sol", "contract.sol"),
+            ("/home/user/project/./contract.sol", "contract.sol"),
+            ("/home/user/project//src/../contract.sol", "contract.sol"),
+            ("/home/user/project/src/contract.sol", "src/contract.sol"),
+            ("/home/user/projects/contract.sol", "/home/user/projects/contract.sol"),
+            ("/home/user/contract.sol", "/home/user/contract.sol"),
+            ("/home/user/project/../project/contract.sol", "contract.sol"),
+        ];
+        for (path, name) in table {
+            assert_eq!(vfs.source_unit_name(Path::new(path)), name, "{path}");
+        }
+
+        let vfs =
+            SolcVfs::new("/", "/home/user").with_include_paths(["/usr/lib", "/home/user/lib"]);
+        let table = [
+            ("/home/user/project/contract.sol", "project/contract.sol"),
+            ("/home/user/lib/math.sol", "lib/math.sol"),
+            ("/usr/lib/dapp-bin/token.sol", "dapp-bin/token.sol"),
+            ("/usr/local/lib/x.sol", "/usr/local/lib/x.sol"),
+        ];
+        for (path, name) in table {
+            assert_eq!(vfs.source_unit_name(Path::new(path)), name, "{path}");
+        }
+
+        let vfs = SolcVfs::new("/home/user/project", "").with_include_paths(["../lib"]);
+        assert_eq!(vfs.base_path(), Path::new("/home/user/project"));
+        assert_eq!(vfs.include_paths(), [Path::new("/home/user/lib")]);
+        assert_eq!(vfs.source_unit_name(Path::new("./contract.sol")), "contract.sol");
+        assert_eq!(vfs.source_unit_name(Path::new("../lib/math.sol")), "math.sol");
+    }
+
+    // <https://docs.soliditylang.org/en/latest/path-resolution.html#relative-imports>
+    #[test]
+    fn can_resolve_relative_imports() {
+        let table = [
+            ("/project/lib/math.sol", "./util/./util.sol", "/project/lib/util/util.sol"),
+            ("/project/lib/math.sol", "./util//util.sol", "/project/lib/util/util.sol"),
+            ("/project/lib/math.sol", "../util/../array/util.sol", "/project/array/util.sol"),
+            ("/project/lib/math.sol", "../.././../util.sol", "util.sol"),
+            ("/project/lib/math.sol", "../../.././../util.sol", "util.sol"),
+            ("lib/src/../contract.sol", "./util.sol", "lib/src/../util.sol"),
+            ("lib/src/../contract.sol", "../util.sol", "lib/src/util.sol"),
+            ("contract.sol", "./util.sol", "util.sol"),
+            ("contract.sol", "../util.sol", "util.sol"),
+            ("contract.sol", "lib/../util.sol", "lib/../util.sol"),
+            ("contract.sol", "/project/./util.sol", "/project/./util.sol"),
+        ];
+        for (importer, import, name) in table {
+            assert_eq!(SolcVfs::import_unit_name(importer, import, &[]), name, "{import}");
+        }
+    }
+
+    // <https://docs.soliditylang.org/en/latest/path-resolution.html#import-remapping>
+    #[test]
+    fn can_apply_remappings() {
+        let remappings = [
+            "github.com/ethereum/dapp-bin/=dapp-bin/",
+            "module1:github.com/ethereum/dapp-bin/=dapp-bin/",
+            "module2:github.com/ethereum/dapp-bin/=dapp-bin_old/",
+            "github.com/ethereum/dapp-bin/library/=lib/",
+            "/project/=/contracts/",
+            "a/=b/",
+            "a/=c/",
+        ]
+        .map(|r| r.parse::<Remapping>().unwrap());
+
+        let table = [
+            ("source.sol", "github.com/ethereum/dapp-bin/utils/id.sol", "dapp-bin/utils/id.sol"),
+            (
+                "module1/math.sol",
+                "github.com/ethereum/dapp-bin/utils/id.sol",
+                "dapp-bin/utils/id.sol",
+            ),
+            (
+                "module2/math.sol",
+                "github.com/ethereum/dapp-bin/utils/id.sol",
+                "dapp-bin_old/utils/id.sol",
+            ),
+            // the longest context wins over the longest prefix
+            (
+                "module2/math.sol",
+                "github.com/ethereum/dapp-bin/library/math.sol",
+                "dapp-bin_old/library/math.sol",
+            ),
+            ("source.sol", "github.com/ethereum/dapp-bin/library/math.sol", "lib/math.sol"),
+            ("/project/contract.sol", "./util.sol", "/contracts/util.sol"),
+            // remappings are plain string prefixes, and the last one wins ties
+            ("source.sol", "a/x.sol", "c/x.sol"),
+            ("source.sol", "ab/x.sol", "ab/x.sol"),
+        ];
+        for (importer, import, name) in table {
+            assert_eq!(SolcVfs::import_unit_name(importer, import, &remappings), name, "{import}");
+        }
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore = "unix paths")]
+    fn can_convert_remappings() {
+        let vfs = SolcVfs::new("/", "/project").with_include_paths(["/deps"]);
+        let remapping = "/project/lib/a:x/=/deps/x/src/".parse::<Remapping>().unwrap();
+        assert_eq!(vfs.remapping(&remapping).to_string(), "lib/a:x/=x/src/");
+        let remapping = "y/=lib/y/".parse::<Remapping>().unwrap();
+        assert_eq!(vfs.remapping(&remapping), remapping);
+    }
+}
@@ -24,8 +24,8 @@ use foundry_compilers_artifacts::{
         EvmOutputSelection, EwasmOutputSelection,
     },
 };
-use foundry_compilers_core::utils;
-use std::{fs, path::Path};
+use foundry_compilers_core::vfs::{FileSystem, OsFileSystem};
+use std::path::Path;
 
 /// An `Artifact` implementation that can be configured to include additional content and emit
 /// additional files
@@ -176,6 +176,15 @@ impl ArtifactOutput for ConfigurableArtifacts {
         &self,
         contracts: &crate::VersionedContracts<Contract>,
         artifacts: &crate::Artifacts<Self::Artifact>,
+    ) -> Result<(), SolcError> {
+        self.handle_artifacts_in(&OsFileSystem, contracts, artifacts)
+    }
+
+    fn handle_artifacts_in(
+        &self,
+        fs: &dyn FileSystem,
+        contracts: &crate::VersionedContracts<Contract>,
+        artifacts: &crate::Artifacts<Self::Artifact>,
     ) -> Result<(), SolcError> {
         for (file, contracts) in contracts.as_ref() {
             for (name, versioned_contracts) in contracts {
@@ -187,8 +196,10 @@ impl ArtifactOutput for ConfigurableArtifacts {
                         &contract.profile,
                     ) {
                         let file = &artifact.file;
-                        utils::create_parent_dir_all(file)?;
-                        self.additional_files.write_extras(&contract.contract, file)?;
+                        if let Some(parent) = file.parent() {
+                            fs.create_dir_all(parent).map_err(|err| SolcError::io(err, parent))?;
+                        }
+                        self.additional_files.write_extras_in(fs, &contract.contract, file)?;
                     }
                 }
             }
@@ -399,19 +410,38 @@ impl ArtifactOutput for ConfigurableArtifacts {
     fn handle_cached_artifacts(
         &self,
         artifacts: &crate::Artifacts<Self::Artifact>,
+    ) -> Result<(), SolcError> {
+        self.handle_cached_artifacts_in(&OsFileSystem, artifacts)
+    }
+
+    fn handle_cached_artifacts_in(
+        &self,
+        fs: &dyn FileSystem,
+        artifacts: &crate::Artifacts<Self::Artifact>,
     ) -> Result<(), SolcError> {
         for artifacts in artifacts.values() {
             for artifacts in artifacts.values() {
                 for artifact_file in artifacts {
                     let file = &artifact_file.file;
                     let artifact = &artifact_file.artifact;
-                    self.additional_files.process_abi(artifact.abi.as_ref(), file)?;
-                    self.additional_files.process_assembly(artifact.assembly.as_deref(), file)?;
-                    self.additional_files
-                        .process_legacy_assembly(artifact.legacy_assembly.clone(), file)?;
-                    self.additional_files
-                        .process_bytecode(artifact.bytecode.as_ref().map(|b| &b.object), file)?;
+                    self.additional_files.process_abi(fs, artifact.abi.as_ref(), file)?;
+                    self.additional_files.process_assembly(
+                        fs,
+                        artifact.assembly.as_deref(),
+                        file,
+                    )?;
+                    self.additional_files.process_legacy_assembly(
+                        fs,
+                        artifact.legacy_assembly.clone(),
+                        file,
+                    )?;
+                    self.additional_files.process_bytecode(
+                        fs,
+                        artifact.bytecode.as_ref().map(|b| &b.object),
+                        file,
+                    )?;
                     self.additional_files.process_deployed_bytecode(
+                        fs,
                         artifact
                             .deployed_bytecode
                             .as_ref()
@@ -419,15 +449,24 @@ impl ArtifactOutput for ConfigurableArtifacts {
                             .map(|b| &b.object),
                         file,
                     )?;
-                    self.additional_files
-                        .process_generated_sources(Some(&artifact.generated_sources), file)?;
-                    self.additional_files.process_ir(artifact.ir.as_deref(), file)?;
-                    self.additional_files
-                        .process_ir_optimized(artifact.ir_optimized.as_deref(), file)?;
-                    self.additional_files.process_ewasm(artifact.ewasm.as_ref(), file)?;
-                    self.additional_files.process_metadata(artifact.metadata.as_ref(), file)?;
-                    self.additional_files
-                        .process_source_map(artifact.get_source_map_str().as_deref(), file)?;
+                    self.additional_files.process_generated_sources(
+                        fs,
+                        Some(&artifact.generated_sources),
+                        file,
+                    )?;
+                    self.additional_files.process_ir(fs, artifact.ir.as_deref(), file)?;
+                    self.additional_files.process_ir_optimized(
+                        fs,
+                        artifact.ir_optimized.as_deref(),
+                        file,
+                    )?;
+                    self.additional_files.process_ewasm(fs, artifact.ewasm.as_ref(), file)?;
+                    self.additional_files.process_metadata(fs, artifact.metadata.as_ref(), file)?;
+                    self.additional_files.process_source_map(
+                        fs,
+                        artifact.get_source_map_str().as_deref(),
+                        file,
+                    )?;
                 }
             }
         }
@@ -680,40 +719,56 @@ impl ExtraOutputFiles {
         config
     }
 
-    fn process_abi(&self, abi: Option<&JsonAbi>, file: &Path) -> Result<(), SolcError> {
+    fn process_abi(
+        &self,
+        fs: &dyn FileSystem,
+        abi: Option<&JsonAbi>,
+        file: &Path,
+    ) -> Result<(), SolcError> {
         if self.abi
             && let Some(abi) = abi
         {
             let file = file.with_extension("abi.json");
-            fs::write(&file, serde_json::to_string_pretty(abi)?)
+            fs.write(&file, serde_json::to_string_pretty(abi)?.as_bytes())
                 .map_err(|err| SolcError::io(err, file))?
         }
         Ok(())
     }
 
-    fn process_metadata(&self, metadata: Option<&Metadata>, file: &Path) -> Result<(), SolcError> {
+    fn process_metadata(
+        &self,
+        fs: &dyn FileSystem,
+        metadata: Option<&Metadata>,
+        file: &Path,
+    ) -> Result<(), SolcError> {
         if self.metadata
             && let Some(metadata) = metadata
         {
             let file = file.with_extension("metadata.json");
-            fs::write(&file, serde_json::to_string_pretty(metadata)?)
+            fs.write(&file, serde_json::to_string_pretty(metadata)?.as_bytes())
                 .map_err(|err| SolcError::io(err, file))?
         }
         Ok(())
     }
 
-    fn process_ir(&self, ir: Option<&str>, file: &Path) -> Result<(), SolcError> {
+    fn process_ir(
+        &self,
+        fs: &dyn FileSystem,
+        ir: Option<&str>,
+        file: &Path,
+    ) -> Result<(), SolcError> {
         if self.ir
             && let Some(ir) = ir
         {
             let file = file.with_extension("ir");
-            fs::write(&file, ir).map_err(|err| SolcError::io(err, file))?
+            fs.write(&file, ir.as_bytes()).map_err(|err| SolcError::io(err, file))?
         }
         Ok(())
     }
 
     fn process_ir_optimized(
         &self,
+        fs: &dyn FileSystem,
         ir_optimized: Option<&str>,
         file: &Path,
     ) -> Result<(), SolcError> {
@@ -721,34 +776,45 @@ impl ExtraOutputFiles {
             && let Some(ir_optimized) = ir_optimized
         {
             let file = file.with_extension("iropt");
-            fs::write(&file, ir_optimized).map_err(|err| SolcError::io(err, file))?
+            fs.write(&file, ir_optimized.as_bytes()).map_err(|err| SolcError::io(err, file))?
         }
         Ok(())
     }
 
-    fn process_ewasm(&self, ewasm: Option<&Ewasm>, file: &Path) -> Result<(), SolcError> {
+    fn process_ewasm(
+        &self,
+        fs: &dyn FileSystem,
+        ewasm: Option<&Ewasm>,
+        file: &Path,
+    ) -> Result<(), SolcError> {
         if self.ewasm
             && let Some(ewasm) = ewasm
         {
             let file = file.with_extension("ewasm");
-            fs::write(&file, serde_json::to_vec_pretty(ewasm)?)
+            fs.write(&file, &serde_json::to_vec_pretty(ewasm)?)
                 .map_err(|err| SolcError::io(err, file))?;
         }
         Ok(())
     }
 
-    fn process_assembly(&self, asm: Option<&str>, file: &Path) -> Result<(), SolcError> {
+    fn process_assembly(
+        &self,
+        fs: &dyn FileSystem,
+        asm: Option<&str>,
+        file: &Path,
+    ) -> Result<(), SolcError> {
         if self.assembly
             && let Some(asm) = asm
         {
             let file = file.with_extension("asm");
-            fs::write(&file, asm).map_err(|err| SolcError::io(err, file))?
+            fs.write(&file, asm.as_bytes()).map_err(|err| SolcError::io(err, file))?
         }
         Ok(())
     }
 
     fn process_legacy_assembly(
         &self,
+        fs: &dyn FileSystem,
         asm: Option<serde_json::Value>,
         file: &Path,
     ) -> Result<(), SolcError> {
@@ -756,13 +822,15 @@ impl ExtraOutputFiles {
             && let Some(legacy_asm) = asm
         {
             let file = file.with_extension("legacyAssembly.json");
-            fs::write(&file, format!("{legacy_asm}")).map_err(|err| SolcError::io(err, file))?
+            fs.write(&file, legacy_asm.to_string().as_bytes())
+                .map_err(|err| SolcError::io(err, file))?
         }
         Ok(())
     }
 
     fn process_generated_sources(
         &self,
+        fs: &dyn FileSystem,
         generated_sources: Option<&Vec<GeneratedSource>>,
         file: &Path,
     ) -> Result<(), SolcError> {
@@ -770,24 +838,30 @@ impl ExtraOutputFiles {
             && let Some(generated_sources) = generated_sources
         {
             let file = file.with_extension("gensources");
-            fs::write(&file, serde_json::to_vec_pretty(generated_sources)?)
+            fs.write(&file, &serde_json::to_vec_pretty(generated_sources)?)
                 .map_err(|err| SolcError::io(err, file))?;
         }
         Ok(())
     }
 
-    fn process_source_map(&self, source_map: Option<&str>, file: &Path) -> Result<(), SolcError> {
+    fn process_source_map(
+        &self,
+        fs: &dyn FileSystem,
+        source_map: Option<&str>,
+        file: &Path,
+    ) -> Result<(), SolcError> {
         if self.source_map
             && let Some(source_map) = source_map
         {
             let file = file.with_extension("sourcemap");
-            fs::write(&file, source_map).map_err(|err| SolcError::io(err, file))?
+            fs.write(&file, source_map.as_bytes()).map_err(|err| SolcError::io(err, file))?
         }
         Ok(())
     }
 
     fn process_bytecode(
         &self,
+        fs: &dyn FileSystem,
         bytecode: Option<&BytecodeObject>,
         file: &Path,
     ) -> Result<(), SolcError> {
@@ -796,13 +870,14 @@ impl ExtraOutputFiles {
         {
             let code = hex::encode(bytecode.as_ref());
             let file = file.with_extension("bin");
-            fs::write(&file, code).map_err(|err| SolcError::io(err, file))?
+            fs.write(&file, code.as_bytes()).map_err(|err| SolcError::io(err, file))?
         }
         Ok(())
     }
 
     fn process_deployed_bytecode(
         &self,
+        fs: &dyn FileSystem,
         deployed: Option<&BytecodeObject>,
         file: &Path,
     ) -> Result<(), SolcError> {
@@ -811,30 +886,41 @@ impl ExtraOutputFiles {
         {
             let code = hex::encode(deployed.as_ref());
             let file = file.with_extension("deployed-bin");
-            fs::write(&file, code).map_err(|err| SolcError::io(err, file))?
+            fs.write(&file, code.as_bytes()).map_err(|err| SolcError::io(err, file))?
         }
         Ok(())
     }
 
     /// Write the set values as separate files
     pub fn write_extras(&self, contract: &Contract, file: &Path) -> Result<(), SolcError> {
-        self.process_abi(contract.abi.as_ref(), file)?;
-        self.process_metadata(contract.metadata.as_ref().map(|m| &m.metadata), file)?;
-        self.process_ir(contract.ir.as_deref(), file)?;
-        self.process_ir_optimized(contract.ir_optimized.as_deref(), file)?;
-        self.process_ewasm(contract.ewasm.as_ref(), file)?;
+        self.write_extras_in(&OsFileSystem, contract, file)
+    }
+
+    /// Same as [`Self::write_extras`], but writes to the given [`FileSystem`].
+    pub fn write_extras_in(
+        &self,
+        fs: &dyn FileSystem,
+        contract: &Contract,
+        file: &Path,
+    ) -> Result<(), SolcError> {
+        self.process_abi(fs, contract.abi.as_ref(), file)?;
+        self.process_metadata(fs, contract.metadata.as_ref().map(|m| &m.metadata), file)?;
+        self.process_ir(fs, contract.ir.as_deref(), file)?;
+        self.process_ir_optimized(fs, contract.ir_optimized.as_deref(), file)?;
+        self.process_ewasm(fs, contract.ewasm.as_ref(), file)?;
 
         let evm = contract.evm.as_ref();
-        self.process_assembly(evm.and_then(|evm| evm.assembly.as_deref()), file)?;
-        self.process_legacy_assembly(evm.and_then(|evm| evm.legacy_assembly.clone()), file)?;
+        self.process_assembly(fs, evm.and_then(|evm| evm.assembly.as_deref()), file)?;
+        self.process_legacy_assembly(fs, evm.and_then(|evm| evm.legacy_assembly.clone()), file)?;
 
         let bytecode = evm.and_then(|evm| evm.bytecode.as_ref());
-        self.process_generated_sources(bytecode.map(|b| &b.generated_sources), file)?;
+        self.process_generated_sources(fs, bytecode.map(|b| &b.generated_sources), file)?;
 
         let deployed_bytecode = evm.and_then(|evm| evm.deployed_bytecode.as_ref());
-        self.process_source_map(bytecode.and_then(|b| b.source_map.as_deref()), file)?;
-        self.process_bytecode(bytecode.map(|b| &b.object), file)?;
+        self.process_source_map(fs, bytecode.and_then(|b| b.source_map.as_deref()), file)?;
+        self.process_bytecode(fs, bytecode.map(|b| &b.object), file)?;
         self.process_deployed_bytecode(
+            fs,
             deployed_bytecode.and_then(|d| d.bytecode.as_ref()).map(|b| &b.object),
             file,
         )?;
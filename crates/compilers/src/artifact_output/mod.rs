@@ -11,6 +11,7 @@ use foundry_compilers_artifacts::{
 use foundry_compilers_core::{
     error::{Result, SolcError, SolcIoError},
     utils::{self, strip_prefix_owned},
+    vfs::{self, FileSystem},
 };
 use path_slash::PathBufExt;
 use semver::Version;
@@ -19,7 +20,7 @@ use std::{
     borrow::Cow,
     collections::{HashMap, HashSet, btree_map::BTreeMap},
     ffi::OsString,
-    fmt, fs,
+    fmt,
     hash::Hash,
     ops::Deref,
     path::{Path, PathBuf},
@@ -130,6 +131,12 @@ impl<T: Serialize> ArtifactFile<T> {
         utils::create_parent_dir_all(&self.file)?;
         utils::write_json_file(&self.artifact, &self.file, 64 * 1024)
     }
+
+    /// Same as [`Self::write`], but writes to the given [`FileSystem`].
+    pub fn write_in(&self, fs: &dyn FileSystem) -> Result<()> {
+        trace!("writing artifact file {:?} {}", self.file, self.version);
+        vfs::write_json_file(fs, &self.artifact, &self.file)
+    }
 }
 
 impl<T> ArtifactFile<T> {
@@ -213,6 +220,14 @@ impl<T: Serialize> Artifacts<T> {
         }
         Ok(())
     }
+
+    /// Same as [`Self::write_all`], but writes to the given [`FileSystem`].
+    pub fn write_all_in(&self, fs: &dyn FileSystem) -> Result<()> {
+        for artifact in self.artifact_files() {
+            artifact.write_in(fs)?;
+        }
+        Ok(())
+    }
 }
 
 impl<T> Artifacts<T> {
@@ -640,13 +655,15 @@ pub trait ArtifactOutput {
     ) -> Result<Artifacts<Self::Artifact>> {
         let mut artifacts =
             self.output_to_artifacts(contracts, sources, ctx, layout, primary_profiles);
-        fs::create_dir_all(&layout.artifacts)
+        layout
+            .fs
+            .create_dir_all(&layout.artifacts)
             .map_err(|err| SolcIoError::new(err, &layout.artifacts))?;
 
         artifacts.join_all(&layout.artifacts);
-        artifacts.write_all()?;
+        artifacts.write_all_in(&*layout.fs)?;
 
-        self.handle_artifacts(contracts, &artifacts)?;
+        self.handle_artifacts_in(&*layout.fs, contracts, &artifacts)?;
 
         Ok(artifacts)
     }
@@ -660,6 +677,18 @@ pub trait ArtifactOutput {
         Ok(())
     }
 
+    /// Same as [`Self::handle_artifacts`], but for artifacts written to the given [`FileSystem`].
+    ///
+    /// Defaults to [`Self::handle_artifacts`].
+    fn handle_artifacts_in(
+        &self,
+        _fs: &dyn FileSystem,
+        contracts: &VersionedContracts<Self::CompilerContract>,
+        artifacts: &Artifacts<Self::Artifact>,
+    ) -> Result<()> {
+        self.handle_artifacts(contracts, artifacts)
+    }
+
     /// Returns the file name for the contract's artifact
     /// `Greeter.json`
     fn output_file_name(
@@ -894,6 +923,7 @@ pub trait ArtifactOutput {
                 let unique_profiles =
                     versioned_contracts.iter().map(|c| &c.profile).collect::<HashSet<_>>();
                 let primary_profile = primary_profiles.get(file);
+                let artifacts_folder = layout.artifacts_dir(file);
 
                 for contract in versioned_contracts {
                     non_standalone_sources.insert(file);
@@ -906,7 +936,7 @@ pub trait ArtifactOutput {
                         &taken_paths_lowercase,
                         file,
                         name,
-                        layout.artifacts.as_path(),
+                        &artifacts_folder,
                         &contract.version,
                         &contract.profile,
                         unique_versions.len() > 1,
@@ -977,7 +1007,7 @@ pub trait ArtifactOutput {
                             &taken_paths_lowercase,
                             file,
                             name,
-                            &layout.artifacts,
+                            &layout.artifacts_dir(file),
                             &source.version,
                             &source.profile,
                             unique_versions.len() > 1,
@@ -1030,6 +1060,18 @@ pub trait ArtifactOutput {
     fn handle_cached_artifacts(&self, _artifacts: &Artifacts<Self::Artifact>) -> Result<()> {
         Ok(())
     }
+
+    /// Same as [`Self::handle_cached_artifacts`], but for artifacts read from the given
+    /// [`FileSystem`].
+    ///
+    /// Defaults to [`Self::handle_cached_artifacts`].
+    fn handle_cached_artifacts_in(
+        &self,
+        _fs: &dyn FileSystem,
+        artifacts: &Artifacts<Self::Artifact>,
+    ) -> Result<()> {
+        self.handle_cached_artifacts(artifacts)
+    }
 }
 
 /// Additional context to use during [`ArtifactOutput::on_output()`]
@@ -15,6 +15,7 @@ use foundry_compilers_artifacts::{
 use foundry_compilers_core::{
     error::{Result, SolcError},
     utils::{self, strip_prefix},
+    vfs::{self, FileSystem, OsFileSystem},
 };
 use semver::Version;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
@@ -119,9 +120,14 @@ impl<S: CompilerSettings> CompilerCache<S> {
     /// cache.join_artifacts_files(project.artifacts_path());
     /// # Ok::<_, Box<dyn std::error::Error>>(())
     /// ```
-    #[instrument(name = "CompilerCache::read", err)]
     pub fn read(path: &Path) -> Result<Self> {
-        let cache: Self = utils::read_json_file(path)?;
+        Self::read_in(&OsFileSystem, path)
+    }
+
+    /// Same as [`Self::read`], but reads from the given [`FileSystem`].
+    #[instrument(name = "CompilerCache::read", skip(fs), err)]
+    pub fn read_in(fs: &dyn FileSystem, path: &Path) -> Result<Self> {
+        let cache: Self = vfs::read_json_file(fs, path)?;
         trace!(cache.format, cache.files = cache.files.len(), "read cache");
         Ok(cache)
     }
@@ -143,24 +149,34 @@ impl<S: CompilerSettings> CompilerCache<S> {
     /// # Ok::<_, Box<dyn std::error::Error>>(())
     /// ```
     pub fn read_joined<L>(paths: &ProjectPathsConfig<L>) -> Result<Self> {
-        let mut cache = Self::read(&paths.cache)?;
+        let mut cache = Self::read_in(&*paths.fs, &paths.cache)?;
         cache.join_entries(&paths.root).join_artifacts_files(&paths.artifacts);
         Ok(cache)
     }
 
     /// Write the cache as json file to the given path
-    #[instrument(name = "CompilerCache::write", skip_all)]
     pub fn write(&self, path: &Path) -> Result<()> {
+        self.write_in(&OsFileSystem, path)
+    }
+
+    /// Same as [`Self::write`], but writes to the given [`FileSystem`].
+    #[instrument(name = "CompilerCache::write", skip_all)]
+    pub fn write_in(&self, fs: &dyn FileSystem, path: &Path) -> Result<()> {
         trace!("writing cache with {} entries to json file: \"{}\"", self.len(), path.display());
-        utils::create_parent_dir_all(path)?;
-        utils::write_json_file(self, path, 128 * 1024)?;
+        vfs::write_json_file(fs, self, path)?;
         trace!("cache file located: \"{}\"", path.display());
         Ok(())
     }
 
     /// Removes build infos which don't have any artifacts linked to them.
-    #[instrument(skip_all)]
     pub fn remove_outdated_builds(&mut self) {
+        self.remove_outdated_builds_in(&OsFileSystem)
+    }
+
+    /// Same as [`Self::remove_outdated_builds`], but removes the files from the given
+    /// [`FileSystem`].
+    #[instrument(skip_all)]
+    pub fn remove_outdated_builds_in(&mut self, fs: &dyn FileSystem) {
         let mut outdated = Vec::new();
         for build_id in &self.builds {
             if !self
@@ -177,7 +193,7 @@ impl<S: CompilerSettings> CompilerCache<S> {
         for build_id in outdated {
             self.builds.remove(&build_id);
             let path = self.paths.build_infos.join(build_id).with_extension("json");
-            let _ = std::fs::remove_file(path);
+            let _ = fs.remove_file(&path);
         }
     }
 
@@ -218,11 +234,16 @@ impl<S: CompilerSettings> CompilerCache<S> {
     /// Removes all `CacheEntry` which source files don't exist on disk
     ///
     /// **NOTE:** this assumes the `files` are absolute
-    #[instrument(skip_all)]
     pub fn remove_missing_files(&mut self) {
+        self.remove_missing_files_in(&OsFileSystem)
+    }
+
+    /// Same as [`Self::remove_missing_files`], but checks the given [`FileSystem`].
+    #[instrument(skip_all)]
+    pub fn remove_missing_files_in(&mut self, fs: &dyn FileSystem) {
         trace!("remove non existing files from cache");
         self.files.retain(|file, _| {
-            let exists = file.exists();
+            let exists = fs.exists(file);
             if !exists {
                 trace!("remove {} from cache", file.display());
             }
@@ -326,16 +347,24 @@ impl<S: CompilerSettings> CompilerCache<S> {
     /// let artifacts = cache.read_artifacts::<CompactContractBytecode>()?;
     /// # Ok::<_, Box<dyn std::error::Error>>(())
     /// ```
-    #[instrument(skip_all)]
     pub fn read_artifacts<Artifact: DeserializeOwned + Send + Sync>(
         &self,
+    ) -> Result<Artifacts<Artifact>> {
+        self.read_artifacts_in(&OsFileSystem)
+    }
+
+    /// Same as [`Self::read_artifacts`], but reads from the given [`FileSystem`].
+    #[instrument(skip_all)]
+    pub fn read_artifacts_in<Artifact: DeserializeOwned + Send + Sync>(
+        &self,
+        fs: &dyn FileSystem,
     ) -> Result<Artifacts<Artifact>> {
         use rayon::prelude::*;
 
         let artifacts = self
             .files
             .par_iter()
-            .map(|(file, entry)| entry.read_artifact_files().map(|files| (file.clone(), files)))
+            .map(|(file, entry)| entry.read_artifact_files(fs).map(|files| (file.clone(), files)))
             .collect::<Result<ArtifactsMap<_>>>()?;
         Ok(Artifacts(artifacts))
     }
@@ -344,14 +373,23 @@ impl<S: CompilerSettings> CompilerCache<S> {
     /// objects, so we are basically just partially deserializing build infos here.
     ///
     /// [BuildContext]: crate::buildinfo::BuildContext
-    #[instrument(skip_all)]
     pub fn read_builds<L: Language>(&self, build_info_dir: &Path) -> Result<Builds<L>> {
+        self.read_builds_in(&OsFileSystem, build_info_dir)
+    }
+
+    /// Same as [`Self::read_builds`], but reads from the given [`FileSystem`].
+    #[instrument(skip_all)]
+    pub fn read_builds_in<L: Language>(
+        &self,
+        fs: &dyn FileSystem,
+        build_info_dir: &Path,
+    ) -> Result<Builds<L>> {
         use rayon::prelude::*;
 
         self.builds
             .par_iter()
             .map(|build_id| {
-                utils::read_json_file(&build_info_dir.join(build_id).with_extension("json"))
+                vfs::read_json_file(fs, &build_info_dir.join(build_id).with_extension("json"))
                     .map(|b| (build_id.clone(), b))
             })
             .collect::<Result<_>>()
@@ -504,13 +542,14 @@ impl CacheEntry {
     #[instrument(skip_all)]
     fn read_artifact_files<Artifact: DeserializeOwned>(
         &self,
+        fs: &dyn FileSystem,
     ) -> Result<BTreeMap<String, Vec<ArtifactFile<Artifact>>>> {
         let mut artifacts = BTreeMap::new();
         for (artifact_name, versioned_files) in &self.artifacts {
             let mut files = Vec::with_capacity(versioned_files.len());
             for (version, cached_artifact) in versioned_files {
                 for (profile, cached_artifact) in cached_artifact {
-                    let artifact: Artifact = utils::read_json_file(&cached_artifact.path)?;
+                    let artifact: Artifact = vfs::read_json_file(fs, &cached_artifact.path)?;
                     files.push(ArtifactFile {
                         artifact,
                         file: cached_artifact.path.clone(),
@@ -640,6 +679,25 @@ impl GroupedSources {
     }
 }
 
+/// The cache state after a compilation, kept in memory to be reused by the next compilation
+/// instead of reading the cache file, artifacts and build infos from disk.
+#[derive(Debug)]
+pub(crate) struct InMemoryCache<S, A, L> {
+    /// The cache, with paths joined with the project root.
+    pub cache: CompilerCache<S>,
+    /// All artifacts of the cache entries.
+    pub artifacts: Artifacts<A>,
+    /// All build infos of the cache.
+    pub builds: Builds<L>,
+}
+
+/// The [`InMemoryCache`] of a project.
+pub(crate) type ProjectInMemoryCache<T, C> = InMemoryCache<
+    <C as Compiler>::Settings,
+    <T as ArtifactOutput>::Artifact,
+    <C as Compiler>::Language,
+>;
+
 /// A helper abstraction over the [`CompilerCache`] used to determine what files need to compiled
 /// and which `Artifacts` can be reused.
 #[derive(Debug)]
@@ -677,6 +735,9 @@ pub(crate) struct ArtifactsCacheInner<
 
     /// The interface representations for source files.
     pub interface_repr_hashes: HashMap<PathBuf, String>,
+
+    /// Where to keep the cache in memory after compilation, if it's reused.
+    pub in_memory: Option<&'a mut Option<ProjectInMemoryCache<T, C>>>,
 }
 
 impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
@@ -856,7 +917,7 @@ impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
 
         // Read all sources, marking entries as dirty on I/O errors.
         for file in &files {
-            let Ok(source) = Source::read(file) else {
+            let Ok(source) = self.project.paths.read_file(file) else {
                 self.dirty_sources.insert(file.clone());
                 continue;
             };
@@ -865,7 +926,9 @@ impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
 
         // Build a temporary graph for walking imports. We need this because `self.edges`
         // only contains graph data for in-scope sources but we are operating on cache entries.
-        if let Ok(graph) = Graph::<C::Parser>::resolve_sources(&self.project.paths, sources) {
+        if let Ok(graph) =
+            Graph::resolve_sources_with(&self.project.paths, sources, self.project.parser())
+        {
             let (sources, edges) = graph.into_sources();
 
             // Calculate content hashes for later comparison.
@@ -1043,11 +1106,15 @@ impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
     ArtifactsCache<'a, T, C>
 {
     /// Create a new cache instance with the given files
-    #[instrument(name = "ArtifactsCache::new", skip(project, edges))]
+    ///
+    /// If `in_memory` is set, the cache kept in it by the previous compilation is used instead of
+    /// reading it from disk, and the new cache is kept in it after compilation.
+    #[instrument(name = "ArtifactsCache::new", skip(project, edges, in_memory))]
     pub fn new(
         project: &'a Project<C, T>,
         edges: GraphEdges<C::Parser>,
         preprocessed: bool,
+        mut in_memory: Option<&'a mut Option<ProjectInMemoryCache<T, C>>>,
     ) -> Result<Self> {
         /// Returns the [CompilerCache] to use
         ///
@@ -1061,7 +1128,7 @@ impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
             let paths = project.paths.paths_relative();
 
             if !invalidate_cache
-                && project.cache_path().exists()
+                && project.paths.fs.exists(project.cache_path())
                 && let Ok(cache) = CompilerCache::read_joined(&project.paths)
                 && cache.paths == paths
                 && preprocessed == cache.preprocessed
@@ -1082,25 +1149,43 @@ impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
             // positives
             let invalidate_cache = !edges.unresolved_imports().is_empty();
 
-            // read the cache file if it already exists
-            let mut cache = get_cache(project, invalidate_cache, preprocessed);
-
-            cache.remove_missing_files();
+            let reused = in_memory.as_deref_mut().and_then(Option::take).filter(|reused| {
+                !invalidate_cache
+                    && reused.cache.paths == project.paths.paths_relative()
+                    && reused.cache.preprocessed == preprocessed
+            });
 
-            // read all artifacts
-            let mut cached_artifacts = if project.paths.artifacts.exists() {
-                trace!("reading artifacts from cache...");
-                // if we failed to read the whole set of artifacts we use an empty set
-                let artifacts = cache.read_artifacts::<T::Artifact>().unwrap_or_default();
-                trace!("read {} artifacts from cache", artifacts.artifact_files().count());
-                artifacts
+            let (cache, mut cached_artifacts, cached_builds) = if let Some(reused) = reused {
+                trace!("reusing in-memory cache");
+                let mut cache = reused.cache;
+                cache.remove_missing_files_in(&*project.paths.fs);
+                (cache, reused.artifacts, reused.builds)
             } else {
-                Default::default()
+                // read the cache file if it already exists
+                let mut cache = get_cache(project, invalidate_cache, preprocessed);
+
+                cache.remove_missing_files_in(&*project.paths.fs);
+
+                // read all artifacts
+                let cached_artifacts = if project.paths.fs.exists(&project.paths.artifacts) {
+                    trace!("reading artifacts from cache...");
+                    // if we failed to read the whole set of artifacts we use an empty set
+                    let artifacts = cache
+                        .read_artifacts_in::<T::Artifact>(&*project.paths.fs)
+                        .unwrap_or_default();
+                    trace!("read {} artifacts from cache", artifacts.artifact_files().count());
+                    artifacts
+                } else {
+                    Default::default()
+                };
+
+                trace!("reading build infos from cache...");
+                let cached_builds = cache
+                    .read_builds_in(&*project.paths.fs, &project.paths.build_infos)
+                    .unwrap_or_default();
+                (cache, cached_artifacts, cached_builds)
             };
 
-            trace!("reading build infos from cache...");
-            let cached_builds = cache.read_builds(&project.paths.build_infos).unwrap_or_default();
-
             // Remove artifacts for which we are missing a build info.
             cached_artifacts.0.retain(|_, artifacts| {
                 artifacts.retain(|_, artifacts| {
@@ -1120,6 +1205,7 @@ impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
                 content_hashes: Default::default(),
                 sources_in_scope: Default::default(),
                 interface_repr_hashes: Default::default(),
+                in_memory,
             };
 
             ArtifactsCache::Cached(cache)
@@ -1234,8 +1320,29 @@ impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
             edges,
             content_hashes: _,
             interface_repr_hashes: _,
+            in_memory,
         } = cache;
 
+        // The artifacts of cached files which are out of scope are kept in memory alongside their
+        // cache entries, so that they can be reused once these files are in scope again.
+        let mut out_of_scope = Artifacts::default();
+        if in_memory.is_some() {
+            for (file, artifacts) in &mut cached_artifacts.0 {
+                if dirty_sources.contains(file) || !cache.files.contains_key(file) {
+                    continue;
+                }
+                for (name, artifacts) in artifacts {
+                    let (kept, in_scope): (Vec<_>, _) = std::mem::take(artifacts)
+                        .into_iter()
+                        .partition(|artifact| !sources_in_scope.contains(file, &artifact.version));
+                    *artifacts = in_scope;
+                    if !kept.is_empty() {
+                        out_of_scope.0.entry(file.clone()).or_default().insert(name.clone(), kept);
+                    }
+                }
+            }
+        }
+
         // Remove cached artifacts which are out of scope, dirty or appear in `written_artifacts`.
         cached_artifacts.0.retain(|file, artifacts| {
             let file = Path::new(file);
@@ -1276,18 +1383,49 @@ impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
 
         // write to disk
         if write_to_disk {
-            cache.remove_outdated_builds();
+            cache.remove_outdated_builds_in(&*project.paths.fs);
+            // the artifacts in scope are added by the caller, since they're not cloneable
+            let kept = in_memory.map(|in_memory| {
+                let builds = cached_builds
+                    .iter()
+                    .map(|(id, context)| (id.clone(), context.clone()))
+                    .chain(written_build_infos.iter().map(|build_info| {
+                        (build_info.id.clone(), build_info.build_context.clone())
+                    }))
+                    .filter(|(id, _)| cache.builds.contains(id))
+                    .collect();
+                (
+                    in_memory,
+                    InMemoryCache {
+                        cache: cache.clone(),
+                        artifacts: out_of_scope,
+                        builds: Builds(builds),
+                    },
+                )
+            });
             // make all `CacheEntry` paths relative to the project root and all artifact
             // paths relative to the artifact's directory
             cache
                 .strip_entries_prefix(project.root())
                 .strip_artifact_files_prefixes(project.artifacts_path());
-            cache.write(project.cache_path())?;
+            cache.write_in(&*project.paths.fs, project.cache_path())?;
+            if let Some((in_memory, kept)) = kept {
+                *in_memory = Some(kept);
+            }
         }
 
         Ok((cached_artifacts, cached_builds, edges))
     }
 
+    /// Removes the entries of the given files, so that they're not persisted.
+    pub fn remove_entries<'b>(&mut self, files: impl IntoIterator<Item = &'b PathBuf>) {
+        if let ArtifactsCache::Cached(cache) = self {
+            for file in files {
+                cache.cache.remove(file);
+            }
+        }
+    }
+
     /// Marks the cached entry as seen by the compiler, if it's cached.
     pub fn compiler_seen(&mut self, file: &Path) {
         if let ArtifactsCache::Cached(cache) = self
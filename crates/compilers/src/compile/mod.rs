@@ -3,4 +3,7 @@ pub mod many;
 pub mod output;
 pub use output::{contracts, info, sources};
 
+pub mod preflight;
+pub use preflight::PreflightMode;
+
 pub mod project;
@@ -1,13 +1,16 @@
 //! The output of a compiled project
 use contracts::{VersionedContract, VersionedContracts};
 use foundry_compilers_artifacts::{CompactContractBytecode, CompactContractRef, Severity};
-use foundry_compilers_core::error::{SolcError, SolcIoError};
+use foundry_compilers_core::{
+    error::{SolcError, SolcIoError},
+    vfs::{FileSystem, OsFileSystem},
+};
 use info::ContractInfoRef;
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use sources::{VersionedSourceFile, VersionedSourceFiles};
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashSet},
     fmt,
     ops::{Deref, DerefMut},
     path::{Path, PathBuf},
@@ -86,6 +89,8 @@ pub struct ProjectCompileOutput<
     pub(crate) builds: Builds<C::Language>,
     /// The relationship between the source files and their imports
     pub(crate) edges: GraphEdges<C::Parser>,
+    /// Files which are overlaid or import overlaid files
+    pub(crate) overlay_dependents: HashSet<PathBuf>,
 }
 
 impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
@@ -480,6 +485,18 @@ impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
     pub fn graph(&self) -> &GraphEdges<C::Parser> {
         &self.edges
     }
+
+    /// Returns all files whose output depends on the project's
+    /// [`SourceOverlay`](crate::SourceOverlay), because they're overlaid or import an overlaid
+    /// file.
+    pub fn overlay_dependents(&self) -> &HashSet<PathBuf> {
+        &self.overlay_dependents
+    }
+
+    /// Returns `true` if the output of the given file depends on overlaid content.
+    pub fn depends_on_overlay(&self, file: &Path) -> bool {
+        self.overlay_dependents.contains(file)
+    }
 }
 
 impl<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>>
@@ -638,16 +655,36 @@ impl<C: Compiler> AggregatedCompilerOutput<C> {
     ///
     /// The created files have a unique identifier as their name.
     pub fn write_build_infos(&self, build_info_dir: &Path) -> Result<(), SolcError> {
-        if self.build_infos.is_empty() {
+        self.write_build_infos_in(&OsFileSystem, build_info_dir)
+    }
+
+    /// Same as [`Self::write_build_infos`], but writes to the given [`FileSystem`].
+    pub fn write_build_infos_in(
+        &self,
+        fs: &dyn FileSystem,
+        build_info_dir: &Path,
+    ) -> Result<(), SolcError> {
+        self.write_build_infos_filtered(fs, build_info_dir, |_| true)
+    }
+
+    /// Same as [`Self::write_build_infos_in`], but only writes the build infos matching `filter`.
+    pub(crate) fn write_build_infos_filtered(
+        &self,
+        fs: &dyn FileSystem,
+        build_info_dir: &Path,
+        filter: impl Fn(&RawBuildInfo<C::Language>) -> bool,
+    ) -> Result<(), SolcError> {
+        let mut build_infos =
+            self.build_infos.iter().filter(|build_info| filter(build_info)).peekable();
+        if build_infos.peek().is_none() {
             return Ok(());
         }
-        std::fs::create_dir_all(build_info_dir)
-            .map_err(|err| SolcIoError::new(err, build_info_dir))?;
-        for build_info in &self.build_infos {
+        fs.create_dir_all(build_info_dir).map_err(|err| SolcIoError::new(err, build_info_dir))?;
+        for build_info in build_infos {
             trace!("writing build info file {}", build_info.id);
             let file_name = format!("{}.json", build_info.id);
             let file = build_info_dir.join(file_name);
-            std::fs::write(&file, &serde_json::to_string(build_info)?)
+            fs.write(&file, serde_json::to_string(build_info)?.as_bytes())
                 .map_err(|err| SolcIoError::new(err, file))?;
         }
         Ok(())
@@ -0,0 +1,238 @@
+//! Pre-flight semantic check of Solidity sources.
+//!
+//! Before spawning solc, the dirty sources can be parsed and analyzed with [solar](solar), which
+//! takes milliseconds instead of seconds. Errors found this way, like unresolved imports,
+//! undeclared identifiers or type mismatches, are reported in the
+//! [`AggregatedCompilerOutput`](crate::AggregatedCompilerOutput) like compiler errors, or only
+//! to the [reporter](crate::report) if they shouldn't affect the build.
+
+use crate::{ProjectPathsConfig, SourceParser, resolver::parse::SolParser};
+use foundry_compilers_artifacts::{Error, Severity, error::SourceLocation, sources::Sources};
+use foundry_compilers_core::{
+    error::{Result, SolcError, SolcIoError},
+    utils::strip_prefix,
+};
+use solar::interface::{
+    ColorChoice, SourceMap,
+    diagnostics::{Diag, Emitter, HumanBufferEmitter},
+    source_map::FileName,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// How the solar pre-flight check affects compilation, see
+/// [`ProjectBuilder::preflight`](crate::ProjectBuilder::preflight).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PreflightMode {
+    /// Report errors found by solar as warnings to the
+    /// [reporter](crate::report::Reporter::on_preflight_diagnostics) and invoke the compiler
+    /// regardless.
+    ///
+    /// The warnings are not added to the compiler output, where they would duplicate the errors
+    /// reported by the compiler and fail builds which deny warnings. Useful if solar rejects
+    /// valid code which it doesn't support yet.
+    WarnOnly,
+    /// Report errors found by solar and don't invoke the compiler for the sources which would be
+    /// compiled together with the files with errors. Sources compiled with other versions or
+    /// settings are still compiled.
+    FailFast,
+}
+
+impl PreflightMode {
+    /// Returns the severity errors found by solar are reported with.
+    pub fn severity(self) -> Severity {
+        match self {
+            Self::WarnOnly => Severity::Warning,
+            Self::FailFast => Severity::Error,
+        }
+    }
+}
+
+/// Parses and analyzes the given Solidity sources and their imports with solar.
+///
+/// Returns the errors found, with the given severity and paths relative to the project root.
+pub(crate) fn check<L>(
+    paths: &ProjectPathsConfig<L>,
+    sources: &Sources,
+    severity: Severity,
+) -> Result<Vec<Error>> {
+    let mut compiler = SolParser::new(paths.with_language_ref()).into_compiler();
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let source_map = compiler.sess().clone_source_map();
+    compiler.dcx().set_emitter(Box::new(PreflightEmitter {
+        human: HumanBufferEmitter::new(ColorChoice::Never).source_map(Some(source_map.clone())),
+        source_map,
+        root: paths.root.clone(),
+        severity,
+        errors: errors.clone(),
+    }));
+    // Don't leak solar's internal locations into the messages in debug builds.
+    compiler.dcx().set_flags(|flags| flags.track_diagnostics = false);
+
+    compiler.enter_mut(|compiler| -> Result<()> {
+        let mut pcx = compiler.parse();
+        for (path, source) in sources {
+            let file = pcx
+                .sess
+                .source_map()
+                .new_source_file(path.clone(), source.content.as_str())
+                .map_err(|e| SolcError::Io(SolcIoError::new(e, path)))?;
+            pcx.add_file(file);
+        }
+        pcx.parse();
+
+        // Errors are collected by the emitter, so the results only tell whether to continue.
+        if let Ok(flow) = compiler.lower_asts()
+            && flow.is_continue()
+        {
+            let _ = compiler.analysis();
+        }
+        Ok(())
+    })?;
+
+    Ok(std::mem::take(&mut *errors.lock().unwrap_or_else(PoisonError::into_inner)))
+}
+
+/// Collects solar error diagnostics as solc [`Error`]s.
+struct PreflightEmitter {
+    /// Renders the formatted message of each error.
+    human: HumanBufferEmitter,
+    source_map: Arc<SourceMap>,
+    root: PathBuf,
+    severity: Severity,
+    errors: Arc<Mutex<Vec<Error>>>,
+}
+
+impl PreflightEmitter {
+    fn source_location(&self, diagnostic: &Diag) -> Option<SourceLocation> {
+        let span = diagnostic.span.primary_span()?;
+        let source = self.source_map.span_to_source(span).ok()?;
+        let FileName::Real(path) = &source.file.name else { return None };
+        Some(SourceLocation {
+            file: strip_prefix(path, &self.root).to_string_lossy().into_owned(),
+            start: source.data.start as i32,
+            end: source.data.end as i32,
+        })
+    }
+}
+
+impl Emitter for PreflightEmitter {
+    fn emit_diagnostic(&mut self, diagnostic: &mut Diag) {
+        if !diagnostic.is_error() {
+            return;
+        }
+
+        self.human.buffer_mut().clear();
+        self.human.emit_diagnostic(diagnostic);
+        let error = Error {
+            source_location: self.source_location(diagnostic),
+            secondary_source_locations: Vec::new(),
+            r#type: "Error".to_string(),
+            component: "solar".to_string(),
+            severity: self.severity,
+            error_code: None,
+            message: diagnostic.label().into_owned(),
+            formatted_message: Some(self.human.buffer().to_string()),
+        };
+        self.errors.lock().unwrap_or_else(PoisonError::into_inner).push(error);
+    }
+
+    fn source_map(&self) -> Option<&Arc<SourceMap>> {
+        Some(&self.source_map)
+    }
+}
+
+/// Returns whether the given path is a Solidity source which can be checked by solar.
+pub(crate) fn is_solidity(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "sol")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::{
+        CompilationError, ProjectBuilder,
+        multi::MultiCompiler,
+        report::{self, Report, Reporter},
+        test_utils::{multi_compiler, write_script},
+        vyper::Vyper,
+    };
+    use foundry_compilers_core::utils;
+    use semver::Version;
+    use std::fs;
+
+    #[test]
+    fn can_check_before_compiling() {
+        let tmp = utils::tempdir("preflight").unwrap();
+        // `solc` and `vyper` stubs which record their compilations and return an empty output.
+        let [solc, vyper] = ["solc", "vyper"].map(|name| {
+            let path = tmp.path().join(name);
+            write_script(
+                &path,
+                r#"if [ "$1" = "--version" ]; then echo "Version: 0.8.28+commit.7893614a"; exit 0; fi
+cat > /dev/null
+echo "$(basename "$0")" >> "$(dirname "$0")/calls"
+echo '{}'
+"#,
+            );
+            path
+        });
+        let calls = || fs::read_to_string(tmp.path().join("calls")).unwrap_or_default();
+
+        let root = tmp.path().join("project");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(
+            root.join("src/A.sol"),
+            "pragma solidity ^0.8.0;\ncontract A { function f() public { y = 1; } }\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/B.sol"), "pragma solidity ^0.8.0;\ncontract B {}\n").unwrap();
+        fs::write(root.join("src/C.vy"), "# pragma version ^0.4.0\n").unwrap();
+
+        let compiler = MultiCompiler {
+            vyper: Some(Vyper { path: vyper, version: Version::new(0, 4, 0) }),
+            ..multi_compiler(&solc)
+        };
+        let mut project = ProjectBuilder::<MultiCompiler>::default()
+            .paths(ProjectPathsConfig::dapptools(&root).unwrap())
+            .preflight(PreflightMode::FailFast)
+            .build(compiler)
+            .unwrap();
+
+        let output = project.compile().unwrap();
+        assert!(output.has_compiler_errors());
+        let errors = &output.output().errors;
+        assert!(errors.iter().all(CompilationError::is_error), "{errors:?}");
+        let location = errors[0].source_location().unwrap();
+        assert_eq!(location.file, "src/A.sol");
+        assert!(output.to_string().contains("unresolved symbol `y`"), "{output}");
+        // only the sources compiled with the same version as the erroneous file are skipped
+        assert_eq!(calls(), "vyper\n");
+
+        project.preflight = Some(PreflightMode::WarnOnly);
+        let reporter = DiagnosticsReporter::default();
+        let output =
+            report::with_scoped(&Report::new(reporter.clone()), || project.compile().unwrap());
+        assert!(!output.has_compiler_errors(), "{output}");
+        assert!(!output.has_compiler_warnings(), "{output}");
+        let mut all_calls = calls().lines().map(str::to_string).collect::<Vec<_>>();
+        all_calls.sort();
+        assert_eq!(all_calls, ["solc", "vyper", "vyper"]);
+        let diagnostics = reporter.0.lock().unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert!(diagnostics[0].message.contains("unresolved symbol `y`"), "{diagnostics:?}");
+    }
+
+    /// Records the pre-flight diagnostics it's invoked with.
+    #[derive(Clone, Debug, Default)]
+    struct DiagnosticsReporter(Arc<Mutex<Vec<Error>>>);
+
+    impl Reporter for DiagnosticsReporter {
+        fn on_preflight_diagnostics(&self, diagnostics: &[Error]) {
+            self.0.lock().unwrap().extend_from_slice(diagnostics);
+        }
+    }
+}
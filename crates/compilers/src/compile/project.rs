@@ -102,23 +102,31 @@
 
 use crate::{
     ArtifactOutput, CompilerSettings, Graph, Project, ProjectCompileOutput, ProjectPathsConfig,
-    Sources,
+    SourceUnitNames, Sources,
     artifact_output::Artifacts,
     buildinfo::RawBuildInfo,
-    cache::ArtifactsCache,
-    compilers::{Compiler, CompilerInput, CompilerOutput, Language},
+    cache::{ArtifactsCache, ProjectInMemoryCache},
+    compile::preflight::{self, PreflightMode},
+    compilers::{CompilationError, Compiler, CompilerInput, CompilerOutput, Language},
     filter::SparseOutputFilter,
-    output::{AggregatedCompilerOutput, Builds},
+    output::{
+        AggregatedCompilerOutput, Builds, contracts::VersionedContracts,
+        sources::VersionedSourceFiles,
+    },
     report,
-    resolver::{GraphEdges, ResolvedSources},
+    resolver::{CompilerLock, GraphEdges, ResolvedSources, check_solc_precedence},
+};
+use foundry_compilers_artifacts::{SolcVfs, sources::SourceCompilationKind};
+use foundry_compilers_core::{
+    error::{Result, SolcError},
+    utils,
 };
-use foundry_compilers_core::error::Result;
 use rayon::prelude::*;
 use semver::Version;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::Debug,
-    path::PathBuf,
+    path::{Path, PathBuf},
     time::Instant,
 };
 
@@ -154,6 +162,10 @@ pub struct ProjectCompiler<
     sources: CompilerSources<'a, C::Language, C::Settings>,
     /// Optional preprocessor
     preprocessor: Option<Box<dyn Preprocessor<C>>>,
+    /// Where the cache is kept in memory between compilations, if it's reused.
+    in_memory_cache: Option<&'a mut Option<ProjectInMemoryCache<T, C>>>,
+    /// The updated compiler lock, written once the sources compiled successfully.
+    lock: Option<CompilerLock<C::Language>>,
 }
 
 impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
@@ -176,8 +188,8 @@ impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
         if let Some(filter) = &project.sparse_output {
             sources.retain(|f, _| filter.is_match(f))
         }
-        let graph = Graph::resolve_sources(&project.paths, sources)?;
-        let ResolvedSources { sources, primary_profiles, edges } =
+        let graph = Graph::resolve_sources_with(&project.paths, sources, project.parser())?;
+        let ResolvedSources { sources, primary_profiles, edges, lock } =
             graph.into_sources_by_version(project)?;
 
         // If there are multiple different versions, and we can use multiple jobs we can compile
@@ -188,13 +200,31 @@ impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
             sources,
         };
 
-        Ok(Self { edges, primary_profiles, project, sources, preprocessor: None })
+        Ok(Self {
+            edges,
+            primary_profiles,
+            project,
+            sources,
+            preprocessor: None,
+            in_memory_cache: None,
+            lock,
+        })
     }
 
     pub fn with_preprocessor(self, preprocessor: impl Preprocessor<C> + 'static) -> Self {
         Self { preprocessor: Some(Box::new(preprocessor)), ..self }
     }
 
+    /// Reuses the cache kept in memory by a previous compilation instead of reading it from disk,
+    /// and keeps the updated cache in memory.
+    #[cfg(feature = "watch")]
+    pub(crate) fn with_in_memory_cache(
+        self,
+        in_memory_cache: &'a mut Option<ProjectInMemoryCache<T, C>>,
+    ) -> Self {
+        Self { in_memory_cache: Some(in_memory_cache), ..self }
+    }
+
     /// Compiles all the sources of the `Project` in the appropriate mode
     ///
     /// If caching is enabled, the sources are filtered and only _dirty_ sources are recompiled.
@@ -211,12 +241,21 @@ impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
     #[instrument(name = "compile_project", skip_all)]
-    pub fn compile(self) -> Result<ProjectCompileOutput<C, T>> {
-        let slash_paths = self.project.slash_paths;
+    pub fn compile(mut self) -> Result<ProjectCompileOutput<C, T>> {
+        let project = self.project;
+        let slash_paths = project.slash_paths;
+        let lock = self.lock.take();
 
         // drive the compiler statemachine to completion
         let mut output = self.preprocess()?.compile()?.write_artifacts()?.write_cache()?;
 
+        // the resolved versions are only locked once they're known to compile
+        if let Some((lock, lockfile)) = lock.zip(project.lockfile.as_ref())
+            && !output.has_compiler_errors()
+        {
+            lock.write_in(&*project.paths.fs, lockfile)?;
+        }
+
         if slash_paths {
             // ensures we always use `/` paths
             output.slash_paths();
@@ -231,13 +270,22 @@ impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
     #[instrument(skip_all)]
     fn preprocess(self) -> Result<PreprocessedState<'a, T, C>> {
         trace!("preprocessing");
-        let Self { edges, project, mut sources, primary_profiles, preprocessor } = self;
+        let Self {
+            edges,
+            project,
+            mut sources,
+            primary_profiles,
+            preprocessor,
+            in_memory_cache,
+            lock: _,
+        } = self;
 
         // convert paths on windows to ensure consistency with the `CompilerOutput` `solc` emits,
         // which is unix style `/`
         sources.slash_paths();
 
-        let mut cache = ArtifactsCache::new(project, edges, preprocessor.is_some())?;
+        let mut cache =
+            ArtifactsCache::new(project, edges, preprocessor.is_some(), in_memory_cache)?;
         // retain and compile only dirty sources and all their imports
         sources.filter(&mut cache);
 
@@ -271,9 +319,35 @@ impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
     #[instrument(skip_all)]
     fn compile(self) -> Result<CompiledState<'a, T, C>> {
         trace!("compiling");
-        let PreprocessedState { sources, mut cache, primary_profiles, preprocessor } = self;
+        let PreprocessedState { mut sources, mut cache, primary_profiles, preprocessor } = self;
 
-        let mut output = sources.compile(&mut cache, preprocessor)?;
+        let project = cache.project();
+        let overlay = &project.paths.overlay;
+        let overlay_dependents = if overlay.is_empty() {
+            HashSet::new()
+        } else {
+            cache.graph().overlay_dependents(overlay)
+        };
+        // outputs depending on overlaid content are only kept in memory
+        let in_memory =
+            if overlay.is_persisted() { HashSet::new() } else { overlay_dependents.clone() };
+
+        let preflight_errors = match project.preflight {
+            Some(mode @ PreflightMode::FailFast) => {
+                let errors = sources.preflight(project, mode)?;
+                sources.remove_failed_preflight(&errors, project.root());
+                errors.into_iter().filter_map(C::CompilationError::from_solc_error).collect()
+            }
+            Some(mode @ PreflightMode::WarnOnly) => {
+                report::preflight_diagnostics(&sources.preflight(project, mode)?);
+                Vec::new()
+            }
+            None => Vec::new(),
+        };
+
+        let (mut output, in_memory_builds) =
+            sources.compile(&mut cache, preprocessor, &in_memory)?;
+        output.errors.splice(0..0, preflight_errors);
 
         // source paths get stripped before handing them over to solc, so solc never uses absolute
         // paths, instead `--base-path <root dir>` is set. this way any metadata that's derived from
@@ -282,7 +356,7 @@ impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
         // contracts again
         output.join_all(cache.project().root());
 
-        Ok(CompiledState { output, cache, primary_profiles })
+        Ok(CompiledState { output, cache, primary_profiles, overlay_dependents, in_memory_builds })
     }
 }
 
@@ -292,6 +366,10 @@ struct CompiledState<'a, T: ArtifactOutput<CompilerContract = C::CompilerContrac
     output: AggregatedCompilerOutput<C>,
     cache: ArtifactsCache<'a, T, C>,
     primary_profiles: HashMap<PathBuf, &'a str>,
+    /// Files which are overlaid or import overlaid files.
+    overlay_dependents: HashSet<PathBuf>,
+    /// Ids of the builds which contain overlaid content that is not persisted.
+    in_memory_builds: HashSet<String>,
 }
 
 impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
@@ -303,10 +381,12 @@ impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
     /// successful
     #[instrument(skip_all)]
     fn write_artifacts(self) -> Result<ArtifactsState<'a, T, C>> {
-        let CompiledState { output, cache, primary_profiles } = self;
+        let CompiledState { output, cache, primary_profiles, overlay_dependents, in_memory_builds } =
+            self;
 
         let project = cache.project();
         let ctx = cache.output_ctx();
+        let overlay = &project.paths.overlay;
         // write all artifacts via the handler but only if the build succeeded and project wasn't
         // configured with `no_artifacts == true`
         let compiled_artifacts = if project.no_artifacts {
@@ -331,6 +411,53 @@ impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
                 &project.paths,
                 &primary_profiles,
             )
+        } else if !overlay_dependents.is_empty() && !overlay.is_persisted() {
+            trace!(
+                "keeping output of {} overlay dependent files in memory",
+                overlay_dependents.len()
+            );
+            let (overlay_contracts, contracts): (BTreeMap<_, _>, BTreeMap<_, _>) = output
+                .contracts
+                .0
+                .iter()
+                .map(|(file, contracts)| (file.clone(), contracts.clone()))
+                .partition(|(file, _)| overlay_dependents.contains(file));
+            let (overlay_sources, sources): (BTreeMap<_, _>, BTreeMap<_, _>) = output
+                .sources
+                .0
+                .iter()
+                .map(|(file, sources)| (file.clone(), sources.clone()))
+                .partition(|(file, _)| overlay_dependents.contains(file));
+
+            let mut artifacts = project.artifacts_handler().on_output(
+                &VersionedContracts(contracts),
+                &VersionedSourceFiles(sources),
+                &project.paths,
+                ctx.clone(),
+                &primary_profiles,
+            )?;
+            artifacts.0.extend(
+                project
+                    .artifacts_handler()
+                    .output_to_artifacts(
+                        &VersionedContracts(overlay_contracts),
+                        &VersionedSourceFiles(overlay_sources),
+                        ctx,
+                        &project.paths,
+                        &primary_profiles,
+                    )
+                    .0,
+            );
+
+            // the other files are compiled separately, so only the build infos of the overlay
+            // dependent files contain overlaid content
+            output.write_build_infos_filtered(
+                &*project.paths.fs,
+                project.build_info_path(),
+                |build_info| !in_memory_builds.contains(&build_info.id),
+            )?;
+
+            artifacts
         } else {
             trace!(
                 "handling artifact output for {} contracts and {} sources",
@@ -347,12 +474,18 @@ impl<'a, T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
             )?;
 
             // emits all the build infos, if they exist
-            output.write_build_infos(project.build_info_path())?;
+            output.write_build_infos_in(&*project.paths.fs, project.build_info_path())?;
 
             artifacts
         };
 
-        Ok(ArtifactsState { output, cache, compiled_artifacts })
+        Ok(ArtifactsState {
+            output,
+            cache,
+            compiled_artifacts,
+            overlay_dependents,
+            in_memory_builds,
+        })
     }
 }
 
@@ -362,6 +495,10 @@ struct ArtifactsState<'a, T: ArtifactOutput<CompilerContract = C::CompilerContra
     output: AggregatedCompilerOutput<C>,
     cache: ArtifactsCache<'a, T, C>,
     compiled_artifacts: Artifacts<T::Artifact>,
+    /// Files which are overlaid or import overlaid files.
+    overlay_dependents: HashSet<PathBuf>,
+    /// Ids of the builds which contain overlaid content that is not persisted.
+    in_memory_builds: HashSet<String>,
 }
 
 impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
@@ -372,7 +509,13 @@ impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
     /// this concludes the [`Project::compile()`] statemachine
     #[instrument(skip_all)]
     fn write_cache(self) -> Result<ProjectCompileOutput<C, T>> {
-        let ArtifactsState { output, cache, compiled_artifacts } = self;
+        let ArtifactsState {
+            output,
+            mut cache,
+            compiled_artifacts,
+            overlay_dependents,
+            in_memory_builds,
+        } = self;
         let project = cache.project();
         let ignored_error_codes = project.ignored_error_codes.clone();
         let ignored_error_codes_from = project.ignored_error_codes_from.clone();
@@ -387,10 +530,25 @@ impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
         let skip_write_to_disk = project.no_artifacts || has_error;
         trace!(has_error, project.no_artifacts, skip_write_to_disk, cache_path=?project.cache_path(),"prepare writing cache file");
 
+        // outputs depending on overlaid content are only kept in memory
+        let keep_in_memory =
+            !overlay_dependents.is_empty() && !project.paths.overlay.is_persisted();
+        if keep_in_memory {
+            cache.remove_entries(&overlay_dependents);
+        }
+        let written_build_infos = output
+            .build_infos
+            .iter()
+            .filter(|build_info| !in_memory_builds.contains(&build_info.id))
+            .cloned()
+            .collect();
+
         let (cached_artifacts, cached_builds, edges) =
-            cache.consume(&compiled_artifacts, &output.build_infos, !skip_write_to_disk)?;
+            cache.consume(&compiled_artifacts, &written_build_infos, !skip_write_to_disk)?;
 
-        project.artifacts_handler().handle_cached_artifacts(&cached_artifacts)?;
+        project
+            .artifacts_handler()
+            .handle_cached_artifacts_in(&*project.paths.fs, &cached_artifacts)?;
 
         let builds = Builds(
             output
@@ -412,6 +570,7 @@ impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler>
             compiler_severity_filter,
             builds,
             edges,
+            overlay_dependents,
         })
     }
 }
@@ -471,6 +630,72 @@ impl<L: Language, S: CompilerSettings> CompilerSources<'_, L, S> {
         }
     }
 
+    /// Checks the dirty Solidity sources with solar, see [`PreflightMode`].
+    ///
+    /// Sources compiled with solc older than 0.8 are skipped, since solar doesn't support them.
+    #[instrument(name = "CompilerSources::preflight", skip_all)]
+    fn preflight<
+        C: Compiler<Language = L, Settings = S>,
+        T: ArtifactOutput<CompilerContract = C::CompilerContract>,
+    >(
+        &self,
+        project: &Project<C, T>,
+        mode: PreflightMode,
+    ) -> Result<Vec<foundry_compilers_artifacts::Error>> {
+        let min_version = Version::new(0, 8, 0);
+        let sources = self
+            .sources
+            .values()
+            .flatten()
+            .filter(|(version, _, _)| *version >= min_version)
+            .flat_map(|(_, sources, _)| sources.dirty())
+            .filter(|(path, _)| preflight::is_solidity(path))
+            .map(|(path, source)| (path.clone(), source.clone()))
+            .collect::<Sources>();
+        if sources.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let start = Instant::now();
+        let errors = preflight::check(&project.paths, &sources, mode.severity())?;
+        trace!("pre-flight check of {} sources took {:?}", sources.len(), start.elapsed());
+
+        Ok(errors)
+    }
+
+    /// Removes the version groups containing files with any of the given pre-flight errors, so
+    /// that only the other groups are compiled.
+    ///
+    /// Errors without a source location can't be attributed to a group and remove all groups.
+    fn remove_failed_preflight(
+        &mut self,
+        errors: &[foundry_compilers_artifacts::Error],
+        root: &Path,
+    ) {
+        let mut failed = HashSet::new();
+        for error in errors.iter().filter(|error| error.severity.is_error()) {
+            match &error.source_location {
+                Some(location) => {
+                    failed.insert(root.join(&location.file));
+                }
+                None => {
+                    trace!("skip compiling due to pre-flight error without location");
+                    self.sources.clear();
+                    return;
+                }
+            }
+        }
+        for versioned_sources in self.sources.values_mut() {
+            versioned_sources.retain(|(version, sources, _)| {
+                let skip = sources.keys().any(|file| failed.contains(file));
+                if skip {
+                    trace!("skip compiling {} due to pre-flight errors", version);
+                }
+                !skip
+            });
+        }
+    }
+
     /// Compiles all the files with `Solc`
     fn compile<
         C: Compiler<Language = L, Settings = S>,
@@ -479,7 +704,8 @@ impl<L: Language, S: CompilerSettings> CompilerSources<'_, L, S> {
         self,
         cache: &mut ArtifactsCache<'_, T, C>,
         preprocessor: Option<Box<dyn Preprocessor<C>>>,
-    ) -> Result<AggregatedCompilerOutput<C>> {
+        in_memory: &HashSet<PathBuf>,
+    ) -> Result<(AggregatedCompilerOutput<C>, HashSet<String>)> {
         let project = cache.project();
         let graph = cache.graph();
 
@@ -490,6 +716,37 @@ impl<L: Language, S: CompilerSettings> CompilerSources<'_, L, S> {
         // Include additional paths collected during graph resolution.
         let mut include_paths = project.paths.include_paths.clone();
         include_paths.extend(graph.include_paths().clone());
+        let mut allowed_paths = project.paths.allowed_paths.clone();
+
+        // Source unit names are computed from the paths on disk, even if the sources are
+        // materialized in a temporary directory below.
+        let vfs = match project.paths.source_unit_names {
+            SourceUnitNames::StripRoot => None,
+            SourceUnitNames::Solc => {
+                // solc is invoked in the project root
+                let vfs = SolcVfs::new(&project.paths.root, &project.paths.root)
+                    .with_include_paths(&include_paths);
+                check_solc_precedence(&project.paths.remappings, &vfs)?;
+                Some(vfs)
+            }
+        };
+
+        // Compilers may read files from disk, so the sources of a virtual file system are written
+        // to a temporary directory which replaces the project root.
+        let materialized = if project.paths.fs.is_virtual() {
+            let dir =
+                tempfile::tempdir().map_err(|err| SolcError::io(err, std::env::temp_dir()))?;
+            let rebase = |path: &PathBuf| {
+                dir.path().join(path.strip_prefix(&project.paths.root).unwrap_or(path))
+            };
+            include_paths = include_paths.iter().map(rebase).collect();
+            allowed_paths = allowed_paths.iter().map(rebase).collect();
+            Some(dir)
+        } else {
+            None
+        };
+        let base_path =
+            materialized.as_ref().map_or(project.paths.root.as_path(), |dir| dir.path());
 
         // Get current list of mocks from cache. This will be passed to preprocessors and updated
         // accordingly, then set back in cache.
@@ -497,6 +754,12 @@ impl<L: Language, S: CompilerSettings> CompilerSources<'_, L, S> {
 
         let mut jobs = Vec::new();
         for (language, versioned_sources) in self.sources {
+            let versioned_sources =
+                versioned_sources.into_iter().flat_map(|(version, sources, settings)| {
+                    split_in_memory(sources, in_memory)
+                        .into_iter()
+                        .map(move |sources| (version.clone(), sources, settings))
+                });
             for (version, sources, (profile, opt_settings)) in versioned_sources {
                 let mut opt_settings = opt_settings.clone();
                 if sources.is_empty() {
@@ -520,14 +783,36 @@ impl<L: Language, S: CompilerSettings> CompilerSources<'_, L, S> {
                 trace!("calling {} with {} sources {:?}", version, sources.len(), sources.keys());
 
                 let settings = opt_settings
-                    .with_base_path(&project.paths.root)
-                    .with_allow_paths(&project.paths.allowed_paths)
+                    .with_base_path(base_path)
+                    .with_allow_paths(&allowed_paths)
                     .with_include_paths(&include_paths)
                     .with_remappings(&project.paths.remappings);
 
                 let mut input = C::Input::build(sources, settings, language, version.clone());
 
-                input.strip_prefix(project.paths.root.as_path());
+                match &vfs {
+                    Some(vfs) => input.apply_vfs(vfs),
+                    None => input.strip_prefix(project.paths.root.as_path()),
+                }
+
+                if materialized.is_some() {
+                    for (name, source) in input.sources() {
+                        // files outside the root keep their absolute names, which compilers would
+                        // read from disk
+                        if !name.is_relative() {
+                            return Err(SolcError::msg(format!(
+                                "virtual file {} is outside of the project root {} and can't be \
+                                 passed to the compiler",
+                                name.display(),
+                                project.paths.root.display()
+                            )));
+                        }
+                        let file = base_path.join(name);
+                        utils::create_parent_dir_all(&file)?;
+                        std::fs::write(&file, source.content.as_bytes())
+                            .map_err(|err| SolcError::io(err, file))?;
+                    }
+                }
 
                 if let Some(preprocessor) = preprocessor.as_ref() {
                     preprocessor.preprocess(
@@ -545,6 +830,16 @@ impl<L: Language, S: CompilerSettings> CompilerSources<'_, L, S> {
         // Update cache with mocks updated by preprocessors.
         cache.update_mocks(mocks);
 
+        if let Some(mirror) = project.offline_mirror.as_deref() {
+            let versions = jobs
+                .iter()
+                .map(|(input, _, _)| (input.language(), input.version().clone()))
+                .collect::<HashSet<_>>();
+            for (language, version) in versions {
+                project.compiler.install_from_mirror(&language, &version, mirror)?;
+            }
+        }
+
         let results = if let Some(num_jobs) = jobs_cnt {
             compile_parallel(&project.compiler, jobs, num_jobs)
         } else {
@@ -552,6 +847,7 @@ impl<L: Language, S: CompilerSettings> CompilerSources<'_, L, S> {
         }?;
 
         let mut aggregated = AggregatedCompilerOutput::default();
+        let mut in_memory_builds = HashSet::new();
 
         for (input, mut output, profile, actually_dirty) in results {
             let version = input.version();
@@ -562,19 +858,62 @@ impl<L: Language, S: CompilerSettings> CompilerSources<'_, L, S> {
             }
 
             let build_info = RawBuildInfo::new(&input, &output, project.build_info)?;
+            if actually_dirty.iter().any(|file| in_memory.contains(file)) {
+                in_memory_builds.insert(build_info.id.clone());
+            }
 
-            output.retain_files(
-                actually_dirty
+            if let Some(vfs) = &vfs {
+                let files = actually_dirty
                     .iter()
-                    .map(|f| f.strip_prefix(project.paths.root.as_path()).unwrap_or(f)),
-            );
-            output.join_all(project.paths.root.as_path());
+                    .map(|f| (PathBuf::from(vfs.source_unit_name(f)), f))
+                    .collect::<HashMap<_, _>>();
+                output.retain_files(files.keys());
+                output.map_files(|name| {
+                    files
+                        .get(name)
+                        .map_or_else(|| project.paths.root.join(name), |f| f.to_path_buf())
+                });
+            } else {
+                output.retain_files(
+                    actually_dirty
+                        .iter()
+                        .map(|f| f.strip_prefix(project.paths.root.as_path()).unwrap_or(f)),
+                );
+                output.join_all(project.paths.root.as_path());
+            }
 
             aggregated.extend(version.clone(), build_info, profile, output);
         }
 
-        Ok(aggregated)
+        Ok((aggregated, in_memory_builds))
+    }
+}
+
+/// Splits the sources of a compiler run if some of its dirty files are kept in memory, see
+/// [`SourceOverlay::is_persisted`](crate::SourceOverlay::is_persisted).
+///
+/// The other dirty files are compiled without the in-memory files, which they don't import, so
+/// that their build infos don't contain overlaid content and can be written to disk.
+fn split_in_memory(sources: Sources, in_memory: &HashSet<PathBuf>) -> Vec<Sources> {
+    if !sources.dirty_files().any(|file| in_memory.contains(file)) {
+        return vec![sources];
     }
+    let persisted = sources
+        .iter()
+        .filter(|(file, _)| !in_memory.contains(*file))
+        .map(|(file, source)| (file.clone(), source.clone()))
+        .collect();
+    let in_memory = sources
+        .0
+        .into_iter()
+        .map(|(file, mut source)| {
+            if !in_memory.contains(&file) {
+                source.kind = SourceCompilationKind::Optimized;
+            }
+            (file, source)
+        })
+        .collect();
+    vec![Sources(persisted), Sources(in_memory)]
 }
 
 type CompilationResult<'a, I, E, C> = Result<Vec<(I, CompilerOutput<E, C>, &'a str, Vec<PathBuf>)>>;
@@ -642,8 +981,6 @@ fn compile_parallel<'a, C: Compiler>(
 #[cfg(test)]
 #[cfg(all(feature = "project-util", feature = "svm-solc"))]
 mod tests {
-    use std::path::Path;
-
     use foundry_compilers_artifacts::output_selection::ContractOutputSelection;
 
     use crate::{
@@ -0,0 +1,44 @@
+use crate::compilers::CompilationError;
+use core::fmt;
+use foundry_compilers_artifacts::{Severity, error::SourceLocation};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Error reported by `huffc` when compiling a source file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HuffCompilationError {
+    pub message: String,
+    pub file: Option<PathBuf>,
+    pub severity: Severity,
+}
+
+impl fmt::Display for HuffCompilationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            writeln!(f, "Location: {}", file.display())?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl CompilationError for HuffCompilationError {
+    fn is_warning(&self) -> bool {
+        self.severity.is_warning()
+    }
+
+    fn is_error(&self) -> bool {
+        self.severity.is_error()
+    }
+
+    fn source_location(&self) -> Option<SourceLocation> {
+        None
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn error_code(&self) -> Option<u64> {
+        None
+    }
+}
@@ -0,0 +1,52 @@
+use super::{HuffLanguage, settings::HuffSettings};
+use crate::compilers::CompilerInput;
+use foundry_compilers_artifacts::sources::{Source, Sources};
+use foundry_compilers_core::utils::strip_prefix_owned;
+use semver::Version;
+use serde::Serialize;
+use std::{borrow::Cow, path::Path};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct HuffVersionedInput {
+    pub sources: Sources,
+    pub settings: HuffSettings,
+    #[serde(skip)]
+    pub version: Version,
+}
+
+impl CompilerInput for HuffVersionedInput {
+    type Settings = HuffSettings;
+    type Language = HuffLanguage;
+
+    fn build(
+        sources: Sources,
+        settings: Self::Settings,
+        _language: Self::Language,
+        version: Version,
+    ) -> Self {
+        Self { sources, settings, version }
+    }
+
+    fn compiler_name(&self) -> Cow<'static, str> {
+        "Huff".into()
+    }
+
+    fn strip_prefix(&mut self, base: &Path) {
+        self.sources = std::mem::take(&mut self.sources)
+            .into_iter()
+            .map(|(path, s)| (strip_prefix_owned(path, base), s))
+            .collect();
+    }
+
+    fn language(&self) -> Self::Language {
+        HuffLanguage
+    }
+
+    fn version(&self) -> &Version {
+        &self.version
+    }
+
+    fn sources(&self) -> impl Iterator<Item = (&Path, &Source)> {
+        self.sources.iter().map(|(path, source)| (path.as_path(), source))
+    }
+}
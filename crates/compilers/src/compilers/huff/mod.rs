@@ -0,0 +1,474 @@
+use self::{
+    error::HuffCompilationError,
+    input::HuffVersionedInput,
+    parser::{HuffParser, abi_items, includes, is_contract},
+    settings::HuffSettings,
+};
+use super::{Compiler, CompilerOutput, CompilerVersion, Language};
+use alloy_json_abi::{AbiItem, JsonAbi};
+use alloy_primitives::Bytes;
+use core::fmt;
+use foundry_compilers_artifacts::{
+    Bytecode, BytecodeObject, Contract, DeployedBytecode, Evm, Severity, SourceFile,
+    sources::Sources,
+};
+use foundry_compilers_core::{
+    error::{Result, SolcError},
+    utils,
+};
+use semver::Version;
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Component, Path, PathBuf},
+    process::{Command, Stdio},
+    sync::OnceLock,
+};
+
+pub mod error;
+pub mod input;
+pub mod parser;
+pub mod settings;
+
+/// File extensions that are recognized as Huff source files.
+pub const HUFF_EXTENSIONS: &[&str] = &["huff"];
+
+/// Huff language, used as [Compiler::Language] for the Huff compiler.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct HuffLanguage;
+
+impl serde::Serialize for HuffLanguage {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str("huff")
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for HuffLanguage {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let res = String::deserialize(deserializer)?;
+        if res != "huff" {
+            Err(serde::de::Error::custom(format!("Invalid Huff language: {res}")))
+        } else {
+            Ok(Self)
+        }
+    }
+}
+
+impl Language for HuffLanguage {
+    const FILE_EXTENSIONS: &'static [&'static str] = HUFF_EXTENSIONS;
+}
+
+impl fmt::Display for HuffLanguage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Huff")
+    }
+}
+
+/// Huff compiler. Wrapper around the `huffc` binary.
+///
+/// Every source defining a `MAIN` macro is compiled into a contract named after the file. The ABI
+/// of the contract is built from the functions, events and errors defined in the file and the
+/// files it includes.
+#[derive(Clone, Debug)]
+pub struct Huff {
+    pub path: PathBuf,
+    version: OnceLock<Version>,
+}
+
+impl Huff {
+    /// Creates a new instance of the Huff compiler using the `huffc` binary at the given path.
+    ///
+    /// Fails if the version of the binary can't be determined, see [`Self::lazy`] to defer this.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self> {
+        let huff = Self::lazy(path);
+        huff.version()?;
+        Ok(huff)
+    }
+
+    /// Creates a new instance without invoking `huffc`. The version is determined when it's first
+    /// needed, i.e. only for projects with Huff sources.
+    pub fn lazy(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), version: OnceLock::new() }
+    }
+
+    /// Creates a new instance with the given version, without invoking `huffc`.
+    pub fn new_with_version(path: impl Into<PathBuf>, version: Version) -> Self {
+        Self { path: path.into(), version: OnceLock::from(version) }
+    }
+
+    /// Returns the version of the binary, invoking `huffc --version` on first use.
+    pub fn version(&self) -> Result<&Version> {
+        if let Some(version) = self.version.get() {
+            return Ok(version);
+        }
+        let version = Self::binary_version(&self.path)?;
+        Ok(self.version.get_or_init(|| version))
+    }
+
+    /// Invokes `huffc --version` and parses the output as a SemVer [`Version`].
+    pub fn binary_version(huffc: impl Into<PathBuf>) -> Result<Version> {
+        crate::cache_version(huffc.into(), &[], |huffc| {
+            let mut cmd = Command::new(huffc);
+            cmd.arg("--version")
+                .stdin(Stdio::piped())
+                .stderr(Stdio::piped())
+                .stdout(Stdio::piped());
+            debug!(?cmd, "getting Huff version");
+            let output = cmd.output().map_err(|e| SolcError::io(e, huffc))?;
+            trace!(?output);
+            if output.status.success() {
+                // e.g. `huffc 0.3.2`
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let version = stdout.split_whitespace().last().unwrap_or_default();
+                Ok(Version::parse(version)?)
+            } else {
+                Err(SolcError::solc_output(&output))
+            }
+        })
+    }
+
+    /// Compiles all contracts of the given input.
+    ///
+    /// `huffc` reads sources and their includes from disk, so the sources of the input are written
+    /// to a temporary directory first. This way the compiled content is the one of the input, e.g.
+    /// of [overlays](crate::SourceOverlay), rather than the one on disk.
+    ///
+    /// Failures of `huffc` are reported as errors in the output rather than returned.
+    #[instrument(name = "Huff::compile", skip_all)]
+    pub fn compile(
+        &self,
+        input: &HuffVersionedInput,
+    ) -> Result<CompilerOutput<HuffCompilationError, Contract>> {
+        let mut output = CompilerOutput::default();
+
+        let dir = tempfile::tempdir().map_err(|err| SolcError::io(err, std::env::temp_dir()))?;
+        for (path, source) in &input.sources {
+            let file = dir.path().join(sandboxed(path));
+            utils::create_parent_dir_all(&file)?;
+            std::fs::write(&file, source.content.as_bytes())
+                .map_err(|err| SolcError::io(err, file))?;
+        }
+
+        for (id, (path, source)) in input.sources.iter().enumerate() {
+            output.sources.insert(path.clone(), SourceFile { id: id as u32, ast: None });
+
+            if !is_contract(&source.content) {
+                continue;
+            }
+
+            let (bytecode, deployed_bytecode) =
+                match self.compile_contract(dir.path(), path, &input.settings)? {
+                    Ok(bytecodes) => bytecodes,
+                    Err(error) => {
+                        output.errors.push(error);
+                        continue;
+                    }
+                };
+
+            let contract = Contract {
+                abi: Some(abi(path, &input.sources)),
+                evm: Some(Evm {
+                    bytecode: Some(bytecode),
+                    deployed_bytecode: Some(DeployedBytecode {
+                        bytecode: Some(deployed_bytecode),
+                        immutable_references: Default::default(),
+                    }),
+                    assembly: None,
+                    legacy_assembly: None,
+                    method_identifiers: Default::default(),
+                    gas_estimates: None,
+                }),
+                metadata: None,
+                userdoc: Default::default(),
+                devdoc: Default::default(),
+                ir: None,
+                storage_layout: Default::default(),
+                transient_storage_layout: Default::default(),
+                ewasm: None,
+                ir_optimized: None,
+                ir_optimized_ast: None,
+            };
+            let name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            output.contracts.insert(path.clone(), BTreeMap::from([(name, contract)]));
+        }
+
+        Ok(output)
+    }
+
+    /// Invokes `huffc` in the given directory for the source at `path`, returning the creation
+    /// and runtime bytecode from the artifact it writes with `-o`.
+    ///
+    /// Returns an error only if `huffc` could not be invoked at all.
+    fn compile_contract(
+        &self,
+        dir: &Path,
+        path: &Path,
+        settings: &HuffSettings,
+    ) -> Result<std::result::Result<(Bytecode, Bytecode), HuffCompilationError>> {
+        let artifact_path = dir.join(ARTIFACT_FILE);
+        let mut cmd = Command::new(&self.path);
+        cmd.arg(sandboxed(path)).arg("-o").arg(ARTIFACT_FILE);
+        if let Some(evm_version) = settings.evm_version {
+            cmd.arg("-e").arg(evm_version.as_str());
+        }
+        for (name, value) in &settings.constants {
+            cmd.arg("-c").arg(format!("{name}={value}"));
+        }
+        cmd.current_dir(dir);
+        cmd.stdin(Stdio::null()).stderr(Stdio::piped()).stdout(Stdio::piped());
+        debug!(?cmd, "compiling");
+
+        let error = |message: String| HuffCompilationError {
+            message,
+            file: Some(path.to_path_buf()),
+            severity: Severity::Error,
+        };
+
+        let _ = std::fs::remove_file(&artifact_path);
+        let output = cmd.output().map_err(|err| SolcError::io(err, &self.path))?;
+        debug!(%output.status, output.stderr = ?String::from_utf8_lossy(&output.stderr), "finished");
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = if stderr.trim().is_empty() {
+                String::from_utf8_lossy(&output.stdout).trim().to_string()
+            } else {
+                stderr.trim().to_string()
+            };
+            return Ok(Err(error(message)));
+        }
+
+        let artifact = match utils::read_json_file::<HuffArtifact>(&artifact_path) {
+            Ok(artifact) => artifact,
+            Err(err) => return Ok(Err(error(format!("invalid artifact written by huffc: {err}")))),
+        };
+        let bytecode = |hex: &str| {
+            let object = hex
+                .trim()
+                .parse::<Bytes>()
+                .map_err(|err| error(format!("invalid bytecode returned by huffc: {err}")))?;
+            Ok(Bytecode {
+                object: BytecodeObject::Bytecode(object),
+                function_debug_data: Default::default(),
+                opcodes: None,
+                source_map: None,
+                generated_sources: Default::default(),
+                link_references: Default::default(),
+            })
+        };
+
+        Ok(bytecode(&artifact.bytecode)
+            .and_then(|creation| Ok((creation, bytecode(&artifact.runtime)?))))
+    }
+}
+
+/// The file `huffc` writes the artifact of a compiled source to, relative to its working dir.
+const ARTIFACT_FILE: &str = "artifact.json";
+
+/// The parts of an artifact written by `huffc` which are used, with hex encoded bytecode.
+#[derive(Deserialize)]
+struct HuffArtifact {
+    bytecode: String,
+    runtime: String,
+}
+
+/// Returns the path the source at `path` is written to in the temporary directory `huffc` is
+/// invoked in, with root and parent components removed so that it can't escape the directory.
+fn sandboxed(path: &Path) -> PathBuf {
+    path.components().filter(|c| matches!(c, Component::Normal(_))).collect()
+}
+
+/// Builds the ABI of the contract at the given path from the definitions in the file and its
+/// transitive includes.
+fn abi(path: &Path, sources: &Sources) -> JsonAbi {
+    let mut visited = BTreeSet::new();
+    let mut queue = vec![path.to_path_buf()];
+    let mut items = Vec::new();
+    while let Some(path) = queue.pop() {
+        let Some(source) = sources.get(&path) else { continue };
+        if !visited.insert(path.clone()) {
+            continue;
+        }
+        for item in abi_items(&source.content) {
+            match AbiItem::parse(item) {
+                Ok(item) => items.push(item),
+                Err(err) => warn!("failed to parse Huff ABI definition `{item}`: {err}"),
+            }
+        }
+        let dir = path.parent().unwrap_or(Path::new(""));
+        queue.extend(
+            includes(&source.content).map(|include| parser::clean_path(&dir.join(include))),
+        );
+    }
+    items.into_iter().collect()
+}
+
+impl Compiler for Huff {
+    type Settings = HuffSettings;
+    type CompilationError = HuffCompilationError;
+    type Parser = HuffParser;
+    type Input = HuffVersionedInput;
+    type Language = HuffLanguage;
+    type CompilerContract = Contract;
+
+    fn compile(
+        &self,
+        input: &Self::Input,
+    ) -> Result<CompilerOutput<HuffCompilationError, Contract>> {
+        self.compile(input)
+    }
+
+    fn available_versions(&self, _language: &Self::Language) -> Vec<CompilerVersion> {
+        match self.version() {
+            Ok(version) => vec![CompilerVersion::Installed(Version::new(
+                version.major,
+                version.minor,
+                version.patch,
+            ))],
+            Err(err) => {
+                debug!(%err, path = %self.path.display(), "Huff compiler is not available");
+                Vec::new()
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::{ProjectBuilder, ProjectPathsConfig, test_utils::write_script};
+    use foundry_compilers_artifacts::EvmVersion;
+    use std::fs;
+
+    /// Writes a `huffc` stub which records its arguments and writes an artifact with fixed
+    /// bytecode, or fails if the source contains `INVALID`.
+    fn stub_huffc(dir: &Path) -> PathBuf {
+        let path = dir.join("huffc");
+        write_script(
+            &path,
+            &format!(
+                r#"if [ "$1" = "--version" ]; then echo "huffc 0.3.2"; exit 0; fi
+echo "$@" >> "{}"
+if grep -q INVALID "$1"; then echo "Error: invalid opcode" >&2; exit 1; fi
+echo '{{"bytecode":"600a","runtime":"6001"}}' > "$3"
+"#,
+                dir.join("calls").display()
+            ),
+        );
+        path
+    }
+
+    #[test]
+    fn can_compile_with_stub() {
+        let tmp = utils::tempdir("huff").unwrap();
+        let huffc = stub_huffc(tmp.path());
+        let root = tmp.path().join("project");
+        fs::create_dir_all(root.join("src/utils")).unwrap();
+        fs::write(
+            root.join("src/Token.huff"),
+            r#"#include "./utils/Ownable.huff"
+
+#define function balanceOf(address) view returns (uint256)
+
+#define macro MAIN() = takes (0) returns (0) {
+    OWNABLE()
+}
+"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/utils/Ownable.huff"),
+            r#"#define function owner() view returns (address)
+#define event OwnerUpdated(address indexed)
+
+#define macro OWNABLE() = takes (0) returns (0) {}
+"#,
+        )
+        .unwrap();
+
+        let huff = Huff::lazy(&huffc);
+        assert_eq!(huff.version().unwrap(), &Version::new(0, 3, 2));
+        let mut settings =
+            HuffSettings { evm_version: Some(EvmVersion::Cancun), ..Default::default() };
+        settings.constants.insert("OWNER".to_string(), "0x01".to_string());
+        let project = ProjectBuilder::<Huff>::default()
+            .settings(settings)
+            .paths(ProjectPathsConfig::dapptools(&root).unwrap())
+            .build(huff)
+            .unwrap();
+
+        let output = project.compile().unwrap();
+        output.assert_success();
+        let token = output.find_first("Token").unwrap();
+        let abi = token.abi.as_ref().unwrap();
+        assert!(abi.functions.contains_key("balanceOf"));
+        assert!(abi.functions.contains_key("owner"));
+        assert!(abi.events.contains_key("OwnerUpdated"));
+        assert_eq!(
+            token.bytecode.as_ref().unwrap().object,
+            BytecodeObject::Bytecode(Bytes::from_static(&[0x60, 0x0a]))
+        );
+        assert_eq!(
+            token.deployed_bytecode.as_ref().unwrap().bytecode.as_ref().unwrap().object,
+            BytecodeObject::Bytecode(Bytes::from_static(&[0x60, 0x01]))
+        );
+        assert!(output.find_first("Ownable").is_none());
+
+        let calls = fs::read_to_string(tmp.path().join("calls")).unwrap();
+        assert_eq!(
+            calls.lines().collect::<Vec<_>>(),
+            ["src/Token.huff -o artifact.json -e cancun -c OWNER=0x01"]
+        );
+
+        // sources are cached
+        let output = project.compile().unwrap();
+        assert!(output.is_unchanged());
+        assert_eq!(fs::read_to_string(tmp.path().join("calls")).unwrap(), calls);
+
+        fs::write(
+            root.join("src/Broken.huff"),
+            "#define macro MAIN() = takes (0) returns (0) { INVALID }\n",
+        )
+        .unwrap();
+        let output = project.compile().unwrap();
+        assert!(output.has_compiler_errors());
+        assert!(output.to_string().contains("invalid opcode"), "{output}");
+    }
+
+    #[test]
+    fn compiles_content_of_input() {
+        use crate::compilers::CompilerInput;
+        use foundry_compilers_artifacts::Source;
+
+        let tmp = utils::tempdir("huff").unwrap();
+        let huff = Huff::lazy(stub_huffc(tmp.path()));
+        let input = |content: &str| {
+            let sources = [(PathBuf::from("src/A.huff"), Source::new(content))].into_iter();
+            HuffVersionedInput::build(
+                sources.collect(),
+                Default::default(),
+                HuffLanguage,
+                Version::new(0, 3, 2),
+            )
+        };
+
+        // the source doesn't exist on disk
+        let output =
+            huff.compile(&input("#define macro MAIN() = takes (0) returns (0) {}")).unwrap();
+        assert!(output.errors.is_empty(), "{:?}", output.errors);
+        assert!(output.contracts[Path::new("src/A.huff")].contains_key("A"));
+
+        let output = huff
+            .compile(&input("#define macro MAIN() = takes (0) returns (0) { INVALID }"))
+            .unwrap();
+        assert_eq!(output.errors.len(), 1);
+        assert!(output.contracts.is_empty());
+    }
+}
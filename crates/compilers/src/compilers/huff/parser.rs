@@ -0,0 +1,196 @@
+use super::HuffLanguage;
+use crate::{ProjectPathsConfig, SourceParser, compilers::ParsedSource};
+use foundry_compilers_core::{
+    error::{Result, SolcError},
+    utils::{RE_HUFF_ABI_ITEM, RE_HUFF_INCLUDE, RE_HUFF_MAIN},
+};
+use semver::VersionReq;
+use std::{
+    collections::BTreeSet,
+    path::{Component, Path, PathBuf},
+};
+
+#[derive(Clone, Debug, Default)]
+pub struct HuffParser {
+    _inner: (),
+}
+
+impl SourceParser for HuffParser {
+    type ParsedSource = HuffParsedSource;
+
+    fn new(_config: &ProjectPathsConfig) -> Self {
+        Self { _inner: () }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HuffParsedSource {
+    path: PathBuf,
+    includes: Vec<String>,
+    contract_names: Vec<String>,
+}
+
+impl ParsedSource for HuffParsedSource {
+    type Language = HuffLanguage;
+
+    #[instrument(name = "HuffParsedSource::parse", skip_all)]
+    fn parse(content: &str, file: &Path) -> Result<Self> {
+        let includes = includes(content).map(str::to_string).collect();
+
+        // A file defining `MAIN` is compiled into a contract named after the file.
+        let contract_names = is_contract(content)
+            .then(|| file.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+            .flatten()
+            .into_iter()
+            .collect();
+
+        Ok(Self { path: file.to_path_buf(), includes, contract_names })
+    }
+
+    fn version_req(&self) -> Option<&VersionReq> {
+        None
+    }
+
+    fn contract_names(&self) -> &[String] {
+        &self.contract_names
+    }
+
+    fn language(&self) -> Self::Language {
+        HuffLanguage
+    }
+
+    fn resolve_imports<C>(
+        &self,
+        paths: &ProjectPathsConfig<C>,
+        _include_paths: &mut BTreeSet<PathBuf>,
+    ) -> Result<Vec<PathBuf>> {
+        let mut imports = Vec::new();
+        'outer: for include in &self.includes {
+            // Potential locations of the included source. Like huffc, explicitly relative
+            // includes are only resolved relative to the including file.
+            let mut candidate_dirs = Vec::new();
+            if let Some(parent) = self.path.parent() {
+                candidate_dirs.push(parent);
+            }
+            if !include.starts_with("./") && !include.starts_with("../") {
+                candidate_dirs.push(paths.root.as_path());
+                candidate_dirs.extend(paths.libraries.iter().map(PathBuf::as_path));
+            }
+
+            for candidate_dir in candidate_dirs {
+                let candidate = clean_path(&candidate_dir.join(include));
+                trace!("trying {}", candidate.display());
+                if candidate.is_file() {
+                    imports.push(candidate);
+                    continue 'outer;
+                }
+            }
+
+            return Err(SolcError::msg(format!(
+                "failed to resolve include {include} at {}",
+                self.path.display()
+            )));
+        }
+        Ok(imports)
+    }
+}
+
+/// Returns the paths of all `#include` directives in the given source.
+pub(crate) fn includes(content: &str) -> impl Iterator<Item = &str> {
+    RE_HUFF_INCLUDE
+        .captures_iter(content)
+        .filter_map(|cap| cap.name("p1").or_else(|| cap.name("p2")))
+        .map(|path| path.as_str())
+}
+
+/// Returns whether the given source defines a `MAIN` macro, i.e. can be compiled into a contract.
+pub(crate) fn is_contract(content: &str) -> bool {
+    RE_HUFF_MAIN.is_match(content)
+}
+
+/// Returns the human-readable ABI items defined in the given source, e.g.
+/// `function balanceOf(address) view returns (uint256)`.
+pub(crate) fn abi_items(content: &str) -> impl Iterator<Item = &str> {
+    RE_HUFF_ABI_ITEM.captures_iter(content).filter_map(|cap| cap.name("item")).map(|m| m.as_str())
+}
+
+/// Lexically resolves `.` and `..` components of the given path.
+pub(crate) fn clean_path(path: &Path) -> PathBuf {
+    let mut cleaned = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(cleaned.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                cleaned.pop();
+            }
+            component => cleaned.push(component),
+        }
+    }
+    cleaned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_compilers_core::utils;
+
+    #[test]
+    fn can_parse_source() {
+        let content = r#"
+#include "./utils/Ownable.huff"
+#include 'Errors.huff'
+
+#define function balanceOf(address) view returns (uint256) // ERC20
+#define event Transfer(address indexed, address indexed, uint256)
+#define error Unauthorized(address)
+#define constant OWNER = 0x00
+
+#define macro MAIN() = takes (0) returns (0) {
+    0x00 calldataload
+}
+"#;
+        let parsed = HuffParsedSource::parse(content, Path::new("src/Token.huff")).unwrap();
+        assert_eq!(parsed.includes, ["./utils/Ownable.huff", "Errors.huff"]);
+        assert_eq!(parsed.contract_names(), ["Token"]);
+        assert_eq!(parsed.version_req(), None);
+        assert_eq!(
+            abi_items(content).collect::<Vec<_>>(),
+            [
+                "function balanceOf(address) view returns (uint256)",
+                "event Transfer(address indexed, address indexed, uint256)",
+                "error Unauthorized(address)",
+            ]
+        );
+
+        let parsed = HuffParsedSource::parse("#define macro OWNABLE() = {}", Path::new("A.huff"));
+        assert!(parsed.unwrap().contract_names().is_empty());
+    }
+
+    #[test]
+    fn can_resolve_includes() {
+        let tmp = utils::tempdir("huff").unwrap();
+        let root = tmp.path();
+        for file in ["src/utils/Ownable.huff", "src/Token.huff", "lib/huffmate/Errors.huff"] {
+            std::fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+            std::fs::write(root.join(file), "").unwrap();
+        }
+        let paths = ProjectPathsConfig::<HuffLanguage>::dapptools(root).unwrap();
+
+        let token = root.join("src/Token.huff");
+        let parsed = HuffParsedSource::parse(
+            "#include \"./utils/Ownable.huff\"\n#include \"huffmate/Errors.huff\"\n",
+            &token,
+        )
+        .unwrap();
+        let imports = parsed.resolve_imports(&paths, &mut Default::default()).unwrap();
+        assert_eq!(
+            imports,
+            [root.join("src/utils/Ownable.huff"), root.join("lib/huffmate/Errors.huff")]
+        );
+
+        let parsed = HuffParsedSource::parse("#include \"./Errors.huff\"\n", &token).unwrap();
+        assert!(parsed.resolve_imports(&paths, &mut Default::default()).is_err());
+    }
+}
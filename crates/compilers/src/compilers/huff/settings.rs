@@ -0,0 +1,65 @@
+use crate::{
+    compilers::{CompilerSettings, restrictions::CompilerSettingsRestrictions},
+    solc::Restriction,
+};
+use foundry_compilers_artifacts::{EvmVersion, output_selection::OutputSelection};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Settings passed to `huffc`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HuffSettings {
+    /// The EVM version to compile for, passed as `-e`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evm_version: Option<EvmVersion>,
+    /// Overrides of `#define constant` values, passed as `-c NAME=VALUE`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub constants: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HuffRestrictions {
+    pub evm_version: Restriction<EvmVersion>,
+}
+
+impl CompilerSettingsRestrictions for HuffRestrictions {
+    fn merge(self, other: Self) -> Option<Self> {
+        Some(Self { evm_version: self.evm_version.merge(other.evm_version)? })
+    }
+}
+
+impl CompilerSettings for HuffSettings {
+    type Restrictions = HuffRestrictions;
+
+    fn update_output_selection(&mut self, _f: impl FnMut(&mut OutputSelection)) {}
+
+    fn can_use_cached(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    fn satisfies_restrictions(&self, restrictions: &Self::Restrictions) -> bool {
+        restrictions.evm_version.satisfies(self.evm_version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_use_cached_compares_all_settings() {
+        let settings = HuffSettings {
+            constants: BTreeMap::from([("OWNER".to_string(), "0x01".to_string())]),
+            ..Default::default()
+        };
+        assert!(settings.can_use_cached(&settings.clone()));
+
+        let mut other = settings.clone();
+        other.constants.insert("OWNER".to_string(), "0x02".to_string());
+        assert!(!settings.can_use_cached(&other));
+
+        other = HuffSettings { evm_version: Some(EvmVersion::Cancun), ..settings.clone() };
+        assert!(!settings.can_use_cached(&other));
+    }
+}
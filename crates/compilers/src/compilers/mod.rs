@@ -2,7 +2,8 @@ use crate::{ProjectPathsConfig, resolver::Node};
 use alloy_json_abi::JsonAbi;
 use core::fmt;
 use foundry_compilers_artifacts::{
-    BytecodeObject, CompactContractRef, Contract, FileToContractsMap, Severity, SourceFile,
+    BytecodeObject, CompactContractRef, Contract, Error, FileToContractsMap, Severity, SolcVfs,
+    SourceFile,
     error::SourceLocation,
     output_selection::OutputSelection,
     remappings::Remapping,
@@ -21,9 +22,12 @@ use std::{
     sync::{Mutex, OnceLock},
 };
 
+pub mod huff;
 pub mod multi;
+pub mod registry;
 pub mod solc;
 pub mod vyper;
+pub use huff::{HUFF_EXTENSIONS, Huff, HuffLanguage};
 pub use vyper::*;
 
 mod restrictions;
@@ -138,6 +142,14 @@ pub trait CompilerInput: Serialize + Send + Sync + Sized + Debug {
 
     /// Strips given prefix from all paths.
     fn strip_prefix(&mut self, base: &Path);
+
+    /// Converts all paths to the source unit names solc's virtual file system would assign to
+    /// them, see [`SourceUnitNames::Solc`](crate::SourceUnitNames::Solc).
+    ///
+    /// Defaults to stripping the base path, for compilers which don't emulate solc's naming.
+    fn apply_vfs(&mut self, vfs: &SolcVfs) {
+        self.strip_prefix(vfs.base_path());
+    }
 }
 
 /// [`ParsedSource`] parser.
@@ -234,6 +246,15 @@ pub trait CompilationError:
     fn source_location(&self) -> Option<SourceLocation>;
     fn severity(&self) -> Severity;
     fn error_code(&self) -> Option<u64>;
+
+    /// Converts a diagnostic in the solc format, e.g. one emitted by the
+    /// [pre-flight check](crate::PreflightMode).
+    ///
+    /// Returns `None` if this error type can't represent Solidity diagnostics.
+    fn from_solc_error(error: Error) -> Option<Self> {
+        let _ = error;
+        None
+    }
 }
 
 /// Output of the compiler, including contracts, sources, errors and metadata. might be
@@ -274,13 +295,18 @@ impl<E, C> CompilerOutput<E, C> {
     }
 
     pub fn join_all(&mut self, root: &Path) {
+        self.map_files(|path| root.join(path));
+    }
+
+    /// Replaces the paths of all files with the result of `f`.
+    pub fn map_files(&mut self, mut f: impl FnMut(&Path) -> PathBuf) {
         self.contracts = std::mem::take(&mut self.contracts)
             .into_iter()
-            .map(|(path, contracts)| (root.join(path), contracts))
+            .map(|(path, contracts)| (f(&path), contracts))
             .collect();
         self.sources = std::mem::take(&mut self.sources)
             .into_iter()
-            .map(|(path, source)| (root.join(path), source))
+            .map(|(path, source)| (f(&path), source))
             .collect();
     }
 
@@ -381,6 +407,34 @@ pub trait Compiler: Send + Sync + Clone {
     /// Returns all versions available locally and remotely. Should return versions with stripped
     /// metadata.
     fn available_versions(&self, language: &Self::Language) -> Vec<CompilerVersion>;
+
+    /// Creates the parser resolving the sources of projects compiled with this compiler.
+    ///
+    /// Defaults to [`SourceParser::new`]. Overridden by compilers whose parsers depend on the
+    /// compiler, see [`registry::CompilerRegistry`].
+    fn parser(&self, config: &ProjectPathsConfig) -> Self::Parser {
+        Self::Parser::new(config)
+    }
+
+    /// Returns the versions which can be installed from the given local mirror of compiler
+    /// binaries, see [`ProjectBuilder::offline_mirror`](crate::ProjectBuilder::offline_mirror).
+    ///
+    /// Defaults to no versions, for compilers which can't be installed from a mirror.
+    fn mirrored_versions(&self, _language: &Self::Language, _mirror: &Path) -> Vec<Version> {
+        Vec::new()
+    }
+
+    /// Installs the given version from the local mirror, unless it's already installed.
+    ///
+    /// Called before compiling with a version returned by [`Self::mirrored_versions`].
+    fn install_from_mirror(
+        &self,
+        _language: &Self::Language,
+        _version: &Version,
+        _mirror: &Path,
+    ) -> Result<()> {
+        Ok(())
+    }
 }
 
 pub(crate) fn cache_version(
@@ -4,7 +4,7 @@ use super::{
     restrictions::CompilerSettingsRestrictions,
     solc::{SOLC_EXTENSIONS, SolcCompiler, SolcSettings, SolcVersionedInput},
     vyper::{
-        VYPER_EXTENSIONS, Vyper, VyperLanguage, input::VyperVersionedInput,
+        VYPER_EXTENSIONS, Vyper, VyperCompiler, VyperLanguage, input::VyperVersionedInput,
         parser::VyperParsedSource,
     },
 };
@@ -17,7 +17,7 @@ use crate::{
     solc::SolcRestrictions,
 };
 use foundry_compilers_artifacts::{
-    Contract, Error, Severity, SolcLanguage,
+    Contract, Error, Severity, SolcLanguage, SolcVfs,
     error::SourceLocation,
     output_selection::OutputSelection,
     remappings::Remapping,
@@ -38,6 +38,8 @@ use std::{
 pub struct MultiCompiler {
     pub solc: Option<SolcCompiler>,
     pub vyper: Option<Vyper>,
+    /// Additional Vyper installations, see [`Self::with_vyper_installations`].
+    pub vyper_installations: VyperCompiler,
 }
 
 impl Default for MultiCompiler {
@@ -49,14 +51,35 @@ impl Default for MultiCompiler {
         #[cfg(not(feature = "svm-solc"))]
         let solc = crate::solc::Solc::new("solc").map(SolcCompiler::Specific).ok();
 
-        Self { solc, vyper }
+        Self { solc, vyper, vyper_installations: Default::default() }
     }
 }
 
 impl MultiCompiler {
     pub fn new(solc: Option<SolcCompiler>, vyper_path: Option<PathBuf>) -> Result<Self> {
         let vyper = vyper_path.map(Vyper::new).transpose()?;
-        Ok(Self { solc, vyper })
+        Ok(Self { solc, vyper, vyper_installations: Default::default() })
+    }
+
+    /// Creates a new instance with the given solc compiler and multiple Vyper installations.
+    ///
+    /// Vyper sources are compiled with the installation matching the version picked for them by
+    /// the resolver, see [`VyperCompiler`]. All installations in a directory can be found with
+    /// [`Vyper::find_all_in`].
+    pub fn with_vyper_installations(
+        solc: Option<SolcCompiler>,
+        installations: impl IntoIterator<Item = Vyper>,
+    ) -> Self {
+        Self { solc, vyper: None, vyper_installations: VyperCompiler::new(installations) }
+    }
+
+    /// Returns the Vyper installation with the given version, preferring [`Self::vyper`] over
+    /// [`Self::vyper_installations`].
+    fn vyper_installation(&self, version: &Version) -> Option<&Vyper> {
+        self.vyper
+            .as_ref()
+            .filter(|vyper| vyper.has_version(version))
+            .or_else(|| self.vyper_installations.get(version))
     }
 }
 
@@ -316,6 +339,13 @@ impl CompilerInput for MultiCompilerInput {
         }
     }
 
+    fn apply_vfs(&mut self, vfs: &SolcVfs) {
+        match self {
+            Self::Solc(input) => input.apply_vfs(vfs),
+            Self::Vyper(input) => input.apply_vfs(vfs),
+        }
+    }
+
     fn version(&self) -> &Version {
         match self {
             Self::Solc(input) => input.version(),
@@ -354,11 +384,15 @@ impl Compiler for MultiCompiler {
                 }
             }
             MultiCompilerInput::Vyper(input) => {
-                if let Some(vyper) = &self.vyper {
+                if let Some(vyper) = self.vyper_installation(&input.version) {
                     Compiler::compile(vyper, input)
                         .map(|res| res.map_err(MultiCompilerError::Vyper))
-                } else {
+                } else if self.vyper.is_none()
+                    && self.vyper_installations.installations().is_empty()
+                {
                     Err(SolcError::msg("vyper compiler is not available"))
+                } else {
+                    Err(SolcError::msg(format!("Vyper {} is not installed", input.version)))
                 }
             }
         }
@@ -370,10 +404,41 @@ impl Compiler for MultiCompiler {
                 self.solc.as_ref().map(|s| s.available_versions(language)).unwrap_or_default()
             }
             MultiCompilerLanguage::Vyper(language) => {
-                self.vyper.as_ref().map(|v| v.available_versions(language)).unwrap_or_default()
+                let mut versions = self
+                    .vyper
+                    .iter()
+                    .flat_map(|vyper| vyper.available_versions(language))
+                    .chain(self.vyper_installations.available_versions(language))
+                    .collect::<Vec<_>>();
+                versions.sort();
+                versions.dedup();
+                versions
             }
         }
     }
+
+    fn mirrored_versions(&self, language: &Self::Language, mirror: &Path) -> Vec<Version> {
+        match (language, &self.solc) {
+            (MultiCompilerLanguage::Solc(language), Some(solc)) => {
+                solc.mirrored_versions(language, mirror)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn install_from_mirror(
+        &self,
+        language: &Self::Language,
+        version: &Version,
+        mirror: &Path,
+    ) -> Result<()> {
+        match (language, &self.solc) {
+            (MultiCompilerLanguage::Solc(language), Some(solc)) => {
+                solc.install_from_mirror(language, version, mirror)
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 impl SourceParser for MultiCompilerParser {
@@ -596,4 +661,8 @@ impl CompilationError for MultiCompilerError {
             Self::Vyper(error) => error.error_code(),
         }
     }
+
+    fn from_solc_error(error: Error) -> Option<Self> {
+        Some(Self::Solc(error))
+    }
 }
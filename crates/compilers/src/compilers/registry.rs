@@ -0,0 +1,1714 @@
+//! Compiler dispatching to a set of compilers registered at runtime.
+//!
+//! Unlike the [`MultiCompiler`], which is hard-wired to Solc and Vyper, a
+//! [`CompilerRegistry`] can be extended with any [`Compiler`] producing [`Contract`] artifacts,
+//! including ones for languages defined outside of this crate, while still using a single
+//! [`Project`](crate::Project) type.
+//!
+//! A registry is generic over the set of languages `L` it compiles and the set of settings
+//! restrictions `R`, which default to the languages and restrictions of the [`MultiCompiler`]. A
+//! downstream crate adding a language defines its own language set, e.g. an enum over the
+//! languages of the compilers it registers, whose [`Language::FILE_EXTENSIONS`] determine the
+//! source files of the project, and implements [`RegistryLanguage`] for it. Sources are dispatched
+//! to the registered compiler whose language is the member of the language set they are written
+//! in.
+//!
+//! Compilers are registered under a name, which also identifies their settings in
+//! [`RegistrySettings`]. The settings of a project must be created or configured by the registry,
+//! see [`CompilerRegistry::settings`] and [`CompilerRegistry::configure_settings`].
+//!
+//! ```no_run
+//! use foundry_compilers::{
+//!     ProjectBuilder, ProjectPathsConfig, Vyper,
+//!     registry::CompilerRegistry,
+//!     solc::{Solc, SolcCompiler, SolcSettings},
+//! };
+//!
+//! let compiler = CompilerRegistry::new()
+//!     .with("solc", SolcCompiler::Specific(Solc::new("solc")?))?
+//!     .with("vyper", Vyper::new("vyper")?)?;
+//! let mut settings = compiler.settings();
+//! settings.get_mut::<SolcSettings>("solc").unwrap().settings.optimizer.enabled = Some(true);
+//!
+//! let project = ProjectBuilder::<CompilerRegistry>::default()
+//!     .settings(settings)
+//!     .paths(ProjectPathsConfig::builder().build()?)
+//!     .build(compiler)?;
+//! let output = project.compile()?;
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use super::{
+    CompilationError, Compiler, CompilerInput, CompilerOutput, CompilerSettings,
+    CompilerSettingsRestrictions, CompilerVersion, Language, ParsedSource, SourceParser,
+    huff::HuffLanguage,
+    multi::{MultiCompiler, MultiCompilerLanguage, MultiCompilerRestrictions},
+    solc::SolcLanguage,
+    vyper::{VyperCompiler, VyperLanguage},
+};
+use crate::{ProjectPathsConfig, resolver::Node};
+use foundry_compilers_artifacts::{
+    Contract, Severity, SolcVfs,
+    error::SourceLocation,
+    output_selection::OutputSelection,
+    remappings::Remapping,
+    sources::{Source, Sources},
+};
+use foundry_compilers_core::error::{Result, SolcError};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::{
+    any::Any,
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::trace;
+
+/// A [`Compiler`] which can be registered in a [`CompilerRegistry`].
+///
+/// Implemented for all compilers producing [`Contract`] artifacts.
+pub trait RegistrableCompiler:
+    Compiler<
+        CompilerContract = Contract,
+        Input: 'static,
+        Parser: SourceParser<ParsedSource: 'static> + 'static,
+        Settings: CompilerSettings<Restrictions: 'static>,
+    > + 'static
+{
+}
+
+impl<C> RegistrableCompiler for C where
+    C: Compiler<
+            CompilerContract = Contract,
+            Input: 'static,
+            Parser: SourceParser<ParsedSource: 'static> + 'static,
+            Settings: CompilerSettings<Restrictions: 'static>,
+        > + 'static
+{
+}
+
+/// Restrictions on the settings of the compilers registered in a [`CompilerRegistry`].
+///
+/// Holds restrictions for each type of settings, which apply to all registered compilers using
+/// that type of settings.
+pub trait RegistryRestrictions: CompilerSettingsRestrictions + 'static {
+    /// Returns the restrictions of type `T`, if held.
+    fn get<T: CompilerSettingsRestrictions + 'static>(&self) -> Option<T>;
+}
+
+impl RegistryRestrictions for MultiCompilerRestrictions {
+    fn get<T: CompilerSettingsRestrictions + 'static>(&self) -> Option<T> {
+        let restrictions: [&dyn Any; 2] = [&self.solc, &self.vyper];
+        restrictions.into_iter().find_map(|restrictions| restrictions.downcast_ref()).copied()
+    }
+}
+
+/// A set of languages compiled by a [`CompilerRegistry`].
+///
+/// Each member of the set is the language of a compiler, which is identified by its type.
+pub trait RegistryLanguage: Language {
+    /// Returns the language as the member of type `T`, if it is one.
+    fn get<T: Language>(&self) -> Option<T>;
+
+    /// Returns the member of the set for the given language, if it is part of the set.
+    fn from_language<T: Language>(language: T) -> Option<Self>;
+}
+
+impl RegistryLanguage for MultiCompilerLanguage {
+    fn get<T: Language>(&self) -> Option<T> {
+        match self {
+            Self::Solc(language) => downcast(language),
+            Self::Vyper(language) => downcast(language),
+        }
+    }
+
+    fn from_language<T: Language>(language: T) -> Option<Self> {
+        downcast(&language).map(Self::Solc).or_else(|| downcast(&language).map(Self::Vyper))
+    }
+}
+
+macro_rules! impl_single_language {
+    ($($language:ty),*) => {$(
+        impl RegistryLanguage for $language {
+            fn get<T: Language>(&self) -> Option<T> {
+                downcast(self)
+            }
+
+            fn from_language<T: Language>(language: T) -> Option<Self> {
+                downcast(&language)
+            }
+        }
+    )*};
+}
+
+impl_single_language!(SolcLanguage, VyperLanguage, HuffLanguage);
+
+/// Returns the given language as a language of type `B`, if it is one.
+fn downcast<A: Language, B: Language>(language: &A) -> Option<B> {
+    (language as &dyn Any).downcast_ref().copied()
+}
+
+/// Compiler which dispatches to the compilers registered in it.
+///
+/// See the [module-level documentation](self) for more details.
+pub struct CompilerRegistry<L = MultiCompilerLanguage, R = MultiCompilerRestrictions> {
+    backends: Vec<Arc<dyn Backend<L, R>>>,
+}
+
+impl<L: RegistryLanguage, R: RegistryRestrictions> CompilerRegistry<L, R> {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the given compiler under the given name, replacing the compiler previously
+    /// registered under it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the language set `L` doesn't include the file extensions of the given
+    /// compiler's language, or if a compiler registered under a different name recognizes some of
+    /// them.
+    pub fn register<C: RegistrableCompiler>(
+        &mut self,
+        name: &str,
+        compiler: C,
+    ) -> Result<&mut Self> {
+        let extensions = C::Language::FILE_EXTENSIONS;
+        if let Some(ext) = extensions.iter().find(|ext| !L::FILE_EXTENSIONS.contains(ext)) {
+            return Err(SolcError::msg(format!(
+                "compiler {name} recognizes .{ext} files, which are not part of the languages of \
+                 the registry"
+            )));
+        }
+        if let Some(other) = self.backends.iter().find(|backend| {
+            backend.name() != name
+                && backend.extensions().iter().any(|ext| extensions.contains(ext))
+        }) {
+            return Err(SolcError::msg(format!(
+                "compiler {} already recognizes some of the extensions of {name}",
+                other.name()
+            )));
+        }
+        self.insert(name, compiler);
+        Ok(self)
+    }
+
+    /// Same as [`Self::register`], but consumes and returns the registry.
+    pub fn with<C: RegistrableCompiler>(mut self, name: &str, compiler: C) -> Result<Self> {
+        self.register(name, compiler)?;
+        Ok(self)
+    }
+
+    /// Returns the compiler registered under the given name, if it is of type `C`.
+    pub fn get<C: 'static>(&self, name: &str) -> Option<&C> {
+        self.backend(name)?.compiler().downcast_ref()
+    }
+
+    /// Returns the names of all registered compilers, in registration order.
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.backends.iter().map(|backend| backend.name())
+    }
+
+    /// Returns the name of the compiler the given language is dispatched to.
+    pub fn compiler_for(&self, language: &L) -> Option<&str> {
+        self.backend_for(language).map(|backend| backend.name())
+    }
+
+    /// Returns the default settings of all registered compilers.
+    pub fn settings(&self) -> RegistrySettings<L, R> {
+        let settings = self
+            .backends
+            .iter()
+            .map(|backend| (backend.name().to_string(), backend.default_settings()))
+            .collect();
+        RegistrySettings { settings, untyped: BTreeMap::new() }
+    }
+
+    /// Configures the given settings for the registered compilers.
+    ///
+    /// Types deserialized settings, binds the settings inserted with [`RegistrySettings::insert`]
+    /// to their compilers and adds the default settings of the compilers without settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings of a registered compiler are not of its settings type.
+    pub fn configure_settings(&self, settings: &mut RegistrySettings<L, R>) -> Result<()> {
+        for backend in &self.backends {
+            let name = backend.name();
+            let configured = if let Some(value) = settings.untyped.remove(name) {
+                backend.deserialize_settings(value).map_err(|err| {
+                    SolcError::msg(format!("invalid settings of compiler {name}: {err}"))
+                })?
+            } else if let Some(existing) = settings.settings.get(name) {
+                backend.bind_settings(&**existing).ok_or_else(|| {
+                    SolcError::msg(format!("settings of compiler {name} are of a different type"))
+                })?
+            } else {
+                backend.default_settings()
+            };
+            settings.settings.insert(name.to_string(), configured);
+        }
+        Ok(())
+    }
+
+    fn insert<C: RegistrableCompiler>(&mut self, name: &str, compiler: C) {
+        let backend: Arc<dyn Backend<L, R>> =
+            Arc::new(TypedBackend { name: name.to_string(), compiler });
+        if let Some(existing) = self.backends.iter_mut().find(|backend| backend.name() == name) {
+            *existing = backend;
+        } else {
+            self.backends.push(backend);
+        }
+    }
+
+    fn backend(&self, name: &str) -> Option<&dyn Backend<L, R>> {
+        self.backends.iter().find(|backend| backend.name() == name).map(|backend| &**backend)
+    }
+
+    /// Returns the first registered compiler which recognizes the given language.
+    fn backend_for(&self, language: &L) -> Option<&dyn Backend<L, R>> {
+        self.backends.iter().find(|backend| backend.recognizes(language)).map(|backend| &**backend)
+    }
+}
+
+impl<L, R> Default for CompilerRegistry<L, R> {
+    fn default() -> Self {
+        Self { backends: Vec::new() }
+    }
+}
+
+impl<L, R> Clone for CompilerRegistry<L, R> {
+    fn clone(&self) -> Self {
+        Self { backends: self.backends.clone() }
+    }
+}
+
+impl<L, R> fmt::Debug for CompilerRegistry<L, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.backends.iter().map(|backend| backend.name())).finish()
+    }
+}
+
+impl From<MultiCompiler> for CompilerRegistry {
+    /// Registers the compilers of the [`MultiCompiler`] as `solc` and `vyper`.
+    fn from(multi: MultiCompiler) -> Self {
+        // the languages of the multi compiler have distinct extensions
+        let mut registry = Self::new();
+        if let Some(solc) = multi.solc {
+            registry.insert("solc", solc);
+        }
+        if multi.vyper.is_some() || !multi.vyper_installations.installations().is_empty() {
+            let installations = multi.vyper_installations.installations().iter().cloned();
+            registry
+                .insert("vyper", VyperCompiler::new(multi.vyper.into_iter().chain(installations)));
+        }
+        registry
+    }
+}
+
+impl<L: RegistryLanguage, R: RegistryRestrictions> Compiler for CompilerRegistry<L, R> {
+    type Input = RegistryInput<L, R>;
+    type CompilationError = RegistryError;
+    type Parser = RegistryParser<L>;
+    type Settings = RegistrySettings<L, R>;
+    type Language = L;
+    type CompilerContract = Contract;
+
+    fn compile(
+        &self,
+        input: &Self::Input,
+    ) -> Result<CompilerOutput<Self::CompilationError, Self::CompilerContract>> {
+        if input.input.as_any().is::<UnavailableInput>() {
+            return Err(SolcError::msg(format!(
+                "no settings of the {} compiler are configured in the project settings, see \
+                 `CompilerRegistry::settings`",
+                input.language
+            )));
+        }
+        self.backend_for(&input.language)
+            .ok_or_else(|| SolcError::msg(format!("{} compiler is not available", input.language)))?
+            .compile(&*input.input)
+    }
+
+    fn available_versions(&self, language: &Self::Language) -> Vec<CompilerVersion> {
+        self.backend_for(language)
+            .map(|backend| backend.available_versions(language))
+            .unwrap_or_default()
+    }
+
+    fn mirrored_versions(&self, language: &Self::Language, mirror: &Path) -> Vec<Version> {
+        self.backend_for(language)
+            .map(|backend| backend.mirrored_versions(language, mirror))
+            .unwrap_or_default()
+    }
+
+    fn install_from_mirror(
+        &self,
+        language: &Self::Language,
+        version: &Version,
+        mirror: &Path,
+    ) -> Result<()> {
+        match self.backend_for(language) {
+            Some(backend) => backend.install_from_mirror(language, version, mirror),
+            None => Ok(()),
+        }
+    }
+
+    /// Creates a parser dispatching to the parsers of the registered compilers.
+    fn parser(&self, config: &ProjectPathsConfig) -> Self::Parser {
+        let parsers = self
+            .backends
+            .iter()
+            .map(|backend| RegisteredParser {
+                backend: backend.name().to_string(),
+                extensions: backend.extensions(),
+                parser: backend.parser(config),
+            })
+            .collect();
+        RegistryParser { parsers: Some(parsers) }
+    }
+}
+
+/// Settings for the [`CompilerRegistry`], keyed by the names compilers are registered under.
+///
+/// Only settings created or configured by a registry, see [`CompilerRegistry::settings`], are
+/// bound to the registered compilers and can be used to compile sources. Deserialized settings are
+/// only typed once they are configured, or when accessed with [`Self::get_mut`].
+pub struct RegistrySettings<L = MultiCompilerLanguage, R = MultiCompilerRestrictions> {
+    settings: BTreeMap<String, Box<dyn ErasedSettings<L, R>>>,
+    /// Deserialized settings of compilers whose settings type is not known yet.
+    untyped: BTreeMap<String, serde_json::Value>,
+}
+
+impl<L: RegistryLanguage, R: RegistryRestrictions> RegistrySettings<L, R> {
+    /// Returns the settings of the given compiler, if configured and of type `S`.
+    pub fn get<S: CompilerSettings>(&self, compiler: &str) -> Option<&S> {
+        self.settings.get(compiler)?.as_any().downcast_ref()
+    }
+
+    /// Returns the settings of the given compiler, initializing them with the defaults if not
+    /// configured yet.
+    ///
+    /// Returns `None` if the settings are not of type `S`.
+    pub fn get_mut<S: CompilerSettings>(&mut self, compiler: &str) -> Option<&mut S> {
+        if !self.settings.contains_key(compiler) {
+            let settings = match self.untyped.remove(compiler) {
+                Some(value) => serde_json::from_value::<S>(value).ok()?,
+                None => S::default(),
+            };
+            self.settings.insert(compiler.to_string(), Box::new(Entry::unbound(settings)));
+        }
+        self.settings.get_mut(compiler)?.as_any_mut().downcast_mut()
+    }
+
+    /// Sets the settings of the given compiler.
+    ///
+    /// Settings replacing settings of the same type stay bound to their compiler, other ones have
+    /// to be bound with [`CompilerRegistry::configure_settings`].
+    pub fn insert<S: CompilerSettings>(&mut self, compiler: &str, settings: S) -> &mut Self {
+        self.untyped.remove(compiler);
+        match self.settings.get_mut(compiler).and_then(|s| s.as_any_mut().downcast_mut()) {
+            Some(existing) => *existing = settings,
+            None => {
+                self.settings.insert(compiler.to_string(), Box::new(Entry::unbound(settings)));
+            }
+        }
+        self
+    }
+
+    /// Same as [`Self::insert`], but consumes and returns the settings.
+    pub fn with<S: CompilerSettings>(mut self, compiler: &str, settings: S) -> Self {
+        self.insert(compiler, settings);
+        self
+    }
+
+    /// Returns the settings of the given compiler.
+    ///
+    /// Deserialized or missing settings are typed like `like`, if given.
+    fn resolve(
+        &self,
+        compiler: &str,
+        like: Option<&dyn ErasedSettings<L, R>>,
+    ) -> Option<Box<dyn ErasedSettings<L, R>>> {
+        if let Some(settings) = self.settings.get(compiler) {
+            return Some(settings.clone());
+        }
+        match self.untyped.get(compiler) {
+            Some(value) => like?.deserialize_like(value.clone()).ok(),
+            None => like.map(ErasedSettings::default_like),
+        }
+    }
+
+    fn map(
+        mut self,
+        f: impl Fn(Box<dyn ErasedSettings<L, R>>) -> Box<dyn ErasedSettings<L, R>>,
+    ) -> Self {
+        self.settings =
+            self.settings.into_iter().map(|(name, settings)| (name, f(settings))).collect();
+        self
+    }
+}
+
+impl<L, R> Default for RegistrySettings<L, R> {
+    fn default() -> Self {
+        Self { settings: BTreeMap::new(), untyped: BTreeMap::new() }
+    }
+}
+
+impl<L: RegistryLanguage, R: RegistryRestrictions> Clone for RegistrySettings<L, R> {
+    fn clone(&self) -> Self {
+        Self { settings: self.settings.clone(), untyped: self.untyped.clone() }
+    }
+}
+
+impl<L, R> fmt::Debug for RegistrySettings<L, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegistrySettings")
+            .field("settings", &self.settings)
+            .field("untyped", &self.untyped)
+            .finish()
+    }
+}
+
+impl<L, R> Serialize for RegistrySettings<L, R> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::{Error, SerializeMap};
+
+        let mut map = serializer.serialize_map(Some(self.settings.len() + self.untyped.len()))?;
+        for (name, settings) in &self.settings {
+            map.serialize_entry(name, &settings.to_json().map_err(S::Error::custom)?)?;
+        }
+        for (name, value) in &self.untyped {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de, L, R> Deserialize<'de> for RegistrySettings<L, R> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let untyped = BTreeMap::<String, serde_json::Value>::deserialize(deserializer)?;
+        Ok(Self { untyped, ..Default::default() })
+    }
+}
+
+impl<L: RegistryLanguage, R: RegistryRestrictions> CompilerSettings for RegistrySettings<L, R> {
+    type Restrictions = R;
+
+    fn update_output_selection(&mut self, mut f: impl FnMut(&mut OutputSelection)) {
+        for settings in self.settings.values_mut() {
+            settings.update_output_selection(&mut f);
+        }
+    }
+
+    fn can_use_cached(&self, other: &Self) -> bool {
+        let names = [self, other]
+            .into_iter()
+            .flat_map(|settings| settings.settings.keys().chain(settings.untyped.keys()))
+            .collect::<BTreeSet<_>>();
+        names.into_iter().all(|name| {
+            let settings = self.resolve(name, other.settings.get(name).map(|s| &**s));
+            let cached = other.resolve(name, settings.as_deref());
+            match (settings, cached) {
+                (Some(settings), Some(cached)) => settings.can_use_cached(&*cached),
+                (None, None) => self.untyped.get(name) == other.untyped.get(name),
+                _ => false,
+            }
+        })
+    }
+
+    fn with_remappings(self, remappings: &[Remapping]) -> Self {
+        self.map(|settings| settings.with_remappings(remappings))
+    }
+
+    fn with_base_path(self, base_path: &Path) -> Self {
+        self.map(|settings| settings.with_base_path(base_path))
+    }
+
+    fn with_allow_paths(self, allowed_paths: &BTreeSet<PathBuf>) -> Self {
+        self.map(|settings| settings.with_allow_paths(allowed_paths))
+    }
+
+    fn with_include_paths(self, include_paths: &BTreeSet<PathBuf>) -> Self {
+        self.map(|settings| settings.with_include_paths(include_paths))
+    }
+
+    fn satisfies_restrictions(&self, restrictions: &Self::Restrictions) -> bool {
+        self.settings.values().all(|settings| settings.satisfies_restrictions(restrictions))
+    }
+}
+
+/// Input for the [`CompilerRegistry`], wrapping the input of the compiler it's dispatched to.
+#[derive(Debug)]
+pub struct RegistryInput<L = MultiCompilerLanguage, R = MultiCompilerRestrictions> {
+    language: L,
+    input: Box<dyn ErasedInput>,
+    _restrictions: PhantomData<R>,
+}
+
+impl<L, R> RegistryInput<L, R> {
+    /// Returns the input of the underlying compiler, if it is of type `I`.
+    pub fn downcast_ref<I: CompilerInput + 'static>(&self) -> Option<&I> {
+        self.input.as_any().downcast_ref()
+    }
+}
+
+impl<L, R> Serialize for RegistryInput<L, R> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.input.to_json().map_err(serde::ser::Error::custom)?.serialize(serializer)
+    }
+}
+
+impl<L: RegistryLanguage, R: RegistryRestrictions> CompilerInput for RegistryInput<L, R> {
+    type Settings = RegistrySettings<L, R>;
+    type Language = L;
+
+    fn build(
+        sources: Sources,
+        settings: Self::Settings,
+        language: Self::Language,
+        version: Version,
+    ) -> Self {
+        let input = match settings.settings.into_values().find(|s| s.recognizes(&language)) {
+            Some(settings) => settings.build_input(sources, language, version),
+            // fails to compile, see `CompilerRegistry::compile`
+            None => Box::new(UnavailableInput { sources, version }),
+        };
+        Self { language, input, _restrictions: PhantomData }
+    }
+
+    fn language(&self) -> Self::Language {
+        self.language
+    }
+
+    fn version(&self) -> &Version {
+        self.input.version()
+    }
+
+    fn sources(&self) -> impl Iterator<Item = (&Path, &Source)> {
+        self.input.sources().into_iter()
+    }
+
+    fn compiler_name(&self) -> Cow<'static, str> {
+        self.input.compiler_name()
+    }
+
+    fn strip_prefix(&mut self, base: &Path) {
+        self.input.strip_prefix(base);
+    }
+
+    fn apply_vfs(&mut self, vfs: &SolcVfs) {
+        self.input.apply_vfs(vfs);
+    }
+}
+
+/// Compilation error of a compiler registered in a [`CompilerRegistry`].
+///
+/// Serialized as the underlying error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegistryError {
+    /// The formatted error message.
+    pub message: String,
+    pub severity: Severity,
+    pub source_location: Option<SourceLocation>,
+    pub error_code: Option<u64>,
+    /// The serialized underlying error.
+    pub error: serde_json::Value,
+}
+
+impl RegistryError {
+    fn new<E: CompilationError>(error: E) -> Self {
+        Self {
+            message: error.to_string(),
+            severity: error.severity(),
+            source_location: error.source_location(),
+            error_code: error.error_code(),
+            error: serde_json::to_value(&error).unwrap_or_default(),
+        }
+    }
+}
+
+impl Serialize for RegistryError {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.error.serialize(serializer)
+    }
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl CompilationError for RegistryError {
+    fn is_warning(&self) -> bool {
+        self.severity.is_warning()
+    }
+
+    fn is_error(&self) -> bool {
+        self.severity.is_error()
+    }
+
+    fn source_location(&self) -> Option<SourceLocation> {
+        self.source_location.clone()
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn error_code(&self) -> Option<u64> {
+        self.error_code
+    }
+
+    fn from_solc_error(error: foundry_compilers_artifacts::Error) -> Option<Self> {
+        Some(Self::new(error))
+    }
+}
+
+/// Source parser for the [`CompilerRegistry`]. Dispatches to the parsers of the registered
+/// compilers by file extension.
+///
+/// Created by the registry with [`Compiler::parser`]. Parsers created with [`SourceParser::new`]
+/// don't know the registered compilers and fail to parse any sources.
+#[derive(Clone, Debug)]
+pub struct RegistryParser<L = MultiCompilerLanguage> {
+    /// The parsers of the registered compilers, `None` if not created by a registry.
+    parsers: Option<Vec<RegisteredParser<L>>>,
+}
+
+#[derive(Clone, Debug)]
+struct RegisteredParser<L> {
+    backend: String,
+    extensions: &'static [&'static str],
+    parser: Box<dyn ErasedParser<L>>,
+}
+
+impl<L: RegistryLanguage> RegistryParser<L> {
+    /// Returns the parser of the given compiler, if it is of type `P`.
+    pub fn get<P: SourceParser + 'static>(&self, compiler: &str) -> Option<&P> {
+        let parser = self.parsers.as_ref()?.iter().find(|p| p.backend == compiler)?;
+        parser.parser.as_any().downcast_ref()
+    }
+
+    /// Returns the parser of the given compiler, if it is of type `P`.
+    pub fn get_mut<P: SourceParser + 'static>(&mut self, compiler: &str) -> Option<&mut P> {
+        let parser = self.parsers.as_mut()?.iter_mut().find(|p| p.backend == compiler)?;
+        parser.parser.as_any_mut().downcast_mut()
+    }
+
+    /// Returns the parsers of the registered compilers.
+    fn parsers(&mut self) -> Result<&mut Vec<RegisteredParser<L>>> {
+        self.parsers.as_mut().ok_or_else(|| {
+            SolcError::msg(
+                "sources can only be parsed by a parser created by a compiler registry, see \
+                 `Compiler::parser`",
+            )
+        })
+    }
+}
+
+/// Returns the index of the parser for the given file, if any compiler recognizes it.
+fn index_for<L>(parsers: &[RegisteredParser<L>], path: &Path) -> Option<usize> {
+    let extension = path.extension().and_then(|e| e.to_str())?;
+    parsers.iter().position(|parser| parser.extensions.contains(&extension))
+}
+
+impl<L: RegistryLanguage> SourceParser for RegistryParser<L> {
+    type ParsedSource = RegistryParsedSource<L>;
+
+    /// Creates a parser which doesn't know the registered compilers and fails to parse any
+    /// sources, see [`Compiler::parser`].
+    fn new(_config: &ProjectPathsConfig) -> Self {
+        Self { parsers: None }
+    }
+
+    fn read(&mut self, path: &Path) -> Result<Node<Self::ParsedSource>> {
+        let parsers = self.parsers()?;
+        let idx = index_for(parsers, path).ok_or_else(|| {
+            SolcError::msg(format!("no registered compiler recognizes {}", path.display()))
+        })?;
+        parsers[idx].parser.read(path)
+    }
+
+    /// Parses the sources recognized by the registered compilers, the other sources are ignored.
+    fn parse_sources(
+        &mut self,
+        sources: &mut Sources,
+    ) -> Result<Vec<(PathBuf, Node<Self::ParsedSource>)>> {
+        let parsers = self.parsers()?;
+        let mut partitioned = vec![Sources::new(); parsers.len()];
+        for (path, source) in std::mem::take(sources) {
+            match index_for(parsers, &path) {
+                Some(idx) => {
+                    partitioned[idx].insert(path, source);
+                }
+                None => {
+                    trace!("no registered compiler recognizes {}", path.display());
+                    sources.insert(path, source);
+                }
+            }
+        }
+
+        let mut nodes = Vec::new();
+        for (parser, mut own) in parsers.iter_mut().zip(partitioned) {
+            nodes.extend(parser.parser.parse_sources(&mut own)?);
+            sources.extend(own);
+        }
+        Ok(nodes)
+    }
+
+    fn finalize_imports(
+        &mut self,
+        all_nodes: &mut Vec<Node<Self::ParsedSource>>,
+        include_paths: &BTreeSet<PathBuf>,
+    ) -> Result<()> {
+        let parsers = self.parsers()?;
+        // Must maintain original order.
+        let mut partitioned = (0..parsers.len()).map(|_| Vec::new()).collect::<Vec<_>>();
+        let mut order = Vec::new();
+        for node in std::mem::take(all_nodes) {
+            let idx =
+                parsers.iter().position(|parser| parser.backend == node.data.backend).ok_or_else(
+                    || SolcError::msg(format!("no parser for {} sources", node.data.backend)),
+                )?;
+            order.push(idx);
+            partitioned[idx].push(node);
+        }
+
+        let mut partitioned = parsers
+            .iter_mut()
+            .zip(partitioned)
+            .map(|(parser, nodes)| {
+                parser.parser.finalize_imports(nodes, include_paths).map(Vec::into_iter)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Assume that the order was not changed by the parsers.
+        for idx in order {
+            all_nodes.push(partitioned[idx].next().unwrap());
+        }
+        assert!(partitioned.iter_mut().all(|nodes| nodes.next().is_none()));
+
+        Ok(())
+    }
+}
+
+/// Source parsed by the parser of a compiler registered in a [`CompilerRegistry`].
+#[derive(Clone, Debug)]
+pub struct RegistryParsedSource<L = MultiCompilerLanguage> {
+    language: L,
+    /// The name of the compiler whose parser parsed the source.
+    backend: String,
+    data: Box<dyn ErasedParsedSource>,
+}
+
+impl<L> RegistryParsedSource<L> {
+    /// Returns the parsed source of the underlying compiler, if it is of type `P`.
+    pub fn downcast_ref<P: ParsedSource + 'static>(&self) -> Option<&P> {
+        self.data.as_any().downcast_ref()
+    }
+}
+
+impl<L: RegistryLanguage> ParsedSource for RegistryParsedSource<L> {
+    type Language = L;
+
+    /// Always fails, sources can only be parsed by a [`RegistryParser`] which knows the
+    /// registered compilers.
+    fn parse(_content: &str, file: &Path) -> Result<Self> {
+        Err(SolcError::msg(format!(
+            "{} can only be parsed by the parser of a compiler registry",
+            file.display()
+        )))
+    }
+
+    fn version_req(&self) -> Option<&VersionReq> {
+        self.data.version_req()
+    }
+
+    fn contract_names(&self) -> &[String] {
+        self.data.contract_names()
+    }
+
+    fn language(&self) -> Self::Language {
+        self.language
+    }
+
+    fn resolve_imports<C>(
+        &self,
+        paths: &ProjectPathsConfig<C>,
+        include_paths: &mut BTreeSet<PathBuf>,
+    ) -> Result<Vec<PathBuf>> {
+        self.data.resolve_imports(paths.with_language_ref(), include_paths)
+    }
+
+    fn compilation_dependencies<'a>(
+        &self,
+        imported_nodes: impl Iterator<Item = (&'a Path, &'a Self)>,
+    ) -> impl Iterator<Item = &'a Path>
+    where
+        Self: 'a,
+    {
+        let imported_nodes = imported_nodes
+            .filter(|(_, node)| node.backend == self.backend)
+            .map(|(path, node)| (path, &*node.data))
+            .collect();
+        self.data.compilation_dependencies(imported_nodes).into_iter()
+    }
+}
+
+/// Type-erased operations of a registered compiler.
+trait Backend<L, R>: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn compiler(&self) -> &dyn Any;
+
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// Returns whether the given language is a language of the compiler.
+    fn recognizes(&self, language: &L) -> bool;
+
+    fn parser(&self, config: &ProjectPathsConfig) -> Box<dyn ErasedParser<L>>;
+
+    fn default_settings(&self) -> Box<dyn ErasedSettings<L, R>>;
+
+    fn deserialize_settings(
+        &self,
+        value: serde_json::Value,
+    ) -> serde_json::Result<Box<dyn ErasedSettings<L, R>>>;
+
+    /// Binds the given settings to the compiler, if they are of its settings type.
+    fn bind_settings(
+        &self,
+        settings: &dyn ErasedSettings<L, R>,
+    ) -> Option<Box<dyn ErasedSettings<L, R>>>;
+
+    fn compile(&self, input: &dyn ErasedInput) -> Result<CompilerOutput<RegistryError, Contract>>;
+
+    fn available_versions(&self, language: &L) -> Vec<CompilerVersion>;
+
+    fn mirrored_versions(&self, language: &L, mirror: &Path) -> Vec<Version>;
+
+    fn install_from_mirror(&self, language: &L, version: &Version, mirror: &Path) -> Result<()>;
+}
+
+struct TypedBackend<C> {
+    name: String,
+    compiler: C,
+}
+
+impl<C: RegistrableCompiler, L: RegistryLanguage, R: RegistryRestrictions> Backend<L, R>
+    for TypedBackend<C>
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn compiler(&self) -> &dyn Any {
+        &self.compiler
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        C::Language::FILE_EXTENSIONS
+    }
+
+    fn recognizes(&self, language: &L) -> bool {
+        language.get::<C::Language>().is_some()
+    }
+
+    fn parser(&self, config: &ProjectPathsConfig) -> Box<dyn ErasedParser<L>> {
+        Box::new(TypedParser { backend: self.name.clone(), parser: self.compiler.parser(config) })
+    }
+
+    fn default_settings(&self) -> Box<dyn ErasedSettings<L, R>> {
+        Box::new(Entry::bound::<C>(C::Settings::default()))
+    }
+
+    fn deserialize_settings(
+        &self,
+        value: serde_json::Value,
+    ) -> serde_json::Result<Box<dyn ErasedSettings<L, R>>> {
+        Ok(Box::new(Entry::bound::<C>(serde_json::from_value::<C::Settings>(value)?)))
+    }
+
+    fn bind_settings(
+        &self,
+        settings: &dyn ErasedSettings<L, R>,
+    ) -> Option<Box<dyn ErasedSettings<L, R>>> {
+        let settings = settings.as_any().downcast_ref::<C::Settings>()?.clone();
+        Some(Box::new(Entry::bound::<C>(settings)))
+    }
+
+    fn compile(&self, input: &dyn ErasedInput) -> Result<CompilerOutput<RegistryError, Contract>> {
+        let input = input
+            .as_any()
+            .downcast_ref::<C::Input>()
+            .ok_or_else(|| SolcError::msg(format!("invalid input for {} compiler", self.name)))?;
+        Compiler::compile(&self.compiler, input).map(|output| output.map_err(RegistryError::new))
+    }
+
+    fn available_versions(&self, language: &L) -> Vec<CompilerVersion> {
+        language
+            .get()
+            .map(|language| self.compiler.available_versions(&language))
+            .unwrap_or_default()
+    }
+
+    fn mirrored_versions(&self, language: &L, mirror: &Path) -> Vec<Version> {
+        language
+            .get()
+            .map(|language| self.compiler.mirrored_versions(&language, mirror))
+            .unwrap_or_default()
+    }
+
+    fn install_from_mirror(&self, language: &L, version: &Version, mirror: &Path) -> Result<()> {
+        match language.get() {
+            Some(language) => self.compiler.install_from_mirror(&language, version, mirror),
+            None => Ok(()),
+        }
+    }
+}
+
+trait ErasedSettings<L, R>: dyn_clone::DynClone + fmt::Debug + Send + Sync {
+    /// Returns the underlying settings.
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    fn to_json(&self) -> serde_json::Result<serde_json::Value>;
+
+    /// Returns the default settings of the same type.
+    fn default_like(&self) -> Box<dyn ErasedSettings<L, R>>;
+
+    /// Deserializes settings of the same type.
+    fn deserialize_like(
+        &self,
+        value: serde_json::Value,
+    ) -> serde_json::Result<Box<dyn ErasedSettings<L, R>>>;
+
+    fn can_use_cached(&self, other: &dyn ErasedSettings<L, R>) -> bool;
+
+    fn update_output_selection(&mut self, f: &mut dyn FnMut(&mut OutputSelection));
+
+    fn with_remappings(self: Box<Self>, remappings: &[Remapping]) -> Box<dyn ErasedSettings<L, R>>;
+
+    fn with_base_path(self: Box<Self>, base_path: &Path) -> Box<dyn ErasedSettings<L, R>>;
+
+    fn with_allow_paths(
+        self: Box<Self>,
+        paths: &BTreeSet<PathBuf>,
+    ) -> Box<dyn ErasedSettings<L, R>>;
+
+    fn with_include_paths(
+        self: Box<Self>,
+        paths: &BTreeSet<PathBuf>,
+    ) -> Box<dyn ErasedSettings<L, R>>;
+
+    fn satisfies_restrictions(&self, restrictions: &R) -> bool;
+
+    /// Returns whether the settings are bound to a compiler of the given language.
+    fn recognizes(&self, language: &L) -> bool;
+
+    /// Builds the input of the compiler the settings are bound to.
+    fn build_input(
+        self: Box<Self>,
+        sources: Sources,
+        language: L,
+        version: Version,
+    ) -> Box<dyn ErasedInput>;
+}
+
+dyn_clone::clone_trait_object!(<L, R> ErasedSettings<L, R>);
+
+/// Settings of a registered compiler, bound to it if created by the registry.
+struct Entry<S, L> {
+    settings: S,
+    binding: Option<Binding<S, L>>,
+}
+
+/// Functions of the compiler the settings of an [`Entry`] are bound to.
+struct Binding<S, L> {
+    recognizes: fn(&L) -> bool,
+    build_input: fn(Sources, S, L, Version) -> Box<dyn ErasedInput>,
+}
+
+impl<S, L> Clone for Binding<S, L> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S, L> Copy for Binding<S, L> {}
+
+impl<S, L: RegistryLanguage> Entry<S, L> {
+    fn unbound(settings: S) -> Self {
+        Self { settings, binding: None }
+    }
+
+    fn bound<C: RegistrableCompiler<Settings = S>>(settings: S) -> Self {
+        let binding = Binding {
+            recognizes: |language: &L| language.get::<C::Language>().is_some(),
+            build_input: |sources, settings, language, version| {
+                let language = language.get().expect("recognized language");
+                Box::new(C::Input::build(sources, settings, language, version))
+            },
+        };
+        Self { settings, binding: Some(binding) }
+    }
+
+    fn map(self, f: impl FnOnce(S) -> S) -> Self {
+        Self { settings: f(self.settings), binding: self.binding }
+    }
+}
+
+impl<S: Clone, L> Clone for Entry<S, L> {
+    fn clone(&self) -> Self {
+        Self { settings: self.settings.clone(), binding: self.binding }
+    }
+}
+
+impl<S: fmt::Debug, L> fmt::Debug for Entry<S, L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Entry")
+            .field("settings", &self.settings)
+            .field("bound", &self.binding.is_some())
+            .finish()
+    }
+}
+
+impl<S, L, R> ErasedSettings<L, R> for Entry<S, L>
+where
+    S: CompilerSettings<Restrictions: 'static>,
+    L: RegistryLanguage,
+    R: RegistryRestrictions,
+{
+    fn as_any(&self) -> &dyn Any {
+        &self.settings
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        &mut self.settings
+    }
+
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(&self.settings)
+    }
+
+    fn default_like(&self) -> Box<dyn ErasedSettings<L, R>> {
+        Box::new(Self { settings: S::default(), binding: self.binding })
+    }
+
+    fn deserialize_like(
+        &self,
+        value: serde_json::Value,
+    ) -> serde_json::Result<Box<dyn ErasedSettings<L, R>>> {
+        Ok(Box::new(Self { settings: serde_json::from_value(value)?, binding: self.binding }))
+    }
+
+    fn can_use_cached(&self, other: &dyn ErasedSettings<L, R>) -> bool {
+        other
+            .as_any()
+            .downcast_ref()
+            .is_some_and(|other| CompilerSettings::can_use_cached(&self.settings, other))
+    }
+
+    fn update_output_selection(&mut self, f: &mut dyn FnMut(&mut OutputSelection)) {
+        CompilerSettings::update_output_selection(&mut self.settings, f)
+    }
+
+    fn with_remappings(self: Box<Self>, remappings: &[Remapping]) -> Box<dyn ErasedSettings<L, R>> {
+        Box::new(self.map(|settings| settings.with_remappings(remappings)))
+    }
+
+    fn with_base_path(self: Box<Self>, base_path: &Path) -> Box<dyn ErasedSettings<L, R>> {
+        Box::new(self.map(|settings| settings.with_base_path(base_path)))
+    }
+
+    fn with_allow_paths(
+        self: Box<Self>,
+        paths: &BTreeSet<PathBuf>,
+    ) -> Box<dyn ErasedSettings<L, R>> {
+        Box::new(self.map(|settings| settings.with_allow_paths(paths)))
+    }
+
+    fn with_include_paths(
+        self: Box<Self>,
+        paths: &BTreeSet<PathBuf>,
+    ) -> Box<dyn ErasedSettings<L, R>> {
+        Box::new(self.map(|settings| settings.with_include_paths(paths)))
+    }
+
+    fn satisfies_restrictions(&self, restrictions: &R) -> bool {
+        restrictions
+            .get::<S::Restrictions>()
+            .is_none_or(|restrictions| self.settings.satisfies_restrictions(&restrictions))
+    }
+
+    fn recognizes(&self, language: &L) -> bool {
+        self.binding.is_some_and(|binding| (binding.recognizes)(language))
+    }
+
+    fn build_input(
+        self: Box<Self>,
+        sources: Sources,
+        language: L,
+        version: Version,
+    ) -> Box<dyn ErasedInput> {
+        match self.binding {
+            Some(binding) => (binding.build_input)(sources, self.settings, language, version),
+            None => Box::new(UnavailableInput { sources, version }),
+        }
+    }
+}
+
+trait ErasedInput: fmt::Debug + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+
+    fn to_json(&self) -> serde_json::Result<serde_json::Value>;
+
+    fn version(&self) -> &Version;
+
+    fn sources(&self) -> Vec<(&Path, &Source)>;
+
+    fn compiler_name(&self) -> Cow<'static, str>;
+
+    fn strip_prefix(&mut self, base: &Path);
+
+    fn apply_vfs(&mut self, vfs: &SolcVfs);
+}
+
+impl<I: CompilerInput + 'static> ErasedInput for I {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+
+    fn version(&self) -> &Version {
+        CompilerInput::version(self)
+    }
+
+    fn sources(&self) -> Vec<(&Path, &Source)> {
+        CompilerInput::sources(self).collect()
+    }
+
+    fn compiler_name(&self) -> Cow<'static, str> {
+        CompilerInput::compiler_name(self)
+    }
+
+    fn strip_prefix(&mut self, base: &Path) {
+        CompilerInput::strip_prefix(self, base)
+    }
+
+    fn apply_vfs(&mut self, vfs: &SolcVfs) {
+        CompilerInput::apply_vfs(self, vfs)
+    }
+}
+
+/// Input built with settings which are not bound to the compiler of its language.
+#[derive(Debug)]
+struct UnavailableInput {
+    sources: Sources,
+    version: Version,
+}
+
+impl ErasedInput for UnavailableInput {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(&self.sources)
+    }
+
+    fn version(&self) -> &Version {
+        &self.version
+    }
+
+    fn sources(&self) -> Vec<(&Path, &Source)> {
+        self.sources.iter().map(|(path, source)| (path.as_path(), source)).collect()
+    }
+
+    fn compiler_name(&self) -> Cow<'static, str> {
+        "unavailable".into()
+    }
+
+    fn strip_prefix(&mut self, base: &Path) {
+        self.sources = std::mem::take(&mut self.sources)
+            .into_iter()
+            .map(|(path, source)| (path.strip_prefix(base).map(Into::into).unwrap_or(path), source))
+            .collect();
+    }
+
+    fn apply_vfs(&mut self, _vfs: &SolcVfs) {}
+}
+
+trait ErasedParsedSource: dyn_clone::DynClone + fmt::Debug + Send {
+    fn as_any(&self) -> &dyn Any;
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+
+    fn version_req(&self) -> Option<&VersionReq>;
+
+    fn contract_names(&self) -> &[String];
+
+    fn resolve_imports(
+        &self,
+        paths: &ProjectPathsConfig,
+        include_paths: &mut BTreeSet<PathBuf>,
+    ) -> Result<Vec<PathBuf>>;
+
+    fn compilation_dependencies<'a>(
+        &self,
+        imported_nodes: Vec<(&'a Path, &'a dyn ErasedParsedSource)>,
+    ) -> Vec<&'a Path>;
+}
+
+dyn_clone::clone_trait_object!(ErasedParsedSource);
+
+impl<P: ParsedSource + 'static> ErasedParsedSource for P {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn version_req(&self) -> Option<&VersionReq> {
+        ParsedSource::version_req(self)
+    }
+
+    fn contract_names(&self) -> &[String] {
+        ParsedSource::contract_names(self)
+    }
+
+    fn resolve_imports(
+        &self,
+        paths: &ProjectPathsConfig,
+        include_paths: &mut BTreeSet<PathBuf>,
+    ) -> Result<Vec<PathBuf>> {
+        ParsedSource::resolve_imports(self, paths, include_paths)
+    }
+
+    fn compilation_dependencies<'a>(
+        &self,
+        imported_nodes: Vec<(&'a Path, &'a dyn ErasedParsedSource)>,
+    ) -> Vec<&'a Path> {
+        let imported_nodes = imported_nodes
+            .into_iter()
+            .filter_map(|(path, node)| node.as_any().downcast_ref::<P>().map(|node| (path, node)));
+        ParsedSource::compilation_dependencies(self, imported_nodes).collect()
+    }
+}
+
+trait ErasedParser<L>: dyn_clone::DynClone + fmt::Debug + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    fn read(&mut self, path: &Path) -> Result<Node<RegistryParsedSource<L>>>;
+
+    fn parse_sources(
+        &mut self,
+        sources: &mut Sources,
+    ) -> Result<Vec<(PathBuf, Node<RegistryParsedSource<L>>)>>;
+
+    fn finalize_imports(
+        &mut self,
+        nodes: Vec<Node<RegistryParsedSource<L>>>,
+        include_paths: &BTreeSet<PathBuf>,
+    ) -> Result<Vec<Node<RegistryParsedSource<L>>>>;
+}
+
+dyn_clone::clone_trait_object!(<L> ErasedParser<L>);
+
+#[derive(Clone, Debug)]
+struct TypedParser<P> {
+    backend: String,
+    parser: P,
+}
+
+impl<P: SourceParser<ParsedSource: 'static> + 'static, L: RegistryLanguage> ErasedParser<L>
+    for TypedParser<P>
+{
+    fn as_any(&self) -> &dyn Any {
+        &self.parser
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        &mut self.parser
+    }
+
+    fn read(&mut self, path: &Path) -> Result<Node<RegistryParsedSource<L>>> {
+        let node = self.parser.read(path)?;
+        self.erase(node)
+    }
+
+    fn parse_sources(
+        &mut self,
+        sources: &mut Sources,
+    ) -> Result<Vec<(PathBuf, Node<RegistryParsedSource<L>>)>> {
+        self.parser
+            .parse_sources(sources)?
+            .into_iter()
+            .map(|(path, node)| Ok((path, self.erase(node)?)))
+            .collect()
+    }
+
+    fn finalize_imports(
+        &mut self,
+        nodes: Vec<Node<RegistryParsedSource<L>>>,
+        include_paths: &BTreeSet<PathBuf>,
+    ) -> Result<Vec<Node<RegistryParsedSource<L>>>> {
+        let mut nodes = nodes
+            .into_iter()
+            .map(|node| {
+                node.map_data(|data| {
+                    *data.data.into_any().downcast::<P::ParsedSource>().expect("parsed by P")
+                })
+            })
+            .collect();
+        self.parser.finalize_imports(&mut nodes, include_paths)?;
+        nodes.into_iter().map(|node| self.erase(node)).collect()
+    }
+}
+
+impl<P: SourceParser<ParsedSource: 'static>> TypedParser<P> {
+    fn erase<L: RegistryLanguage>(
+        &self,
+        node: Node<P::ParsedSource>,
+    ) -> Result<Node<RegistryParsedSource<L>>> {
+        let language = node.data.language();
+        let language = L::from_language(language).ok_or_else(|| {
+            SolcError::msg(format!("{language} is not part of the languages of the registry"))
+        })?;
+        Ok(node.map_data(|data| RegistryParsedSource {
+            language,
+            backend: self.backend.clone(),
+            data: Box::new(data),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Graph, ProjectBuilder,
+        solc::{Restriction, Solc, SolcCompiler, SolcSettings},
+        vyper::{Vyper, VyperSettings, settings::VyperRestrictions},
+    };
+    use foundry_compilers_artifacts::EvmVersion;
+    use foundry_compilers_core::utils;
+    use std::fs;
+
+    fn registry() -> CompilerRegistry {
+        CompilerRegistry::new()
+            .with(
+                "solc",
+                SolcCompiler::Specific(Solc::new_with_version("solc", Version::new(0, 8, 28))),
+            )
+            .unwrap()
+            .with("vyper", Vyper { path: "vyper".into(), version: Version::new(0, 4, 0) })
+            .unwrap()
+    }
+
+    #[test]
+    fn can_resolve_sources_by_language() {
+        let tmp = utils::tempdir("registry").unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/A.sol"), "pragma solidity ^0.8.0;\ncontract A {}\n").unwrap();
+        fs::write(root.join("src/B.vy"), "# pragma version ^0.4.0\n").unwrap();
+        fs::write(root.join("src/README.md"), "").unwrap();
+
+        let compiler = registry();
+        assert_eq!(compiler.names().collect::<Vec<_>>(), ["solc", "vyper"]);
+        assert!(compiler.get::<Vyper>("vyper").is_some());
+        assert!(compiler.get::<Vyper>("solc").is_none());
+
+        let project = ProjectBuilder::<CompilerRegistry>::default()
+            .settings(compiler.settings())
+            .paths(ProjectPathsConfig::dapptools(root).unwrap())
+            .build(compiler)
+            .unwrap();
+        let graph = Graph::resolve_with(&project.paths, project.parser()).unwrap();
+        assert_eq!(graph.files().len(), 2);
+
+        let resolved = graph.into_sources_by_version(&project).unwrap();
+        let mut languages = resolved
+            .sources
+            .iter()
+            .map(|(language, inputs)| {
+                let (version, sources, _) = &inputs[0];
+                (language.to_string(), version.clone(), sources.keys().cloned().collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>();
+        languages.sort();
+        assert_eq!(
+            languages,
+            [
+                ("Solidity".to_string(), Version::new(0, 8, 28), vec![root.join("src/A.sol")]),
+                ("Vyper".to_string(), Version::new(0, 4, 0), vec![root.join("src/B.vy")]),
+            ]
+        );
+
+        let language: MultiCompilerLanguage = serde_json::from_str("\"vyper\"").unwrap();
+        assert_eq!(project.compiler.compiler_for(&language), Some("vyper"));
+    }
+
+    #[test]
+    fn can_scope_languages_to_registry() {
+        let tmp = utils::tempdir("registry").unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/A.sol"), "pragma solidity ^0.8.0;\ncontract A {}\n").unwrap();
+        fs::write(root.join("src/B.huff"), "#define macro MAIN() = takes (0) returns (0) {}\n")
+            .unwrap();
+
+        // Huff isn't part of the languages of the multi compiler
+        let huff = crate::Huff::new_with_version("huffc", Version::new(0, 3, 2));
+        assert!(registry().with("huff", huff).is_err());
+
+        let compiler = registry();
+        let project = ProjectBuilder::<CompilerRegistry>::default()
+            .settings(compiler.settings())
+            .paths(ProjectPathsConfig::dapptools(root).unwrap())
+            .build(compiler)
+            .unwrap();
+        let graph = Graph::resolve_with(&project.paths, project.parser()).unwrap();
+        assert_eq!(graph.files().len(), 1);
+        assert!(graph.files().contains_key(&root.join("src/A.sol")));
+
+        // parsers which are not created by the registry don't know any compilers
+        let err = Graph::<RegistryParser>::resolve(&project.paths).unwrap_err();
+        assert!(err.to_string().contains("`Compiler::parser`"), "{err}");
+    }
+
+    #[test]
+    fn can_parse_with_parser_of_registered_compiler() {
+        let tmp = utils::tempdir("registry").unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/A.sol"), "pragma solidity ^0.8.0;\ncontract A {}\n").unwrap();
+
+        // the parser of a nested registry depends on the compilers registered in it
+        let solc = SolcCompiler::Specific(Solc::new_with_version("solc", Version::new(0, 8, 28)));
+        let nested = CompilerRegistry::<SolcLanguage>::new().with("solc", solc).unwrap();
+        let compiler =
+            CompilerRegistry::<MultiCompilerLanguage>::new().with("nested", nested).unwrap();
+        let paths = ProjectPathsConfig::dapptools(root).unwrap();
+        let graph = Graph::resolve_with(&paths, compiler.parser(&paths)).unwrap();
+        assert!(graph.files().contains_key(&root.join("src/A.sol")));
+    }
+
+    #[test]
+    fn can_convert_languages_by_type() {
+        let solidity = MultiCompilerLanguage::Solc(SolcLanguage::Solidity);
+        assert_eq!(solidity.get::<SolcLanguage>(), Some(SolcLanguage::Solidity));
+        assert_eq!(solidity.get::<VyperLanguage>(), None);
+        assert_eq!(
+            MultiCompilerLanguage::from_language(VyperLanguage::default()),
+            Some(MultiCompilerLanguage::Vyper(VyperLanguage::default()))
+        );
+        assert_eq!(MultiCompilerLanguage::from_language(HuffLanguage::default()), None);
+        assert_eq!(SolcLanguage::from_language(solidity), None);
+    }
+
+    #[test]
+    fn cannot_register_conflicting_extensions() {
+        let err = registry()
+            .with(
+                "solc-0.8.28",
+                SolcCompiler::Specific(Solc::new_with_version("solc", Version::new(0, 8, 28))),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("compiler solc already recognizes"), "{err}");
+
+        // replacing a compiler under the same name is fine
+        let vyper = Vyper { path: "vyper-0.3".into(), version: Version::new(0, 3, 10) };
+        let registry = registry().with("vyper", vyper).unwrap();
+        assert_eq!(registry.get::<Vyper>("vyper").unwrap().version, Version::new(0, 3, 10));
+
+        // compilers of languages outside of the language set can't be registered
+        let vyper = Vyper { path: "vyper".into(), version: Version::new(0, 4, 0) };
+        let err =
+            CompilerRegistry::<crate::solc::SolcLanguage>::new().with("vyper", vyper).unwrap_err();
+        assert!(err.to_string().contains("not part of the languages"), "{err}");
+    }
+
+    #[test]
+    fn can_serialize_settings() {
+        let registry = registry();
+
+        let mut settings = registry.settings().with(
+            "vyper",
+            VyperSettings { evm_version: Some(EvmVersion::Cancun), ..Default::default() },
+        );
+        settings.get_mut::<SolcSettings>("solc").unwrap().settings.optimizer.enabled = Some(true);
+        assert!(settings.get::<SolcSettings>("vyper").is_none());
+
+        let json = serde_json::to_value(&settings).unwrap();
+        assert_eq!(json["vyper"]["evmVersion"], "cancun");
+        let mut deserialized: RegistrySettings = serde_json::from_value(json.clone()).unwrap();
+        assert!(CompilerSettings::can_use_cached(&settings, &deserialized));
+        registry.configure_settings(&mut deserialized).unwrap();
+        assert_eq!(
+            deserialized.get::<VyperSettings>("vyper").unwrap().evm_version,
+            Some(EvmVersion::Cancun)
+        );
+        let mut deserialized: RegistrySettings = serde_json::from_value(json).unwrap();
+        assert!(
+            deserialized
+                .get_mut::<SolcSettings>("solc")
+                .unwrap()
+                .settings
+                .optimizer
+                .enabled
+                .unwrap()
+        );
+
+        // settings of the wrong type can't be bound
+        let mut invalid: RegistrySettings =
+            RegistrySettings::default().with("solc", VyperSettings::default());
+        assert!(registry.configure_settings(&mut invalid).is_err());
+
+        // missing settings are the same as the defaults
+        let defaults: RegistrySettings =
+            RegistrySettings::default().with("solc", SolcSettings::default());
+        assert!(CompilerSettings::can_use_cached(&defaults, &RegistrySettings::default()));
+        assert!(!CompilerSettings::can_use_cached(&settings, &RegistrySettings::default()));
+        assert!(CompilerSettings::can_use_cached(
+            &registry.settings(),
+            &RegistrySettings::default()
+        ));
+        assert!(!CompilerSettings::can_use_cached(&settings, &registry.settings()));
+
+        let restrictions = MultiCompilerRestrictions {
+            vyper: VyperRestrictions {
+                evm_version: Restriction { min: Some(EvmVersion::Shanghai), max: None },
+            },
+            ..Default::default()
+        };
+        assert!(CompilerSettings::satisfies_restrictions(&settings, &restrictions));
+        let restrictions = restrictions.merge(MultiCompilerRestrictions::default()).unwrap();
+        assert!(!CompilerSettings::satisfies_restrictions(&registry.settings(), &restrictions));
+    }
+
+    #[test]
+    fn cannot_compile_with_unbound_settings() {
+        let tmp = utils::tempdir("registry").unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/A.vy"), "# pragma version ^0.4.0\n").unwrap();
+
+        let project = ProjectBuilder::<CompilerRegistry>::default()
+            .paths(ProjectPathsConfig::dapptools(root).unwrap())
+            .build(registry())
+            .unwrap();
+        let err = project.compile().unwrap_err();
+        assert!(err.to_string().contains("CompilerRegistry::settings"), "{err}");
+    }
+
+    /// Language set of a downstream crate which adds Huff to the languages of the multi compiler.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum DownstreamLanguage {
+        Solc(SolcLanguage),
+        Vyper(VyperLanguage),
+        Huff(HuffLanguage),
+    }
+
+    impl Language for DownstreamLanguage {
+        const FILE_EXTENSIONS: &'static [&'static str] = &["sol", "vy", "vyi", "yul", "huff"];
+    }
+
+    impl RegistryLanguage for DownstreamLanguage {
+        fn get<T: Language>(&self) -> Option<T> {
+            match self {
+                Self::Solc(language) => language.get(),
+                Self::Vyper(language) => language.get(),
+                Self::Huff(language) => language.get(),
+            }
+        }
+
+        fn from_language<T: Language>(language: T) -> Option<Self> {
+            SolcLanguage::from_language(language)
+                .map(Self::Solc)
+                .or_else(|| VyperLanguage::from_language(language).map(Self::Vyper))
+                .or_else(|| HuffLanguage::from_language(language).map(Self::Huff))
+        }
+    }
+
+    impl fmt::Display for DownstreamLanguage {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Solc(language) => language.fmt(f),
+                Self::Vyper(language) => language.fmt(f),
+                Self::Huff(language) => language.fmt(f),
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn can_compile_downstream_language() {
+        use crate::{Huff, test_utils::write_script};
+
+        let tmp = utils::tempdir("registry").unwrap();
+        let huffc = tmp.path().join("huffc");
+        write_script(
+            &huffc,
+            r#"if [ "$1" = "--version" ]; then echo "huffc 0.3.2"; exit 0; fi
+echo '{"bytecode":"600a","runtime":"600a"}' > "$3"
+"#,
+        );
+
+        let root = tmp.path().join("project");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/Token.huff"), "#define macro MAIN() = takes (0) returns (0) {}\n")
+            .unwrap();
+
+        let compiler = CompilerRegistry::<DownstreamLanguage>::new()
+            .with(
+                "solc",
+                SolcCompiler::Specific(Solc::new_with_version("solc", Version::new(0, 8, 28))),
+            )
+            .unwrap()
+            .with("huff", Huff::new(&huffc).unwrap())
+            .unwrap();
+        assert_eq!(compiler.names().collect::<Vec<_>>(), ["solc", "huff"]);
+        let project = ProjectBuilder::<CompilerRegistry<DownstreamLanguage>>::default()
+            .settings(compiler.settings())
+            .paths(ProjectPathsConfig::dapptools(&root).unwrap())
+            .build(compiler)
+            .unwrap();
+
+        let output = project.compile().unwrap();
+        output.assert_success();
+        assert!(output.find_first("Token").is_some());
+
+        let output = project.compile().unwrap();
+        assert!(output.is_unchanged());
+    }
+}
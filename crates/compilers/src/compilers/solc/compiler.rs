@@ -351,25 +351,22 @@ impl Solc {
             }
         }
 
-        use sha2::Digest;
-        let mut hasher = sha2::Sha256::new();
-        hasher.update(content);
-        let checksum_calc = &hasher.finalize()[..];
-
-        let checksum_found = &RELEASES
+        let checksum_found = RELEASES
             .0
             .get_checksum(&version)
             .ok_or_else(|| SolcError::ChecksumNotFound { version: version.clone() })?;
+        check_checksum(&checksum_found, &version, &content, &version_path)
+    }
 
-        if checksum_calc == checksum_found {
-            Ok(())
-        } else {
-            use alloy_primitives::hex;
-            let expected = hex::encode(checksum_found);
-            let detected = hex::encode(checksum_calc);
-            warn!(target: "solc", "checksum mismatch for {:?}, expected {}, but found {} for file {:?}", version, expected, detected, version_path);
-            Err(SolcError::ChecksumMismatch { version, expected, detected, file: version_path })
-        }
+    /// Installs the provided version of Solc from a local mirror of the official binaries, see
+    /// [`SolcMirror`](super::SolcMirror).
+    ///
+    /// The binary is verified against the checksum in the mirror's `list.json` and installed
+    /// under the svm dir, like [`Self::blocking_install`].
+    #[cfg(feature = "svm-solc")]
+    #[instrument(name = "Solc::install_from_mirror", skip_all)]
+    pub fn install_from_mirror(mirror: &Path, version: &Version) -> Result<Self> {
+        super::SolcMirror::open(mirror)?.install(version)
     }
 
     /// Convenience function for compiling all sources under the given path
@@ -540,6 +537,35 @@ impl Solc {
     }
 }
 
+/// Checks the SHA256 checksum of the given solc binary content against the expected checksum.
+#[cfg(feature = "svm-solc")]
+pub(crate) fn check_checksum(
+    checksum_found: &[u8],
+    version: &Version,
+    content: &[u8],
+    file: &Path,
+) -> Result<()> {
+    use sha2::Digest;
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(content);
+    let checksum_calc = &hasher.finalize()[..];
+
+    if checksum_calc == checksum_found {
+        Ok(())
+    } else {
+        use alloy_primitives::hex;
+        let expected = hex::encode(checksum_found);
+        let detected = hex::encode(checksum_calc);
+        warn!(target: "solc", "checksum mismatch for {:?}, expected {}, but found {} for file {:?}", version, expected, detected, file);
+        Err(SolcError::ChecksumMismatch {
+            version: version.clone(),
+            expected,
+            detected,
+            file: file.to_path_buf(),
+        })
+    }
+}
+
 #[cfg(feature = "async")]
 impl Solc {
     /// Convenience function for compiling all sources under the given path
@@ -0,0 +1,313 @@
+//! Installing solc from a local mirror of the official binaries.
+
+use foundry_compilers_core::{
+    error::{Result, SolcError},
+    utils,
+};
+use semver::Version;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// The name of the release list in a [`SolcMirror`].
+pub const MIRROR_RELEASE_LIST: &str = "list.json";
+
+/// A local mirror of the official solc binaries, for environments without network access.
+///
+/// The mirror directory is laid out like a platform directory of
+/// [binaries.soliditylang.org](https://binaries.soliditylang.org/), e.g. `linux-amd64`: a
+/// `list.json` release list, and the binaries referenced by its `releases` next to it.
+///
+/// Projects in offline mode install missing versions from a mirror, see
+/// [`ProjectBuilder::offline_mirror`](crate::ProjectBuilder::offline_mirror).
+#[derive(Clone, Debug)]
+pub struct SolcMirror {
+    root: PathBuf,
+    releases: ReleaseList,
+}
+
+/// The parts of a `list.json` release list which are needed to install binaries.
+#[derive(Clone, Debug, Deserialize)]
+struct ReleaseList {
+    #[cfg(feature = "svm-solc")]
+    builds: Vec<Build>,
+    releases: BTreeMap<Version, String>,
+}
+
+#[cfg(feature = "svm-solc")]
+#[derive(Clone, Debug, Deserialize)]
+struct Build {
+    path: String,
+    #[serde(with = "alloy_primitives::hex")]
+    sha256: Vec<u8>,
+}
+
+impl SolcMirror {
+    /// Opens the mirror at the given directory by reading its release list.
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        let releases = utils::read_json_file(&root.join(MIRROR_RELEASE_LIST))?;
+        Ok(Self { root, releases })
+    }
+
+    /// Returns the mirror directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns all versions available in the mirror, sorted in ascending order.
+    pub fn versions(&self) -> Vec<Version> {
+        self.releases.releases.keys().cloned().collect()
+    }
+
+    /// Returns the path to the binary of the given version in the mirror.
+    pub fn binary(&self, version: &Version) -> Result<PathBuf> {
+        self.artifact(version).map(|artifact| self.root.join(artifact))
+    }
+
+    fn artifact(&self, version: &Version) -> Result<&str> {
+        self.releases.releases.get(version).map(String::as_str).ok_or_else(|| {
+            SolcError::msg(format!(
+                "solc {version} is not available in mirror {}",
+                self.root.display()
+            ))
+        })
+    }
+}
+
+#[cfg(feature = "svm-solc")]
+impl SolcMirror {
+    /// Installs the given version under the svm dir and returns the [Solc](super::Solc) instance
+    /// pointing to the installation.
+    ///
+    /// Like `svm`, this holds the installation lock of the version, so that parallel
+    /// installations of the same version wait for each other and reuse the installed binary.
+    pub fn install(&self, version: &Version) -> Result<super::Solc> {
+        let version = if version.pre.is_empty() {
+            Version::new(version.major, version.minor, version.patch)
+        } else {
+            // Preserve version if it is a prerelease.
+            version.clone()
+        };
+
+        trace!("installing solc version \"{}\" from mirror {}", version, self.root.display());
+        crate::report::solc_installation_start(&version);
+        match self.install_locked(&version) {
+            Ok(solc) => {
+                crate::report::solc_installation_success(&version);
+                Ok(solc)
+            }
+            Err(err) => {
+                crate::report::solc_installation_error(&version, &err.to_string());
+                Err(err)
+            }
+        }
+    }
+
+    fn install_locked(&self, version: &Version) -> Result<super::Solc> {
+        let dir = svm::version_path(&version.to_string());
+        std::fs::create_dir_all(&dir).map_err(|err| SolcError::io(err, &dir))?;
+        // the same lock file as the one `svm::install` uses
+        let lock_path = dir.join(".lock");
+        let lock = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|err| SolcError::io(err, &lock_path))?;
+        lock.lock().map_err(|err| SolcError::io(err, &lock_path))?;
+
+        self.install_to(version, &svm::version_binary(&version.to_string()))
+    }
+
+    /// Copies the binary of the given version to `dest` after verifying its checksum against the
+    /// mirror's release list.
+    ///
+    /// An existing binary at `dest` with the same content is reused, since it might be in use by
+    /// another process. Otherwise the binary is written to a temporary file first, so that `dest`
+    /// never contains a partially written binary.
+    pub fn install_to(&self, version: &Version, dest: &Path) -> Result<super::Solc> {
+        use std::fs;
+
+        let artifact = self.artifact(version)?;
+        let binary = self.root.join(artifact);
+        let checksum = self
+            .releases
+            .builds
+            .iter()
+            .find(|build| build.path == artifact)
+            .map(|build| build.sha256.as_slice())
+            .ok_or_else(|| SolcError::ChecksumNotFound { version: version.clone() })?;
+        let content = fs::read(&binary).map_err(|err| SolcError::io(err, &binary))?;
+        super::compiler::check_checksum(checksum, version, &content, &binary)?;
+
+        if fs::read(dest).ok().as_ref() != Some(&content) {
+            utils::create_parent_dir_all(dest)?;
+            let mut tmp = dest.as_os_str().to_owned();
+            tmp.push(".part");
+            let tmp = PathBuf::from(tmp);
+            fs::write(&tmp, content).map_err(|err| SolcError::io(err, &tmp))?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&tmp, fs::Permissions::from_mode(0o755))
+                    .map_err(|err| SolcError::io(err, &tmp))?;
+            }
+            fs::rename(&tmp, dest).map_err(|err| SolcError::io(err, dest))?;
+        }
+
+        Ok(super::Solc::new_with_version(dest, version.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn mirror(root: &Path, checksum: &str, binary: &[u8]) {
+        let list = serde_json::json!({
+            "builds": [{
+                "path": "solc-v0.8.25",
+                "version": "0.8.25",
+                "sha256": checksum,
+            }],
+            "releases": { "0.8.25": "solc-v0.8.25" },
+        });
+        fs::write(root.join(MIRROR_RELEASE_LIST), list.to_string()).unwrap();
+        fs::write(root.join("solc-v0.8.25"), binary).unwrap();
+    }
+
+    #[test]
+    fn can_open_mirror() {
+        let tmp = utils::tempdir("mirror").unwrap();
+        mirror(tmp.path(), "0x00", b"solc");
+
+        let mirror = SolcMirror::open(tmp.path()).unwrap();
+        assert_eq!(mirror.versions(), vec![Version::new(0, 8, 25)]);
+        assert_eq!(
+            mirror.binary(&Version::new(0, 8, 25)).unwrap(),
+            tmp.path().join("solc-v0.8.25")
+        );
+        let err = mirror.binary(&Version::new(0, 8, 24)).unwrap_err();
+        assert!(err.to_string().contains("solc 0.8.24 is not available in mirror"), "{err}");
+    }
+
+    #[cfg(feature = "svm-solc")]
+    fn sha256(content: &[u8]) -> String {
+        use sha2::Digest;
+        alloy_primitives::hex::encode_prefixed(sha2::Sha256::digest(content))
+    }
+
+    #[cfg(feature = "svm-solc")]
+    #[test]
+    fn can_install_from_mirror() {
+        let tmp = utils::tempdir("mirror").unwrap();
+        mirror(tmp.path(), &sha256(b"solc"), b"solc");
+
+        let mirror = SolcMirror::open(tmp.path()).unwrap();
+        let dest = tmp.path().join("svm/0.8.25/solc-0.8.25");
+        let solc = mirror.install_to(&Version::new(0, 8, 25), &dest).unwrap();
+        assert_eq!(solc.solc, dest);
+        assert_eq!(fs::read(&dest).unwrap(), b"solc");
+
+        // installing again reuses the binary
+        mirror.install_to(&Version::new(0, 8, 25), &dest).unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), b"solc");
+    }
+
+    #[cfg(feature = "svm-solc")]
+    #[test]
+    fn rejects_checksum_mismatch() {
+        let tmp = utils::tempdir("mirror").unwrap();
+        mirror(tmp.path(), &sha256(b"solc"), b"tampered");
+
+        let mirror = SolcMirror::open(tmp.path()).unwrap();
+        let dest = tmp.path().join("solc");
+        let err = mirror.install_to(&Version::new(0, 8, 25), &dest).unwrap_err();
+        assert!(matches!(err, SolcError::ChecksumMismatch { .. }), "{err}");
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn offline_project_can_use_mirrored_versions() {
+        use crate::{
+            CompilerVersion, Graph, ProjectBuilder, ProjectPathsConfig,
+            compilers::{Compiler, CompilerOutput},
+            resolver::parse::SolParser,
+            solc::{SolcLanguage, SolcSettings, SolcVersionedInput},
+        };
+        use foundry_compilers_artifacts::{Contract, Error};
+
+        /// Solc compiler with its own installation directory, which only knows about the 0.8.24
+        /// release.
+        #[derive(Clone, Debug)]
+        struct MirroredSolc {
+            installs: PathBuf,
+        }
+
+        impl Compiler for MirroredSolc {
+            type Input = SolcVersionedInput;
+            type CompilationError = Error;
+            type CompilerContract = Contract;
+            type Parser = SolParser;
+            type Settings = SolcSettings;
+            type Language = SolcLanguage;
+
+            fn compile(&self, _input: &Self::Input) -> Result<CompilerOutput<Error, Contract>> {
+                unimplemented!()
+            }
+
+            fn available_versions(&self, _language: &SolcLanguage) -> Vec<CompilerVersion> {
+                // installations are laid out like a mirror
+                let installed = SolcMirror::open(&self.installs)
+                    .map(|installs| installs.versions())
+                    .unwrap_or_default();
+                installed
+                    .into_iter()
+                    .map(CompilerVersion::Installed)
+                    .chain([CompilerVersion::Remote(Version::new(0, 8, 24))])
+                    .collect()
+            }
+
+            fn mirrored_versions(&self, _language: &SolcLanguage, mirror: &Path) -> Vec<Version> {
+                SolcMirror::open(mirror).map(|mirror| mirror.versions()).unwrap_or_default()
+            }
+        }
+
+        let tmp = utils::tempdir("mirror").unwrap();
+        let root = tmp.path().join("project");
+        let [mirror_dir, installs] = ["mirror", "installs"].map(|dir| tmp.path().join(dir));
+        for dir in [root.join("src"), mirror_dir.clone(), installs.clone()] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(root.join("src/A.sol"), "pragma solidity =0.8.25;\ncontract A {}").unwrap();
+        mirror(&mirror_dir, "0x00", b"solc");
+        let compiler = MirroredSolc { installs: installs.clone() };
+
+        let resolve = |builder: ProjectBuilder<MirroredSolc>| {
+            let project = builder
+                .paths(ProjectPathsConfig::dapptools(&root).unwrap())
+                .build(compiler.clone())
+                .unwrap();
+            let graph = Graph::resolve(&project.paths).unwrap();
+            graph.into_sources_by_version(&project).map(|resolved| {
+                resolved.sources.into_values().flatten().map(|(v, _, _)| v).collect::<Vec<_>>()
+            })
+        };
+
+        // 0.8.25 is neither installed nor a known release, but available in the mirror
+        let version = Version::new(0, 8, 25);
+        assert_eq!(
+            resolve(ProjectBuilder::default().offline_mirror(&mirror_dir)).unwrap(),
+            std::slice::from_ref(&version)
+        );
+        assert!(resolve(ProjectBuilder::default().offline()).is_err());
+
+        mirror(&installs, "0x00", b"solc");
+        assert_eq!(resolve(ProjectBuilder::default().offline()).unwrap(), [version]);
+    }
+}
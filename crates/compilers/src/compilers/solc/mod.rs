@@ -10,7 +10,7 @@ use crate::{
     },
 };
 use foundry_compilers_artifacts::{
-    BytecodeHash, Contract, Error, EvmVersion, Settings, Severity, SolcInput,
+    BytecodeHash, Contract, Error, EvmVersion, Settings, Severity, SolcInput, SolcVfs,
     error::SourceLocation,
     output_selection::OutputSelection,
     remappings::Remapping,
@@ -32,6 +32,9 @@ pub use foundry_compilers_artifacts::SolcLanguage;
 mod compiler;
 pub use compiler::{SOLC_EXTENSIONS, Solc};
 
+mod mirror;
+pub use mirror::{MIRROR_RELEASE_LIST, SolcMirror};
+
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "svm-solc", derive(Default))]
 pub enum SolcCompiler {
@@ -113,6 +116,38 @@ impl Compiler for SolcCompiler {
             }
         }
     }
+
+    #[cfg(feature = "svm-solc")]
+    fn mirrored_versions(&self, _language: &Self::Language, mirror: &Path) -> Vec<Version> {
+        match self {
+            Self::Specific(_) => Vec::new(),
+            Self::AutoDetect => match SolcMirror::open(mirror) {
+                Ok(mirror) => mirror.versions(),
+                Err(err) => {
+                    warn!("failed to open solc mirror {}: {err}", mirror.display());
+                    Vec::new()
+                }
+            },
+        }
+    }
+
+    #[cfg(feature = "svm-solc")]
+    fn install_from_mirror(
+        &self,
+        _language: &Self::Language,
+        version: &Version,
+        mirror: &Path,
+    ) -> Result<()> {
+        match self {
+            Self::Specific(_) => Ok(()),
+            Self::AutoDetect => {
+                if Solc::find_svm_installed_version(version)?.is_none() {
+                    Solc::install_from_mirror(mirror, version)?;
+                }
+                Ok(())
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -165,6 +200,10 @@ impl CompilerInput for SolcVersionedInput {
     fn strip_prefix(&mut self, base: &Path) {
         self.input.strip_prefix(base);
     }
+
+    fn apply_vfs(&mut self, vfs: &SolcVfs) {
+        self.input.apply_vfs(vfs);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
@@ -521,6 +560,10 @@ impl CompilationError for Error {
     fn error_code(&self) -> Option<u64> {
         self.error_code
     }
+
+    fn from_solc_error(error: Error) -> Option<Self> {
+        Some(error)
+    }
 }
 
 #[cfg(test)]
@@ -79,6 +79,44 @@ impl Vyper {
         Ok(Self { path, version })
     }
 
+    /// Discovers all Vyper binaries in the given directory, sorted by version.
+    ///
+    /// Binaries are recognized by their name, which must contain the version, e.g.
+    /// `vyper-0.4.0`, or the official release name `vyper.0.4.0+commit.e9db8d9f.linux`. Binaries
+    /// named `vyper` inside a version directory, e.g. `0.4.0/vyper`, are recognized as well.
+    ///
+    /// The binaries are not executed, so the versions are not verified.
+    pub fn find_all_in(dir: &Path) -> Result<Vec<Self>> {
+        let mut all = Vec::new();
+        for entry in std::fs::read_dir(dir).map_err(|err| SolcError::io(err, dir))? {
+            let path = entry.map_err(|err| SolcError::io(err, dir))?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else { continue };
+
+            if path.is_dir() {
+                let binary = path.join(if cfg!(windows) { "vyper.exe" } else { "vyper" });
+                if let Ok(version) = Version::parse(name)
+                    && binary.is_file()
+                {
+                    all.push(Self { path: binary, version });
+                }
+            } else if let Some(version) = name
+                .strip_prefix("vyper")
+                .and_then(|rest| rest.strip_prefix(['-', '.']))
+                .and_then(parse_version_prefix)
+            {
+                all.push(Self { path, version });
+            }
+        }
+        all.sort_by(|a, b| a.version.cmp(&b.version));
+        Ok(all)
+    }
+
+    /// Returns whether this is the given version, ignoring pre-release and build metadata.
+    pub(crate) fn has_version(&self, version: &Version) -> bool {
+        (self.version.major, self.version.minor, self.version.patch)
+            == (version.major, version.minor, version.patch)
+    }
+
     /// Convenience function for compiling all sources under the given path
     pub fn compile_source(&self, path: &Path) -> Result<VyperOutput> {
         let input = VyperInput::new(
@@ -199,6 +237,78 @@ impl Vyper {
     }
 }
 
+/// A set of Vyper installations with distinct versions.
+///
+/// Sources are compiled with the installation matching the version picked for them by the
+/// resolver, so a project can mix sources requiring different Vyper versions.
+#[derive(Clone, Debug, Default)]
+pub struct VyperCompiler {
+    installations: Vec<Vyper>,
+}
+
+impl VyperCompiler {
+    /// Creates a new instance from the given installations.
+    ///
+    /// If multiple installations have the same version, the first one is used.
+    pub fn new(installations: impl IntoIterator<Item = Vyper>) -> Self {
+        let mut installations = installations.into_iter().collect::<Vec<_>>();
+        installations.sort_by(|a, b| a.version.cmp(&b.version));
+        installations.dedup_by(|a, b| a.version == b.version);
+        Self { installations }
+    }
+
+    /// Creates a new instance from all Vyper binaries in the given directory, see
+    /// [`Vyper::find_all_in`].
+    pub fn find_all_in(dir: &Path) -> Result<Self> {
+        Vyper::find_all_in(dir).map(Self::new)
+    }
+
+    /// Returns all installations, sorted by version.
+    pub fn installations(&self) -> &[Vyper] {
+        &self.installations
+    }
+
+    /// Returns the installation with the given version, ignoring pre-release and build metadata.
+    pub fn get(&self, version: &Version) -> Option<&Vyper> {
+        self.installations.iter().find(|vyper| vyper.has_version(version))
+    }
+}
+
+impl From<Vyper> for VyperCompiler {
+    fn from(vyper: Vyper) -> Self {
+        Self::new([vyper])
+    }
+}
+
+impl Compiler for VyperCompiler {
+    type Settings = VyperSettings;
+    type CompilationError = VyperCompilationError;
+    type Parser = VyperParser;
+    type Input = VyperVersionedInput;
+    type Language = VyperLanguage;
+    type CompilerContract = Contract;
+
+    fn compile(
+        &self,
+        input: &Self::Input,
+    ) -> Result<CompilerOutput<VyperCompilationError, Contract>> {
+        let vyper = self
+            .get(&input.version)
+            .ok_or_else(|| SolcError::msg(format!("Vyper {} is not installed", input.version)))?;
+        Compiler::compile(vyper, input)
+    }
+
+    fn available_versions(&self, language: &Self::Language) -> Vec<super::CompilerVersion> {
+        self.installations.iter().flat_map(|vyper| vyper.available_versions(language)).collect()
+    }
+}
+
+/// Parses the `major.minor.patch` version at the start of the given string.
+fn parse_version_prefix(s: &str) -> Option<Version> {
+    let end = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+    Version::parse(s[..end].trim_end_matches('.')).ok()
+}
+
 impl Compiler for Vyper {
     type Settings = VyperSettings;
     type CompilationError = VyperCompilationError;
@@ -222,3 +332,97 @@ impl Compiler for Vyper {
         ))]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_compilers_core::utils;
+
+    #[test]
+    fn can_find_all_in_dir() {
+        let tmp = utils::tempdir("vyper").unwrap();
+        let root = tmp.path();
+        for file in ["vyper-0.3.10", "vyper.0.4.0+commit.e9db8d9f.linux", "vyper-latest", "README"]
+        {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+        std::fs::create_dir_all(root.join("0.4.1")).unwrap();
+        let binary = if cfg!(windows) { "vyper.exe" } else { "vyper" };
+        std::fs::write(root.join("0.4.1").join(binary), "").unwrap();
+
+        let all = Vyper::find_all_in(root).unwrap();
+        assert_eq!(
+            all.iter().map(|vyper| vyper.version.to_string()).collect::<Vec<_>>(),
+            ["0.3.10", "0.4.0", "0.4.1"]
+        );
+        assert_eq!(all[2].path, root.join("0.4.1").join(binary));
+    }
+
+    #[test]
+    fn can_select_installation_by_version() {
+        let vyper = |version: &str| Vyper {
+            path: PathBuf::from(format!("vyper-{version}")),
+            version: version.parse().unwrap(),
+        };
+        let compiler = VyperCompiler::new([vyper("0.4.0"), vyper("0.3.10"), vyper("0.4.0")]);
+
+        assert_eq!(
+            compiler.available_versions(&VyperLanguage),
+            vec![
+                super::super::CompilerVersion::Installed(Version::new(0, 3, 10)),
+                super::super::CompilerVersion::Installed(Version::new(0, 4, 0)),
+            ]
+        );
+        assert_eq!(compiler.get(&Version::new(0, 3, 10)).unwrap().path, Path::new("vyper-0.3.10"));
+        assert!(compiler.get(&Version::new(0, 3, 9)).is_none());
+
+        let multi =
+            crate::MultiCompiler::with_vyper_installations(None, [vyper("0.4.0"), vyper("0.3.10")]);
+        assert!(multi.vyper.is_none());
+        assert_eq!(
+            multi.available_versions(&VyperLanguage.into()),
+            compiler.available_versions(&VyperLanguage)
+        );
+        assert_eq!(
+            crate::registry::CompilerRegistry::from(multi)
+                .available_versions(&VyperLanguage.into()),
+            compiler.available_versions(&VyperLanguage)
+        );
+    }
+
+    #[test]
+    fn can_resolve_version_by_pragma() {
+        let tmp = utils::tempdir("vyper").unwrap();
+        let root = tmp.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/Old.vy"), "# @version ^0.3.0\n").unwrap();
+        std::fs::write(root.join("src/New.vy"), "# pragma version ^0.4.0\n").unwrap();
+
+        let compiler = VyperCompiler::new(["0.3.10", "0.4.0"].map(|version| Vyper {
+            path: PathBuf::from(format!("vyper-{version}")),
+            version: version.parse().unwrap(),
+        }));
+        let project = crate::ProjectBuilder::<VyperCompiler>::default()
+            .paths(crate::ProjectPathsConfig::dapptools(root).unwrap())
+            .build(compiler)
+            .unwrap();
+
+        let graph = crate::Graph::resolve(&project.paths).unwrap();
+        let resolved = graph.into_sources_by_version(&project).unwrap();
+        let mut versions = resolved.sources[&VyperLanguage]
+            .iter()
+            .map(|(version, sources, _)| {
+                let files = sources
+                    .keys()
+                    .map(|path| path.file_name().unwrap().to_str().unwrap())
+                    .collect::<Vec<_>>();
+                (version.to_string(), files)
+            })
+            .collect::<Vec<_>>();
+        versions.sort();
+        assert_eq!(
+            versions,
+            vec![("0.3.10".to_string(), vec!["Old.vy"]), ("0.4.0".to_string(), vec!["New.vy"])]
+        );
+    }
+}
@@ -2,6 +2,7 @@ use super::VyperLanguage;
 use crate::{
     ProjectPathsConfig, SourceParser,
     compilers::{ParsedSource, vyper::VYPER_EXTENSIONS},
+    resolver::parse::SolData,
 };
 use foundry_compilers_core::{
     error::{Result, SolcError},
@@ -53,7 +54,7 @@ impl ParsedSource for VyperParsedSource {
     fn parse(content: &str, file: &Path) -> Result<Self> {
         let version_req = capture_outer_and_inner(content, &RE_VYPER_VERSION, &["version"])
             .first()
-            .and_then(|(cap, _)| VersionReq::parse(cap.as_str()).ok());
+            .and_then(|(_, version)| SolData::parse_version_req(version.as_str().trim()).ok());
 
         let imports = parse_imports(content);
 
@@ -193,9 +194,28 @@ fn parse_import(input: &mut &str) -> ModalResult<VyperImport> {
 
 #[cfg(test)]
 mod tests {
-    use super::{VyperImport, parse_import};
+    use super::{VyperImport, VyperParsedSource, parse_import};
+    use crate::ParsedSource;
+    use std::path::Path;
     use winnow::Parser;
 
+    #[test]
+    fn can_parse_version_pragma() {
+        let version_req = |content: &str| {
+            VyperParsedSource::parse(content, Path::new("A.vy"))
+                .unwrap()
+                .version_req()
+                .map(ToString::to_string)
+        };
+        assert_eq!(version_req("# pragma version ^0.4.0\n").as_deref(), Some("^0.4.0"));
+        assert_eq!(version_req("# @version 0.3.10\n").as_deref(), Some("=0.3.10"));
+        assert_eq!(
+            version_req("#pragma version >=0.3.0 <0.4.0").as_deref(),
+            Some(">=0.3.0, <0.4.0")
+        );
+        assert_eq!(version_req("x: uint256\n"), None);
+    }
+
     #[test]
     fn can_parse_import() {
         assert_eq!(
@@ -1,9 +1,9 @@
 use crate::{
-    Graph,
+    Graph, SourceOverlay,
     cache::SOLIDITY_FILES_CACHE_FILENAME,
     compilers::{Language, multi::MultiCompilerLanguage},
     flatten::{collect_ordered_deps, combine_version_pragmas},
-    resolver::{SolImportAlias, parse::SolParser},
+    resolver::{ImportResolver, SolImportAlias, default_import_resolvers, parse::SolParser},
 };
 use foundry_compilers_artifacts::{
     Libraries, Settings, SolcLanguage,
@@ -14,14 +14,15 @@ use foundry_compilers_artifacts::{
 use foundry_compilers_core::{
     error::{Result, SolcError, SolcIoError},
     utils::{self, strip_prefix_owned},
+    vfs::{self, FileSystem},
 };
 use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeSet,
     fmt::{self, Formatter},
-    fs,
     marker::PhantomData,
     path::{Component, Path, PathBuf},
+    sync::Arc,
 };
 
 /// Where to find all files or where to write them
@@ -41,6 +42,9 @@ pub struct ProjectPathsConfig<L = MultiCompilerLanguage> {
     pub tests: PathBuf,
     /// Where to find scripts
     pub scripts: PathBuf,
+    /// Additional named groups of input files, such as further source roots
+    #[serde(default)]
+    pub groups: Vec<InputGroup>,
     /// Where to look for libraries
     pub libraries: Vec<PathBuf>,
     /// The compiler remappings
@@ -49,6 +53,18 @@ pub struct ProjectPathsConfig<L = MultiCompilerLanguage> {
     pub include_paths: BTreeSet<PathBuf>,
     /// The paths which will be allowed for library inclusion
     pub allowed_paths: BTreeSet<PathBuf>,
+    /// In-memory file contents which take precedence over the files on disk
+    #[serde(skip)]
+    pub overlay: SourceOverlay,
+    /// The file system sources are read from and the cache and artifacts are written to
+    #[serde(skip, default = "vfs::os")]
+    pub fs: Arc<dyn FileSystem>,
+    /// The resolvers which are consulted in order to resolve imports
+    #[serde(skip, default = "default_import_resolvers")]
+    pub import_resolvers: Vec<Arc<dyn ImportResolver>>,
+    /// How the paths of the sources are converted to the source unit names passed to the compiler
+    #[serde(default)]
+    pub source_unit_names: SourceUnitNames,
 
     pub _l: PhantomData<L>,
 }
@@ -109,7 +125,7 @@ impl ProjectPathsConfig<SolcLanguage> {
             input_files.push(flatten_target.clone());
         }
 
-        let sources = Source::read_all_files(input_files)?;
+        let sources = self.read_files(input_files)?;
         let graph = Graph::<SolParser>::resolve_sources(self, sources)?;
         let ordered_deps = collect_ordered_deps(&flatten_target, self, &graph)?;
 
@@ -257,12 +273,12 @@ impl<L> ProjectPathsConfig<L> {
 
     /// Returns true if the given path is a test file.
     pub fn is_test(&self, path: &Path) -> bool {
-        path_starts_with_rooted(path, &self.tests, &self.root)
+        self.input_kind(path) == InputKind::Test
     }
 
     /// Returns true if the given path is a script file.
     pub fn is_script(&self, path: &Path) -> bool {
-        path_starts_with_rooted(path, &self.scripts, &self.root)
+        self.input_kind(path) == InputKind::Script
     }
 
     /// Returns true if the given path is a test or script file.
@@ -270,6 +286,44 @@ impl<L> ProjectPathsConfig<L> {
         !self.is_test_or_script(path)
     }
 
+    /// Returns how the given path is classified.
+    ///
+    /// The most specific directory containing the path decides, out of `Self::tests`,
+    /// `Self::scripts` and the paths of the [`InputGroup`]s. Paths outside of all of them are
+    /// sources.
+    pub fn input_kind(&self, path: &Path) -> InputKind {
+        self.classify(path).0
+    }
+
+    /// Returns the [`InputGroup`] the given path belongs to, if any.
+    ///
+    /// See [`Self::input_kind`] for how overlapping directories are handled.
+    pub fn input_group(&self, path: &Path) -> Option<&InputGroup> {
+        self.classify(path).1
+    }
+
+    /// Returns the [`InputGroup`] with the given name.
+    pub fn group(&self, name: &str) -> Option<&InputGroup> {
+        self.groups.iter().find(|group| group.name == name)
+    }
+
+    /// Returns the directory the artifacts of the given file are written to.
+    ///
+    /// This is `Self::artifacts`, or its subdirectory configured for the [`InputGroup`] of the
+    /// file.
+    pub fn artifacts_dir(&self, file: &Path) -> PathBuf {
+        match self.input_group(file).and_then(|group| group.artifacts_subdir.as_ref()) {
+            Some(subdir) => self.artifacts.join(subdir),
+            None => self.artifacts.clone(),
+        }
+    }
+
+    fn classify(&self, path: &Path) -> (InputKind, Option<&InputGroup>) {
+        classify_input(&self.tests, &self.scripts, &self.groups, |dir| {
+            path_starts_with_rooted(path, dir, &self.root)
+        })
+    }
+
     /// Returns a new [ProjectPaths] instance that contains all directories configured for this
     /// project
     pub fn paths(&self) -> ProjectPaths {
@@ -279,6 +333,7 @@ impl<L> ProjectPathsConfig<L> {
             sources: self.sources.clone(),
             tests: self.tests.clone(),
             scripts: self.scripts.clone(),
+            groups: self.groups.clone(),
             libraries: self.libraries.iter().cloned().collect(),
         }
     }
@@ -294,20 +349,57 @@ impl<L> ProjectPathsConfig<L> {
 
     /// Creates all configured dirs and files
     pub fn create_all(&self) -> std::result::Result<(), SolcIoError> {
+        let fs = &*self.fs;
         if let Some(parent) = self.cache.parent() {
-            fs::create_dir_all(parent).map_err(|err| SolcIoError::new(err, parent))?;
+            fs.create_dir_all(parent).map_err(|err| SolcIoError::new(err, parent))?;
+        }
+        fs.create_dir_all(&self.artifacts).map_err(|err| SolcIoError::new(err, &self.artifacts))?;
+        fs.create_dir_all(&self.sources).map_err(|err| SolcIoError::new(err, &self.sources))?;
+        fs.create_dir_all(&self.tests).map_err(|err| SolcIoError::new(err, &self.tests))?;
+        fs.create_dir_all(&self.scripts).map_err(|err| SolcIoError::new(err, &self.scripts))?;
+        for dir in self.groups.iter().flat_map(|group| &group.paths) {
+            fs.create_dir_all(dir).map_err(|err| SolcIoError::new(err, dir))?;
         }
-        fs::create_dir_all(&self.artifacts)
-            .map_err(|err| SolcIoError::new(err, &self.artifacts))?;
-        fs::create_dir_all(&self.sources).map_err(|err| SolcIoError::new(err, &self.sources))?;
-        fs::create_dir_all(&self.tests).map_err(|err| SolcIoError::new(err, &self.tests))?;
-        fs::create_dir_all(&self.scripts).map_err(|err| SolcIoError::new(err, &self.scripts))?;
         for lib in &self.libraries {
-            fs::create_dir_all(lib).map_err(|err| SolcIoError::new(err, lib))?;
+            fs.create_dir_all(lib).map_err(|err| SolcIoError::new(err, lib))?;
         }
         Ok(())
     }
 
+    /// Reads the given file, preferring the content of the [`SourceOverlay`] over the
+    /// [`FileSystem`].
+    pub fn read_file(&self, path: &Path) -> std::result::Result<Source, SolcIoError> {
+        if let Some(source) = self.overlay.get(path) {
+            return Ok(source.clone());
+        }
+        trace!(file=%path.display(), "reading file");
+        let mut content =
+            self.fs.read_to_string(path).map_err(|err| SolcIoError::new(err, path))?;
+        // Normalize line endings to ensure deterministic metadata, like `Source::read`.
+        if content.contains('\r') {
+            content = content.replace("\r\n", "\n");
+        }
+        Ok(Source::new(content))
+    }
+
+    /// Reads all of the given files, see [`Self::read_file`].
+    pub fn read_files<T, I>(&self, files: I) -> std::result::Result<Sources, SolcIoError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<PathBuf>,
+    {
+        files
+            .into_iter()
+            .map(Into::into)
+            .map(|file| self.read_file(&file).map(|source| (file, source)))
+            .collect()
+    }
+
+    /// Returns `true` if the given file exists in the [`SourceOverlay`] or the [`FileSystem`].
+    pub fn file_exists(&self, path: &Path) -> bool {
+        self.overlay.contains(path) || self.fs.exists(path)
+    }
+
     /// Converts all `\\` separators in _all_ paths to `/`
     pub fn slash_paths(&mut self) {
         #[cfg(windows)]
@@ -324,6 +416,7 @@ impl<L> ProjectPathsConfig<L> {
             slashed(&mut self.sources);
             slashed(&mut self.tests);
             slashed(&mut self.scripts);
+            self.groups.iter_mut().flat_map(|group| &mut group.paths).for_each(slashed);
 
             self.libraries.iter_mut().for_each(slashed);
             self.remappings.iter_mut().for_each(Remapping::slash_path);
@@ -397,11 +490,30 @@ impl<L> ProjectPathsConfig<L> {
     ///
     /// This will also populate the `include_paths` with any nested library root paths that should
     /// be provided to solc via `--include-path` because it uses absolute imports.
+    ///
+    /// The configured [`Self::import_resolvers`] are consulted in order.
     pub fn resolve_import_and_include_paths(
         &self,
         cwd: &Path,
         import: &Path,
         include_paths: &mut BTreeSet<PathBuf>,
+    ) -> Result<PathBuf> {
+        crate::resolver::resolve_import(
+            &self.import_resolvers,
+            self.with_language_ref(),
+            cwd,
+            import,
+            include_paths,
+        )
+    }
+
+    /// Resolves an `import` the way the
+    /// [`DefaultImportResolver`](crate::resolver::DefaultImportResolver) does.
+    pub(crate) fn resolve_import_default(
+        &self,
+        cwd: &Path,
+        import: &Path,
+        include_paths: &mut BTreeSet<PathBuf>,
     ) -> Result<PathBuf> {
         let component = import
             .components()
@@ -411,7 +523,7 @@ impl<L> ProjectPathsConfig<L> {
         if component == Component::CurDir || component == Component::ParentDir {
             // if the import is relative we assume it's already part of the processed input
             // file set
-            let resolved = utils::normalize_solidity_import_path(cwd, import).map_err(|err| {
+            let resolved = self.normalize_import_path(cwd, import).map_err(|err| {
                 SolcError::msg(format!("failed to resolve relative import \"{err:?}\""))
             })?;
             // Check if the resolved path matches a remapping and should be redirected
@@ -426,7 +538,7 @@ impl<L> ProjectPathsConfig<L> {
                 // src/interfaces/IConfig.sol`, or make use of a remapping (`src/=....`)
                 if let Some(lib) = self.find_library_ancestor(cwd)
                     && let Some((include_path, import)) =
-                        utils::resolve_absolute_library(lib, cwd, import)
+                        utils::resolve_absolute_library_in(&*self.fs, lib, cwd, import)
                 {
                     // track the path for this absolute import inside a nested library
                     include_paths.insert(include_path);
@@ -435,7 +547,7 @@ impl<L> ProjectPathsConfig<L> {
                 // also try to resolve absolute imports from the project paths
                 for path in [&self.root, &self.sources, &self.tests, &self.scripts] {
                     if cwd.starts_with(path)
-                        && let Ok(import) = utils::normalize_solidity_import_path(path, import)
+                        && let Ok(import) = self.normalize_import_path(path, import)
                     {
                         return Ok(import);
                     }
@@ -451,6 +563,19 @@ impl<L> ProjectPathsConfig<L> {
         }
     }
 
+    /// Same as [`utils::normalize_solidity_import_path`], but checks the [`FileSystem`] and also
+    /// accepts files which only exist in the [`SourceOverlay`].
+    fn normalize_import_path(
+        &self,
+        directory: &Path,
+        import: &Path,
+    ) -> Result<PathBuf, SolcIoError> {
+        utils::normalize_solidity_import_path_in(&*self.fs, directory, import).or_else(|err| {
+            let path = utils::clean_solidity_path(&directory.join(import));
+            if self.overlay.contains(&path) { Ok(path) } else { Err(err) }
+        })
+    }
+
     /// Attempts to resolve an `import` from the given working directory.
     ///
     /// The `cwd` path is the parent dir of the file that includes the `import`
@@ -509,16 +634,39 @@ impl<L> ProjectPathsConfig<L> {
     /// `@openzeppelin/contracts/contracts/token/ERC20/IERC20.sol` we check for this edge case
     /// here so that both styles work out of the box.
     pub fn resolve_library_import(&self, cwd: &Path, import: &Path) -> Option<PathBuf> {
+        if let Some((_, path)) = self.remap_library_import(cwd, import) {
+            Some(self.root.join(path))
+        } else {
+            utils::resolve_library_in(&*self.fs, &self.libraries, import)
+        }
+    }
+
+    /// Returns `true` if the remapping is global or `cwd` is located in its context.
+    ///
+    /// Contexts are either relative to the root or absolute, like the ones detected by
+    /// [`Remapping::find_many`].
+    pub(crate) fn is_in_remapping_context(&self, remapping: &Remapping, cwd: &Path) -> bool {
+        let Some(context) = remapping.context.as_deref().map(Path::new) else { return true };
+        let cwd = cwd.strip_prefix(&self.root).unwrap_or(cwd);
+        cwd.starts_with(context.strip_prefix(&self.root).unwrap_or(context))
+    }
+
+    /// Returns the remapping that [`Self::resolve_library_import`] applies to the given `import`,
+    /// together with the remapped path.
+    ///
+    /// Remappings are checked in order and the first one whose name is a prefix of the import
+    /// wins, regardless of whether another remapping has a longer matching prefix.
+    pub(crate) fn remap_library_import(
+        &self,
+        cwd: &Path,
+        import: &Path,
+    ) -> Option<(&Remapping, PathBuf)> {
         // if the import path starts with the name of the remapping then we get the resolved path by
         // removing the name and adding the remainder to the path of the remapping
-        let cwd = cwd.strip_prefix(&self.root).unwrap_or(cwd);
-        if let Some(path) = self
-            .remappings
+        self.remappings
             .iter()
-            .filter(|r| {
-                // only check remappings that are either global or for `cwd`
-                if let Some(ctx) = r.context.as_ref() { cwd.starts_with(ctx) } else { true }
-            })
+            // only check remappings that are either global or for `cwd`
+            .filter(|r| self.is_in_remapping_context(r, cwd))
             .find_map(|r| {
                 import.strip_prefix(&r.name).ok().map(|stripped_import| {
                     let lib_path =
@@ -533,18 +681,13 @@ impl<L> ProjectPathsConfig<L> {
                     // `contracts`
                     if let Ok(adjusted_import) = stripped_import.strip_prefix("contracts/")
                         && r.path.ends_with("contracts/")
-                        && !self.root.join(&lib_path).exists()
+                        && !self.file_exists(&self.root.join(&lib_path))
                     {
-                        return Path::new(&r.path).join(adjusted_import);
+                        return (r, Path::new(&r.path).join(adjusted_import));
                     }
-                    lib_path
+                    (r, lib_path)
                 })
             })
-        {
-            Some(self.root.join(path))
-        } else {
-            utils::resolve_library(&self.libraries, import)
-        }
     }
 
     /// Applies remappings to a resolved absolute path.
@@ -564,20 +707,24 @@ impl<L> ProjectPathsConfig<L> {
     /// The remapped path if a matching remapping exists and the target file exists,
     /// otherwise returns the original path.
     pub fn apply_remapping_to_path(&self, cwd: &Path, resolved_path: &Path) -> PathBuf {
-        // Get the path relative to root
-        let relative_path = match resolved_path.strip_prefix(&self.root) {
-            Ok(p) => p,
-            Err(_) => return resolved_path.to_path_buf(),
-        };
+        self.remap_resolved_path(cwd, resolved_path)
+            .map_or_else(|| resolved_path.to_path_buf(), |(_, path)| path)
+    }
 
-        let cwd_relative = cwd.strip_prefix(&self.root).unwrap_or(cwd);
+    /// Returns the remapping that [`Self::apply_remapping_to_path`] applies to the given path,
+    /// together with the remapped absolute path.
+    pub(crate) fn remap_resolved_path(
+        &self,
+        cwd: &Path,
+        resolved_path: &Path,
+    ) -> Option<(&Remapping, PathBuf)> {
+        // Get the path relative to root
+        let relative_path = resolved_path.strip_prefix(&self.root).ok()?;
 
         // Check if any remapping matches this path
         for r in &self.remappings {
             // Check context
-            if let Some(ctx) = r.context.as_ref()
-                && !cwd_relative.starts_with(ctx)
-            {
+            if !self.is_in_remapping_context(r, cwd) {
                 continue;
             }
 
@@ -587,13 +734,13 @@ impl<L> ProjectPathsConfig<L> {
                 let remapped_absolute = self.root.join(&remapped_relative);
 
                 // Only use remapped path if the target file exists
-                if remapped_absolute.exists() {
-                    return remapped_absolute;
+                if self.file_exists(&remapped_absolute) {
+                    return Some((r, remapped_absolute));
                 }
             }
         }
 
-        resolved_path.to_path_buf()
+        None
     }
 
     pub fn with_language_ref<Lang>(&self) -> &ProjectPathsConfig<Lang> {
@@ -625,19 +772,42 @@ impl<L: Language> ProjectPathsConfig<L> {
     /// Returns all sources found under the project's configured `sources` path
     pub fn read_sources(&self) -> Result<Sources> {
         trace!("reading all sources from \"{}\"", self.sources.display());
-        Ok(Source::read_all_from(&self.sources, L::FILE_EXTENSIONS)?)
+        self.read_all_from(&self.sources)
     }
 
     /// Returns all sources found under the project's configured `test` path
     pub fn read_tests(&self) -> Result<Sources> {
         trace!("reading all tests from \"{}\"", self.tests.display());
-        Ok(Source::read_all_from(&self.tests, L::FILE_EXTENSIONS)?)
+        self.read_all_from(&self.tests)
     }
 
     /// Returns all sources found under the project's configured `script` path
     pub fn read_scripts(&self) -> Result<Sources> {
         trace!("reading all scripts from \"{}\"", self.scripts.display());
-        Ok(Source::read_all_from(&self.scripts, L::FILE_EXTENSIONS)?)
+        self.read_all_from(&self.scripts)
+    }
+
+    /// Returns all sources which belong to the [`InputGroup`] with the given name
+    pub fn read_group(&self, name: &str) -> Result<Sources> {
+        let group = self.group(name).ok_or_else(|| SolcError::msg(format!("no group {name}")))?;
+        trace!("reading all files of group {name}");
+        let files = group.paths.iter().flat_map(|dir| self.files_in(dir)).collect::<Vec<_>>();
+        Ok(self
+            .read_files(files.into_iter().filter(|file| self.input_group(file) == Some(group)))?)
+    }
+
+    /// Reads all source files under the given dir, including the overlaid ones.
+    fn read_all_from(&self, dir: &Path) -> Result<Sources> {
+        Ok(self.read_files(self.files_in(dir))?)
+    }
+
+    /// Returns all source files under the given dir, including the ones which only exist in the
+    /// overlay.
+    fn files_in<'a>(&'a self, dir: &'a Path) -> impl Iterator<Item = PathBuf> + 'a {
+        self.fs
+            .source_files(dir, L::FILE_EXTENSIONS)
+            .into_iter()
+            .chain(self.overlay.virtual_files_in(&*self.fs, dir, L::FILE_EXTENSIONS))
     }
 
     /// Returns true if the there is at least one solidity file in this config.
@@ -647,23 +817,53 @@ impl<L: Language> ProjectPathsConfig<L> {
         self.input_files_iter().next().is_some()
     }
 
-    /// Returns an iterator that yields all solidity file paths for `Self::sources`, `Self::tests`
-    /// and `Self::scripts`
+    /// Returns an iterator that yields all solidity file paths for `Self::sources`, `Self::tests`,
+    /// `Self::scripts` and the paths of `Self::groups`
     pub fn input_files_iter(&self) -> impl Iterator<Item = PathBuf> + '_ {
-        utils::source_files_iter(&self.sources, L::FILE_EXTENSIONS)
-            .chain(utils::source_files_iter(&self.tests, L::FILE_EXTENSIONS))
-            .chain(utils::source_files_iter(&self.scripts, L::FILE_EXTENSIONS))
+        self.files_in(&self.sources)
+            .chain(self.files_in(&self.tests))
+            .chain(self.files_in(&self.scripts))
+            .chain(self.group_dirs().flat_map(|dir| self.files_in(dir)))
+    }
+
+    /// Returns the paths of `Self::groups` which are not inside of another input directory, so
+    /// that [`Self::input_files_iter`] yields every file once.
+    fn group_dirs(&self) -> impl Iterator<Item = &Path> + '_ {
+        let dirs = self.groups.iter().flat_map(|group| &group.paths);
+        dirs.clone()
+            .enumerate()
+            .filter(move |(i, dir)| {
+                ![&self.sources, &self.tests, &self.scripts]
+                    .into_iter()
+                    .chain(dirs.clone().take(*i))
+                    .any(|outer| dir.starts_with(outer))
+            })
+            .map(|(_, dir)| dir.as_path())
+    }
+
+    /// Returns true if the given path is a source file in `Self::sources`, `Self::tests`,
+    /// `Self::scripts` or `Self::groups`, like the files of [`Self::input_files`].
+    pub fn is_input_file(&self, path: &Path) -> bool {
+        [&self.sources, &self.tests, &self.scripts]
+            .into_iter()
+            .chain(self.groups.iter().flat_map(|group| &group.paths))
+            .any(|dir| path.starts_with(dir))
+            && path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| L::FILE_EXTENSIONS.contains(&ext))
     }
 
-    /// Returns the combined set solidity file paths for `Self::sources`, `Self::tests` and
-    /// `Self::scripts`
+    /// Returns the combined set solidity file paths for `Self::sources`, `Self::tests`,
+    /// `Self::scripts` and `Self::groups`
     pub fn input_files(&self) -> Vec<PathBuf> {
         self.input_files_iter().collect()
     }
 
     /// Returns the combined set of `Self::read_sources` + `Self::read_tests` + `Self::read_scripts`
+    /// and the files of `Self::groups`
     pub fn read_input_files(&self) -> Result<Sources> {
-        Ok(Source::read_all(self.input_files_iter())?)
+        Ok(self.read_files(self.input_files_iter())?)
     }
 }
 
@@ -674,6 +874,12 @@ impl fmt::Display for ProjectPathsConfig {
         writeln!(f, "artifacts: {}", self.artifacts.display())?;
         writeln!(f, "tests: {}", self.tests.display())?;
         writeln!(f, "scripts: {}", self.scripts.display())?;
+        if !self.groups.is_empty() {
+            writeln!(f, "groups:")?;
+            for group in &self.groups {
+                writeln!(f, "    {group}")?;
+            }
+        }
         writeln!(f, "libs:")?;
         for lib in &self.libraries {
             writeln!(f, "    {}", lib.display())?;
@@ -694,6 +900,8 @@ pub struct ProjectPaths {
     pub sources: PathBuf,
     pub tests: PathBuf,
     pub scripts: PathBuf,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<InputGroup>,
     pub libraries: BTreeSet<PathBuf>,
 }
 
@@ -705,6 +913,9 @@ impl ProjectPaths {
         self.sources = root.join(&self.sources);
         self.tests = root.join(&self.tests);
         self.scripts = root.join(&self.scripts);
+        for path in self.groups.iter_mut().flat_map(|group| &mut group.paths) {
+            *path = root.join(&*path);
+        }
         let libraries = std::mem::take(&mut self.libraries);
         self.libraries.extend(libraries.into_iter().map(|p| root.join(p)));
         self
@@ -727,6 +938,11 @@ impl ProjectPaths {
         if let Ok(stripped) = self.scripts.strip_prefix(base) {
             self.scripts = stripped.to_path_buf();
         }
+        for path in self.groups.iter_mut().flat_map(|group| &mut group.paths) {
+            if let Ok(stripped) = path.strip_prefix(base) {
+                *path = stripped.to_path_buf();
+            }
+        }
         self.libraries = std::mem::take(&mut self.libraries)
             .into_iter()
             .map(|path| strip_prefix_owned(path, base))
@@ -741,18 +957,32 @@ impl ProjectPaths {
 
     /// Returns true if the given path is a test file.
     pub fn is_test(&self, path: &Path) -> bool {
-        path.starts_with(&self.tests)
+        self.input_kind(path) == InputKind::Test
     }
 
     /// Returns true if the given path is a script file.
     pub fn is_script(&self, path: &Path) -> bool {
-        path.starts_with(&self.scripts)
+        self.input_kind(path) == InputKind::Script
     }
 
     /// Returns true if the given path is a test or script file.
     pub fn is_source_file(&self, path: &Path) -> bool {
         !self.is_test_or_script(path)
     }
+
+    /// Returns how the given path is classified, see [`ProjectPathsConfig::input_kind`].
+    pub fn input_kind(&self, path: &Path) -> InputKind {
+        self.classify(path).0
+    }
+
+    /// Returns the [`InputGroup`] the given path belongs to, if any.
+    pub fn input_group(&self, path: &Path) -> Option<&InputGroup> {
+        self.classify(path).1
+    }
+
+    fn classify(&self, path: &Path) -> (InputKind, Option<&InputGroup>) {
+        classify_input(&self.tests, &self.scripts, &self.groups, |dir| path.starts_with(dir))
+    }
 }
 
 impl Default for ProjectPaths {
@@ -763,11 +993,130 @@ impl Default for ProjectPaths {
             sources: "src".into(),
             tests: "test".into(),
             scripts: "script".into(),
+            groups: Vec::new(),
             libraries: Default::default(),
         }
     }
 }
 
+/// How the paths of the sources are converted to the source unit names passed to the compiler.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SourceUnitNames {
+    /// The project root is stripped from the paths of the sources inside of it, and all other
+    /// paths are kept absolute.
+    #[default]
+    StripRoot,
+    /// The names solc assigns to the files when it's invoked on the command line with the project
+    /// root as `--base-path` and the configured `--include-path`s, see
+    /// [`SolcVfs`](foundry_compilers_artifacts::SolcVfs).
+    ///
+    /// Sources outside of the root but inside of an include path are named relative to the
+    /// include path, and paths in the remappings are converted the same way. This makes the
+    /// metadata hashes equal to the ones of builds made by invoking solc directly.
+    ///
+    /// Since imports are resolved with the first matching remapping while solc prefers the most
+    /// specific one, compilation fails if a later remapping takes precedence in solc over an
+    /// earlier one matching the same imports.
+    Solc,
+}
+
+/// How the files of an [`InputGroup`] are classified.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InputKind {
+    /// Regular sources, like the files in [`ProjectPathsConfig::sources`].
+    #[default]
+    Source,
+    /// Test files, like the files in [`ProjectPathsConfig::tests`].
+    Test,
+    /// Script files, like the files in [`ProjectPathsConfig::scripts`].
+    Script,
+}
+
+/// A named group of input directories, in addition to the `sources`, `tests` and `scripts` of a
+/// [`ProjectPathsConfig`].
+///
+/// Groups can be used for additional source roots, such as `contracts/core` and
+/// `contracts/periphery`, or for other kinds of inputs, such as `certora/harness`.
+///
+/// # Examples
+///
+/// ```
+/// use foundry_compilers::{InputGroup, InputKind};
+///
+/// let group = InputGroup::new("invariants", InputKind::Test)
+///     .path("invariants")
+///     .profile("fuzz")
+///     .artifacts_subdir("invariants");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputGroup {
+    /// The name of the group
+    pub name: String,
+    /// The directories containing the files of the group
+    pub paths: Vec<PathBuf>,
+    /// How the files of the group are classified
+    #[serde(default)]
+    pub kind: InputKind,
+    /// The settings profile the files of the group are compiled with, unless it's incompatible
+    /// with their restrictions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// The subdirectory of the artifacts directory the artifacts of the group are written to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifacts_subdir: Option<PathBuf>,
+}
+
+impl InputGroup {
+    /// Creates a new group without any paths.
+    pub fn new(name: impl Into<String>, kind: InputKind) -> Self {
+        Self { name: name.into(), paths: Vec::new(), kind, profile: None, artifacts_subdir: None }
+    }
+
+    /// Adds a directory to the group.
+    ///
+    /// Relative paths are resolved against the project root.
+    #[must_use]
+    pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.paths.push(path.into());
+        self
+    }
+
+    /// Adds directories to the group.
+    #[must_use]
+    pub fn paths(mut self, paths: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        self.paths.extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets the settings profile the files of the group are compiled with.
+    #[must_use]
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    /// Sets the subdirectory of the artifacts directory the artifacts of the group are written
+    /// to.
+    #[must_use]
+    pub fn artifacts_subdir(mut self, subdir: impl Into<PathBuf>) -> Self {
+        self.artifacts_subdir = Some(subdir.into());
+        self
+    }
+}
+
+impl fmt::Display for InputGroup {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:?}):", self.name, self.kind)?;
+        for path in &self.paths {
+            write!(f, " {}", path.display())?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum PathStyle {
     HardHat,
@@ -808,10 +1157,14 @@ pub struct ProjectPathsConfigBuilder {
     sources: Option<PathBuf>,
     tests: Option<PathBuf>,
     scripts: Option<PathBuf>,
+    groups: Vec<InputGroup>,
     libraries: Option<Vec<PathBuf>>,
     remappings: Option<Vec<Remapping>>,
     include_paths: BTreeSet<PathBuf>,
     allowed_paths: BTreeSet<PathBuf>,
+    fs: Option<Arc<dyn FileSystem>>,
+    import_resolvers: Option<Vec<Arc<dyn ImportResolver>>>,
+    source_unit_names: SourceUnitNames,
 }
 
 impl ProjectPathsConfigBuilder {
@@ -850,6 +1203,18 @@ impl ProjectPathsConfigBuilder {
         self
     }
 
+    /// Adds a named group of input directories.
+    pub fn group(mut self, group: InputGroup) -> Self {
+        self.groups.push(group);
+        self
+    }
+
+    /// Adds named groups of input directories.
+    pub fn groups(mut self, groups: impl IntoIterator<Item = InputGroup>) -> Self {
+        self.groups.extend(groups);
+        self
+    }
+
     /// Specifically disallow additional libraries
     pub fn no_libs(mut self) -> Self {
         self.libraries = Some(Vec::new());
@@ -918,33 +1283,76 @@ impl ProjectPathsConfigBuilder {
         self
     }
 
+    /// Sets the [`FileSystem`] to read sources from and to write the cache and artifacts to.
+    ///
+    /// The default directories and remappings are detected in this file system.
+    pub fn fs(mut self, fs: Arc<dyn FileSystem>) -> Self {
+        self.fs = Some(fs);
+        self
+    }
+
+    /// Sets the resolvers which are consulted in order to resolve imports.
+    ///
+    /// Defaults to the [`DefaultImportResolver`](crate::resolver::DefaultImportResolver), which has
+    /// to be included explicitly to keep the built-in resolution.
+    pub fn import_resolvers(
+        mut self,
+        resolvers: impl IntoIterator<Item = Arc<dyn ImportResolver>>,
+    ) -> Self {
+        self.import_resolvers = Some(resolvers.into_iter().collect());
+        self
+    }
+
+    /// Sets how the paths of the sources are converted to source unit names.
+    pub fn source_unit_names(mut self, source_unit_names: SourceUnitNames) -> Self {
+        self.source_unit_names = source_unit_names;
+        self
+    }
+
     pub fn build_with_root<C>(self, root: impl Into<PathBuf>) -> ProjectPathsConfig<C> {
-        let root = utils::canonicalized(root);
+        let fs = self.fs.unwrap_or_else(vfs::os);
+        let root = root.into();
+        let root = fs.canonicalize(&root).unwrap_or(root);
 
-        let libraries = self.libraries.unwrap_or_else(|| ProjectPathsConfig::find_libs(&root));
-        let artifacts =
-            self.artifacts.unwrap_or_else(|| ProjectPathsConfig::find_artifacts_dir(&root));
+        let libraries = self.libraries.unwrap_or_else(|| {
+            vec![utils::find_fave_or_alt_path_in(&*fs, &root, "lib", "node_modules")]
+        });
+        let artifacts = self
+            .artifacts
+            .unwrap_or_else(|| utils::find_fave_or_alt_path_in(&*fs, &root, "out", "artifacts"));
 
         let mut allowed_paths = self.allowed_paths;
         // allow every contract under root by default
         allowed_paths.insert(root.clone());
 
+        let mut groups = self.groups;
+        for path in groups.iter_mut().flat_map(|group| &mut group.paths) {
+            *path = root.join(&*path);
+        }
+
         ProjectPathsConfig {
             cache: self
                 .cache
                 .unwrap_or_else(|| root.join("cache").join(SOLIDITY_FILES_CACHE_FILENAME)),
             build_infos: self.build_infos.unwrap_or_else(|| artifacts.join("build-info")),
             artifacts,
-            sources: self.sources.unwrap_or_else(|| ProjectPathsConfig::find_source_dir(&root)),
+            sources: self.sources.unwrap_or_else(|| {
+                utils::find_fave_or_alt_path_in(&*fs, &root, "src", "contracts")
+            }),
             tests: self.tests.unwrap_or_else(|| root.join("test")),
             scripts: self.scripts.unwrap_or_else(|| root.join("script")),
+            groups,
             remappings: self.remappings.unwrap_or_else(|| {
-                libraries.iter().flat_map(|p| Remapping::find_many(p)).collect()
+                libraries.iter().flat_map(|p| Remapping::find_many_in(&*fs, p)).collect()
             }),
             libraries,
             root,
             include_paths: self.include_paths,
             allowed_paths,
+            overlay: SourceOverlay::default(),
+            fs,
+            import_resolvers: self.import_resolvers.unwrap_or_else(default_import_resolvers),
+            source_unit_names: self.source_unit_names,
             _l: PhantomData,
         }
     }
@@ -1044,6 +1452,29 @@ impl SolcConfigBuilder {
     }
 }
 
+/// Classifies a path by the most specific of the test, script and group directories which
+/// `contains` it, and returns the group that directory belongs to.
+fn classify_input<'a>(
+    tests: &Path,
+    scripts: &Path,
+    groups: &'a [InputGroup],
+    contains: impl Fn(&Path) -> bool,
+) -> (InputKind, Option<&'a InputGroup>) {
+    let builtin = [(tests, InputKind::Test, None), (scripts, InputKind::Script, None)];
+    let groups = groups.iter().flat_map(|group| {
+        group.paths.iter().map(move |path| (path.as_path(), group.kind, Some(group)))
+    });
+
+    let mut best: Option<(usize, InputKind, Option<&InputGroup>)> = None;
+    for (dir, kind, group) in builtin.into_iter().chain(groups) {
+        let depth = dir.components().count();
+        if contains(dir) && best.is_none_or(|(best, ..)| depth > best) {
+            best = Some((depth, kind, group));
+        }
+    }
+    best.map_or((InputKind::Source, None), |(_, kind, group)| (kind, group))
+}
+
 /// Return true if `a` starts with `b` or `b - root`.
 fn path_starts_with_rooted(a: &Path, b: &Path, root: &Path) -> bool {
     if a.starts_with(b) {
@@ -1058,6 +1489,7 @@ fn path_starts_with_rooted(a: &Path, b: &Path, root: &Path) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
 
     #[test]
     fn can_autodetect_dirs() {
@@ -1316,4 +1748,160 @@ mod tests {
         // The resolved path should be the local override, not the external file
         assert_eq!(resolved, local_dir.join("LibMem.sol"));
     }
+
+    #[test]
+    fn can_classify_input_groups() {
+        use crate::{FileFilter, InputGroupFilter};
+        use foundry_compilers_core::vfs::MemoryFileSystem;
+
+        let root = Path::new("/groups-project");
+        let fs = Arc::new(MemoryFileSystem::from_iter(
+            [
+                "src/A.sol",
+                "contracts/core/Core.sol",
+                "contracts/core/test/Core.t.sol",
+                "contracts/periphery/Router.sol",
+                "certora/harness/Harness.sol",
+                "test/A.t.sol",
+                "test/invariants/Inv.t.sol",
+            ]
+            .map(|file| (root.join(file), "contract C {}")),
+        ));
+        let paths: ProjectPathsConfig = ProjectPathsConfig::builder()
+            .fs(fs)
+            .group(
+                InputGroup::new("core", InputKind::Source)
+                    .paths(["contracts/core", "contracts/periphery"]),
+            )
+            .group(InputGroup::new("core-tests", InputKind::Test).path("contracts/core/test"))
+            .group(
+                InputGroup::new("certora", InputKind::Source)
+                    .path("certora/harness")
+                    .profile("certora")
+                    .artifacts_subdir("certora"),
+            )
+            .group(InputGroup::new("invariants", InputKind::Test).path("test/invariants"))
+            .build_with_root(root);
+
+        assert_eq!(paths.groups[0].paths[0], root.join("contracts/core"));
+        assert_eq!(paths.input_kind(&root.join("src/A.sol")), InputKind::Source);
+        assert_eq!(paths.input_kind(&root.join("contracts/core/Core.sol")), InputKind::Source);
+        assert!(paths.is_test(&root.join("contracts/core/test/Core.t.sol")));
+        assert!(paths.is_test(Path::new("test/A.t.sol")));
+        assert!(paths.is_source_file(&root.join("contracts/periphery/Router.sol")));
+
+        let group = |file: &str| paths.input_group(&root.join(file)).map(|g| g.name.as_str());
+        assert_eq!(group("src/A.sol"), None);
+        assert_eq!(group("contracts/periphery/Router.sol"), Some("core"));
+        assert_eq!(group("contracts/core/test/Core.t.sol"), Some("core-tests"));
+        assert_eq!(group("test/A.t.sol"), None);
+        assert_eq!(group("test/invariants/Inv.t.sol"), Some("invariants"));
+
+        assert_eq!(
+            paths.artifacts_dir(&root.join("certora/harness/Harness.sol")),
+            paths.artifacts.join("certora")
+        );
+        assert_eq!(paths.artifacts_dir(&root.join("src/A.sol")), paths.artifacts);
+
+        // files of nested groups are only yielded once
+        let files = paths.input_files();
+        assert_eq!(files.len(), 7);
+        assert_eq!(files.iter().collect::<BTreeSet<_>>().len(), files.len());
+        assert!(paths.is_input_file(&root.join("certora/harness/Harness.sol")));
+        assert_eq!(
+            paths.read_group("core").unwrap().keys().collect::<Vec<_>>(),
+            [root.join("contracts/core/Core.sol"), root.join("contracts/periphery/Router.sol")]
+                .iter()
+                .collect::<Vec<_>>()
+        );
+
+        let mut relative = paths.paths_relative();
+        assert_eq!(relative.groups[2].paths, [PathBuf::from("certora/harness")]);
+        assert!(relative.is_test(Path::new("contracts/core/test/Core.t.sol")));
+        assert_eq!(*relative.join_all(root), paths.paths());
+
+        let filter = InputGroupFilter::new(&paths, ["core", "certora"]);
+        assert!(filter.is_match(&root.join("certora/harness/Harness.sol")));
+        assert!(filter.is_match(&root.join("contracts/core/Core.sol")));
+        assert!(!filter.is_match(&root.join("contracts/core/test/Core.t.sol")));
+        assert!(!filter.is_match(&root.join("src/A.sol")));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn can_compile_with_memory_file_system() {
+        use crate::{
+            ProjectBuilder,
+            multi::MultiCompiler,
+            test_utils::{multi_compiler, write_script},
+        };
+        use foundry_compilers_core::vfs::MemoryFileSystem;
+
+        let tmp = utils::tempdir("vfs").unwrap();
+        // `solc` stub which checks that the sources were written to its working directory.
+        let calls = tmp.path().join("calls");
+        let solc = tmp.path().join("solc");
+        write_script(
+            &solc,
+            &format!(
+                r#"cat > /dev/null
+test -f src/B.sol && test -f lib/ds/src/D.sol || exit 1
+echo >> {}
+echo '{{"contracts":{{"src/A.sol":{{"A":{{"abi":[]}}}},"src/B.sol":{{"B":{{"abi":[]}}}}}},"sources":{{"src/A.sol":{{"id":0}},"src/B.sol":{{"id":1}},"lib/ds/src/D.sol":{{"id":2}}}}}}'
+"#,
+                calls.display()
+            ),
+        );
+
+        let root = Path::new("/vfs-project");
+        let fs = Arc::new(MemoryFileSystem::from_iter([
+            (
+                root.join("src/A.sol"),
+                "pragma solidity ^0.8.0;\nimport \"ds/D.sol\";\ncontract A {}\n",
+            ),
+            (
+                root.join("src/B.sol"),
+                "pragma solidity ^0.8.0;\nimport \"./A.sol\";\ncontract B {}\n",
+            ),
+            (root.join("lib/ds/src/D.sol"), "pragma solidity ^0.8.0;\ncontract D {}\n"),
+        ]));
+        let paths = ProjectPathsConfig::builder().fs(fs.clone()).build_with_root(root);
+        assert_eq!(paths.sources, root.join("src"));
+        assert_eq!(paths.libraries, [root.join("lib")]);
+        assert_eq!(
+            paths.remappings,
+            [format!("ds/={}/lib/ds/src/", root.display()).parse::<Remapping>().unwrap()]
+        );
+
+        let compiler = multi_compiler(&solc);
+        let project =
+            ProjectBuilder::<MultiCompiler>::default().paths(paths).build(compiler).unwrap();
+
+        let output = project.compile().unwrap();
+        assert!(!output.has_compiler_errors(), "{output}");
+        assert!(output.find_first("B").is_some());
+        assert!(!root.exists());
+        assert!(fs.is_file(project.cache_path()));
+        assert!(fs.is_file(&project.artifacts_path().join("A.sol/A.json")));
+        assert!(fs.is_dir(project.build_info_path()));
+
+        let output = project.compile().unwrap();
+        assert!(output.is_unchanged());
+        assert!(output.find_first("B").is_some());
+        assert_eq!(fs::read_to_string(&calls).unwrap().lines().count(), 1);
+        assert!(!root.exists());
+
+        // files outside the root would be read from disk by the compiler
+        fs.insert("/vfs-outside/E.sol", "pragma solidity ^0.8.0;\ncontract E {}\n");
+        fs.insert(
+            root.join("src/C.sol"),
+            "pragma solidity ^0.8.0;\nimport \"/vfs-outside/E.sol\";\ncontract C {}\n",
+        );
+        let err = project.compile().unwrap_err();
+        assert!(
+            err.to_string().contains("/vfs-outside/E.sol is outside of the project root"),
+            "{err}"
+        );
+        assert!(!Path::new("/vfs-outside").exists());
+    }
 }
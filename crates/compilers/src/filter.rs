@@ -1,7 +1,7 @@
 //! Types to apply filter to input types
 
 use crate::{
-    SourceParser, Sources,
+    ProjectPaths, ProjectPathsConfig, SourceParser, Sources,
     compilers::{CompilerSettings, ParsedSource, multi::MultiCompilerParsedSource},
     resolver::{GraphEdges, parse::SolData},
 };
@@ -50,6 +50,37 @@ impl FileFilter for TestFileFilter {
     }
 }
 
+/// An [FileFilter] that matches the files of the named [`InputGroup`](crate::InputGroup)s of a
+/// project
+#[derive(Clone, Debug)]
+pub struct InputGroupFilter {
+    paths: ProjectPaths,
+    names: Vec<String>,
+}
+
+impl InputGroupFilter {
+    /// Creates a filter matching the files which belong to one of the groups with the given
+    /// names, see [`ProjectPathsConfig::input_group`].
+    pub fn new<L>(
+        paths: &ProjectPathsConfig<L>,
+        names: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self { paths: paths.paths(), names: names.into_iter().map(Into::into).collect() }
+    }
+}
+
+impl fmt::Display for InputGroupFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "InputGroupFilter({})", self.names.join(", "))
+    }
+}
+
+impl FileFilter for InputGroupFilter {
+    fn is_match(&self, file: &Path) -> bool {
+        self.paths.input_group(file).is_some_and(|group| self.names.contains(&group.name))
+    }
+}
+
 pub trait MaybeSolData {
     fn sol_data(&self) -> Option<&SolData>;
 }
@@ -1,28 +1,41 @@
 use crate::{
     ArtifactOutput, CompilerSettings, Graph, Project, ProjectPathsConfig, SourceParser, Updates,
-    apply_updates,
     compilers::{Compiler, ParsedSource},
     filter::MaybeSolData,
-    resolver::parse::SolData,
+    replace_source_content,
+    resolver::{
+        ImportSuggester,
+        parse::{SolData, SolParser},
+    },
+    solc::{SOLC_EXTENSIONS, SolcLanguage},
 };
 use foundry_compilers_artifacts::{
     ast::{visitor::Visitor, *},
     output_selection::OutputSelection,
-    sources::{Source, Sources},
+    sources::Sources,
 };
 use foundry_compilers_core::{
     error::{Result, SolcError},
     utils,
 };
 use itertools::Itertools;
+use solar::parse::lexer::token::RawTokenKind;
 use std::{
     collections::{BTreeSet, HashMap, HashSet},
     hash::Hash,
     path::{Path, PathBuf},
-    sync::Arc,
 };
 use visitor::Walk;
 
+mod options;
+mod resolve;
+mod source_map;
+mod verify;
+pub use options::{FileBanner, FlattenOptions, LicensePolicy, PragmaPolicy, SourceOrder};
+pub use source_map::FlattenedSourceMap;
+use source_map::{Mapping, OffsetTracker};
+pub use verify::{BytecodeKind, BytecodeMismatch, FlattenVerification};
+
 /// Alternative of `SourceLocation` which includes path of the file.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 struct ItemLocation {
@@ -110,35 +123,88 @@ impl Visitor for ReferencesCollector {
     }
 }
 
+/// A file-level declaration that was renamed to avoid a name conflict in the flattened output.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RenamedSymbol {
+    /// Path of the source the declaration is in.
+    pub path: PathBuf,
+    /// Name of the declaration in the source.
+    pub name: String,
+    /// Name of the declaration in the flattened output.
+    pub renamed: String,
+}
+
+/// Result of flattening a target file.
+///
+/// Holds the updated sources and can map the flattened output back to them, see
+/// [`Self::source_map`].
 pub struct FlatteningResult {
     /// Updated source in the order they should be written to the output file.
     sources: Vec<String>,
+    /// Paths of the sources in the order they should be written to the output file.
+    paths: Vec<PathBuf>,
+    /// Mappings of each updated source back to its original content.
+    mappings: Vec<Vec<Mapping>>,
     /// Pragmas that should be present in the target file.
     pragmas: Vec<String>,
     /// License identifier that should be present in the target file.
     license: Option<String>,
+    /// File-level declarations that were renamed.
+    renamed_symbols: Vec<RenamedSymbol>,
 }
 
 impl FlatteningResult {
     fn new(
-        mut flattener: Flattener,
+        flattener: Flattener,
         updates: Updates,
         pragmas: Vec<String>,
         license: Option<String>,
+        renamed_symbols: Vec<RenamedSymbol>,
     ) -> Self {
-        apply_updates(&mut flattener.sources, updates);
+        let mut sources = Vec::with_capacity(flattener.ordered_sources.len());
+        let mut mappings = Vec::with_capacity(flattener.ordered_sources.len());
+        for (idx, path) in flattener.ordered_sources.iter().enumerate() {
+            let content = flattener.sources[path].content.as_str();
+            let updates = updates.get(path);
+
+            mappings.push(source_map::source_mappings(idx, content.len(), updates));
+            sources.push(match updates {
+                Some(updates) => replace_source_content(
+                    content,
+                    updates.iter().map(|(start, end, update)| ((*start..*end), update.as_str())),
+                ),
+                None => content.to_string(),
+            });
+        }
 
-        let sources = flattener
-            .ordered_sources
-            .iter()
-            .map(|path| flattener.sources.remove(path).unwrap().content)
-            .map(Arc::unwrap_or_clone)
-            .collect();
+        Self {
+            sources,
+            paths: flattener.ordered_sources,
+            mappings,
+            pragmas,
+            license,
+            renamed_symbols,
+        }
+    }
+
+    /// Returns the file-level declarations that were renamed to avoid name conflicts, ordered by
+    /// their source in the flattened output.
+    pub fn renamed_symbols(&self) -> &[RenamedSymbol] {
+        &self.renamed_symbols
+    }
 
-        Self { sources, pragmas, license }
+    /// Returns the flattened target file.
+    pub fn get_flattened_target(&self) -> String {
+        self.render().0
     }
 
-    fn get_flattened_target(&self) -> String {
+    /// Returns the mapping of the flattened target file returned by
+    /// [`Self::get_flattened_target`] back to the original sources.
+    pub fn source_map(&self) -> FlattenedSourceMap {
+        self.render().1
+    }
+
+    fn render(&self) -> (String, FlattenedSourceMap) {
         let mut result = String::new();
 
         if let Some(license) = &self.license {
@@ -147,11 +213,33 @@ impl FlatteningResult {
         for pragma in &self.pragmas {
             result.push_str(&format!("{pragma}\n"));
         }
-        for source in &self.sources {
-            result.push_str(&format!("\n\n{source}"));
+        let mut mappings = Vec::new();
+        for (source, source_mappings) in self.sources.iter().zip(&self.mappings) {
+            result.push_str("\n\n");
+            let offset = result.len() as isize;
+            mappings.extend(source_mappings.iter().map(|mapping| Mapping {
+                flattened: utils::range_by_offset(&mapping.flattened, offset),
+                ..mapping.clone()
+            }));
+            result.push_str(source);
+        }
+
+        // Track the offsets removed by collapsing newlines and trimming the result.
+        let mut offsets = OffsetTracker::default();
+        let trimmed_start = result.len() - result.trim_start().len();
+        let trimmed_end = result.trim_end().len().max(trimmed_start);
+        offsets.remove(0..trimmed_start);
+        for newlines in utils::RE_THREE_OR_MORE_NEWLINES.find_iter(&result) {
+            offsets
+                .remove((newlines.start() + 2).max(trimmed_start)..newlines.end().min(trimmed_end));
         }
+        offsets.remove(trimmed_end..result.len());
 
-        format!("{}\n", utils::RE_THREE_OR_MORE_NEWLINES.replace_all(&result, "\n\n").trim())
+        let flattened =
+            format!("{}\n", utils::RE_THREE_OR_MORE_NEWLINES.replace_all(&result, "\n\n").trim());
+        let mappings = mappings.into_iter().flat_map(|mapping| offsets.map(mapping)).collect();
+
+        (flattened, FlattenedSourceMap::new(self.paths.clone(), mappings))
     }
 }
 
@@ -169,37 +257,303 @@ impl<T: Into<SolcError>> From<T> for FlattenerError {
     }
 }
 
-/// Context for flattening. Stores all sources and ASTs that are in scope of the flattening target.
+/// Declarations, references and directives in the sources that are in scope of the flattening
+/// target.
+///
+/// This is all the flattener needs to know about the sources. It's either collected from the ASTs
+/// produced by solc or by analyzing the sources with solar.
+#[derive(Debug, Default)]
+struct Analysis {
+    /// File-level definitions, by name, with their ids and the locations of their names.
+    top_level_definitions: HashMap<String, HashSet<(usize, ItemLocation)>>,
+    /// Contract-level definitions, by id, with their names and the ids of their contracts.
+    contract_level_definitions: HashMap<usize, (String, usize)>,
+    /// All references to declarations, by id of the referenced declaration.
+    references: HashMap<usize, HashSet<ItemLocation>>,
+    /// References to import aliases which qualify a member access, e.g. `Lib` in `Lib.foo()`.
+    qualified_imports: HashSet<ItemLocation>,
+    /// Ids of the file-level declarations visible in each source, by name.
+    exported_symbols: HashMap<PathBuf, HashMap<String, usize>>,
+    /// NatSpec comments of contract-level functions, variables, events and errors.
+    docs: HashSet<ItemLocation>,
+    /// Import directives.
+    imports: HashSet<ItemLocation>,
+    /// Pragma directives.
+    pragmas: HashSet<ItemLocation>,
+}
+
+impl Analysis {
+    /// Collects the analysis from the ASTs produced by solc.
+    fn from_solc_asts(asts: &[(PathBuf, SourceUnit)], sources: &Sources) -> Self {
+        let mut analysis = Self::default();
+        let mut import_ids = HashSet::new();
+
+        for (path, ast) in asts {
+            let mut collector =
+                ReferencesCollector { path: path.clone(), references: HashMap::new() };
+            ast.walk(&mut collector);
+            for (id, locs) in collector.references {
+                // Negative ids refer to builtins.
+                if let Ok(id) = usize::try_from(id) {
+                    analysis.references.entry(id).or_default().extend(locs);
+                }
+            }
+
+            // @inheritdoc value is either one of the exported symbols or qualified import path
+            // which we don't support
+            analysis.exported_symbols.insert(
+                path.clone(),
+                ast.exported_symbols
+                    .iter()
+                    .filter_map(|(name, ids)| Some((name.clone(), *ids.first()?)))
+                    .collect(),
+            );
+
+            for node in &ast.nodes {
+                let (name, id, src, name_src) = match node {
+                    SourceUnitPart::ImportDirective(import) => {
+                        import_ids.insert(import.id);
+                        analysis
+                            .imports
+                            .extend(ItemLocation::try_from_source_loc(&import.src, path.clone()));
+                        continue;
+                    }
+                    SourceUnitPart::PragmaDirective(pragma) => {
+                        analysis
+                            .pragmas
+                            .extend(ItemLocation::try_from_source_loc(&pragma.src, path.clone()));
+                        continue;
+                    }
+                    SourceUnitPart::ContractDefinition(contract) => {
+                        analysis.collect_solc_contract_level_definitions(path, contract);
+                        (&contract.name, contract.id, &contract.src, &contract.name_location)
+                    }
+                    SourceUnitPart::EnumDefinition(enum_) => {
+                        (&enum_.name, enum_.id, &enum_.src, &enum_.name_location)
+                    }
+                    SourceUnitPart::StructDefinition(struct_) => {
+                        (&struct_.name, struct_.id, &struct_.src, &struct_.name_location)
+                    }
+                    SourceUnitPart::FunctionDefinition(func) => {
+                        (&func.name, func.id, &func.src, &func.name_location)
+                    }
+                    SourceUnitPart::VariableDeclaration(var) => {
+                        (&var.name, var.id, &var.src, &var.name_location)
+                    }
+                    SourceUnitPart::UserDefinedValueTypeDefinition(type_) => {
+                        (&type_.name, type_.id, &type_.src, &type_.name_location)
+                    }
+                    _ => continue,
+                };
+
+                let loc = match name_src {
+                    Some(src) => ItemLocation::try_from_source_loc(src, path.clone()).unwrap(),
+                    None => {
+                        // Find location of name in source
+                        let content: &str = &sources.get(path).unwrap().content;
+                        let start = src.start.unwrap();
+                        let end = start + src.length.unwrap();
+
+                        let name_start = content[start..end].find(name.as_str()).unwrap();
+                        let name_end = name_start + name.len();
+
+                        ItemLocation {
+                            path: path.clone(),
+                            start: start + name_start,
+                            end: start + name_end,
+                        }
+                    }
+                };
+                analysis.top_level_definitions.entry(name.clone()).or_default().insert((id, loc));
+            }
+        }
+
+        // Every qualified import part is an `Identifier` with `referencedDeclaration` field
+        // matching ID of one of the import directives.
+        for id in import_ids {
+            if let Some(locs) = analysis.references.get(&id) {
+                analysis.qualified_imports.extend(locs.iter().cloned());
+            }
+        }
+
+        analysis
+    }
+
+    /// Collects the definitions and NatSpec comments of the given contract.
+    fn collect_solc_contract_level_definitions(
+        &mut self,
+        path: &Path,
+        contract: &ContractDefinition,
+    ) {
+        for node in &contract.nodes {
+            let (name, id, documentation) = match node {
+                ContractDefinitionPart::EnumDefinition(enum_) => (&enum_.name, enum_.id, None),
+                ContractDefinitionPart::ErrorDefinition(error) => {
+                    (&error.name, error.id, error.documentation.as_ref())
+                }
+                ContractDefinitionPart::EventDefinition(event) => {
+                    (&event.name, event.id, event.documentation.as_ref())
+                }
+                ContractDefinitionPart::StructDefinition(struct_) => {
+                    (&struct_.name, struct_.id, None)
+                }
+                ContractDefinitionPart::FunctionDefinition(function) => {
+                    (&function.name, function.id, function.documentation.as_ref())
+                }
+                ContractDefinitionPart::VariableDeclaration(variable) => {
+                    (&variable.name, variable.id, variable.documentation.as_ref())
+                }
+                ContractDefinitionPart::UserDefinedValueTypeDefinition(value_type) => {
+                    (&value_type.name, value_type.id, None)
+                }
+                _ => continue,
+            };
+            self.contract_level_definitions.insert(id, (name.clone(), contract.id));
+
+            if let Some(Documentation::Structured(doc)) = documentation {
+                self.docs.extend(ItemLocation::try_from_source_loc(&doc.src, path.to_path_buf()));
+            }
+        }
+    }
+}
+
+/// Context for flattening. Stores all sources that are in scope of the flattening target and the
+/// analysis of their declarations and references.
 pub struct Flattener {
-    /// Target file to flatten.
-    target: PathBuf,
+    /// Target files to flatten.
+    targets: Vec<PathBuf>,
     /// Sources including only target and it dependencies (imports of any depth).
     sources: Sources,
-    /// Vec of (path, ast) pairs.
-    asts: Vec<(PathBuf, SourceUnit)>,
+    /// Declarations, references and directives in the sources.
+    analysis: Analysis,
     /// Sources in the order they should be written to the output file.
     ordered_sources: Vec<PathBuf>,
+    /// Direct imports of each source.
+    imports: HashMap<PathBuf, Vec<PathBuf>>,
     /// Project root directory.
     project_root: PathBuf,
+    /// Options to configure the output.
+    options: FlattenOptions,
 }
 
 impl Flattener {
-    /// Compiles the target file and prepares AST and analysis data for flattening.
+    /// Prepares analysis data for flattening the target file.
+    ///
+    /// The sources are analyzed with solar, see [`Self::new_with_solar`]. If solar fails to parse
+    /// or resolve them, the target file is compiled with solc instead, see
+    /// [`Self::new_with_solc`].
     pub fn new<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>>(
-        mut project: Project<C, T>,
+        project: Project<C, T>,
+        target: &Path,
+    ) -> std::result::Result<Self, FlattenerError>
+    where
+        C::Parser: SourceParser<ParsedSource: MaybeSolData>,
+    {
+        Self::new_multi(project, &[target.to_path_buf()])
+    }
+
+    /// Prepares analysis data for flattening multiple target files into a single file.
+    ///
+    /// Every dependency of the targets is written once, and conflicting file-level names are
+    /// renamed consistently across all targets, see [`FlatteningResult::renamed_symbols`].
+    ///
+    /// Like [`Self::new`], this falls back to compiling the targets with solc if solar fails to
+    /// analyze them.
+    pub fn new_multi<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>>(
+        project: Project<C, T>,
+        targets: &[PathBuf],
+    ) -> std::result::Result<Self, FlattenerError>
+    where
+        C::Parser: SourceParser<ParsedSource: MaybeSolData>,
+    {
+        match Self::new_multi_with_solar(&project.paths, targets) {
+            Ok(flattener) => Ok(flattener),
+            Err(err) => {
+                debug!(%err, "failed to analyze sources with solar, compiling with solc");
+                Self::new_multi_with_solc(project, targets)
+            }
+        }
+    }
+
+    /// Analyzes the target file and its imports with solar and prepares analysis data for
+    /// flattening.
+    ///
+    /// This doesn't require a solc installation and is much faster than compiling the sources,
+    /// but fails if solar can't parse or resolve them.
+    pub fn new_with_solar<L>(
+        paths: &ProjectPathsConfig<L>,
+        target: &Path,
+    ) -> std::result::Result<Self, FlattenerError> {
+        Self::new_multi_with_solar(paths, &[target.to_path_buf()])
+    }
+
+    /// Analyzes multiple target files and their imports with solar and prepares analysis data for
+    /// flattening them into a single file.
+    ///
+    /// See [`Self::new_with_solar`] and [`Self::new_multi`].
+    pub fn new_multi_with_solar<L>(
+        paths: &ProjectPathsConfig<L>,
+        targets: &[PathBuf],
+    ) -> std::result::Result<Self, FlattenerError> {
+        let paths = paths.with_language_ref::<SolcLanguage>();
+        check_targets(targets)?;
+
+        let sources = paths.read_files(targets)?;
+        let mut graph = Graph::<SolParser>::resolve_sources(paths, sources)?;
+
+        let ordered_sources = collect_ordered_deps_multi(targets, paths, &graph)?;
+        let imports = collect_imports(&ordered_sources, paths, &graph)?;
+        let sources = paths.read_files(&ordered_sources)?;
+
+        let analysis = resolve::analyze(graph.parser_mut().compiler_mut(), &sources)?;
+
+        Ok(Self {
+            targets: targets.to_vec(),
+            sources,
+            analysis,
+            ordered_sources,
+            imports,
+            project_root: paths.root.clone(),
+            options: FlattenOptions::default(),
+        })
+    }
+
+    /// Compiles the target file and prepares AST and analysis data for flattening.
+    pub fn new_with_solc<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>>(
+        project: Project<C, T>,
         target: &Path,
     ) -> std::result::Result<Self, FlattenerError>
     where
         C::Parser: SourceParser<ParsedSource: MaybeSolData>,
     {
-        // Configure project to compile the target file and only request AST for target file.
+        Self::new_multi_with_solc(project, &[target.to_path_buf()])
+    }
+
+    /// Compiles multiple target files and prepares AST and analysis data for flattening them into
+    /// a single file.
+    ///
+    /// See [`Self::new_with_solc`] and [`Self::new_multi`].
+    pub fn new_multi_with_solc<
+        C: Compiler,
+        T: ArtifactOutput<CompilerContract = C::CompilerContract>,
+    >(
+        mut project: Project<C, T>,
+        targets: &[PathBuf],
+    ) -> std::result::Result<Self, FlattenerError>
+    where
+        C::Parser: SourceParser<ParsedSource: MaybeSolData>,
+    {
+        check_targets(targets)?;
+
+        // Configure project to compile the target files and only request AST for target files.
         project.cached = false;
         project.no_artifacts = true;
         project.settings.update_output_selection(|selection| {
             *selection = OutputSelection::ast_output_selection();
         });
 
-        let output = project.compile_file(target).map_err(FlattenerError::Compilation)?;
+        let output =
+            project.compile_files(targets.iter().cloned()).map_err(FlattenerError::Compilation)?;
 
         if output.has_compiler_errors() {
             return Err(FlattenerError::Compilation(SolcError::msg(&output)));
@@ -207,22 +561,25 @@ impl Flattener {
 
         let output = output.compiler_output;
 
-        let sources = Source::read_all([target.to_path_buf()])?;
-        let graph = Graph::<C::Parser>::resolve_sources(&project.paths, sources)?;
+        let sources = project.paths.read_files(targets)?;
+        let graph = Graph::resolve_sources_with(&project.paths, sources, project.parser())?;
 
-        let ordered_sources = collect_ordered_deps(target, &project.paths, &graph)?;
+        let ordered_sources = collect_ordered_deps_multi(targets, &project.paths, &graph)?;
+        let imports = collect_imports(&ordered_sources, &project.paths, &graph)?;
 
         #[cfg(windows)]
-        let ordered_sources = {
-            let mut sources = ordered_sources;
+        let (ordered_sources, imports, targets) = {
             use path_slash::PathBufExt;
-            for p in &mut sources {
-                *p = PathBuf::from(p.to_slash_lossy().to_string());
-            }
-            sources
+            let slash = |p: &PathBuf| PathBuf::from(p.to_slash_lossy().to_string());
+            let sources = ordered_sources.iter().map(slash).collect::<Vec<_>>();
+            let imports = imports
+                .iter()
+                .map(|(path, imports)| (slash(path), imports.iter().map(slash).collect()))
+                .collect::<HashMap<_, _>>();
+            (sources, imports, &targets.iter().map(slash).collect::<Vec<_>>()[..])
         };
 
-        let sources = Source::read_all(&ordered_sources)?;
+        let sources = project.paths.read_files(&ordered_sources)?;
 
         // Convert all ASTs from artifacts to strongly typed ASTs
         let mut asts: Vec<(PathBuf, SourceUnit)> = Vec::new();
@@ -237,15 +594,50 @@ impl Flattener {
             asts.push((PathBuf::from(path), serde_json::from_str(&serde_json::to_string(ast)?)?));
         }
 
+        let analysis = Analysis::from_solc_asts(&asts, &sources);
+
         Ok(Self {
-            target: target.into(),
+            targets: targets.to_vec(),
             sources,
-            asts,
+            analysis,
             ordered_sources,
+            imports,
             project_root: project.root().to_path_buf(),
+            options: FlattenOptions::default(),
         })
     }
 
+    /// Configures the flattened output with the given options.
+    ///
+    /// Returns an error if the pragmas of the sources can't be combined with the configured
+    /// [`PragmaPolicy`].
+    pub fn with_options(
+        mut self,
+        options: FlattenOptions,
+    ) -> std::result::Result<Self, FlattenerError> {
+        if options.pragmas == PragmaPolicy::Intersect {
+            let pragmas = self
+                .analysis
+                .pragmas
+                .iter()
+                .map(|loc| self.read_location(loc))
+                .filter(|pragma| pragma.contains("solidity"))
+                .collect::<Vec<_>>();
+            if let Some(pragma) = combine_version_pragmas(&pragmas)
+                && let Some(Ok(req)) = SolData::parse_version_pragma(&pragma)
+                && !is_satisfiable(&req)
+            {
+                return Err(FlattenerError::Other(SolcError::msg(format!(
+                    "no compiler version satisfies the version pragmas of all sources: {pragma}"
+                ))));
+            }
+        }
+
+        self.ordered_sources = order_sources(&self.targets, &self.imports, options.order);
+        self.options = options;
+        Ok(self)
+    }
+
     /// Flattens target file and returns the result as a string
     ///
     /// Flattening process includes following steps:
@@ -253,13 +645,22 @@ impl Flattener {
     ///    imports.
     /// 2. Find all duplicates among file-level definitions and rename them to avoid conflicts.
     /// 3. Remove all imports.
-    /// 4. Remove all pragmas except for the ones in the target file.
-    /// 5. Remove all license identifiers except for the one in the target file.
+    /// 4. Remove all pragmas and write the combined ones at the top, see [`PragmaPolicy`].
+    /// 5. Remove all license identifiers except for the one in the target file, see
+    ///    [`LicensePolicy`].
+    /// 6. Remove all comments except for NatSpec, if [`FlattenOptions::strip_comments`] is set.
     pub fn flatten(self) -> String {
+        self.flatten_result().get_flattened_target()
+    }
+
+    /// Flattens the target file like [`Self::flatten`], but returns the [`FlatteningResult`] which
+    /// can also map the flattened output back to the original sources.
+    pub fn flatten_result(self) -> FlatteningResult {
         let mut updates = Updates::new();
 
-        self.append_filenames(&mut updates);
+        self.append_banners(&mut updates);
         let top_level_names = self.rename_top_level_definitions(&mut updates);
+        let renamed_symbols = self.renamed_symbols(&top_level_names);
         self.rename_contract_level_types_references(&top_level_names, &mut updates);
         self.remove_qualified_imports(&mut updates);
         self.update_inheritdocs(&top_level_names, &mut updates);
@@ -267,26 +668,28 @@ impl Flattener {
         self.remove_imports(&mut updates);
         let target_pragmas = self.process_pragmas(&mut updates);
         let target_license = self.process_licenses(&mut updates);
+        if self.options.strip_comments {
+            self.strip_comments(&mut updates);
+        }
 
-        self.flatten_result(updates, target_pragmas, target_license).get_flattened_target()
-    }
-
-    fn flatten_result(
-        self,
-        updates: Updates,
-        target_pragmas: Vec<String>,
-        target_license: Option<String>,
-    ) -> FlatteningResult {
-        FlatteningResult::new(self, updates, target_pragmas, target_license)
+        FlatteningResult::new(self, updates, target_pragmas, target_license, renamed_symbols)
     }
 
     /// Appends a comment with the file name to the beginning of each source.
-    fn append_filenames(&self, updates: &mut Updates) {
+    fn append_banners(&self, updates: &mut Updates) {
+        let label = match self.options.banner {
+            FileBanner::None => return,
+            FileBanner::Path => "",
+            FileBanner::File => "File: ",
+        };
         for path in &self.ordered_sources {
             updates.entry(path.clone()).or_default().insert((
                 0,
                 0,
-                format!("// {}\n", path.strip_prefix(&self.project_root).unwrap_or(path).display()),
+                format!(
+                    "// {label}{}\n",
+                    path.strip_prefix(&self.project_root).unwrap_or(path).display()
+                ),
             ));
         }
     }
@@ -301,12 +704,9 @@ impl Flattener {
     ///
     /// Returns mapping from top-level declaration id to its name (possibly updated)
     fn rename_top_level_definitions(&self, updates: &mut Updates) -> HashMap<usize, String> {
-        let top_level_definitions = self.collect_top_level_definitions();
-        let references = self.collect_references();
-
         let mut top_level_names = HashMap::new();
 
-        for (name, ids) in top_level_definitions {
+        for (name, ids) in &self.analysis.top_level_definitions {
             let mut definition_name = name.to_string();
             let needs_rename = ids.len() > 1;
 
@@ -330,7 +730,7 @@ impl Flattener {
                     loc.end,
                     definition_name.clone(),
                 ));
-                if let Some(references) = references.get(&(*id as isize)) {
+                if let Some(references) = self.analysis.references.get(id) {
                     for loc in references {
                         updates.entry(loc.path.clone()).or_default().insert((
                             loc.start,
@@ -346,90 +746,76 @@ impl Flattener {
         top_level_names
     }
 
+    /// Returns the top-level declarations whose name was changed by
+    /// [`Self::rename_top_level_definitions`].
+    fn renamed_symbols(&self, top_level_names: &HashMap<usize, String>) -> Vec<RenamedSymbol> {
+        let mut renamed = self
+            .analysis
+            .top_level_definitions
+            .iter()
+            .flat_map(|(name, ids)| {
+                ids.iter().filter(|(id, _)| top_level_names[id] != *name).map(|(id, loc)| {
+                    let idx = self.ordered_sources.iter().position(|p| p == &loc.path);
+                    let symbol = RenamedSymbol {
+                        path: loc.path.clone(),
+                        name: name.clone(),
+                        renamed: top_level_names[id].clone(),
+                    };
+                    (idx, loc.start, symbol)
+                })
+            })
+            .collect::<Vec<_>>();
+        renamed.sort();
+        renamed.into_iter().map(|(_, _, symbol)| symbol).collect()
+    }
+
     /// This is not very clean, but in most cases effective enough method to remove qualified
     /// imports from sources.
     ///
-    /// Every qualified import part is an `Identifier` with `referencedDeclaration` field matching
-    /// ID of one of the import directives.
-    ///
-    /// This approach works by firstly collecting all IDs of import directives, and then looks for
-    /// any references of them. Once the reference is found, it's full length is getting removed
-    /// from source + 1 character ('.')
+    /// Once a reference to an import alias is found, it's full length is getting removed from
+    /// source + 1 character ('.')
     ///
     /// This should work correctly for vast majority of cases, however there are situations for
     /// which such approach won't work, most of which are related to code being formatted in an
     /// uncommon way.
     fn remove_qualified_imports(&self, updates: &mut Updates) {
-        let imports_ids = self
-            .asts
-            .iter()
-            .flat_map(|(_, ast)| {
-                ast.nodes.iter().filter_map(|node| match node {
-                    SourceUnitPart::ImportDirective(directive) => Some(directive.id),
-                    _ => None,
-                })
-            })
-            .collect::<HashSet<_>>();
-
-        let references = self.collect_references();
-
-        for (id, locs) in references {
-            if !imports_ids.contains(&(id as usize)) {
-                continue;
-            }
-
-            for loc in locs {
-                updates.entry(loc.path).or_default().insert((
-                    loc.start,
-                    loc.end + 1,
-                    String::new(),
-                ));
-            }
+        for loc in &self.analysis.qualified_imports {
+            updates.entry(loc.path.clone()).or_default().insert((
+                loc.start,
+                loc.end + 1,
+                String::new(),
+            ));
         }
     }
 
     /// Here we are going through all references to items defined in scope of contracts and updating
     /// them to be using correct parent contract name.
     ///
-    /// This will only operate on references from `IdentifierPath` nodes.
+    /// This will only operate on references to items which are not referenced by their name only,
+    /// e.g. from `IdentifierPath` nodes.
     fn rename_contract_level_types_references(
         &self,
         top_level_names: &HashMap<usize, String>,
         updates: &mut Updates,
     ) {
-        let contract_level_definitions = self.collect_contract_level_definitions();
-
-        for (path, ast) in &self.asts {
-            for node in &ast.nodes {
-                let mut collector =
-                    ReferencesCollector { path: self.target.clone(), references: HashMap::new() };
-
-                node.walk(&mut collector);
-
-                let references = collector.references;
-
-                for (id, locs) in references {
-                    if let Some((name, contract_id)) =
-                        contract_level_definitions.get(&(id as usize))
-                    {
-                        for loc in &locs {
-                            // If child item is referenced directly by it's name it's either defined
-                            // in the same contract or in one of it's base contracts, so we don't
-                            // have to change anything.
-                            // Comparing lengths is enough because such items cannot be aliased.
-                            if loc.length() == name.len() {
-                                continue;
-                            }
-                            // If it was referenced somehow else, we rename it to `Parent.Child`
-                            // format.
-                            let parent_name = top_level_names.get(contract_id).unwrap();
-                            updates.entry(path.clone()).or_default().insert((
-                                loc.start,
-                                loc.end,
-                                format!("{parent_name}.{name}"),
-                            ));
-                        }
+        for (id, locs) in &self.analysis.references {
+            if let Some((name, contract_id)) = self.analysis.contract_level_definitions.get(id) {
+                for loc in locs {
+                    // If child item is referenced directly by it's name it's either defined
+                    // in the same contract or in one of it's base contracts, so we don't
+                    // have to change anything.
+                    // Comparing lengths is enough because such items cannot be aliased.
+                    if loc.length() == name.len() {
+                        continue;
                     }
+                    // If it was referenced somehow else, we rename it to `Parent.Child`
+                    // format.
+                    let parent_name = top_level_names.get(contract_id).unwrap();
+                    updates.entry(loc.path.clone()).or_default().insert((
+                        loc.start,
+                        loc.end,
+                        format!("{parent_name}.{name}"),
+                    ));
                 }
             }
         }
@@ -441,228 +827,61 @@ impl Flattener {
     /// generating invalid source code.
     fn update_inheritdocs(&self, top_level_names: &HashMap<usize, String>, updates: &mut Updates) {
         trace!("updating @inheritdoc tags");
-        for (path, ast) in &self.asts {
-            // Collect all exported symbols for this source unit
-            // @inheritdoc value is either one of those or qualified import path which we don't
-            // support
-            let exported_symbols = ast
-                .exported_symbols
-                .iter()
-                .filter_map(
-                    |(name, ids)| {
-                        if !ids.is_empty() { Some((name.as_str(), ids[0])) } else { None }
-                    },
-                )
-                .collect::<HashMap<_, _>>();
-
-            // Collect all docs in all contracts
-            let docs = ast
-                .nodes
-                .iter()
-                .filter_map(|node| match node {
-                    SourceUnitPart::ContractDefinition(d) => Some(d),
-                    _ => None,
-                })
-                .flat_map(|contract| {
-                    contract.nodes.iter().filter_map(|node| match node {
-                        ContractDefinitionPart::EventDefinition(event) => {
-                            event.documentation.as_ref()
-                        }
-                        ContractDefinitionPart::ErrorDefinition(error) => {
-                            error.documentation.as_ref()
-                        }
-                        ContractDefinitionPart::FunctionDefinition(func) => {
-                            func.documentation.as_ref()
-                        }
-                        ContractDefinitionPart::VariableDeclaration(var) => {
-                            var.documentation.as_ref()
-                        }
-                        _ => None,
-                    })
-                });
-
-            docs.for_each(|doc| {
-                let Documentation::Structured(doc) = doc else {
-                    return
-                };
-                let src_start = doc.src.start.unwrap();
-                let src_end = src_start + doc.src.length.unwrap();
-
-                // Documentation node has `text` field, however, it does not contain
-                // slashes and we can't use if to find positions.
-                let content: &str = &self.sources.get(path).unwrap().content[src_start..src_end];
-                let tag_len = "@inheritdoc".len();
-
-                if let Some(tag_start) = content.find("@inheritdoc") {
-                    trace!("processing doc with content {:?}", content);
-                    if let Some(name_start) = content[tag_start + tag_len..]
-                        .find(|c| c != ' ')
-                        .map(|p| p + tag_start + tag_len)
-                    {
-                        let name_end = content[name_start..]
-                            .find([' ', '\n', '*', '/'])
-                            .map(|p| p + name_start)
-                            .unwrap_or(content.len());
-
-                        let name = &content[name_start..name_end];
-                        trace!("found name {name}");
-
-                        let mut new_name = None;
-
-                        if let Some(ast_id) = exported_symbols.get(name) {
-                            if let Some(name) = top_level_names.get(ast_id) {
-                                new_name = Some(name);
-                            } else {
-                                trace!(identifiers=?top_level_names, "ast id {ast_id} cannot be matched to top-level identifier");
-                            }
-                        }
-
-                        if let Some(new_name) = new_name {
-                            trace!("updating tag value with {new_name}");
-                            updates.entry(path.to_path_buf()).or_default().insert((
-                                src_start + name_start,
-                                src_start + name_end,
-                                new_name.to_string(),
-                            ));
+        for doc in &self.analysis.docs {
+            let exported_symbols = self.analysis.exported_symbols.get(&doc.path);
+
+            // Documentation node has `text` field, however, it does not contain
+            // slashes and we can't use if to find positions.
+            let content = self.read_location(doc);
+            let tag_len = "@inheritdoc".len();
+
+            if let Some(tag_start) = content.find("@inheritdoc") {
+                trace!("processing doc with content {:?}", content);
+                if let Some(name_start) = content[tag_start + tag_len..]
+                    .find(|c| c != ' ')
+                    .map(|p| p + tag_start + tag_len)
+                {
+                    let name_end = content[name_start..]
+                        .find([' ', '\n', '*', '/'])
+                        .map(|p| p + name_start)
+                        .unwrap_or(content.len());
+
+                    let name = &content[name_start..name_end];
+                    trace!("found name {name}");
+
+                    let mut new_name = None;
+
+                    if let Some(ast_id) = exported_symbols.and_then(|symbols| symbols.get(name)) {
+                        if let Some(name) = top_level_names.get(ast_id) {
+                            new_name = Some(name);
                         } else {
-                            trace!("name is unknown, removing @inheritdoc tag");
-                            updates.entry(path.to_path_buf()).or_default().insert((
-                                src_start + tag_start,
-                                src_start + name_end,
-                                String::new(),
-                            ));
+                            trace!(identifiers=?top_level_names, "ast id {ast_id} cannot be matched to top-level identifier");
                         }
                     }
-                }
-            });
-        }
-    }
-
-    /// Processes all ASTs and collects all top-level definitions in the form of
-    /// a mapping from name to (definition id, source location)
-    fn collect_top_level_definitions(&self) -> HashMap<&String, HashSet<(usize, ItemLocation)>> {
-        self.asts
-            .iter()
-            .flat_map(|(path, ast)| {
-                ast.nodes
-                    .iter()
-                    .filter_map(|node| match node {
-                        SourceUnitPart::ContractDefinition(contract) => Some((
-                            &contract.name,
-                            contract.id,
-                            &contract.src,
-                            &contract.name_location,
-                        )),
-                        SourceUnitPart::EnumDefinition(enum_) => {
-                            Some((&enum_.name, enum_.id, &enum_.src, &enum_.name_location))
-                        }
-                        SourceUnitPart::StructDefinition(struct_) => {
-                            Some((&struct_.name, struct_.id, &struct_.src, &struct_.name_location))
-                        }
-                        SourceUnitPart::FunctionDefinition(func) => {
-                            Some((&func.name, func.id, &func.src, &func.name_location))
-                        }
-                        SourceUnitPart::VariableDeclaration(var) => {
-                            Some((&var.name, var.id, &var.src, &var.name_location))
-                        }
-                        SourceUnitPart::UserDefinedValueTypeDefinition(type_) => {
-                            Some((&type_.name, type_.id, &type_.src, &type_.name_location))
-                        }
-                        _ => None,
-                    })
-                    .map(|(name, id, src, maybe_name_src)| {
-                        let loc = match maybe_name_src {
-                            Some(src) => {
-                                ItemLocation::try_from_source_loc(src, path.clone()).unwrap()
-                            }
-                            None => {
-                                // Find location of name in source
-                                let content: &str = &self.sources.get(path).unwrap().content;
-                                let start = src.start.unwrap();
-                                let end = start + src.length.unwrap();
-
-                                let name_start = content[start..end].find(name).unwrap();
-                                let name_end = name_start + name.len();
-
-                                ItemLocation {
-                                    path: path.clone(),
-                                    start: start + name_start,
-                                    end: start + name_end,
-                                }
-                            }
-                        };
-
-                        (name, (id, loc))
-                    })
-            })
-            .fold(HashMap::new(), |mut acc, (name, (id, item_location))| {
-                acc.entry(name).or_default().insert((id, item_location));
-                acc
-            })
-    }
 
-    /// Collect all contract-level definitions in the form of a mapping from definition id to
-    /// (definition name, contract id)
-    fn collect_contract_level_definitions(&self) -> HashMap<usize, (&String, usize)> {
-        self.asts
-            .iter()
-            .flat_map(|(_, ast)| {
-                ast.nodes.iter().filter_map(|node| match node {
-                    SourceUnitPart::ContractDefinition(contract) => {
-                        Some((contract.id, &contract.nodes))
-                    }
-                    _ => None,
-                })
-            })
-            .flat_map(|(contract_id, nodes)| {
-                nodes.iter().filter_map(move |node| match node {
-                    ContractDefinitionPart::EnumDefinition(enum_) => {
-                        Some((enum_.id, (&enum_.name, contract_id)))
-                    }
-                    ContractDefinitionPart::ErrorDefinition(error) => {
-                        Some((error.id, (&error.name, contract_id)))
-                    }
-                    ContractDefinitionPart::EventDefinition(event) => {
-                        Some((event.id, (&event.name, contract_id)))
-                    }
-                    ContractDefinitionPart::StructDefinition(struct_) => {
-                        Some((struct_.id, (&struct_.name, contract_id)))
-                    }
-                    ContractDefinitionPart::FunctionDefinition(function) => {
-                        Some((function.id, (&function.name, contract_id)))
-                    }
-                    ContractDefinitionPart::VariableDeclaration(variable) => {
-                        Some((variable.id, (&variable.name, contract_id)))
-                    }
-                    ContractDefinitionPart::UserDefinedValueTypeDefinition(value_type) => {
-                        Some((value_type.id, (&value_type.name, contract_id)))
+                    if let Some(new_name) = new_name {
+                        trace!("updating tag value with {new_name}");
+                        updates.entry(doc.path.clone()).or_default().insert((
+                            doc.start + name_start,
+                            doc.start + name_end,
+                            new_name.to_string(),
+                        ));
+                    } else {
+                        trace!("name is unknown, removing @inheritdoc tag");
+                        updates.entry(doc.path.clone()).or_default().insert((
+                            doc.start + tag_start,
+                            doc.start + name_end,
+                            String::new(),
+                        ));
                     }
-                    _ => None,
-                })
-            })
-            .collect()
-    }
-
-    /// Collects all references to any declaration in the form of a mapping from declaration id to
-    /// set of source locations it appears in
-    fn collect_references(&self) -> HashMap<isize, HashSet<ItemLocation>> {
-        self.asts
-            .iter()
-            .flat_map(|(path, ast)| {
-                let mut collector =
-                    ReferencesCollector { path: path.clone(), references: HashMap::new() };
-                ast.walk(&mut collector);
-                collector.references
-            })
-            .fold(HashMap::new(), |mut acc, (id, locs)| {
-                acc.entry(id).or_default().extend(locs);
-                acc
-            })
+                }
+            }
+        }
     }
 
     /// Removes all imports from all sources.
     fn remove_imports(&self, updates: &mut Updates) {
-        for loc in self.collect_imports() {
+        for loc in &self.analysis.imports {
             updates.entry(loc.path.clone()).or_default().insert((
                 loc.start,
                 loc.end,
@@ -671,31 +890,19 @@ impl Flattener {
         }
     }
 
-    // Collects all imports locations.
-    fn collect_imports(&self) -> HashSet<ItemLocation> {
-        self.asts
-            .iter()
-            .flat_map(|(path, ast)| {
-                ast.nodes.iter().filter_map(|node| match node {
-                    SourceUnitPart::ImportDirective(import) => {
-                        ItemLocation::try_from_source_loc(&import.src, path.clone())
-                    }
-                    _ => None,
-                })
-            })
-            .collect()
-    }
-
-    /// Removes all pragma directives from all sources. Returns Vec with experimental and combined
-    /// version pragmas (if present).
+    /// Removes all pragmas from all sources. Returns the pragmas that should be written at the top
+    /// of the flattened file according to the [`PragmaPolicy`].
     fn process_pragmas(&self, updates: &mut Updates) -> Vec<String> {
         let mut abicoder_v2 = None;
 
-        let pragmas = self.collect_pragmas();
         let mut version_pragmas = Vec::new();
+        let mut target_pragmas = Vec::new();
 
-        for loc in &pragmas {
+        for loc in &self.analysis.pragmas {
             let pragma_content = self.read_location(loc);
+            if let Some(idx) = self.targets.iter().position(|target| target == &loc.path) {
+                target_pragmas.push((idx, loc.start, pragma_content.to_string()));
+            }
             if pragma_content.contains("experimental") || pragma_content.contains("abicoder") {
                 if abicoder_v2.is_none() {
                     abicoder_v2 = Some(self.read_location(loc).to_string());
@@ -711,6 +918,11 @@ impl Flattener {
             ));
         }
 
+        if self.options.pragmas == PragmaPolicy::TargetOnly {
+            target_pragmas.sort();
+            return target_pragmas.into_iter().map(|(_, _, pragma)| pragma).unique().collect();
+        }
+
         let mut pragmas = Vec::new();
 
         if let Some(version_pragma) = combine_version_pragmas(&version_pragmas) {
@@ -724,40 +936,74 @@ impl Flattener {
         pragmas
     }
 
-    // Collects all pragma directives locations.
-    fn collect_pragmas(&self) -> HashSet<ItemLocation> {
-        self.asts
-            .iter()
-            .flat_map(|(path, ast)| {
-                ast.nodes.iter().filter_map(|node| match node {
-                    SourceUnitPart::PragmaDirective(import) => {
-                        ItemLocation::try_from_source_loc(&import.src, path.clone())
-                    }
-                    _ => None,
-                })
-            })
-            .collect()
-    }
-
-    /// Removes all license identifiers from all sources. Returns license identifier from target
-    /// file, if any.
+    /// Removes all license identifiers from all sources. Returns the license identifier that
+    /// should be written at the top of the flattened file according to the [`LicensePolicy`].
+    ///
+    /// If there are multiple targets, their licenses are combined with `AND`.
     fn process_licenses(&self, updates: &mut Updates) -> Option<String> {
-        let mut target_license = None;
+        let mut target_licenses = Vec::new();
+        let mut expressions = Vec::new();
 
         for loc in &self.collect_licenses() {
-            if loc.path == self.target {
-                let license_line = self.read_location(loc);
-                let license_start = license_line.find("SPDX-License-Identifier:").unwrap();
-                target_license = Some(license_line[license_start..].trim().to_string());
+            let license_line = self.read_location(loc);
+            let license_start = license_line.find("SPDX-License-Identifier:").unwrap();
+            let license = license_line[license_start..].trim();
+            let expression = license["SPDX-License-Identifier:".len()..]
+                .trim()
+                .trim_end_matches("*/")
+                .trim_end();
+
+            let idx = self.ordered_sources.iter().position(|p| p == &loc.path);
+            let mut replacement = String::new();
+            if self.targets.contains(&loc.path) {
+                target_licenses.push((idx, license.to_string(), expression.to_string()));
+            } else if self.options.license == LicensePolicy::KeepAsComments {
+                replacement = format!("// License: {expression}");
             }
-            updates.entry(loc.path.clone()).or_default().insert((
-                loc.start,
-                loc.end,
-                String::new(),
-            ));
+            expressions.push((idx, expression.to_string()));
+
+            updates.entry(loc.path.clone()).or_default().insert((loc.start, loc.end, replacement));
         }
 
-        target_license
+        if self.options.license == LicensePolicy::CombineAnd {
+            return combine_licenses(expressions);
+        }
+        if let [(_, license, _)] = target_licenses.as_slice() {
+            return Some(license.clone());
+        }
+        combine_licenses(target_licenses.into_iter().map(|(idx, _, expr)| (idx, expr)).collect())
+    }
+
+    /// Removes all comments except for NatSpec and license identifiers from all sources.
+    fn strip_comments(&self, updates: &mut Updates) {
+        let licenses = self.collect_licenses();
+        for path in &self.ordered_sources {
+            let content = self.sources[path].content.as_str();
+            for (start, token) in solar::parse::Cursor::new(content).with_position() {
+                let end = start + token.len as usize;
+                match token.kind {
+                    RawTokenKind::LineComment { is_doc: false }
+                    | RawTokenKind::BlockComment { is_doc: false, .. } => {}
+                    _ => continue,
+                }
+                if licenses
+                    .iter()
+                    .any(|loc| &loc.path == path && loc.start <= start && end <= loc.end)
+                {
+                    continue;
+                }
+                // Remove the indentation of the comment as well, if it's on its own line.
+                let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+                let start = if content[line_start..start].trim().is_empty() {
+                    line_start
+                } else {
+                    start
+                        - (content[..start].len()
+                            - content[..start].trim_end_matches([' ', '\t']).len())
+                };
+                updates.entry(path.clone()).or_default().insert((start, end, String::new()));
+            }
+        }
     }
 
     // Collects all SPDX-License-Identifier locations.
@@ -795,25 +1041,156 @@ fn collect_deps<P: SourceParser<ParsedSource: MaybeSolData>>(
     deps: &mut HashSet<PathBuf>,
 ) -> Result<()> {
     if deps.insert(path.to_path_buf()) {
-        let target_dir = path.parent().ok_or_else(|| {
-            SolcError::msg(format!("failed to get parent directory for \"{}\"", path.display()))
-        })?;
-
-        let node_id = graph
-            .files()
-            .get(path)
-            .ok_or_else(|| SolcError::msg(format!("cannot resolve file at {}", path.display())))?;
-
-        if let Some(data) = graph.node(*node_id).data.sol_data() {
-            for import in &data.imports {
-                let path = paths.resolve_import(target_dir, import.data().path())?;
-                collect_deps(&path, paths, graph, deps)?;
-            }
+        for path in direct_imports(path, paths, graph)? {
+            collect_deps(&path, paths, graph, deps)?;
         }
     }
     Ok(())
 }
 
+/// Returns the resolved paths of the files directly imported by the given file.
+fn direct_imports<P: SourceParser<ParsedSource: MaybeSolData>>(
+    path: &Path,
+    paths: &ProjectPathsConfig<<P::ParsedSource as ParsedSource>::Language>,
+    graph: &Graph<P>,
+) -> Result<Vec<PathBuf>> {
+    let target_dir = path.parent().ok_or_else(|| {
+        SolcError::msg(format!("failed to get parent directory for \"{}\"", path.display()))
+    })?;
+
+    let node_id = graph
+        .files()
+        .get(path)
+        .ok_or_else(|| SolcError::msg(format!("cannot resolve file at {}", path.display())))?;
+
+    let mut imports = Vec::new();
+    if let Some(data) = graph.node(*node_id).data.sol_data() {
+        for import in &data.imports {
+            let import = import.data().path();
+            let resolved = paths.resolve_import(target_dir, import).map_err(|err| {
+                ImportSuggester::new(paths, SOLC_EXTENSIONS)
+                    .with_suggestions(err, target_dir, import)
+            })?;
+            imports.push(resolved);
+        }
+    }
+    Ok(imports)
+}
+
+/// Collects the direct imports of each of the given files.
+fn collect_imports<P: SourceParser<ParsedSource: MaybeSolData>>(
+    files: &[PathBuf],
+    paths: &ProjectPathsConfig<<P::ParsedSource as ParsedSource>::Language>,
+    graph: &Graph<P>,
+) -> Result<HashMap<PathBuf, Vec<PathBuf>>> {
+    files.iter().map(|path| Ok((path.clone(), direct_imports(path, paths, graph)?))).collect()
+}
+
+/// Orders the target files and their imports of any depth according to the given
+/// [`SourceOrder`].
+///
+/// The target files are always placed last, with targets imported by other targets first.
+fn order_sources(
+    targets: &[PathBuf],
+    imports: &HashMap<PathBuf, Vec<PathBuf>>,
+    order: SourceOrder,
+) -> Vec<PathBuf> {
+    fn visit<'a>(
+        path: &'a Path,
+        imports: &'a HashMap<PathBuf, Vec<PathBuf>>,
+        visited: &mut HashSet<&'a Path>,
+        ordered: &mut Vec<PathBuf>,
+    ) {
+        if visited.insert(path) {
+            for import in imports.get(path).into_iter().flatten() {
+                visit(import, imports, visited, ordered);
+            }
+            ordered.push(path.to_path_buf());
+        }
+    }
+
+    let mut ordered = Vec::new();
+    let mut visited = HashSet::new();
+    for target in targets {
+        visit(target, imports, &mut visited, &mut ordered);
+    }
+    let (ordered_targets, mut ordered): (Vec<_>, Vec<_>) =
+        ordered.into_iter().partition(|path| targets.contains(path));
+
+    match order {
+        SourceOrder::Topological => {}
+        SourceOrder::DependencyCount => {
+            let mut deps_counts = HashMap::new();
+            for path in &ordered {
+                let mut deps = Vec::new();
+                visit(path, imports, &mut HashSet::new(), &mut deps);
+                deps_counts.insert(path.clone(), deps.len());
+            }
+            ordered.sort_by(|path_0, path_1| {
+                deps_counts[path_0]
+                    .cmp(&deps_counts[path_1])
+                    .then_with(|| path_0.file_name().cmp(&path_1.file_name()))
+                    .then_with(|| path_0.cmp(path_1))
+            });
+        }
+        SourceOrder::Alphabetical => {
+            let mut remaining = ordered.drain(..).collect::<BTreeSet<_>>();
+            while !remaining.is_empty() {
+                // Pick the first file whose imports have been written, or the first file if
+                // the remaining files import each other.
+                let next = remaining
+                    .iter()
+                    .find(|path| {
+                        imports
+                            .get(*path)
+                            .into_iter()
+                            .flatten()
+                            .all(|import| !remaining.contains(import) || import == *path)
+                    })
+                    .or_else(|| remaining.first())
+                    .cloned()
+                    .unwrap();
+                remaining.remove(&next);
+                ordered.push(next);
+            }
+        }
+    }
+
+    ordered.extend(ordered_targets);
+    ordered
+}
+
+/// Combines the distinct license expressions, ordered by the index of their source, with `AND`.
+fn combine_licenses(mut expressions: Vec<(Option<usize>, String)>) -> Option<String> {
+    // `AND` takes precedence over `OR`, so compound expressions need to be parenthesized.
+    expressions.sort();
+    let expressions = expressions
+        .into_iter()
+        .map(|(_, expr)| if expr.contains(" OR ") { format!("({expr})") } else { expr })
+        .unique()
+        .collect::<Vec<_>>();
+    (!expressions.is_empty())
+        .then(|| format!("SPDX-License-Identifier: {}", expressions.join(" AND ")))
+}
+
+/// Returns whether any version satisfies the given requirement.
+///
+/// The smallest version satisfying a set of comparators is either one of the versions of the
+/// comparators or the version right after it, so it's enough to check these.
+fn is_satisfiable(req: &semver::VersionReq) -> bool {
+    let mut candidates = vec![semver::Version::new(0, 0, 0)];
+    for comp in &req.comparators {
+        let (major, minor, patch) = (comp.major, comp.minor.unwrap_or(0), comp.patch.unwrap_or(0));
+        candidates.extend([
+            semver::Version::new(major, minor, patch),
+            semver::Version::new(major, minor, patch + 1),
+            semver::Version::new(major, minor + 1, 0),
+            semver::Version::new(major + 1, 0, 0),
+        ]);
+    }
+    candidates.iter().any(|version| req.matches(version))
+}
+
 /// We want to make order in which sources are written to resulted flattened file
 /// deterministic.
 ///
@@ -829,45 +1206,35 @@ pub fn collect_ordered_deps<P: SourceParser<ParsedSource: MaybeSolData>>(
     paths: &ProjectPathsConfig<<P::ParsedSource as ParsedSource>::Language>,
     graph: &Graph<P>,
 ) -> Result<Vec<PathBuf>> {
-    let mut deps = HashSet::new();
-    collect_deps(path, paths, graph, &mut deps)?;
-
-    // Remove path prior counting dependencies
-    // It will be added later to the end of resulted Vec
-    deps.remove(path);
+    collect_ordered_deps_multi(&[path.to_path_buf()], paths, graph)
+}
 
-    let mut paths_with_deps_count = Vec::new();
-    for path in deps {
-        let mut path_deps = HashSet::new();
-        collect_deps(&path, paths, graph, &mut path_deps)?;
-        paths_with_deps_count.push((path_deps.len(), path));
+/// Same as [`collect_ordered_deps`], but for multiple target files which share their
+/// dependencies.
+///
+/// Target files are placed last, with targets imported by other targets first.
+pub fn collect_ordered_deps_multi<P: SourceParser<ParsedSource: MaybeSolData>>(
+    targets: &[PathBuf],
+    paths: &ProjectPathsConfig<<P::ParsedSource as ParsedSource>::Language>,
+    graph: &Graph<P>,
+) -> Result<Vec<PathBuf>> {
+    let mut deps = HashSet::new();
+    for target in targets {
+        collect_deps(target, paths, graph, &mut deps)?;
     }
 
-    paths_with_deps_count.sort_by(|(count_0, path_0), (count_1, path_1)| {
-        // Compare dependency counts
-        match count_0.cmp(count_1) {
-            o if !o.is_eq() => return o,
-            _ => {}
-        };
-
-        // Try comparing file names
-        if let Some((name_0, name_1)) = path_0.file_name().zip(path_1.file_name()) {
-            match name_0.cmp(name_1) {
-                o if !o.is_eq() => return o,
-                _ => {}
-            }
-        }
-
-        // If both filenames and dependecy counts are equal, fallback to comparing file paths
-        path_0.cmp(path_1)
-    });
+    let deps = deps.into_iter().collect::<Vec<_>>();
+    let imports = collect_imports(&deps, paths, graph)?;
 
-    let mut ordered_deps =
-        paths_with_deps_count.into_iter().map(|(_, path)| path).collect::<Vec<_>>();
-
-    ordered_deps.push(path.to_path_buf());
+    Ok(order_sources(targets, &imports, SourceOrder::DependencyCount))
+}
 
-    Ok(ordered_deps)
+/// Ensures that there is at least one target to flatten.
+fn check_targets(targets: &[PathBuf]) -> Result<()> {
+    if targets.is_empty() {
+        return Err(SolcError::msg("no target files to flatten"));
+    }
+    Ok(())
 }
 
 pub fn combine_version_pragmas(pragmas: &[impl AsRef<str>]) -> Option<String> {
@@ -884,3 +1251,772 @@ pub fn combine_version_pragmas(pragmas: &[impl AsRef<str>]) -> Option<String> {
     }
     Some(format!("pragma solidity {};", versions.iter().format(" ")))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_compilers_artifacts::error::SourceLocation;
+    use foundry_compilers_core::utils::tempdir;
+    use std::fs;
+
+    /// Writes the given sources to a dapptools project and flattens the target with solar.
+    fn flatten_with_solar(sources: &[(&str, &str)], target: &str) -> String {
+        flatten_with_options(sources, target, FlattenOptions::default()).unwrap()
+    }
+
+    /// Writes the given sources to a dapptools project and flattens the target with solar and the
+    /// given options.
+    fn flatten_with_options(
+        sources: &[(&str, &str)],
+        target: &str,
+        options: FlattenOptions,
+    ) -> std::result::Result<String, FlattenerError> {
+        let tmp = tempdir("flatten").unwrap();
+        let root = tmp.path();
+        for (name, content) in sources {
+            let path = root.join("src").join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        let paths = ProjectPathsConfig::<SolcLanguage>::dapptools(root).unwrap();
+        let target = paths.sources.join(target);
+        Ok(Flattener::new_with_solar(&paths, &target)?.with_options(options)?.flatten())
+    }
+
+    const OPTIONS_SOURCES: &[(&str, &str)] = &[
+        (
+            "Base.sol",
+            r"// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity >=0.8.0;
+
+// A base contract.
+contract Base {
+    /// @notice NatSpec is kept.
+    function foo() public virtual {} // trailing comment
+}
+",
+        ),
+        (
+            "Alpha.sol",
+            r"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.10;
+
+/* Block comment */
+library Alpha {}
+",
+        ),
+        (
+            "Target.sol",
+            r#"// SPDX-License-Identifier: GPL-3.0
+pragma solidity ^0.8.20;
+pragma abicoder v2;
+
+import {Base} from "./Base.sol";
+import {Alpha} from "./Alpha.sol";
+
+contract Target is Base {
+    string s = "// not a comment";
+}
+"#,
+        ),
+    ];
+
+    #[test]
+    fn can_flatten_with_default_options() {
+        let options = FlattenOptions::default();
+        assert_eq!(
+            flatten_with_options(OPTIONS_SOURCES, "Target.sol", options).unwrap(),
+            r#"// SPDX-License-Identifier: GPL-3.0
+pragma solidity >=0.8.0 ^0.8.10 ^0.8.20;
+pragma abicoder v2;
+
+// src/Alpha.sol
+
+/* Block comment */
+library Alpha {}
+
+// src/Base.sol
+
+// A base contract.
+contract Base {
+    /// @notice NatSpec is kept.
+    function foo() public virtual {} // trailing comment
+}
+
+// src/Target.sol
+
+contract Target is Base {
+    string s = "// not a comment";
+}
+"#
+        );
+    }
+
+    #[test]
+    fn can_flatten_with_custom_options() {
+        let options = FlattenOptions {
+            license: LicensePolicy::CombineAnd,
+            pragmas: PragmaPolicy::TargetOnly,
+            strip_comments: true,
+            banner: FileBanner::File,
+            order: SourceOrder::Topological,
+        };
+        assert_eq!(
+            flatten_with_options(OPTIONS_SOURCES, "Target.sol", options).unwrap(),
+            r#"// SPDX-License-Identifier: (MIT OR Apache-2.0) AND MIT AND GPL-3.0
+pragma solidity ^0.8.20;
+pragma abicoder v2;
+
+// File: src/Base.sol
+
+contract Base {
+    /// @notice NatSpec is kept.
+    function foo() public virtual {}
+}
+
+// File: src/Alpha.sol
+
+library Alpha {}
+
+// File: src/Target.sol
+
+contract Target is Base {
+    string s = "// not a comment";
+}
+"#
+        );
+    }
+
+    #[test]
+    fn can_keep_licenses_as_comments() {
+        let options = FlattenOptions {
+            license: LicensePolicy::KeepAsComments,
+            banner: FileBanner::None,
+            order: SourceOrder::Alphabetical,
+            ..Default::default()
+        };
+        assert_eq!(
+            flatten_with_options(OPTIONS_SOURCES, "Target.sol", options).unwrap(),
+            r#"// SPDX-License-Identifier: GPL-3.0
+pragma solidity >=0.8.0 ^0.8.10 ^0.8.20;
+pragma abicoder v2;
+
+// License: MIT
+
+/* Block comment */
+library Alpha {}
+
+// License: MIT OR Apache-2.0
+
+// A base contract.
+contract Base {
+    /// @notice NatSpec is kept.
+    function foo() public virtual {} // trailing comment
+}
+
+contract Target is Base {
+    string s = "// not a comment";
+}
+"#
+        );
+    }
+
+    #[test]
+    fn orders_sources_alphabetically_after_imports() {
+        let imports = HashMap::from([
+            (PathBuf::from("T.sol"), vec!["C.sol".into(), "A.sol".into()]),
+            ("A.sol".into(), vec!["B.sol".into()]),
+            ("B.sol".into(), vec![]),
+            ("C.sol".into(), vec![]),
+        ]);
+        let order = |order| {
+            order_sources(&[PathBuf::from("T.sol")], &imports, order)
+                .into_iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(order(SourceOrder::DependencyCount), ["B.sol", "C.sol", "A.sol", "T.sol"]);
+        assert_eq!(order(SourceOrder::Topological), ["C.sol", "B.sol", "A.sol", "T.sol"]);
+        assert_eq!(order(SourceOrder::Alphabetical), ["B.sol", "A.sol", "C.sol", "T.sol"]);
+    }
+
+    #[test]
+    fn fails_on_unsatisfiable_pragmas() {
+        let sources = &[
+            ("A.sol", "pragma solidity ^0.7.0;\ncontract A {}\n"),
+            ("B.sol", "pragma solidity ^0.8.0;\nimport \"./A.sol\";\ncontract B is A {}\n"),
+        ];
+        let err = flatten_with_options(sources, "B.sol", FlattenOptions::default()).unwrap_err();
+        assert!(err.to_string().contains("^0.7.0 ^0.8.0"), "{err}");
+
+        let options = FlattenOptions { pragmas: PragmaPolicy::TargetOnly, ..Default::default() };
+        let result = flatten_with_options(sources, "B.sol", options).unwrap();
+        assert!(result.starts_with("pragma solidity ^0.8.0;\n"), "{result}");
+    }
+
+    #[test]
+    fn can_flatten_aliases_with_solar() {
+        let result = flatten_with_solar(
+            &[
+                (
+                    "Contract.sol",
+                    r#"pragma solidity ^0.8.10;
+
+import { ParentContract as Parent } from "./Parent.sol";
+import { MathLibrary as Math } from "./Math.sol";
+import * as Lib from "./SomeLib.sol";
+
+contract Contract is Parent {
+    using Math for uint256;
+
+    string public usingString = "using Math for uint256;";
+
+    Parent public parent;
+
+    constructor(address _parent) {
+        parent = Parent(_parent);
+        parent = new Parent();
+        uint256 x = Math.minusOne(Lib.SomeLib.max());
+    }
+}
+"#,
+                ),
+                ("Parent.sol", "pragma solidity ^0.8.10;\n\ncontract ParentContract { }\n"),
+                (
+                    "Math.sol",
+                    r"pragma solidity ^0.8.10;
+
+library MathLibrary {
+    function minusOne(uint256 val) internal returns (uint256) {
+        return val - 1;
+    }
+}
+",
+                ),
+                (
+                    "SomeLib.sol",
+                    r"pragma solidity ^0.8.10;
+
+library SomeLib {
+    function max() internal returns (uint256) {
+        return type(uint256).max;
+    }
+}
+",
+                ),
+            ],
+            "Contract.sol",
+        );
+        assert_eq!(
+            result,
+            r#"pragma solidity ^0.8.10;
+
+// src/Math.sol
+
+library MathLibrary {
+    function minusOne(uint256 val) internal returns (uint256) {
+        return val - 1;
+    }
+}
+
+// src/Parent.sol
+
+contract ParentContract { }
+
+// src/SomeLib.sol
+
+library SomeLib {
+    function max() internal returns (uint256) {
+        return type(uint256).max;
+    }
+}
+
+// src/Contract.sol
+
+contract Contract is ParentContract {
+    using MathLibrary for uint256;
+
+    string public usingString = "using Math for uint256;";
+
+    ParentContract public parent;
+
+    constructor(address _parent) {
+        parent = ParentContract(_parent);
+        parent = new ParentContract();
+        uint256 x = MathLibrary.minusOne(SomeLib.max());
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn can_flatten_qualified_duplicates_with_solar() {
+        let result = flatten_with_solar(
+            &[
+                (
+                    "A.sol",
+                    r"pragma solidity ^0.8.10;
+
+contract A {
+    type SomeCustomValue is uint256;
+
+    struct SomeStruct {
+        uint256 field;
+    }
+
+    enum SomeEnum { VALUE1, VALUE2 }
+
+    event SomeEvent(uint256 value);
+
+    function foo() public pure returns (uint256) {
+        return 1;
+    }
+}
+",
+                ),
+                (
+                    "B.sol",
+                    "pragma solidity ^0.8.10;\nimport \"./A.sol\" as A_File;\n\ncontract A is A_File.A {}\n",
+                ),
+                (
+                    "C.sol",
+                    r#"pragma solidity ^0.8.10;
+import "./B.sol" as B_File;
+
+B_File.A_File.A.SomeCustomValue constant fileLevelValue = B_File.A_File.A.SomeCustomValue.wrap(1);
+
+contract C is B_File.A_File.A {
+    B_File.A_File.A.SomeStruct public someStruct;
+    B_File.A_File.A.SomeEnum public someEnum = B_File.A_File.A.SomeEnum.VALUE1;
+
+    constructor() B_File.A_File.A() {
+        someStruct = B_File.A_File.A.SomeStruct(1);
+        emit B_File.A_File.A.SomeEvent(1);
+    }
+
+    function getSelector() public pure returns (bytes4) {
+        return B_File.A_File.A.foo.selector;
+    }
+}
+"#,
+                ),
+            ],
+            "C.sol",
+        );
+        assert_eq!(
+            result,
+            r"pragma solidity ^0.8.10;
+
+// src/A.sol
+
+contract A_0 {
+    type SomeCustomValue is uint256;
+
+    struct SomeStruct {
+        uint256 field;
+    }
+
+    enum SomeEnum { VALUE1, VALUE2 }
+
+    event SomeEvent(uint256 value);
+
+    function foo() public pure returns (uint256) {
+        return 1;
+    }
+}
+
+// src/B.sol
+
+contract A_1 is A_0 {}
+
+// src/C.sol
+
+A_0.SomeCustomValue constant fileLevelValue = A_0.SomeCustomValue.wrap(1);
+
+contract C is A_0 {
+    A_0.SomeStruct public someStruct;
+    A_0.SomeEnum public someEnum = A_0.SomeEnum.VALUE1;
+
+    constructor() A_0() {
+        someStruct = A_0.SomeStruct(1);
+        emit A_0.SomeEvent(1);
+    }
+
+    function getSelector() public pure returns (bytes4) {
+        return A_0.foo.selector;
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn can_flatten_renamed_functions_and_constants_with_solar() {
+        let result = flatten_with_solar(
+            &[
+                (
+                    "CustomUint.sol",
+                    r"pragma solidity ^0.8.10;
+
+type CustomUint is uint256;
+
+function mul(CustomUint a, CustomUint b) pure returns(CustomUint) {
+    return CustomUint.wrap(CustomUint.unwrap(a) * CustomUint.unwrap(b));
+}
+
+using {mul} for CustomUint global;
+
+uint256 constant a = 1;",
+                ),
+                (
+                    "CustomInt.sol",
+                    r"pragma solidity ^0.8.10;
+
+type CustomInt is int256;
+
+function mul(CustomInt a, CustomInt b) pure returns(CustomInt) {
+    return CustomInt.wrap(CustomInt.unwrap(a) * CustomInt.unwrap(b));
+}
+
+using {mul} for CustomInt global;
+
+uint256 constant a = 2;",
+                ),
+                (
+                    "Target.sol",
+                    r"pragma solidity ^0.8.10;
+
+import {CustomInt, a as a1} from './CustomInt.sol';
+import {CustomUint, a as a2, mul as mulUint} from './CustomUint.sol';
+
+contract Foo {
+    uint256 val;
+
+    function mul(CustomUint a, CustomUint b) public returns(CustomUint) {
+        return a.mul(mulUint(a, b));
+    }
+
+    function mul(CustomInt a, CustomInt b) public returns(CustomInt) {
+        return a.mul(b);
+    }
+
+    function test() public returns(uint256 x, bytes32 slot) {
+        assembly {
+            x := mul(a1, a2)
+            slot := val.slot
+        }
+    }
+}",
+                ),
+            ],
+            "Target.sol",
+        );
+        assert_eq!(
+            result,
+            r"pragma solidity ^0.8.10;
+
+// src/CustomInt.sol
+
+type CustomInt is int256;
+
+function mul_0(CustomInt a, CustomInt b) pure returns(CustomInt) {
+    return CustomInt.wrap(CustomInt.unwrap(a) * CustomInt.unwrap(b));
+}
+
+using {mul_0} for CustomInt global;
+
+uint256 constant a_0 = 2;
+
+// src/CustomUint.sol
+
+type CustomUint is uint256;
+
+function mul_1(CustomUint a, CustomUint b) pure returns(CustomUint) {
+    return CustomUint.wrap(CustomUint.unwrap(a) * CustomUint.unwrap(b));
+}
+
+using {mul_1} for CustomUint global;
+
+uint256 constant a_1 = 1;
+
+// src/Target.sol
+
+contract Foo {
+    uint256 val;
+
+    function mul(CustomUint a, CustomUint b) public returns(CustomUint) {
+        return a.mul_1(mul_1(a, b));
+    }
+
+    function mul(CustomInt a, CustomInt b) public returns(CustomInt) {
+        return a.mul_0(b);
+    }
+
+    function test() public returns(uint256 x, bytes32 slot) {
+        assembly {
+            x := mul(a_0, a_1)
+            slot := val.slot
+        }
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn can_flatten_inheritdocs_with_solar() {
+        let result = flatten_with_solar(
+            &[
+                ("DuplicateA.sol", "pragma solidity ^0.8.10;\ncontract A {}\n"),
+                (
+                    "A.sol",
+                    r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.10;
+import {A as OtherName} from "./DuplicateA.sol";
+
+contract A {
+    /// Documentation
+    function foo() public virtual {}
+
+    /// Documentation
+    function bar() public virtual {}
+}
+"#,
+                ),
+                (
+                    "B.sol",
+                    r#"// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.10;
+pragma abicoder v2;
+import {A} from "./A.sol";
+
+contract B is A {
+    /// @inheritdoc A
+    function foo() public override {}
+
+    /// @inheritdoc Unknown
+    /// More documentation
+    function bar() public override {}
+}"#,
+                ),
+            ],
+            "B.sol",
+        );
+        assert_eq!(
+            result,
+            r"// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.10;
+pragma abicoder v2;
+
+// src/DuplicateA.sol
+
+contract A_0 {}
+
+// src/A.sol
+
+contract A_1 {
+    /// Documentation
+    function foo() public virtual {}
+
+    /// Documentation
+    function bar() public virtual {}
+}
+
+// src/B.sol
+
+contract B is A_1 {
+    /// @inheritdoc A_1
+    function foo() public override {}
+
+    /// 
+    /// More documentation
+    function bar() public override {}
+}
+"
+        );
+    }
+
+    #[test]
+    fn can_map_flattened_output_to_sources() {
+        let tmp = tempdir("flatten").unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        let parent = "// SPDX-License-Identifier: MIT\npragma solidity ^0.8.10;\n\ncontract A {}\n";
+        let target = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.10;
+
+import {A as Parent} from "./Parent.sol";
+import * as Lib from "./Parent.sol";
+
+contract B is Parent {
+    Lib.A a;
+
+    function foo() public {
+        revert("error");
+    }
+}
+"#;
+        fs::write(root.join("src/Parent.sol"), parent).unwrap();
+        fs::write(root.join("src/B.sol"), target).unwrap();
+
+        let paths = ProjectPathsConfig::<SolcLanguage>::dapptools(root).unwrap();
+        let result = Flattener::new_with_solar(&paths, &paths.sources.join("B.sol"))
+            .unwrap()
+            .flatten_result();
+        let flattened = result.get_flattened_target();
+        let source_map = result.source_map();
+
+        let find = |content: &str, needle: &str| {
+            let start = content.find(needle).unwrap();
+            start..start + needle.len()
+        };
+        let map = |needle: &str| source_map.original_range(find(&flattened, needle));
+
+        let parent_path = paths.sources.join("Parent.sol");
+        let target_path = paths.sources.join("B.sol");
+        assert_eq!(
+            map("contract A {}"),
+            Some((parent_path.as_path(), find(parent, "contract A {}")))
+        );
+        assert_eq!(
+            map("contract B is A {"),
+            Some((target_path.as_path(), find(target, "contract B is Parent {")))
+        );
+        assert_eq!(map("A a;"), Some((target_path.as_path(), find(target, "Lib.A a;"))));
+        assert_eq!(map("// src/B.sol"), None);
+        assert_eq!(map("pragma solidity ^0.8.10;"), None);
+
+        let revert = find(&flattened, "revert(\"error\")");
+        let loc = source_map
+            .original_location(&SourceLocation {
+                file: "Flat.sol".to_string(),
+                start: revert.start as i32,
+                end: revert.end as i32,
+            })
+            .unwrap();
+        let original = find(target, "revert(\"error\")");
+        assert_eq!(loc.file, target_path.display().to_string());
+        assert_eq!((loc.start as usize)..(loc.end as usize), original);
+    }
+
+    #[test]
+    fn can_flatten_multiple_targets() {
+        let tmp = tempdir("flatten").unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        let sources = [
+            ("Ownable.sol", "pragma solidity ^0.8.10;\n\ncontract Ownable {}\n"),
+            (
+                "Impl.sol",
+                r#"pragma solidity ^0.8.10;
+import {Ownable} from "./Ownable.sol";
+
+struct Config { uint256 value; }
+
+contract Impl is Ownable {
+    Config config;
+}
+"#,
+            ),
+            (
+                "Proxy.sol",
+                r#"pragma solidity ^0.8.10;
+import "./Ownable.sol" as O;
+
+struct Config { address implementation; }
+
+contract Proxy is O.Ownable {
+    Config config;
+}
+"#,
+            ),
+            (
+                "Factory.sol",
+                r#"pragma solidity ^0.8.10;
+import {Impl, Config as ImplConfig} from "./Impl.sol";
+import {Proxy} from "./Proxy.sol";
+
+contract Factory {
+    function deploy(ImplConfig memory) external {
+        new Proxy();
+        new Impl();
+    }
+}
+"#,
+            ),
+        ];
+        for (name, content) in sources {
+            fs::write(root.join("src").join(name), content).unwrap();
+        }
+
+        let paths = ProjectPathsConfig::<SolcLanguage>::dapptools(root).unwrap();
+        let targets = ["Factory.sol", "Impl.sol", "Proxy.sol"].map(|name| paths.sources.join(name));
+        let result = Flattener::new_multi_with_solar(&paths, &targets).unwrap().flatten_result();
+
+        assert_eq!(
+            result.get_flattened_target(),
+            r"pragma solidity ^0.8.10;
+
+// src/Ownable.sol
+
+contract Ownable {}
+
+// src/Impl.sol
+
+struct Config_0 { uint256 value; }
+
+contract Impl is Ownable {
+    Config_0 config;
+}
+
+// src/Proxy.sol
+
+struct Config_1 { address implementation; }
+
+contract Proxy is Ownable {
+    Config_1 config;
+}
+
+// src/Factory.sol
+
+contract Factory {
+    function deploy(Config_0 memory) external {
+        new Proxy();
+        new Impl();
+    }
+}
+"
+        );
+        assert_eq!(
+            result.renamed_symbols(),
+            [
+                RenamedSymbol {
+                    path: paths.sources.join("Impl.sol"),
+                    name: "Config".to_string(),
+                    renamed: "Config_0".to_string(),
+                },
+                RenamedSymbol {
+                    path: paths.sources.join("Proxy.sol"),
+                    name: "Config".to_string(),
+                    renamed: "Config_1".to_string(),
+                },
+            ]
+        );
+
+        let err = Flattener::new_multi_with_solar(&paths, &[]).err().unwrap();
+        assert!(err.to_string().contains("no target files"), "{err}");
+    }
+
+    #[test]
+    fn falls_back_on_solar_errors() {
+        let tmp = tempdir("flatten").unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/A.sol"), "pragma solidity ^0.8.10;\ncontract A { B b; }\n")
+            .unwrap();
+        let paths = ProjectPathsConfig::<SolcLanguage>::dapptools(root).unwrap();
+        let err = Flattener::new_with_solar(&paths, &paths.sources.join("A.sol")).err().unwrap();
+        assert!(err.to_string().contains("unresolved symbol `B`"), "{err}");
+    }
+}
@@ -0,0 +1,77 @@
+//! Options to configure the flattened output.
+
+/// How the SPDX license identifiers of the sources are written to the flattened output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LicensePolicy {
+    /// Only the license of the target file is kept.
+    #[default]
+    TargetOnly,
+    /// The distinct licenses of all sources are combined into a single identifier with `AND`.
+    CombineAnd,
+    /// The license of the target file is kept, and the licenses of all other sources are kept
+    /// in place as plain comments.
+    KeepAsComments,
+}
+
+/// How the pragmas of the sources are written to the flattened output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PragmaPolicy {
+    /// Only the pragmas of the target file are kept.
+    TargetOnly,
+    /// The version pragmas of all sources are intersected with
+    /// [`combine_version_pragmas`](super::combine_version_pragmas), and the first ABI coder
+    /// pragma is kept.
+    ///
+    /// [`Flattener::with_options`](super::Flattener::with_options) fails if no compiler version
+    /// satisfies the intersection.
+    #[default]
+    Intersect,
+}
+
+/// Header written before the content of each source in the flattened output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FileBanner {
+    /// No header.
+    None,
+    /// The path of the source relative to the project root, e.g. `// src/Counter.sol`.
+    #[default]
+    Path,
+    /// The labeled path of the source relative to the project root, e.g.
+    /// `// File: src/Counter.sol`.
+    File,
+}
+
+/// Order in which the sources are written to the flattened output.
+///
+/// The target file is always written last, and every order writes imported files before the
+/// files importing them, unless they import each other.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SourceOrder {
+    /// Sources are sorted by the number of their dependencies (imports of any depth), and by
+    /// file name and path if they have the same number of dependencies.
+    #[default]
+    DependencyCount,
+    /// Sources are written in the order they are imported, depth-first.
+    Topological,
+    /// Sources are written in alphabetical order of their paths among the files whose imports
+    /// have been written already.
+    Alphabetical,
+}
+
+/// Options to configure the flattened output.
+///
+/// The default options produce the same output as [`Flattener::flatten`](super::Flattener)
+/// without any options.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FlattenOptions {
+    /// How license identifiers are written.
+    pub license: LicensePolicy,
+    /// How pragmas are written.
+    pub pragmas: PragmaPolicy,
+    /// If `true`, all comments except NatSpec comments are removed.
+    pub strip_comments: bool,
+    /// The header written before each source.
+    pub banner: FileBanner,
+    /// The order in which sources are written.
+    pub order: SourceOrder,
+}
@@ -0,0 +1,462 @@
+//! Flattening analysis built on solar's parser and name resolution, which doesn't require
+//! compiling the sources with solc.
+
+use super::{Analysis, ItemLocation};
+use foundry_compilers_artifacts::sources::Sources;
+use foundry_compilers_core::error::{Result, SolcError};
+use solar::{
+    interface::{Ident, Span, Symbol, source_map::FileName},
+    parse::ast::{self, Visit as _},
+    sema::{
+        Compiler, Gcx,
+        hir::{self, ContractId, ItemId, Res, SourceId, Visit as _},
+    },
+};
+use std::{collections::HashMap, ops::ControlFlow, path::Path};
+
+/// Lowers the sources parsed by the given compiler and collects the flattening analysis of the
+/// given sources.
+///
+/// Returns an error if solar fails to parse or resolve the sources.
+pub(super) fn analyze(compiler: &mut Compiler, sources: &Sources) -> Result<Analysis> {
+    compiler.enter_mut(|compiler| {
+        let _ = compiler.lower_asts();
+    });
+    if let Some(Err(err)) = compiler.sess().emitted_errors() {
+        return Err(SolcError::msg(err));
+    }
+
+    Ok(compiler.enter(|compiler| {
+        let gcx = compiler.gcx();
+        let mut idents = IdentResolutions { hir: &gcx.hir, idents: HashMap::new() };
+        for id in gcx.hir.source_ids() {
+            let _ = idents.visit_nested_source(id);
+        }
+
+        let mut collector = Collector::new(gcx, idents.idents);
+        for (id, source) in gcx.hir.sources_enumerated() {
+            if let FileName::Real(path) = &source.file.name
+                && sources.contains_key(path)
+            {
+                collector.collect_source(id, path);
+            }
+        }
+        collector.analysis
+    }))
+}
+
+/// Collects the resolutions of all identifier expressions, by span.
+///
+/// These can't be resolved again after lowering, since local variables are only known to solar's
+/// resolver while it lowers the function bodies.
+struct IdentResolutions<'hir> {
+    hir: &'hir hir::Hir<'hir>,
+    idents: HashMap<Span, Res>,
+}
+
+impl<'hir> hir::Visit<'hir> for IdentResolutions<'hir> {
+    type BreakValue = ();
+
+    fn hir(&self) -> &'hir hir::Hir<'hir> {
+        self.hir
+    }
+
+    fn visit_nested_item(&mut self, id: ItemId) -> ControlFlow<Self::BreakValue> {
+        // Array lengths in fields and parameters can reference constants.
+        let fields = match id {
+            ItemId::Struct(id) => self.hir.strukt(id).fields,
+            ItemId::Event(id) => self.hir.event(id).parameters,
+            ItemId::Error(id) => self.hir.error(id).parameters,
+            _ => return self.walk_nested_item(id),
+        };
+        fields.iter().try_for_each(|&var| self.visit_nested_var(var))
+    }
+
+    fn visit_expr(&mut self, expr: &'hir hir::Expr<'hir>) -> ControlFlow<Self::BreakValue> {
+        if let hir::ExprKind::Ident(res) = expr.kind
+            && let Some(&res) = res.first()
+        {
+            self.idents.insert(expr.span, res);
+        }
+        self.walk_expr(expr)
+    }
+}
+
+/// Walks the ASTs of the sources and resolves all references to file-level and contract-level
+/// declarations.
+struct Collector<'gcx> {
+    gcx: Gcx<'gcx>,
+    /// Resolutions of identifier expressions, by span.
+    idents: HashMap<Span, Res>,
+    /// Contracts, by span of their name.
+    contracts: HashMap<Span, ContractId>,
+    /// Declarations visible at file level of each source, by name.
+    exports: HashMap<SourceId, HashMap<Symbol, Res>>,
+    /// Free functions attached to user-defined types with `using {f} for T`, by type and name.
+    ///
+    /// These are not scoped to the file or contract containing the directive, which is only
+    /// ambiguous if different functions with the same name are attached to the same type.
+    attached: HashMap<(ItemId, Symbol), ItemId>,
+    /// Flattener ids of the HIR items.
+    ids: HashMap<ItemId, usize>,
+    /// The source being walked.
+    source: SourceId,
+    /// The contract being walked, if any.
+    contract: Option<ContractId>,
+    /// Whether an inline assembly block is being walked.
+    in_assembly: bool,
+    analysis: Analysis,
+}
+
+impl<'gcx> Collector<'gcx> {
+    fn new(gcx: Gcx<'gcx>, idents: HashMap<Span, Res>) -> Self {
+        let mut collector = Self {
+            gcx,
+            idents,
+            contracts: gcx.hir.contracts_enumerated().map(|(id, c)| (c.name.span, id)).collect(),
+            exports: HashMap::new(),
+            attached: HashMap::new(),
+            ids: HashMap::new(),
+            source: SourceId::from_usize(0),
+            contract: None,
+            in_assembly: false,
+            analysis: Analysis::default(),
+        };
+        for id in gcx.hir.source_ids() {
+            collector.collect_exports(id);
+        }
+        for id in gcx.hir.source_ids() {
+            collector.collect_attached_functions(id);
+        }
+        collector
+    }
+
+    /// Collects the declarations, directives and references of the given source.
+    fn collect_source(&mut self, id: SourceId, path: &Path) {
+        let hir = &self.gcx.hir;
+
+        let mut exports = HashMap::new();
+        for (name, res) in self.exports[&id].clone() {
+            if let Res::Item(item) = res {
+                exports.insert(name.to_string(), self.id(item));
+            }
+        }
+        self.analysis.exported_symbols.insert(path.to_path_buf(), exports);
+
+        for &item_id in hir.source(id).items {
+            let item = hir.item(item_id);
+            // Matches the file-level definitions which solc reports as referenceable.
+            if matches!(item_id, ItemId::Event(_) | ItemId::Error(_)) {
+                continue;
+            }
+            let Some(name) = item.name() else { continue };
+            let Some(loc) = self.location(name.span) else { continue };
+            let id = self.id(item_id);
+            self.analysis
+                .top_level_definitions
+                .entry(name.to_string())
+                .or_default()
+                .insert((id, loc));
+
+            if let ItemId::Contract(contract_id) = item_id {
+                for &member in hir.contract(contract_id).items {
+                    if let Some(name) = self.member_name(member) {
+                        let member = self.id(member);
+                        self.analysis
+                            .contract_level_definitions
+                            .insert(member, (name.to_string(), id));
+                    }
+                }
+            }
+        }
+
+        let Some(ast) = self.gcx.sources.get(id).and_then(|source| source.ast.as_ref()) else {
+            return;
+        };
+        self.source = id;
+        self.contract = None;
+        let _ = self.visit_source_unit(ast);
+    }
+
+    /// Collects the declarations visible at file level of the given source and the sources it
+    /// imports.
+    fn collect_exports(&mut self, id: SourceId) {
+        if self.exports.contains_key(&id) {
+            return;
+        }
+        // Guards against import cycles.
+        self.exports.insert(id, HashMap::new());
+
+        let hir = &self.gcx.hir;
+        let source = hir.source(id);
+        let mut exports = HashMap::new();
+        for &item in source.items {
+            if let Some(name) = hir.item(item).name() {
+                exports.entry(name.name).or_insert(Res::Item(item));
+            }
+        }
+
+        let ast = self.gcx.sources.get(id).and_then(|source| source.ast.as_ref());
+        for &(item_id, imported) in source.imports {
+            let Some(ast::ItemKind::Import(import)) = ast.map(|ast| &ast.items[item_id].kind)
+            else {
+                continue;
+            };
+            if let Some(alias) = import.items.source_alias() {
+                exports.insert(alias.name, Res::Namespace(imported));
+                continue;
+            }
+            self.collect_exports(imported);
+            let imported = &self.exports[&imported];
+            match &import.items {
+                ast::ImportItems::Aliases(aliases) => {
+                    for &(name, alias) in aliases.iter() {
+                        if let Some(&res) = imported.get(&name.name) {
+                            exports.insert(alias.unwrap_or(name).name, res);
+                        }
+                    }
+                }
+                _ => {
+                    for (&name, &res) in imported {
+                        exports.entry(name).or_insert(res);
+                    }
+                }
+            }
+        }
+
+        self.exports.insert(id, exports);
+    }
+
+    /// Collects the free functions attached to user-defined types by the `using` directives of
+    /// the given source.
+    fn collect_attached_functions(&mut self, id: SourceId) {
+        let Some(ast) = self.gcx.sources.get(id).and_then(|source| source.ast.as_ref()) else {
+            return;
+        };
+        self.source = id;
+        for item in ast.items.iter() {
+            let (contract, items) = match &item.kind {
+                ast::ItemKind::Contract(contract) => {
+                    (self.contracts.get(&contract.name.span).copied(), &contract.body[..])
+                }
+                _ => (None, std::slice::from_ref(item)),
+            };
+            self.contract = contract;
+            for item in items {
+                let ast::ItemKind::Using(using) = &item.kind else { continue };
+                let (ast::UsingList::Multiple(paths), Some(ty)) = (&using.list, &using.ty) else {
+                    continue;
+                };
+                let ast::TypeKind::Custom(ty) = &ty.kind else { continue };
+                let Some(Res::Item(ty)) = self.resolve_path(ty) else { continue };
+                for (path, _) in paths.iter() {
+                    if let Some(Res::Item(function @ ItemId::Function(_))) = self.resolve_path(path)
+                    {
+                        self.attached.entry((ty, path.last().name)).or_insert(function);
+                    }
+                }
+            }
+        }
+        self.contract = None;
+    }
+
+    /// Returns the name of the given contract-level item, if it can be referenced.
+    fn member_name(&self, id: ItemId) -> Option<Ident> {
+        if let ItemId::Function(function) = id {
+            let function = self.gcx.hir.function(function);
+            if function.kind.is_modifier() || function.gettee.is_some() {
+                return None;
+            }
+        }
+        self.gcx.hir.item(id).name()
+    }
+
+    /// Resolves a name in the scope of the current contract, including inherited members, or the
+    /// current source.
+    fn resolve_name(&self, name: Ident) -> Option<Res> {
+        if let Some(contract) = self.contract
+            && let Some(member) = self.resolve_member(contract, name)
+        {
+            return Some(member);
+        }
+        self.exports[&self.source].get(&name.name).copied()
+    }
+
+    /// Resolves a member of the given contract, including inherited members.
+    fn resolve_member(&self, contract: ContractId, name: Ident) -> Option<Res> {
+        self.gcx
+            .hir
+            .contract_item_ids(contract)
+            .find(|&id| self.member_name(id).is_some_and(|member| member.name == name.name))
+            .map(Res::Item)
+    }
+
+    /// Resolves a name in the scope of the given namespace or contract.
+    fn resolve_in(&self, scope: Res, name: Ident) -> Option<Res> {
+        match scope {
+            Res::Namespace(source) => self.exports[&source].get(&name.name).copied(),
+            Res::Item(ItemId::Contract(contract)) => self.resolve_member(contract, name),
+            _ => None,
+        }
+    }
+
+    /// Resolves a path like `Lib.Contract.Struct`.
+    fn resolve_path(&self, path: &ast::PathSlice) -> Option<Res> {
+        let (first, rest) = path.segments().split_first()?;
+        rest.iter()
+            .try_fold(self.resolve_name(*first)?, |res, &segment| self.resolve_in(res, segment))
+    }
+
+    /// Returns the resolution of an identifier or a member of a namespace.
+    fn resolve_expr(&self, expr: &ast::Expr<'_>) -> Option<Res> {
+        match &expr.kind {
+            ast::ExprKind::Ident(_) => self.idents.get(&expr.span).copied(),
+            ast::ExprKind::Member(base, member) => match self.resolve_expr(base)? {
+                Res::Namespace(source) => self.resolve_in(Res::Namespace(source), *member),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the free function attached to the type of `base` with the given name, if any.
+    fn attached_function(&self, base: &ast::Expr<'_>, name: Ident) -> Option<ItemId> {
+        let Res::Item(ItemId::Variable(var)) = self.resolve_expr(base)? else { return None };
+        let hir::TypeKind::Custom(ty) = self.gcx.hir.variable(var).ty.kind else { return None };
+        self.attached.get(&(ty, name.name)).copied()
+    }
+
+    /// Records a reference to a declaration or an import alias at the given span.
+    fn record(&mut self, res: Res, span: Span) {
+        match res {
+            Res::Item(item) => self.record_reference(item, span),
+            Res::Namespace(_) => {
+                if let Some(loc) = self.location(span) {
+                    self.analysis.qualified_imports.insert(loc);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn record_reference(&mut self, item: ItemId, span: Span) {
+        if let Some(loc) = self.location(span) {
+            let id = self.id(item);
+            self.analysis.references.entry(id).or_default().insert(loc);
+        }
+    }
+
+    /// Records the references of a path which is resolved like a member access expression, e.g.
+    /// the event of an `emit` statement.
+    fn record_expr_path(&mut self, path: &ast::PathSlice) {
+        let (first, rest) = path.segments().split_first().unwrap();
+        let Some(mut res) = self.resolve_name(*first) else { return };
+        self.record(res, first.span);
+        for &segment in rest {
+            let Some(next) = self.resolve_in(res, segment) else { return };
+            if let Res::Namespace(_) = res {
+                self.record(next, segment.span);
+            }
+            res = next;
+        }
+    }
+
+    /// Returns the flattener id of the given item.
+    fn id(&mut self, item: ItemId) -> usize {
+        let next = self.ids.len();
+        *self.ids.entry(item).or_insert(next)
+    }
+
+    fn location(&self, span: Span) -> Option<ItemLocation> {
+        let source = self.gcx.sess.source_map().span_to_source(span).ok()?;
+        let FileName::Real(path) = &source.file.name else { return None };
+        Some(ItemLocation { path: path.clone(), start: source.data.start, end: source.data.end })
+    }
+}
+
+impl<'ast> ast::Visit<'ast> for Collector<'_> {
+    type BreakValue = ();
+
+    fn visit_item(&mut self, item: &'ast ast::Item<'ast>) -> ControlFlow<Self::BreakValue> {
+        match &item.kind {
+            ast::ItemKind::Pragma(_) => {
+                self.analysis.pragmas.extend(self.location(item.span));
+            }
+            ast::ItemKind::Import(_) => {
+                self.analysis.imports.extend(self.location(item.span));
+            }
+            ast::ItemKind::Contract(contract) => {
+                self.contract = self.contracts.get(&contract.name.span).copied();
+                self.walk_item(item)?;
+                self.contract = None;
+                return ControlFlow::Continue(());
+            }
+            ast::ItemKind::Function(_)
+            | ast::ItemKind::Variable(_)
+            | ast::ItemKind::Event(_)
+            | ast::ItemKind::Error(_)
+                if self.contract.is_some() && !item.docs.is_empty() =>
+            {
+                self.analysis.docs.extend(self.location(item.docs.span()));
+            }
+            _ => {}
+        }
+        self.walk_item(item)
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast ast::Stmt<'ast>) -> ControlFlow<Self::BreakValue> {
+        match &stmt.kind {
+            ast::StmtKind::Emit(path, args) | ast::StmtKind::Revert(path, args) => {
+                self.record_expr_path(path);
+                self.visit_call_args(args)
+            }
+            _ => self.walk_stmt(stmt),
+        }
+    }
+
+    fn visit_stmt_assembly(
+        &mut self,
+        assembly: &'ast ast::StmtAssembly<'ast>,
+    ) -> ControlFlow<Self::BreakValue> {
+        self.in_assembly = true;
+        let flow = self.walk_stmt_assembly(assembly);
+        self.in_assembly = false;
+        flow
+    }
+
+    fn visit_expr(&mut self, expr: &'ast ast::Expr<'ast>) -> ControlFlow<Self::BreakValue> {
+        match &expr.kind {
+            ast::ExprKind::Ident(ident) => {
+                if let Some(&res) = self.idents.get(&expr.span) {
+                    self.record(res, ident.span);
+                }
+            }
+            ast::ExprKind::Member(base, member) => {
+                if let Some(res @ Res::Namespace(_)) = self.resolve_expr(base) {
+                    if let Some(res) = self.resolve_in(res, *member) {
+                        self.record(res, member.span);
+                    }
+                } else if let Some(function) = self.attached_function(base, *member) {
+                    self.record_reference(function, member.span);
+                }
+            }
+            _ => {}
+        }
+        self.walk_expr(expr)
+    }
+
+    /// Paths outside of expressions are resolved like solc's `IdentifierPath`s, which reference
+    /// the declaration with their full location.
+    fn visit_path(&mut self, path: &'ast ast::PathSlice) -> ControlFlow<Self::BreakValue> {
+        if self.in_assembly {
+            // Only the first segment can reference a Solidity declaration, e.g. `x` in `x.slot`.
+            let first = *path.first();
+            if let Some(res @ Res::Item(ItemId::Variable(_))) = self.resolve_name(first) {
+                self.record(res, first.span);
+            }
+        } else if let Some(res @ Res::Item(_)) = self.resolve_path(path) {
+            self.record(res, path.span());
+        }
+        ControlFlow::Continue(())
+    }
+}
@@ -0,0 +1,278 @@
+//! Mapping of the flattened output back to the original sources.
+
+use foundry_compilers_artifacts::error::SourceLocation;
+use std::{
+    collections::BTreeSet,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// A contiguous range of the flattened output that originates from a single range of one of the
+/// original sources.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct Mapping {
+    /// Byte range in the flattened output.
+    pub flattened: Range<usize>,
+    /// Index of the original source in [`FlattenedSourceMap::sources`].
+    pub source: usize,
+    /// Byte range in the original source.
+    pub original: Range<usize>,
+}
+
+impl Mapping {
+    /// Whether the range is mapped byte by byte, i.e. it was either left untouched or replaced
+    /// with text of the same length.
+    fn is_linear(&self) -> bool {
+        self.flattened.len() == self.original.len()
+    }
+
+    fn contains(&self, offset: usize) -> bool {
+        self.flattened.contains(&offset)
+    }
+
+    fn original_offset(&self, offset: usize, is_end: bool) -> usize {
+        if self.is_linear() {
+            self.original.start + offset - self.flattened.start
+        } else if is_end {
+            self.original.end
+        } else {
+            self.original.start
+        }
+    }
+}
+
+/// Maps byte ranges of a flattened file back to the files they originate from.
+///
+/// Ranges of the original sources that were rewritten during flattening (e.g. renamed
+/// identifiers or removed qualified imports) map to the whole original range. Text that was added
+/// by the flattener, such as file name comments and the combined pragmas and license of the
+/// target, doesn't map to any original source.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FlattenedSourceMap {
+    /// Original sources in the order they are written to the flattened file.
+    sources: Vec<PathBuf>,
+    /// Non-overlapping mappings sorted by their flattened range.
+    mappings: Vec<Mapping>,
+}
+
+impl FlattenedSourceMap {
+    pub(super) fn new(sources: Vec<PathBuf>, mappings: Vec<Mapping>) -> Self {
+        Self { sources, mappings }
+    }
+
+    /// Returns the original sources in the order they are written to the flattened file.
+    pub fn sources(&self) -> &[PathBuf] {
+        &self.sources
+    }
+
+    /// Returns the original file and byte range of the given byte range of the flattened file.
+    ///
+    /// Returns `None` if the start of the range doesn't originate from any original source. If
+    /// the range spans text of multiple original sources, it is truncated to the end of the
+    /// original source it starts in.
+    pub fn original_range(&self, range: Range<usize>) -> Option<(&Path, Range<usize>)> {
+        let first = self.find(range.start)?;
+        let start = first.original_offset(range.start, false);
+
+        let end = if range.end <= range.start {
+            start
+        } else {
+            match self.find(range.end - 1) {
+                Some(last) if last.source == first.source => last.original_offset(range.end, true),
+                _ => {
+                    // Truncate to the last mapping of the same source following the start.
+                    self.mappings
+                        .iter()
+                        .skip_while(|m| m != &first)
+                        .take_while(|m| m.source == first.source && m.flattened.start < range.end)
+                        .last()
+                        .map_or(first.original.end, |m| m.original.end)
+                }
+            }
+        };
+
+        Some((&self.sources[first.source], start..end.max(start)))
+    }
+
+    /// Rewrites a solc [`SourceLocation`] pointing into the flattened file into a location in the
+    /// original source.
+    ///
+    /// The file of the returned location is the path of the original source.
+    pub fn original_location(&self, loc: &SourceLocation) -> Option<SourceLocation> {
+        let start = usize::try_from(loc.start).ok()?;
+        let end = usize::try_from(loc.end).ok()?.max(start);
+        let (path, range) = self.original_range(start..end)?;
+        Some(SourceLocation {
+            file: path.display().to_string(),
+            start: range.start as i32,
+            end: range.end as i32,
+        })
+    }
+
+    fn find(&self, offset: usize) -> Option<&Mapping> {
+        let idx = self.mappings.partition_point(|m| m.flattened.end <= offset);
+        self.mappings.get(idx).filter(|m| m.contains(offset))
+    }
+}
+
+/// Returns the mappings of a source after applying the given updates to it.
+///
+/// The flattened ranges are relative to the start of the updated source. Text inserted into an
+/// empty range of the original source is not mapped.
+pub(super) fn source_mappings(
+    source: usize,
+    len: usize,
+    updates: Option<&BTreeSet<(usize, usize, String)>>,
+) -> Vec<Mapping> {
+    let mut mappings = Vec::new();
+    let mut push = |flattened: Range<usize>, original: Range<usize>| {
+        if !flattened.is_empty() && !original.is_empty() {
+            mappings.push(Mapping { flattened, source, original });
+        }
+    };
+
+    // Position in the original and in the updated source.
+    let (mut original, mut updated) = (0, 0);
+    for (start, end, value) in updates.into_iter().flatten() {
+        let start = (*start).max(original);
+        let end = (*end).max(start);
+
+        let unchanged = start - original;
+        push(updated..updated + unchanged, original..start);
+        updated += unchanged;
+
+        push(updated..updated + value.len(), start..end);
+        updated += value.len();
+        original = end;
+    }
+    push(updated..updated + len - original, original..len);
+
+    mappings
+}
+
+/// Tracks the byte offsets of the flattened output while it is being assembled and normalized.
+#[derive(Debug, Default)]
+pub(super) struct OffsetTracker {
+    /// Ranges that were removed from the output, in terms of the output before the removal.
+    removed: Vec<Range<usize>>,
+}
+
+impl OffsetTracker {
+    /// Records that the given range of the output was removed.
+    ///
+    /// Removals must be recorded in order and in terms of the output before any removals.
+    pub fn remove(&mut self, range: Range<usize>) {
+        if !range.is_empty() {
+            self.removed.push(range);
+        }
+    }
+
+    /// Returns the offset of the given offset after all removals.
+    pub fn offset(&self, offset: usize) -> usize {
+        let removed: usize = self
+            .removed
+            .iter()
+            .take_while(|r| r.start < offset)
+            .map(|r| r.end.min(offset) - r.start)
+            .sum();
+        offset - removed
+    }
+
+    /// Moves the mapping to the offsets after all removals.
+    ///
+    /// Linear mappings are split around the removed ranges so that they stay linear.
+    pub fn map(&self, mapping: Mapping) -> Vec<Mapping> {
+        let mut pieces = Vec::new();
+        let mut push = |flattened: Range<usize>, original: Range<usize>| {
+            let flattened = self.offset(flattened.start)..self.offset(flattened.end);
+            if !flattened.is_empty() {
+                pieces.push(Mapping { flattened, source: mapping.source, original });
+            }
+        };
+
+        if !mapping.is_linear() {
+            push(mapping.flattened.clone(), mapping.original.clone());
+            return pieces;
+        }
+
+        let mut start = mapping.flattened.start;
+        let original_offset =
+            |offset: usize| mapping.original.start + offset - mapping.flattened.start;
+        for removed in &self.removed {
+            if removed.end <= start || removed.start >= mapping.flattened.end {
+                continue;
+            }
+            if removed.start > start {
+                push(start..removed.start, original_offset(start)..original_offset(removed.start));
+            }
+            start = removed.end.min(mapping.flattened.end);
+        }
+        if start < mapping.flattened.end {
+            let end = mapping.flattened.end;
+            push(start..end, original_offset(start)..original_offset(end));
+        }
+
+        pieces
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_updated_ranges() {
+        // "contract A is B {}" -> "contract A_0 is B_1 {}"
+        let updates = BTreeSet::from([(9, 10, "A_0".to_string()), (14, 15, "B_1".to_string())]);
+        let mappings = source_mappings(0, 18, Some(&updates));
+        let map = FlattenedSourceMap::new(vec!["A.sol".into()], mappings);
+
+        let path = Path::new("A.sol");
+        assert_eq!(map.original_range(0..8), Some((path, 0..8)));
+        assert_eq!(map.original_range(9..12), Some((path, 9..10)));
+        assert_eq!(map.original_range(10..11), Some((path, 9..10)));
+        assert_eq!(map.original_range(16..19), Some((path, 14..15)));
+        assert_eq!(map.original_range(0..22), Some((path, 0..18)));
+        assert_eq!(map.original_range(22..23), None);
+    }
+
+    #[test]
+    fn skips_insertions_and_removals() {
+        // "import './B.sol';\ncontract A {}" -> "// A.sol\n\ncontract A {}"
+        let updates = BTreeSet::from([(0, 0, "// A.sol\n".to_string()), (0, 17, String::new())]);
+        let mappings = source_mappings(0, 31, Some(&updates));
+        assert_eq!(mappings, vec![Mapping { flattened: 9..23, source: 0, original: 17..31 }]);
+
+        let map = FlattenedSourceMap::new(vec!["A.sol".into()], mappings);
+        assert_eq!(map.original_range(0..8), None);
+        assert_eq!(
+            map.original_location(&SourceLocation { file: "flat.sol".into(), start: 10, end: 23 }),
+            Some(SourceLocation { file: "A.sol".into(), start: 18, end: 31 })
+        );
+    }
+
+    #[test]
+    fn tracks_removed_offsets() {
+        let mut tracker = OffsetTracker::default();
+        tracker.remove(0..2);
+        tracker.remove(5..8);
+        assert_eq!(tracker.offset(1), 0);
+        assert_eq!(tracker.offset(4), 2);
+        assert_eq!(tracker.offset(6), 3);
+        assert_eq!(tracker.offset(10), 5);
+
+        let mapping = Mapping { flattened: 1..10, source: 0, original: 11..20 };
+        assert_eq!(
+            tracker.map(mapping),
+            vec![
+                Mapping { flattened: 0..3, source: 0, original: 12..15 },
+                Mapping { flattened: 3..5, source: 0, original: 18..20 },
+            ]
+        );
+        let mapping = Mapping { flattened: 3..4, source: 0, original: 11..20 };
+        assert_eq!(
+            tracker.map(mapping),
+            vec![Mapping { flattened: 1..2, source: 0, original: 11..20 }]
+        );
+    }
+}
@@ -0,0 +1,337 @@
+//! Verification of the flattened output by recompiling it.
+
+use super::{Flattener, FlattenerError};
+use crate::{
+    ArtifactOutput, CompilerSettings, Project, ProjectCompileOutput, SourceParser,
+    compilers::{Compiler, CompilerContract},
+    filter::MaybeSolData,
+    project::ProjectCompiler,
+    resolver::AllowList,
+};
+use alloy_primitives::{Address, Bytes, keccak256};
+use foundry_compilers_artifacts::{
+    BytecodeObject, Source, output_selection::OutputSelection, sources::Sources,
+};
+use foundry_compilers_core::error::SolcError;
+use semver::Version;
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// Name of the file in the project root the flattened output is compiled as.
+const FLATTENED_FILE_NAME: &str = "Flattened.sol";
+
+/// CBOR keys of the metadata hashes appended to the bytecode, followed by the length of the hash.
+const METADATA_HASH_KEYS: [(&[u8], usize); 3] =
+    [(b"\x64ipfs\x58\x22", 34), (b"\x65bzzr0\x58\x20", 32), (b"\x65bzzr1\x58\x20", 32)];
+
+/// Kind of the bytecode that differs between the original and the flattened build.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BytecodeKind {
+    /// The creation bytecode.
+    Creation,
+    /// The runtime bytecode.
+    Runtime,
+}
+
+impl fmt::Display for BytecodeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Creation => f.write_str("creation"),
+            Self::Runtime => f.write_str("runtime"),
+        }
+    }
+}
+
+/// A contract whose bytecode differs between the original and the flattened build.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BytecodeMismatch {
+    /// Path of the source the contract is declared in.
+    pub path: PathBuf,
+    /// Name of the contract in the original source.
+    pub name: String,
+    /// Name of the contract in the flattened output.
+    pub flattened_name: String,
+    /// The differing bytecode, or `None` if the contract is missing from the flattened build.
+    pub kind: Option<BytecodeKind>,
+}
+
+impl fmt::Display for BytecodeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.path.display(), self.name)?;
+        if self.flattened_name != self.name {
+            write!(f, " (flattened as {})", self.flattened_name)?;
+        }
+        match self.kind {
+            Some(kind) => write!(f, ": {kind} bytecode differs"),
+            None => f.write_str(": missing from the flattened output"),
+        }
+    }
+}
+
+/// Result of compiling the flattened output and comparing it against the original build.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlattenVerification {
+    /// The compiler version both builds were compiled with.
+    pub version: Version,
+    /// Contracts whose bytecode differs between the two builds.
+    pub mismatches: Vec<BytecodeMismatch>,
+}
+
+impl FlattenVerification {
+    /// Returns `true` if the bytecode of all contracts matches.
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl fmt::Display for FlattenVerification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return write!(f, "flattened output matches the original build with {}", self.version);
+        }
+        write!(f, "flattened output differs from the original build with {}:", self.version)?;
+        for mismatch in &self.mismatches {
+            write!(f, "\n  {mismatch}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Flattener {
+    /// Flattens the target file like [`Self::flatten`], and verifies the output by compiling it
+    /// with the same compiler version and settings as the original sources.
+    ///
+    /// The creation and runtime bytecode of every contract is compared against the original
+    /// build. Metadata hashes are ignored and library placeholders are compared by library name,
+    /// so that e.g. a library renamed during flattening is reported as a mismatch.
+    pub fn flatten_and_verify<
+        C: Compiler,
+        T: ArtifactOutput<CompilerContract = C::CompilerContract>,
+    >(
+        self,
+        mut project: Project<C, T>,
+    ) -> std::result::Result<(String, FlattenVerification), FlattenerError>
+    where
+        C::Parser: SourceParser<ParsedSource: MaybeSolData>,
+    {
+        let targets = self.targets.clone();
+        let names = self.flattened_names();
+        let flattened = self.flatten();
+
+        project.cached = false;
+        project.no_artifacts = true;
+        project.build_info = false;
+        for settings in
+            std::iter::once(&mut project.settings).chain(project.additional_settings.values_mut())
+        {
+            settings.update_output_selection(|selection| {
+                *selection = OutputSelection::common_output_selection([
+                    "evm.bytecode.object".to_string(),
+                    "evm.deployedBytecode.object".to_string(),
+                ]);
+            });
+        }
+
+        let original = compile(&project, project.paths.read_files(&targets)?)?;
+        let (version, profile) = targets
+            .iter()
+            .filter_map(|target| original.output().contracts.0.get(target))
+            .chain(original.output().contracts.0.values())
+            .flat_map(|contracts| contracts.values().flatten())
+            .map(|c| (c.version.clone(), c.profile.clone()))
+            .next()
+            .ok_or_else(|| SolcError::msg("no contracts in the target files"))?;
+
+        // Compile the flattened output in isolation with the settings of the original targets.
+        let flattened_path = project.paths.root.join(FLATTENED_FILE_NAME);
+        let flattened_sources = Sources::from([(flattened_path, Source::new(flattened.as_str()))]);
+
+        if let Some(settings) = project.additional_settings.remove(&profile) {
+            project.settings = settings;
+        }
+        project.additional_settings.clear();
+        project.restrictions.clear();
+        project.version_selection = Box::new(AllowList::new([version.clone()]));
+        let flattened_output = compile(&project, flattened_sources)?;
+
+        let root = &project.paths.root;
+        let original_libraries =
+            libraries(original.output().contracts.contracts_with_files(), root);
+        let flattened_libraries =
+            libraries(flattened_output.output().contracts.contracts_with_files(), root);
+        let flattened_contracts = flattened_output
+            .output()
+            .contracts
+            .contracts()
+            .map(|(name, contract)| (name.as_str(), contract))
+            .collect::<HashMap<_, _>>();
+
+        let mut mismatches = Vec::new();
+        for (path, name, contract) in original.output().contracts.contracts_with_files() {
+            let flattened_name =
+                names.get(&(path.clone(), name.clone())).cloned().unwrap_or_else(|| name.clone());
+            let mut mismatch = |kind| {
+                mismatches.push(BytecodeMismatch {
+                    path: path.clone(),
+                    name: name.clone(),
+                    flattened_name: flattened_name.clone(),
+                    kind,
+                })
+            };
+
+            let Some(flattened_contract) = flattened_contracts.get(flattened_name.as_str()) else {
+                mismatch(None);
+                continue;
+            };
+            let bytecodes = [
+                (BytecodeKind::Creation, contract.bin_ref(), flattened_contract.bin_ref()),
+                (
+                    BytecodeKind::Runtime,
+                    contract.bin_runtime_ref(),
+                    flattened_contract.bin_runtime_ref(),
+                ),
+            ];
+            for (kind, original, flattened) in bytecodes {
+                let original = original.and_then(|code| normalize(code, &original_libraries));
+                let flattened = flattened.and_then(|code| normalize(code, &flattened_libraries));
+                if original != flattened {
+                    mismatch(Some(kind));
+                }
+            }
+        }
+
+        Ok((flattened, FlattenVerification { version, mismatches }))
+    }
+
+    /// Returns the names of the renamed top-level declarations in the flattened output, keyed by
+    /// their source and original name.
+    fn flattened_names(&self) -> HashMap<(PathBuf, String), String> {
+        let top_level_names = self.rename_top_level_definitions(&mut Default::default());
+        self.renamed_symbols(&top_level_names)
+            .into_iter()
+            .map(|symbol| ((symbol.path, symbol.name), symbol.renamed))
+            .collect()
+    }
+}
+
+fn compile<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>>(
+    project: &Project<C, T>,
+    sources: Sources,
+) -> std::result::Result<ProjectCompileOutput<C, T>, FlattenerError> {
+    let output = ProjectCompiler::with_sources(project, sources)
+        .and_then(|compiler| compiler.compile())
+        .map_err(FlattenerError::Compilation)?;
+    if output.has_compiler_errors() {
+        return Err(FlattenerError::Compilation(SolcError::msg(&output)));
+    }
+    Ok(output)
+}
+
+/// Returns the fully qualified names of the contracts of a build which may be linked as
+/// libraries.
+///
+/// Each contract is included both with its absolute path and with the path relative to the root,
+/// as either may be used as the source name in library placeholders.
+fn libraries<'a, C: 'a>(
+    contracts: impl Iterator<Item = (&'a PathBuf, &'a String, &'a C)>,
+    root: &Path,
+) -> Vec<(String, String)> {
+    contracts
+        .flat_map(|(path, name, _)| {
+            [path.as_path(), path.strip_prefix(root).unwrap_or(path)]
+                .map(|path| (path.to_string_lossy().into_owned(), name.clone()))
+        })
+        .collect()
+}
+
+/// Normalizes the bytecode for comparison.
+///
+/// Library placeholders are linked to an address derived from the library name, so that the
+/// bytecode doesn't depend on the path of the library. Metadata hashes are zeroed out.
+fn normalize(code: &BytecodeObject, libraries: &[(String, String)]) -> Option<Bytes> {
+    let mut code = code.clone();
+    for (file, library) in libraries {
+        if !code.is_unlinked() {
+            break;
+        }
+        code.link(file, library, library_address(library));
+    }
+    let mut bytes = match code.resolve() {
+        Some(bytes) => bytes.to_vec(),
+        None => code.strip_bytecode_placeholders()?.to_vec(),
+    };
+    strip_metadata_hashes(&mut bytes);
+    Some(bytes.into())
+}
+
+/// Returns the address libraries with the given name are linked to for comparison.
+fn library_address(name: &str) -> Address {
+    Address::from_slice(&keccak256(name)[12..])
+}
+
+/// Zeroes out the hashes of all CBOR encoded metadata in the bytecode.
+///
+/// Besides the trailer of the contract itself, the bytecode may contain the metadata of contracts
+/// it creates, so all occurrences are stripped.
+fn strip_metadata_hashes(bytes: &mut [u8]) {
+    for (key, len) in METADATA_HASH_KEYS {
+        let mut pos = 0;
+        while let Some(idx) = bytes[pos..].windows(key.len()).position(|window| window == key) {
+            let start = pos + idx + key.len();
+            let end = (start + len).min(bytes.len());
+            bytes[start..end].fill(0);
+            pos = end;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::hex;
+    use foundry_compilers_core::utils::library_hash_placeholder;
+
+    #[test]
+    fn strips_metadata_hashes() {
+        let hash = [0xab; 34];
+        let metadata =
+            [&b"\xa2\x64ipfs\x58\x22"[..], &hash, b"\x64solc\x43\x00\x08\x1a\x00\x33"].concat();
+        let mut bytes = [&[0x60, 0x80][..], &metadata, &[0xfe], &metadata].concat();
+        strip_metadata_hashes(&mut bytes);
+
+        let stripped =
+            [&b"\xa2\x64ipfs\x58\x22"[..], &[0; 34], b"\x64solc\x43\x00\x08\x1a\x00\x33"].concat();
+        assert_eq!(bytes, [&[0x60, 0x80][..], &stripped, &[0xfe], &stripped].concat());
+    }
+
+    #[test]
+    fn compares_library_placeholders_by_name() {
+        let code = |file: &str, library: &str| {
+            let placeholder = library_hash_placeholder(format!("{file}:{library}"));
+            BytecodeObject::Unlinked(format!("73__{placeholder}__3014"))
+        };
+        let original = [("src/Lib.sol".to_string(), "Lib".to_string())];
+        let flattened = [
+            ("/tmp/Flattened.sol".to_string(), "Lib".to_string()),
+            ("/tmp/Flattened.sol".to_string(), "Lib_0".to_string()),
+        ];
+
+        let expected = [&[0x73][..], library_address("Lib").as_slice(), &[0x30, 0x14]].concat();
+        assert_eq!(normalize(&code("src/Lib.sol", "Lib"), &original), Some(expected.into()));
+        assert_eq!(
+            normalize(&code("src/Lib.sol", "Lib"), &original),
+            normalize(&code("/tmp/Flattened.sol", "Lib"), &flattened)
+        );
+        assert_ne!(
+            normalize(&code("src/Lib.sol", "Lib"), &original),
+            normalize(&code("/tmp/Flattened.sol", "Lib_0"), &flattened)
+        );
+        assert_eq!(
+            normalize(&BytecodeObject::Bytecode(hex!("6080").into()), &original),
+            Some(hex!("6080").into())
+        );
+    }
+}
@@ -19,7 +19,7 @@ pub mod cache;
 pub mod flatten;
 
 pub mod resolver;
-pub use resolver::Graph;
+pub use resolver::{Graph, LockMode, VersionSelection};
 
 pub mod compilers;
 pub use compilers::*;
@@ -31,13 +31,21 @@ pub use compile::{
 };
 
 mod config;
-pub use config::{PathStyle, ProjectPaths, ProjectPathsConfig, SolcConfig};
+pub use config::{
+    InputGroup, InputKind, PathStyle, ProjectPaths, ProjectPathsConfig, SolcConfig, SourceUnitNames,
+};
 
 mod filter;
-pub use filter::{FileFilter, SparseOutputFilter, TestFileFilter};
+pub use filter::{FileFilter, InputGroupFilter, SparseOutputFilter, TestFileFilter};
+
+mod overlay;
+pub use overlay::SourceOverlay;
 
 pub mod report;
 
+mod workspace;
+pub use workspace::{Workspace, WorkspaceBuilder, WorkspaceMember};
+
 /// Updates to be applied to the sources.
 ///
 /// `source_path -> (start, end, new_value)`
@@ -47,8 +55,16 @@ pub type Updates = HashMap<PathBuf, BTreeSet<(usize, usize, String)>>;
 #[cfg(feature = "project-util")]
 pub mod project_util;
 
+#[cfg(all(test, unix))]
+mod test_utils;
+
+#[cfg(feature = "watch")]
+mod watch;
+#[cfg(feature = "watch")]
+pub use watch::WatchUpdate;
+
 pub use foundry_compilers_artifacts as artifacts;
-pub use foundry_compilers_core::{error, utils};
+pub use foundry_compilers_core::{error, utils, vfs};
 
 use cache::CompilerCache;
 use compile::output::contracts::VersionedContracts;
@@ -57,10 +73,13 @@ use foundry_compilers_artifacts::{
     output_selection::OutputSelection,
     solc::{
         Severity, SourceFile, StandardJsonCompilerInput,
-        sources::{Source, SourceCompilationKind, Sources},
+        sources::{SourceCompilationKind, Sources},
     },
 };
-use foundry_compilers_core::error::{Result, SolcError, SolcIoError};
+use foundry_compilers_core::{
+    error::{Result, SolcError, SolcIoError},
+    vfs::FileSystem,
+};
 use output::sources::{VersionedSourceFile, VersionedSourceFiles};
 use project::ProjectCompiler;
 use semver::Version;
@@ -117,6 +136,9 @@ pub struct Project<
     solc_jobs: usize,
     /// Offline mode, if set, network access (download solc) is disallowed
     pub offline: bool,
+    /// Local mirror of compiler binaries from which missing versions are installed in offline
+    /// mode.
+    pub offline_mirror: Option<PathBuf>,
     /// Windows only config value to ensure the all paths use `/` instead of `\\`, same as `solc`
     ///
     /// This is a noop on other platforms
@@ -124,6 +146,14 @@ pub struct Project<
     /// Optional sparse output filter used to optimize compilation.
     #[debug(skip)]
     pub sparse_output: Option<Box<dyn FileFilter>>,
+    /// Path to the compiler lock file, if compiler versions should be locked.
+    pub lockfile: Option<PathBuf>,
+    /// How the compiler lock file is used when resolving compiler versions.
+    pub lock_mode: LockMode,
+    /// Strategy for selecting compiler versions among those satisfying the version requirements.
+    pub version_selection: Box<dyn VersionSelection>,
+    /// Whether dirty Solidity sources are checked with solar before invoking the compiler.
+    pub preflight: Option<PreflightMode>,
 }
 
 impl Project {
@@ -178,7 +208,7 @@ where
     /// Returns standard-json-input to compile the target contract
     pub fn standard_json_input(&self, target: &Path) -> Result<StandardJsonCompilerInput> {
         trace!(?target, "Building standard-json-input");
-        let graph = Graph::<C::Parser>::resolve(&self.paths)?;
+        let graph = Graph::resolve_with(&self.paths, self.parser())?;
         let target_index = graph.files().get(target).ok_or_else(|| {
             SolcError::msg(format!("cannot resolve file at {:?}", target.display()))
         })?;
@@ -233,6 +263,11 @@ impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler> Pro
         &self.paths.cache
     }
 
+    /// Returns the parser resolving the project's sources, see [`Compiler::parser`]
+    pub fn parser(&self) -> C::Parser {
+        self.compiler.parser(self.paths.with_language_ref())
+    }
+
     /// Returns the path to the `build-info` directory nested in the artifacts dir
     pub fn build_info_path(&self) -> &Path {
         &self.paths.build_infos
@@ -287,7 +322,12 @@ impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler> Pro
     /// # Ok::<_, Box<dyn std::error::Error>>(())
     /// ```
     pub fn rerun_if_sources_changed(&self) {
-        println!("cargo:rerun-if-changed={}", self.paths.sources.display())
+        println!("cargo:rerun-if-changed={}", self.paths.sources.display());
+        for group in self.paths.groups.iter().filter(|group| group.kind == InputKind::Source) {
+            for path in &group.paths {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+        }
     }
 
     pub fn compile(&self) -> Result<ProjectCompileOutput<C, T>> {
@@ -306,7 +346,7 @@ impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler> Pro
     /// ```
     pub fn compile_file(&self, file: impl Into<PathBuf>) -> Result<ProjectCompileOutput<C, T>> {
         let file = file.into();
-        let source = Source::read(&file)?;
+        let source = self.paths.read_file(&file)?;
         project::ProjectCompiler::with_sources(self, Sources::from([(file, source)]))?.compile()
     }
 
@@ -326,7 +366,7 @@ impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler> Pro
         I: IntoIterator<Item = P>,
         P: Into<PathBuf>,
     {
-        let sources = Source::read_all(files)?;
+        let sources = self.paths.read_files(files)?;
 
         ProjectCompiler::with_sources(self, sources)?.compile()
     }
@@ -351,20 +391,20 @@ impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler> Pro
     /// ```
     pub fn cleanup(&self) -> std::result::Result<(), SolcIoError> {
         trace!("clean up project");
-        if self.cache_path().exists() {
-            std::fs::remove_file(self.cache_path())
+        let fs = &*self.paths.fs;
+        if fs.exists(self.cache_path()) {
+            fs.remove_file(self.cache_path())
                 .map_err(|err| SolcIoError::new(err, self.cache_path()))?;
             if let Some(cache_folder) =
                 self.cache_path().parent().filter(|cache_folder| self.root() != *cache_folder)
             {
                 // remove the cache folder if the cache file was the only file
-                if cache_folder
-                    .read_dir()
+                if fs
+                    .read_dir(cache_folder)
                     .map_err(|err| SolcIoError::new(err, cache_folder))?
-                    .next()
-                    .is_none()
+                    .is_empty()
                 {
-                    std::fs::remove_dir(cache_folder)
+                    fs.remove_dir_all(cache_folder)
                         .map_err(|err| SolcIoError::new(err, cache_folder))?;
                 }
             }
@@ -372,15 +412,15 @@ impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler> Pro
         }
 
         // clean the artifacts dir
-        if self.artifacts_path().exists() && self.root() != self.artifacts_path() {
-            std::fs::remove_dir_all(self.artifacts_path())
+        if fs.exists(self.artifacts_path()) && self.root() != self.artifacts_path() {
+            fs.remove_dir_all(self.artifacts_path())
                 .map_err(|err| SolcIoError::new(err, self.artifacts_path()))?;
             trace!("removed artifacts dir \"{}\"", self.artifacts_path().display());
         }
 
         // also clean the build-info dir, in case it's not nested in the artifacts dir
-        if self.build_info_path().exists() && self.root() != self.build_info_path() {
-            std::fs::remove_dir_all(self.build_info_path())
+        if fs.exists(self.build_info_path()) && self.root() != self.build_info_path() {
+            fs.remove_dir_all(self.build_info_path())
                 .map_err(|err| SolcIoError::new(err, self.build_info_path()))?;
             tracing::trace!("removed build-info dir \"{}\"", self.build_info_path().display());
         }
@@ -394,7 +434,7 @@ impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler> Pro
         T: Clone,
         C: Clone,
     {
-        let graph = Graph::<C::Parser>::resolve(&self.paths)?;
+        let graph = Graph::resolve_with(&self.paths, self.parser())?;
         let mut contracts: HashMap<String, Vec<PathBuf>> = HashMap::new();
         if !graph.is_empty() {
             for node in &graph.nodes {
@@ -460,6 +500,8 @@ pub struct ProjectBuilder<
     no_artifacts: bool,
     /// Use offline mode
     offline: bool,
+    /// Mirror of compiler binaries used in offline mode
+    offline_mirror: Option<PathBuf>,
     /// Whether to slash paths of the `ProjectCompilerOutput`
     slash_paths: bool,
     /// handles all artifacts related tasks
@@ -475,6 +517,14 @@ pub struct ProjectBuilder<
     solc_jobs: Option<usize>,
     /// Optional sparse output filter used to optimize compilation.
     sparse_output: Option<Box<dyn FileFilter>>,
+    /// Path to the compiler lock file
+    lockfile: Option<PathBuf>,
+    /// How the compiler lock file is used
+    lock_mode: LockMode,
+    /// Strategy for selecting compiler versions
+    version_selection: Box<dyn VersionSelection>,
+    /// Pre-flight check of dirty Solidity sources
+    preflight: Option<PreflightMode>,
 }
 
 impl<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>> ProjectBuilder<C, T> {
@@ -486,6 +536,7 @@ impl<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>> Pro
             build_info: false,
             no_artifacts: false,
             offline: false,
+            offline_mirror: None,
             slash_paths: true,
             artifacts,
             ignored_error_codes: Vec::new(),
@@ -497,6 +548,10 @@ impl<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>> Pro
             sparse_output: None,
             additional_settings: BTreeMap::new(),
             restrictions: BTreeMap::new(),
+            lockfile: None,
+            lock_mode: LockMode::default(),
+            version_selection: Box::new(resolver::PreferInstalled),
+            preflight: None,
         }
     }
 
@@ -581,6 +636,17 @@ impl<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>> Pro
         self
     }
 
+    /// Activates offline mode, installing missing compiler versions from the given local mirror
+    /// of compiler binaries instead of downloading them.
+    ///
+    /// Versions available in the mirror can be selected in addition to the installed ones. See
+    /// [`SolcMirror`](crate::solc::SolcMirror) for the layout of a solc mirror.
+    #[must_use]
+    pub fn offline_mirror(mut self, mirror: impl Into<PathBuf>) -> Self {
+        self.offline_mirror = Some(mirror.into());
+        self.set_offline(true)
+    }
+
     /// Sets whether to slash all paths on windows
     ///
     /// If set to `true` all `\\` separators are replaced with `/`, same as solc
@@ -648,6 +714,51 @@ impl<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>> Pro
         self
     }
 
+    /// Sets the path of the compiler lock file.
+    ///
+    /// If set, the resolved compiler version and settings profile of every source file are
+    /// recorded in this file once the sources compiled successfully, and reused by later builds,
+    /// see [`LockMode`].
+    #[must_use]
+    pub fn lockfile(mut self, lockfile: impl Into<PathBuf>) -> Self {
+        self.lockfile = Some(lockfile.into());
+        self
+    }
+
+    /// Sets how the compiler lock file is used
+    #[must_use]
+    pub fn set_lock_mode(mut self, lock_mode: LockMode) -> Self {
+        self.lock_mode = lock_mode;
+        self
+    }
+
+    /// Fails if the compiler lock file is missing or out of date
+    #[must_use]
+    pub fn locked(self) -> Self {
+        self.set_lock_mode(LockMode::Strict)
+    }
+
+    /// Sets the strategy for selecting compiler versions, see [`VersionSelection`].
+    ///
+    /// By default the newest installed version is preferred.
+    #[must_use]
+    pub fn version_selection<S: VersionSelection + 'static>(mut self, selection: S) -> Self {
+        self.version_selection = Box::new(selection);
+        self
+    }
+
+    /// Enables the pre-flight check of dirty Solidity sources with solar.
+    ///
+    /// Depending on the given [`PreflightMode`], errors found by solar are either reported as
+    /// warnings to the [reporter](crate::report), or in the compiler output as errors which
+    /// prevent invoking the compiler for the affected sources.
+    /// Only sources compiled with solc 0.8 or newer are checked.
+    #[must_use]
+    pub fn preflight(mut self, mode: PreflightMode) -> Self {
+        self.preflight = Some(mode);
+        self
+    }
+
     /// Set arbitrary `ArtifactOutputHandler`
     pub fn artifacts<A: ArtifactOutput<CompilerContract = C::CompilerContract>>(
         self,
@@ -662,6 +773,7 @@ impl<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>> Pro
             compiler_severity_filter,
             solc_jobs,
             offline,
+            offline_mirror,
             build_info,
             slash_paths,
             ignored_file_paths,
@@ -669,6 +781,10 @@ impl<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>> Pro
             sparse_output,
             additional_settings,
             restrictions,
+            lockfile,
+            lock_mode,
+            version_selection,
+            preflight,
             ..
         } = self;
         ProjectBuilder {
@@ -678,6 +794,7 @@ impl<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>> Pro
             additional_settings,
             restrictions,
             offline,
+            offline_mirror,
             slash_paths,
             artifacts,
             ignored_error_codes,
@@ -688,6 +805,10 @@ impl<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>> Pro
             build_info,
             settings,
             sparse_output,
+            lockfile,
+            lock_mode,
+            version_selection,
+            preflight,
         }
     }
 
@@ -703,12 +824,17 @@ impl<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>> Pro
             compiler_severity_filter,
             solc_jobs,
             offline,
+            offline_mirror,
             build_info,
             slash_paths,
             settings,
             sparse_output,
             additional_settings,
             restrictions,
+            lockfile,
+            lock_mode,
+            version_selection,
+            preflight,
         } = self;
 
         let mut paths = paths.map(Ok).unwrap_or_else(ProjectPathsConfig::current_hardhat)?;
@@ -733,11 +859,16 @@ impl<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>> Pro
                 .or_else(|| std::thread::available_parallelism().ok().map(|n| n.get()))
                 .unwrap_or(1),
             offline,
+            offline_mirror,
             slash_paths,
             settings: settings.unwrap_or_default(),
             sparse_output,
             additional_settings,
             restrictions,
+            lockfile,
+            lock_mode,
+            version_selection,
+            preflight,
         })
     }
 }
@@ -775,6 +906,15 @@ impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler> Art
         self.artifacts_handler().handle_artifacts(contracts, artifacts)
     }
 
+    fn handle_artifacts_in(
+        &self,
+        fs: &dyn FileSystem,
+        contracts: &VersionedContracts<C::CompilerContract>,
+        artifacts: &Artifacts<Self::Artifact>,
+    ) -> Result<()> {
+        self.artifacts_handler().handle_artifacts_in(fs, contracts, artifacts)
+    }
+
     fn output_file_name(
         name: &str,
         version: &Version,
@@ -854,6 +994,14 @@ impl<T: ArtifactOutput<CompilerContract = C::CompilerContract>, C: Compiler> Art
     fn handle_cached_artifacts(&self, artifacts: &Artifacts<Self::Artifact>) -> Result<()> {
         self.artifacts_handler().handle_cached_artifacts(artifacts)
     }
+
+    fn handle_cached_artifacts_in(
+        &self,
+        fs: &dyn FileSystem,
+        artifacts: &Artifacts<Self::Artifact>,
+    ) -> Result<()> {
+        self.artifacts_handler().handle_cached_artifacts_in(fs, artifacts)
+    }
 }
 
 // Rebases the given path to the base directory lexically.
@@ -944,7 +1092,7 @@ pub fn replace_source_content(
 pub(crate) fn parse_one_source<R>(
     content: &str,
     path: &Path,
-    f: impl FnOnce(&Session, &solar::parse::ast::SourceUnit<'_>) -> R,
+    f: impl for<'ast> FnOnce(&Session, &'ast solar::parse::ast::SourceUnit<'ast>) -> R,
 ) -> Result<R, EmittedDiagnostics> {
     let sess = Session::builder().with_buffer_emitter(Default::default()).build();
     let res = sess.enter_sequential(|| -> solar::parse::interface::Result<_> {
@@ -952,7 +1100,7 @@ pub(crate) fn parse_one_source<R>(
         let filename = FileName::Real(path.to_path_buf());
         let mut parser = Parser::from_source_code(&sess, &arena, filename, content.to_string())?;
         let ast = parser.parse_file().map_err(|e| e.emit())?;
-        Ok(f(&sess, &ast))
+        Ok(f(&sess, arena.alloc(ast)))
     });
 
     // Return if any diagnostics emitted during content parsing.
@@ -0,0 +1,241 @@
+//! In-memory source overlays.
+
+use foundry_compilers_artifacts::sources::{Source, Sources};
+use foundry_compilers_core::vfs::FileSystem;
+use std::path::{Path, PathBuf};
+
+/// In-memory file contents which take precedence over the files on disk, for example unsaved
+/// editor buffers.
+///
+/// The overlay is consulted whenever the [`ProjectPathsConfig`](crate::ProjectPathsConfig) it's
+/// attached to reads sources with
+/// [`ProjectPathsConfig::read_file`](crate::ProjectPathsConfig::read_file): when collecting the
+/// input files, resolving imports, checking the cache and flattening. Overlaid files don't have to
+/// exist on disk: they're part of the input files if they're located in the sources, tests or
+/// scripts directories and can be imported like any other file.
+///
+/// Outputs which depend on overlaid content are not written to the cache and no artifact or
+/// build info files are written for them, unless this is enabled with [`Self::persist`]. They are
+/// reported by
+/// [`ProjectCompileOutput::overlay_dependents`](crate::ProjectCompileOutput::overlay_dependents).
+///
+/// Artifacts which were written for these files by earlier compilations are left unchanged in the
+/// artifacts directory, e.g. the artifacts of the content on disk. Since the cache entries of the
+/// files are removed, these artifacts are rewritten by the next compilation without the overlay.
+#[derive(Clone, Debug, Default)]
+pub struct SourceOverlay {
+    /// The overlaid files, by absolute path.
+    sources: Sources,
+    /// Whether outputs depending on the overlay are persisted.
+    persist: bool,
+}
+
+impl SourceOverlay {
+    /// Creates a new, empty overlay.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether outputs depending on overlaid content are written to the cache and artifacts
+    /// directory like any other output.
+    #[must_use]
+    pub fn persist(mut self, persist: bool) -> Self {
+        self.persist = persist;
+        self
+    }
+
+    /// Returns whether outputs depending on overlaid content are persisted.
+    pub fn is_persisted(&self) -> bool {
+        self.persist
+    }
+
+    /// Overlays the file at the given absolute path with the given content.
+    ///
+    /// Returns the previously overlaid content, if any.
+    pub fn insert(
+        &mut self,
+        path: impl Into<PathBuf>,
+        content: impl Into<String>,
+    ) -> Option<Source> {
+        self.sources.insert(path.into(), Source::new(content))
+    }
+
+    /// Same as [`Self::insert`], but returns the overlay.
+    #[must_use]
+    pub fn with(mut self, path: impl Into<PathBuf>, content: impl Into<String>) -> Self {
+        self.insert(path, content);
+        self
+    }
+
+    /// Removes the given file from the overlay, so that it's read from disk again.
+    pub fn remove(&mut self, path: &Path) -> Option<Source> {
+        self.sources.remove(path)
+    }
+
+    /// Removes all files from the overlay.
+    pub fn clear(&mut self) {
+        self.sources.clear();
+    }
+
+    /// Returns the overlaid content of the given file, if any.
+    pub fn get(&self, path: &Path) -> Option<&Source> {
+        self.sources.get(path)
+    }
+
+    /// Returns whether the given file is overlaid.
+    pub fn contains(&self, path: &Path) -> bool {
+        self.sources.contains_key(path)
+    }
+
+    /// Returns `true` if no files are overlaid.
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// Returns an iterator over the overlaid files.
+    pub fn files(&self) -> impl Iterator<Item = &PathBuf> + '_ {
+        self.sources.keys()
+    }
+
+    /// Returns the overlaid files which are located in the given directory, have one of the given
+    /// extensions and don't exist in the file system.
+    pub(crate) fn virtual_files_in<'a>(
+        &'a self,
+        fs: &'a dyn FileSystem,
+        dir: &'a Path,
+        extensions: &'a [&str],
+    ) -> impl Iterator<Item = PathBuf> + 'a {
+        self.files()
+            .filter(move |path| {
+                path.starts_with(dir)
+                    && path
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .is_some_and(|ext| extensions.contains(&ext))
+                    && !fs.exists(path)
+            })
+            .cloned()
+    }
+
+    /// Replaces the content of all overlaid files in the given sources.
+    pub fn apply(&self, sources: &mut Sources) {
+        if self.is_empty() {
+            return;
+        }
+        for (path, source) in sources.iter_mut() {
+            if let Some(overlaid) = self.get(path) {
+                source.content = overlaid.content.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Graph, ProjectPathsConfig, resolver::parse::SolParser};
+    use foundry_compilers_core::utils;
+    use std::fs;
+
+    #[test]
+    fn can_resolve_overlaid_sources() {
+        let tmp = utils::tempdir("overlay").unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/A.sol"), "pragma solidity ^0.8.0;\ncontract A {}\n").unwrap();
+
+        let mut paths = ProjectPathsConfig::dapptools(root).unwrap();
+        let a = paths.sources.join("A.sol");
+        let b = paths.sources.join("B.sol");
+        let overlaid = "pragma solidity ^0.8.0;\nimport \"./B.sol\";\ncontract A is B {}\n";
+        paths.overlay = SourceOverlay::new()
+            .with(&a, overlaid)
+            .with(&b, "pragma solidity ^0.8.0;\ncontract B {}\n");
+
+        let sources = paths.read_input_files().unwrap();
+        assert_eq!(sources.keys().collect::<Vec<_>>(), [&a, &b]);
+        assert_eq!(sources[&a].content.as_str(), overlaid);
+
+        // `B.sol` is only reachable through the import of the overlaid `A.sol`
+        let sources = Source::read_all([&a]).unwrap();
+        let graph = Graph::<SolParser>::resolve_sources(&paths, sources).unwrap();
+        assert_eq!(graph.imports(&a).into_iter().collect::<Vec<_>>(), [b.as_path()]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn can_keep_overlay_dependents_in_memory() {
+        use crate::{
+            ProjectBuilder,
+            multi::MultiCompiler,
+            test_utils::{ABC_OUTPUT, multi_compiler, stub_solc},
+        };
+
+        let tmp = utils::tempdir("overlay").unwrap();
+        // `solc` stub which returns a contract for each source.
+        let solc = stub_solc(tmp.path(), ABC_OUTPUT);
+
+        let root = tmp.path().join("project");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/A.sol"), "pragma solidity ^0.8.0;\ncontract A {}\n").unwrap();
+        fs::write(
+            root.join("src/B.sol"),
+            "pragma solidity ^0.8.0;\nimport \"./A.sol\";\ncontract B is A {}\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/C.sol"), "pragma solidity ^0.8.0;\ncontract C {}\n").unwrap();
+
+        let compiler = multi_compiler(&solc);
+        let mut paths = ProjectPathsConfig::dapptools(&root).unwrap();
+        let [a, b, c] = ["A", "B", "C"].map(|name| paths.sources.join(format!("{name}.sol")));
+        paths.overlay.insert(&a, "pragma solidity ^0.8.0;\ncontract A { uint x; }\n");
+        let mut project =
+            ProjectBuilder::<MultiCompiler>::default().paths(paths).build(compiler).unwrap();
+
+        let output = project.compile().unwrap();
+        assert!(!output.has_compiler_errors(), "{output}");
+        assert_eq!(output.overlay_dependents().len(), 2);
+        assert!(output.depends_on_overlay(&a) && output.depends_on_overlay(&b));
+        assert!(!output.depends_on_overlay(&c));
+        assert!(output.find_first("A").is_some());
+        assert!(!project.artifacts_path().join("A.sol").exists());
+        assert!(!project.artifacts_path().join("B.sol").exists());
+        assert!(project.artifacts_path().join("C.sol").exists());
+        // `C.sol` is compiled separately, so only its build info is written
+        assert_eq!(fs::read_dir(project.build_info_path()).unwrap().count(), 1);
+        let cache = project.read_cache_file().unwrap();
+        assert!(!cache.files.contains_key(&a) && !cache.files.contains_key(&b));
+        assert!(cache.files.contains_key(&c));
+
+        // the output of `C.sol` is reused once the overlay is removed
+        let overlay = std::mem::take(&mut project.paths.overlay);
+        let output = project.compile().unwrap();
+        assert!(output.overlay_dependents().is_empty());
+        assert!(output.compiled_artifacts().find_first("C").is_none());
+        assert!(output.cached_artifacts().find_first("C").is_some());
+        assert!(output.compiled_artifacts().find_first("A").is_some());
+
+        project.paths.overlay = overlay.persist(true);
+        let output = project.compile().unwrap();
+        assert!(output.depends_on_overlay(&a));
+        assert!(project.artifacts_path().join("A.sol").exists());
+        assert!(project.artifacts_path().join("B.sol").exists());
+        let cache = project.read_cache_file().unwrap();
+        assert!(cache.files.contains_key(&a) && cache.files.contains_key(&b));
+
+        // artifacts written without the overlay are left unchanged
+        project.paths.overlay.clear();
+        project.compile().unwrap();
+        let artifact = project.artifacts_path().join("A.sol/A.json");
+        let written = fs::read_to_string(&artifact).unwrap();
+        project.paths.overlay =
+            SourceOverlay::new().with(&a, "pragma solidity ^0.8.0;\ncontract A { uint y; }\n");
+        let output = project.compile().unwrap();
+        assert!(output.compiled_artifacts().find_first("A").is_some());
+        assert_eq!(fs::read_to_string(&artifact).unwrap(), written);
+        assert!(!project.read_cache_file().unwrap().files.contains_key(&a));
+        project.paths.overlay.clear();
+        let output = project.compile().unwrap();
+        assert!(output.compiled_artifacts().find_first("A").is_some());
+    }
+}
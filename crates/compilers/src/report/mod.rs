@@ -16,7 +16,9 @@
 
 #![allow(static_mut_refs)] // TODO
 
-use foundry_compilers_artifacts::remappings::Remapping;
+use crate::resolver::CaseMismatch;
+use foundry_compilers_artifacts::{Error as SolcError, remappings::Remapping};
+use foundry_compilers_core::error::ImportSuggestion;
 use semver::Version;
 use std::{
     any::{Any, TypeId},
@@ -129,6 +131,28 @@ pub trait Reporter: 'static + std::fmt::Debug {
     /// list of all import paths and the file they occurred in: `(import stmt, file)`
     fn on_unresolved_imports(&self, _imports: &[(&Path, &Path)], _remappings: &[Remapping]) {}
 
+    /// Invoked if imports couldn't be resolved, with the error reported by the import resolvers
+    /// and the suggested fixes for each import.
+    ///
+    /// Forwards to [`Self::on_unresolved_imports`] by default.
+    fn on_unresolved_import_errors(
+        &self,
+        imports: &[UnresolvedImport<'_>],
+        remappings: &[Remapping],
+    ) {
+        let imports = imports.iter().map(|i| (i.import, i.file)).collect::<Vec<_>>();
+        self.on_unresolved_imports(&imports, remappings)
+    }
+
+    /// Invoked if imports only resolve if case is ignored or files in the graph only differ by
+    /// case
+    fn on_case_mismatches(&self, _mismatches: &[CaseMismatch]) {}
+
+    /// Invoked with the errors found by the pre-flight check in
+    /// [`PreflightMode::WarnOnly`](crate::PreflightMode::WarnOnly), which are not part of the
+    /// compiler output.
+    fn on_preflight_diagnostics(&self, _diagnostics: &[SolcError]) {}
+
     /// If `self` is the same type as the provided `TypeId`, returns an untyped
     /// [`NonNull`] pointer to that type. Otherwise, returns `None`.
     ///
@@ -191,8 +215,16 @@ pub(crate) fn solc_installation_error(version: &Version, error: &str) {
     get_default(|r| r.reporter.on_solc_installation_error(version, error));
 }
 
-pub(crate) fn unresolved_imports(imports: &[(&Path, &Path)], remappings: &[Remapping]) {
-    get_default(|r| r.reporter.on_unresolved_imports(imports, remappings));
+pub(crate) fn unresolved_import_errors(imports: &[UnresolvedImport<'_>], remappings: &[Remapping]) {
+    get_default(|r| r.reporter.on_unresolved_import_errors(imports, remappings));
+}
+
+pub(crate) fn case_mismatches(mismatches: &[CaseMismatch]) {
+    get_default(|r| r.reporter.on_case_mismatches(mismatches));
+}
+
+pub(crate) fn preflight_diagnostics(diagnostics: &[SolcError]) {
+    get_default(|r| r.reporter.on_preflight_diagnostics(diagnostics));
 }
 
 fn get_global() -> Option<&'static Report> {
@@ -368,6 +400,33 @@ impl Reporter for BasicStdoutReporter {
             format_args!("{}", format_unresolved_imports(imports, remappings)),
         );
     }
+
+    fn on_unresolved_import_errors(
+        &self,
+        imports: &[UnresolvedImport<'_>],
+        remappings: &[Remapping],
+    ) {
+        if imports.is_empty() {
+            return;
+        }
+        write_line(
+            io::stdout().lock(),
+            format_args!("{}", format_unresolved_import_errors(imports, remappings)),
+        );
+    }
+
+    fn on_case_mismatches(&self, mismatches: &[CaseMismatch]) {
+        if mismatches.is_empty() {
+            return;
+        }
+        write_line(io::stdout().lock(), format_args!("{}", format_case_mismatches(mismatches)));
+    }
+
+    fn on_preflight_diagnostics(&self, diagnostics: &[SolcError]) {
+        for diagnostic in diagnostics {
+            write_line(io::stderr().lock(), format_args!("{diagnostic}"));
+        }
+    }
 }
 
 /// Write a single line to `writer`, silently discarding `BrokenPipe` errors.
@@ -395,6 +454,52 @@ pub fn format_unresolved_imports(imports: &[(&Path, &Path)], remappings: &[Remap
     )
 }
 
+/// An import which couldn't be resolved, see [`Reporter::on_unresolved_import_errors`].
+#[derive(Clone, Copy, Debug)]
+pub struct UnresolvedImport<'a> {
+    /// The import path.
+    pub import: &'a Path,
+    /// The file which contains the import.
+    pub file: &'a Path,
+    /// The error reported by the import resolvers.
+    pub error: &'a str,
+    /// The suggested fixes, best first.
+    pub suggestions: &'a [ImportSuggestion],
+}
+
+/// Creates a meaningful message for all unresolved imports, including the error reported for
+/// each of them and the suggested fixes
+pub fn format_unresolved_import_errors(
+    imports: &[UnresolvedImport<'_>],
+    remappings: &[Remapping],
+) -> String {
+    let info = imports
+        .iter()
+        .map(|i| {
+            let mut info =
+                format!("\"{}\" in \"{}\": {}", i.import.display(), i.file.display(), i.error);
+            for suggestion in i.suggestions {
+                info.push_str(&format!("\n        {suggestion}"));
+            }
+            info
+        })
+        .collect::<Vec<_>>()
+        .join("\n      ");
+    format!(
+        "Unable to resolve imports:\n      {}\nwith remappings:\n      {}",
+        info,
+        remappings.iter().map(|r| r.to_string()).collect::<Vec<_>>().join("\n      ")
+    )
+}
+
+/// Creates a meaningful message for all case mismatches
+pub fn format_case_mismatches(mismatches: &[CaseMismatch]) -> String {
+    format!(
+        "Found paths which differ in case from the files on disk:\n      {}\nthese only resolve on case-insensitive file systems",
+        mismatches.iter().map(|m| m.to_string()).collect::<Vec<_>>().join("\n      ")
+    )
+}
+
 /// Returned if setting the global reporter fails.
 #[derive(Debug)]
 pub struct SetGlobalReporterError {
@@ -471,6 +576,7 @@ fn set_global_reporter(report: Report) -> Result<(), SetGlobalReporterError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use foundry_compilers_core::error::ImportFix;
     use std::str::FromStr;
 
     #[test]
@@ -572,6 +678,33 @@ mod tests {
             r#"
 Unable to resolve imports:
       "./src/Import.sol" in "src/File.col"
+with remappings:
+      oz/=a/b/c/d/"#
+                .trim()
+        )
+    }
+
+    #[test]
+    fn test_unresolved_error_message() {
+        let suggestions = [ImportSuggestion {
+            fix: ImportFix::Remapping("store/=lib/store/src/".to_string()),
+            file: PathBuf::from("lib/store/src/Token.sol"),
+        }];
+        let unresolved = vec![UnresolvedImport {
+            import: Path::new("store/Token.sol"),
+            file: Path::new("src/File.sol"),
+            error: "package \"Token.sol\" is not in the store",
+            suggestions: &suggestions,
+        }];
+
+        let remappings = vec![Remapping::from_str("oz=a/b/c/d").unwrap()];
+
+        assert_eq!(
+            format_unresolved_import_errors(&unresolved, &remappings).trim(),
+            r#"
+Unable to resolve imports:
+      "store/Token.sol" in "src/File.sol": package "Token.sol" is not in the store
+        help: add the remapping `store/=lib/store/src/`
 with remappings:
       oz/=a/b/c/d/"#
                 .trim()
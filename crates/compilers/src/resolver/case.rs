@@ -0,0 +1,204 @@
+//! Detection of import paths which only match the files on disk if case is ignored.
+
+use crate::ProjectPathsConfig;
+use foundry_compilers_core::vfs::FileSystem;
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::{OsStr, OsString},
+    fmt,
+    path::{Component, Path, PathBuf},
+};
+
+/// A path in the graph whose case doesn't match the files on disk, which breaks the build on
+/// case-sensitive file systems or the checkout on case-insensitive ones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CaseMismatch {
+    /// An import which only resolves if the case of its path is ignored.
+    Import {
+        /// The file which contains the import.
+        file: PathBuf,
+        /// The import path as written.
+        import: PathBuf,
+        /// The correctly-cased path of the imported file.
+        actual: PathBuf,
+    },
+    /// Files in the graph whose paths only differ by case.
+    Files(Vec<PathBuf>),
+}
+
+impl fmt::Display for CaseMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Import { file, import, actual } => write!(
+                f,
+                "\"{}\" in \"{}\" only matches \"{}\" if case is ignored",
+                import.display(),
+                file.display(),
+                actual.display()
+            ),
+            Self::Files(files) => {
+                write!(f, "paths only differ by case:")?;
+                for (i, file) in files.iter().enumerate() {
+                    let sep = if i == 0 { " " } else { ", " };
+                    write!(f, "{sep}\"{}\"", file.display())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Compares paths with the directory listings of a [`FileSystem`], so that case mismatches are
+/// detected regardless of whether the file system itself is case-sensitive.
+#[derive(Debug)]
+pub(crate) struct CaseChecker<'a, L> {
+    paths: &'a ProjectPathsConfig<L>,
+    /// Cached directory listings.
+    listings: HashMap<PathBuf, Vec<OsString>>,
+}
+
+impl<'a, L> CaseChecker<'a, L> {
+    pub(crate) fn new(paths: &'a ProjectPathsConfig<L>) -> Self {
+        Self { paths, listings: HashMap::new() }
+    }
+
+    /// Returns the correctly-cased path if the given absolute path only matches an existing file
+    /// if case is ignored.
+    ///
+    /// Only the components below the project root, or below the library directory or parent
+    /// directory if the file is located outside the root, are checked.
+    pub(crate) fn actual_path(&mut self, path: &Path) -> Option<PathBuf> {
+        let base = [&self.paths.root]
+            .into_iter()
+            .chain(&self.paths.libraries)
+            .find(|dir| path.starts_with(dir))
+            .map(PathBuf::as_path)
+            .or_else(|| path.parent())?;
+        let mut actual = base.to_path_buf();
+        let mut mismatch = false;
+        for component in path.strip_prefix(base).ok()?.components() {
+            let Component::Normal(name) = component else { return None };
+            let entries = self.listing(&actual);
+            if !entries.iter().any(|entry| entry == name) {
+                let entry = entries.iter().find(|entry| eq_ignore_case(entry, name))?;
+                actual.push(entry);
+                mismatch = true;
+            } else {
+                actual.push(name);
+            }
+        }
+        mismatch.then_some(actual)
+    }
+
+    fn listing(&mut self, dir: &Path) -> &[OsString] {
+        let fs: &dyn FileSystem = &*self.paths.fs;
+        self.listings.entry(dir.to_path_buf()).or_insert_with(|| {
+            fs.read_dir(dir)
+                .map(|entries| {
+                    entries
+                        .into_iter()
+                        .filter_map(|entry| entry.path.file_name().map(OsStr::to_os_string))
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// Returns the groups of files whose paths only differ by case.
+pub(crate) fn case_conflicts<'a>(
+    files: impl IntoIterator<Item = &'a PathBuf>,
+) -> Vec<CaseMismatch> {
+    let mut groups = BTreeMap::<_, Vec<_>>::new();
+    for file in files {
+        groups.entry(file.to_string_lossy().to_lowercase()).or_default().push(file.clone());
+    }
+    groups
+        .into_values()
+        .filter(|files| files.len() > 1)
+        .map(|mut files| {
+            files.sort();
+            CaseMismatch::Files(files)
+        })
+        .collect()
+}
+
+fn eq_ignore_case(a: &OsStr, b: &OsStr) -> bool {
+    a.to_string_lossy().to_lowercase() == b.to_string_lossy().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Graph, resolver::parse::SolParser};
+    use foundry_compilers_artifacts::sources::Source;
+    use foundry_compilers_core::vfs::MemoryFileSystem;
+    use std::sync::Arc;
+
+    #[test]
+    fn can_detect_case_mismatches() {
+        let root = Path::new("/case");
+        let a = root.join("src/A.sol");
+        let fs = Arc::new(MemoryFileSystem::from_iter([
+            (
+                a.clone(),
+                "import \"./token.sol\";\nimport \"./utils/Math.sol\";\nimport \"./Utils/Math.sol\";\nimport \"@oz/token/erc20/ERC20.sol\";\nimport \"ds/Test.sol\";\n",
+            ),
+            (root.join("lib/oz/contracts/token/ERC20/ERC20.sol"), "contract ERC20 {}\n"),
+            (root.join("lib/DS/Test.sol"), "contract Test {}\n"),
+            (root.join("src/Token.sol"), "contract Token {}\n"),
+            (root.join("src/utils/Math.sol"), "import \"./math.sol\";\ncontract Math {}\n"),
+            (root.join("src/utils/math.sol"), "contract math {}\n"),
+        ]));
+        let mut paths = ProjectPathsConfig::builder().fs(fs).build_with_root(root);
+        paths.remappings =
+            vec![format!("@oz/={}/lib/oz/contracts/", root.display()).parse().unwrap()];
+
+        let mut checker = CaseChecker::new(&paths);
+        assert_eq!(
+            checker.actual_path(&root.join("src/token.sol")),
+            Some(root.join("src/Token.sol"))
+        );
+        assert_eq!(checker.actual_path(&root.join("src/Token.sol")), None);
+        assert_eq!(checker.actual_path(&root.join("src/missing.sol")), None);
+
+        let graph = Graph::<SolParser>::resolve_sources(
+            &paths,
+            [(a.clone(), Source::new(paths.fs.read_to_string(&a).unwrap()))].into_iter().collect(),
+        )
+        .unwrap();
+        assert_eq!(
+            graph.edges.case_mismatches(),
+            [
+                CaseMismatch::Import {
+                    file: a.clone(),
+                    import: "./token.sol".into(),
+                    actual: root.join("src/Token.sol"),
+                },
+                CaseMismatch::Import {
+                    file: a.clone(),
+                    import: "./Utils/Math.sol".into(),
+                    actual: root.join("src/utils/Math.sol"),
+                },
+                CaseMismatch::Import {
+                    file: a.clone(),
+                    import: "@oz/token/erc20/ERC20.sol".into(),
+                    actual: root.join("lib/oz/contracts/token/ERC20/ERC20.sol"),
+                },
+                CaseMismatch::Import {
+                    file: a,
+                    import: "ds/Test.sol".into(),
+                    actual: root.join("lib/DS/Test.sol"),
+                },
+                CaseMismatch::Files(vec![
+                    root.join("src/utils/Math.sol"),
+                    root.join("src/utils/math.sol"),
+                ]),
+            ]
+        );
+        assert_eq!(
+            graph.edges.case_mismatches()[0].to_string(),
+            "\"./token.sol\" in \"/case/src/A.sol\" only matches \"/case/src/Token.sol\" if case is ignored"
+        );
+    }
+}
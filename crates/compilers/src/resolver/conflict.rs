@@ -0,0 +1,158 @@
+//! Helpers for explaining why the version requirements of a set of files can't be satisfied.
+
+use semver::{Comparator, Op, Version, VersionReq};
+
+/// Returns the indices of an irreducible subset of `reqs` which no version in `versions` satisfies,
+/// or `None` if all requirements can be satisfied together.
+///
+/// Requirements are dropped from the back first, so requirements at the front of `reqs` are
+/// preferred when multiple minimal sets exist.
+pub(crate) fn minimal_conflict(reqs: &[&VersionReq], versions: &[&Version]) -> Option<Vec<usize>> {
+    let satisfiable =
+        |set: &[usize]| versions.iter().any(|v| set.iter().all(|&i| reqs[i].matches(v)));
+
+    let mut set = (0..reqs.len()).collect::<Vec<_>>();
+    if satisfiable(&set) {
+        return None;
+    }
+
+    for i in (0..reqs.len()).rev() {
+        let trial = set.iter().copied().filter(|&j| j != i).collect::<Vec<_>>();
+        if !satisfiable(&trial) {
+            set = trial;
+        }
+    }
+
+    Some(set)
+}
+
+/// Returns all versions accepted by every requirement in `set` except `reqs[idx]`, but rejected by
+/// `reqs[idx]`.
+pub(crate) fn excluded_versions(
+    idx: usize,
+    set: &[usize],
+    reqs: &[&VersionReq],
+    versions: &[&Version],
+) -> Vec<Version> {
+    versions
+        .iter()
+        .filter(|v| {
+            !reqs[idx].matches(v) && set.iter().filter(|&&i| i != idx).all(|&i| reqs[i].matches(v))
+        })
+        .map(|v| (*v).clone())
+        .collect()
+}
+
+/// Finds the narrowest widening of a single requirement in `set` which makes all of `reqs`
+/// satisfiable.
+///
+/// For every requirement in the conflicting `set` this computes the versions accepted by all
+/// *other* requirements and extends the requirement's range to the closest such version. The
+/// requirement which needs the fewest additional versions is returned together with its widened
+/// replacement.
+///
+/// `versions` must be sorted in ascending order.
+pub(crate) fn narrowest_fix(
+    set: &[usize],
+    reqs: &[&VersionReq],
+    versions: &[&Version],
+) -> Option<(usize, VersionReq)> {
+    let mut best: Option<(usize, usize, VersionReq)> = None;
+    for &idx in set {
+        let matched = versions
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| reqs[idx].matches(v))
+            .map(|(pos, _)| pos)
+            .collect::<Vec<_>>();
+        let others = versions
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| reqs.iter().enumerate().all(|(i, req)| i == idx || req.matches(v)))
+            .map(|(pos, _)| pos);
+
+        // pick the version accepted by all others that is the closest to the current range
+        let distance = |pos: usize| match (matched.first(), matched.last()) {
+            (Some(&lo), Some(&hi)) => lo.saturating_sub(pos) + pos.saturating_sub(hi),
+            _ => versions.len() - pos,
+        };
+        let Some(target) = others.min_by_key(|&pos| distance(pos)) else { continue };
+
+        let lo = matched.first().map_or(target, |&lo| lo.min(target));
+        let hi = matched.last().map_or(target, |&hi| hi.max(target));
+        let added = hi - lo + 1 - matched.len();
+
+        if best.as_ref().is_none_or(|(_, best_added, _)| added < *best_added) {
+            best = Some((idx, added, version_range(versions[lo], versions[hi])));
+        }
+    }
+    best.map(|(idx, _, req)| (idx, req))
+}
+
+/// Returns the requirement matching all versions from `lo` to `hi`, both inclusive.
+fn version_range(lo: &Version, hi: &Version) -> VersionReq {
+    let comparator = |op, v: &Version| Comparator {
+        op,
+        major: v.major,
+        minor: Some(v.minor),
+        patch: Some(v.patch),
+        pre: v.pre.clone(),
+    };
+    let comparators = if lo == hi {
+        vec![comparator(Op::Exact, lo)]
+    } else {
+        vec![comparator(Op::GreaterEq, lo), comparator(Op::LessEq, hi)]
+    };
+    VersionReq { comparators }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versions(versions: &[&str]) -> Vec<Version> {
+        versions.iter().map(|v| v.parse().unwrap()).collect()
+    }
+
+    fn reqs(reqs: &[&str]) -> Vec<VersionReq> {
+        reqs.iter().map(|r| r.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn can_find_minimal_conflict() {
+        let versions = versions(&["0.7.0", "0.7.6", "0.8.0", "0.8.19", "0.8.25"]);
+        let versions = versions.iter().collect::<Vec<_>>();
+        let reqs = reqs(&["=0.8.25", ">=0.7.0", "^0.8.0", "=0.7.0", ">=0.4.0"]);
+        let reqs = reqs.iter().collect::<Vec<_>>();
+
+        let set = minimal_conflict(&reqs, &versions).unwrap();
+        assert_eq!(set, vec![0, 3]);
+
+        assert_eq!(excluded_versions(0, &set, &reqs, &versions), vec!["0.7.0".parse().unwrap()]);
+        assert_eq!(excluded_versions(3, &set, &reqs, &versions), vec!["0.8.25".parse().unwrap()]);
+
+        assert!(minimal_conflict(&reqs[..3], &versions).is_none());
+    }
+
+    #[test]
+    fn can_suggest_narrowest_fix() {
+        let versions = versions(&["0.7.0", "0.7.6", "0.8.0", "0.8.19", "0.8.25"]);
+        let versions = versions.iter().collect::<Vec<_>>();
+
+        // widening `^0.8.0` down to 0.7.6 adds a single version
+        let reqs = reqs(&["^0.8.0", ">=0.7.0, <0.8.0"]);
+        let reqs = reqs.iter().collect::<Vec<_>>();
+        let set = minimal_conflict(&reqs, &versions).unwrap();
+        let (idx, req) = narrowest_fix(&set, &reqs, &versions).unwrap();
+        assert_eq!(idx, 0);
+        assert_eq!(req, ">=0.7.6, <=0.8.25".parse().unwrap());
+
+        // requirements matching a single version are widened to the closest compatible one
+        let reqs = self::reqs(&["=0.8.19", "=0.8.25", ">=0.8.0"]);
+        let reqs = reqs.iter().collect::<Vec<_>>();
+        let set = minimal_conflict(&reqs, &versions).unwrap();
+        let (idx, req) = narrowest_fix(&set, &reqs, &versions).unwrap();
+        assert_eq!(idx, 0);
+        assert_eq!(req, ">=0.8.19, <=0.8.25".parse().unwrap());
+    }
+}
@@ -0,0 +1,159 @@
+//! Pluggable import resolution.
+
+use crate::ProjectPathsConfig;
+use foundry_compilers_core::error::{Result, SolcError};
+use std::{
+    collections::BTreeSet,
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Resolves the path of an import statement to the file it refers to.
+///
+/// The resolvers configured in [`ProjectPathsConfig::import_resolvers`] are consulted in order
+/// whenever an import is resolved, and the first one which returns a path wins. The default
+/// configuration only contains the [`DefaultImportResolver`].
+pub trait ImportResolver: fmt::Debug + Send + Sync {
+    /// Attempts to resolve `import` from the given working directory, which is the parent
+    /// directory of the file that contains the import.
+    ///
+    /// Returns `Ok(None)` if the import is not handled by this resolver. An error is reported
+    /// together with the unresolved import if no other resolver can resolve it either.
+    ///
+    /// Library roots which must be passed to solc via `--include-path` should be added to
+    /// `include_paths`.
+    fn resolve_import(
+        &self,
+        paths: &ProjectPathsConfig,
+        cwd: &Path,
+        import: &Path,
+        include_paths: &mut BTreeSet<PathBuf>,
+    ) -> Result<Option<PathBuf>>;
+}
+
+/// The built-in import resolution: relative imports, remappings, library directories, nested
+/// library roots and the project directories.
+///
+/// See [`ProjectPathsConfig::resolve_library_import`] for how library imports are resolved.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultImportResolver;
+
+impl ImportResolver for DefaultImportResolver {
+    fn resolve_import(
+        &self,
+        paths: &ProjectPathsConfig,
+        cwd: &Path,
+        import: &Path,
+        include_paths: &mut BTreeSet<PathBuf>,
+    ) -> Result<Option<PathBuf>> {
+        paths.resolve_import_default(cwd, import, include_paths).map(Some)
+    }
+}
+
+/// Returns the import resolvers [`ProjectPathsConfig`] is configured with by default.
+pub(crate) fn default_import_resolvers() -> Vec<Arc<dyn ImportResolver>> {
+    vec![Arc::new(DefaultImportResolver)]
+}
+
+/// Consults the given resolvers in order and returns the first resolved path.
+///
+/// If no resolver succeeds, the returned error contains the messages of all resolvers that
+/// failed.
+pub(crate) fn resolve_import(
+    resolvers: &[Arc<dyn ImportResolver>],
+    paths: &ProjectPathsConfig,
+    cwd: &Path,
+    import: &Path,
+    include_paths: &mut BTreeSet<PathBuf>,
+) -> Result<PathBuf> {
+    let mut errors = Vec::new();
+    for resolver in resolvers {
+        match resolver.resolve_import(paths, cwd, import, include_paths) {
+            Ok(Some(path)) => return Ok(path),
+            Ok(None) => {}
+            Err(err) => errors.push(err),
+        }
+    }
+
+    match errors.len() {
+        0 => Err(SolcError::msg(format!(
+            "no import resolver could resolve \"{}\"",
+            import.display()
+        ))),
+        1 => Err(errors.pop().unwrap()),
+        _ => Err(SolcError::msg(
+            errors.iter().map(|err| err.to_string()).collect::<Vec<_>>().join("; "),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Graph, resolver::parse::SolParser};
+    use foundry_compilers_artifacts::sources::Source;
+    use foundry_compilers_core::utils;
+    use std::fs;
+
+    /// Resolves `store/<name>/<file>` imports from a content-addressed package store.
+    #[derive(Debug)]
+    struct PackageStore(PathBuf);
+
+    impl ImportResolver for PackageStore {
+        fn resolve_import(
+            &self,
+            _paths: &ProjectPathsConfig,
+            _cwd: &Path,
+            import: &Path,
+            _include_paths: &mut BTreeSet<PathBuf>,
+        ) -> Result<Option<PathBuf>> {
+            let Ok(import) = import.strip_prefix("store") else { return Ok(None) };
+            let path = self.0.join(import);
+            if !path.is_file() {
+                return Err(SolcError::msg(format!(
+                    "package \"{}\" is not in the store",
+                    import.display()
+                )));
+            }
+            Ok(Some(path))
+        }
+    }
+
+    #[test]
+    fn can_use_custom_import_resolver() {
+        let tmp = utils::tempdir("resolver").unwrap();
+        let root = tmp.path().join("project");
+        let store = tmp.path().join("store");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(store.join("token")).unwrap();
+        fs::write(store.join("token/Token.sol"), "pragma solidity ^0.8.0;\ncontract Token {}\n")
+            .unwrap();
+        let a = root.join("src/A.sol");
+        fs::write(
+            &a,
+            "pragma solidity ^0.8.0;\nimport \"store/token/Token.sol\";\nimport \"./B.sol\";\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/B.sol"), "pragma solidity ^0.8.0;\ncontract B {}\n").unwrap();
+
+        let mut paths = ProjectPathsConfig::dapptools(&root).unwrap();
+        assert!(paths.resolve_import(&root, Path::new("store/token/Token.sol")).is_err());
+
+        paths.import_resolvers.insert(0, Arc::new(PackageStore(store.clone())));
+        let graph =
+            Graph::<SolParser>::resolve_sources(&paths, Source::read_all([&a]).unwrap()).unwrap();
+        assert!(graph.edges.unresolved_imports().is_empty());
+        let mut imports = graph.imports(&a).into_iter().collect::<Vec<_>>();
+        imports.sort();
+        assert_eq!(imports, [root.join("src/B.sol").as_path(), &store.join("token/Token.sol")]);
+
+        let err = paths.resolve_import(&root, Path::new("store/vault/Vault.sol")).unwrap_err();
+        assert!(err.to_string().contains("package \"vault/Vault.sol\" is not in the store"));
+        assert!(err.to_string().contains("failed to resolve library import"));
+
+        paths.import_resolvers = vec![Arc::new(PackageStore(store))];
+        let err = paths.resolve_import(&root, Path::new("./B.sol")).unwrap_err();
+        assert_eq!(err.to_string(), "no import resolver could resolve \"./B.sol\"");
+    }
+}
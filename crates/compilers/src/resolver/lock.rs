@@ -0,0 +1,515 @@
+//! Compiler version lock file, similar to `Cargo.lock`.
+//!
+//! Which compiler version is picked for a source file depends on the versions that are installed
+//! locally. A lock file records the resolved version and settings profile of every input file, so
+//! that the same versions are used across machines until version requirements change.
+
+use super::{Graph, L};
+use crate::{ArtifactOutput, Compiler, Language, ParsedSource, Project, SourceParser};
+use foundry_compilers_core::{
+    error::{Result, SolcError},
+    utils,
+    vfs::{self, FileSystem, OsFileSystem},
+};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::{Path, PathBuf},
+};
+
+/// The default file name of the compiler lock file.
+pub const COMPILER_LOCK_FILENAME: &str = "compilers.lock";
+
+const LOCK_FORMAT_VERSION: &str = "foundry-compilers-lock-1";
+
+/// How the compiler lock file is used when resolving compiler versions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LockMode {
+    /// Use the locked versions of all files whose lock entry is up to date, resolve the versions
+    /// of the other files again and update the lock file.
+    #[default]
+    Auto,
+    /// Fail if the lock file is missing or out of date, e.g. in CI.
+    Strict,
+    /// Ignore the locked versions, resolve versions again and overwrite the lock file.
+    Update,
+}
+
+/// The resolved compiler versions and settings profiles of a project.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompilerLock<L> {
+    /// The format version of the lock file.
+    #[serde(rename = "_format")]
+    pub format: String,
+    /// The version requirement of every file that has one at the time the lock was written,
+    /// relative to the project root.
+    ///
+    /// This includes both pragmas and version restrictions, and is used to detect whether the
+    /// lock is out of date.
+    pub requirements: BTreeMap<PathBuf, VersionReq>,
+    /// All version groups, i.e. sets of input files compiled together.
+    pub groups: Vec<LockedGroup<L>>,
+}
+
+/// A set of input files compiled with the same compiler version and settings profile.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedGroup<L> {
+    /// The language of the input files.
+    pub language: L,
+    /// The compiler version the input files are compiled with.
+    pub version: Version,
+    /// The name of the settings profile the input files are compiled with.
+    pub profile: String,
+    /// The input files of this group, relative to the project root.
+    pub sources: BTreeSet<PathBuf>,
+}
+
+impl<L: Language> CompilerLock<L> {
+    /// Reads the lock file from the given path.
+    pub fn read(path: &Path) -> Result<Self> {
+        Self::read_in(&OsFileSystem, path)
+    }
+
+    /// Same as [`Self::read`], but reads from the given [`FileSystem`].
+    pub fn read_in(fs: &dyn FileSystem, path: &Path) -> Result<Self> {
+        vfs::read_json_file(fs, path)
+    }
+
+    /// Writes the lock file to the given path.
+    ///
+    /// Unlike the cache, the lock file is meant to be checked in, so it's pretty-printed.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        self.write_in(&OsFileSystem, path)
+    }
+
+    /// Same as [`Self::write`], but writes to the given [`FileSystem`].
+    pub fn write_in(&self, fs: &dyn FileSystem, path: &Path) -> Result<()> {
+        trace!("writing compiler lock file to {}", path.display());
+        if let Some(parent) = path.parent() {
+            fs.create_dir_all(parent).map_err(|err| SolcError::io(err, parent))?;
+        }
+        let content = serde_json::to_string_pretty(self)? + "\n";
+        fs.write(path, content.as_bytes()).map_err(|err| SolcError::io(err, path))
+    }
+
+    /// Returns the group that contains the given source file.
+    pub fn group(&self, source: &Path) -> Option<&LockedGroup<L>> {
+        self.groups.iter().find(|group| group.sources.contains(source))
+    }
+
+    /// Adds the given source to the group with the given language, version and profile.
+    fn insert(&mut self, language: L, version: &Version, profile: &str, source: PathBuf) {
+        let pos = self.groups.iter().position(|group| {
+            group.language == language && group.version == *version && group.profile == profile
+        });
+        let group = match pos {
+            Some(pos) => &mut self.groups[pos],
+            None => {
+                self.groups.push(LockedGroup {
+                    language,
+                    version: version.clone(),
+                    profile: profile.to_string(),
+                    sources: BTreeSet::new(),
+                });
+                self.groups.last_mut().unwrap()
+            }
+        };
+        group.sources.insert(source);
+    }
+
+    /// Sorts the groups, so that the lock file is deterministic.
+    fn sort(&mut self) {
+        self.groups.sort_by(|a, b| {
+            (&a.version, &a.profile, a.language.to_string()).cmp(&(
+                &b.version,
+                &b.profile,
+                b.language.to_string(),
+            ))
+        });
+    }
+}
+
+impl<L> Default for CompilerLock<L> {
+    fn default() -> Self {
+        Self {
+            format: LOCK_FORMAT_VERSION.to_string(),
+            requirements: Default::default(),
+            groups: Default::default(),
+        }
+    }
+}
+
+impl<P: SourceParser> Graph<P> {
+    /// Resolves the compiler version and settings profile of every input file using the lock
+    /// file at the given path.
+    ///
+    /// Depending on the project's [`LockMode`], the locked version and profile of a file are used
+    /// as long as the version requirements of the file and its imports didn't change since the
+    /// lock was written and the locked version is still available. The versions of all other
+    /// files are resolved again, or an error is returned in [`LockMode::Strict`].
+    ///
+    /// Returns the updated lock if it changed. It's written by the caller once the sources
+    /// compiled successfully.
+    ///
+    /// Entries of files which are not part of this graph, e.g. when only a subset of the project
+    /// is compiled, are kept as long as the files still exist.
+    #[allow(clippy::complexity)]
+    pub(super) fn resolve_locked<
+        C: Compiler<Language = L<P>>,
+        T: ArtifactOutput<CompilerContract = C::CompilerContract>,
+    >(
+        &self,
+        project: &Project<C, T>,
+        lockfile: &Path,
+    ) -> Result<(
+        HashMap<L<P>, HashMap<Version, HashMap<usize, Vec<usize>>>>,
+        Option<CompilerLock<L<P>>>,
+    )> {
+        let fs = &*project.paths.fs;
+        let lock =
+            if fs.exists(lockfile) { Some(CompilerLock::read_in(fs, lockfile)?) } else { None };
+
+        let mut reason = match &lock {
+            _ if project.lock_mode == LockMode::Update => Some("update requested".to_string()),
+            Some(lock) if lock.format != LOCK_FORMAT_VERSION => {
+                Some(format!("unsupported format `{}`", lock.format))
+            }
+            Some(_) => None,
+            None => Some("lock file does not exist".to_string()),
+        };
+        let usable_lock = lock.as_ref().filter(|_| reason.is_none());
+
+        let mut available = HashMap::new();
+        let mut locked = Vec::new();
+        let mut stale: HashMap<_, Vec<_>> = HashMap::new();
+        for idx in 0..self.edges.num_input_files {
+            let language = self.node(idx).data.language();
+            let Some(lock) = usable_lock else {
+                stale.entry(language).or_default().push(idx);
+                continue;
+            };
+            let path = utils::strip_prefix(self.node(idx).path(), &project.paths.root);
+            match self.apply_lock(project, lock, idx) {
+                Ok((version, profile_idx)) => {
+                    // the lock might have been written on a machine with other versions
+                    let versions = available
+                        .entry(language)
+                        .or_insert_with(|| Self::usable_versions(project, &language));
+                    if versions.iter().any(|v| *v.as_ref() == version) {
+                        locked.push((idx, language, version, profile_idx));
+                        continue;
+                    }
+                    let err =
+                        format!("locked version {version} of {} is not available", path.display());
+                    if project.lock_mode == LockMode::Auto {
+                        warn!("{err}, resolving its version again");
+                    }
+                    reason.get_or_insert(err);
+                }
+                Err(err) => {
+                    reason.get_or_insert(err);
+                }
+            }
+            stale.entry(language).or_default().push(idx);
+        }
+
+        if let Some(reason) = &reason {
+            if project.lock_mode == LockMode::Strict {
+                return Err(SolcError::msg(format!(
+                    "compiler lock file {} is out of date: {reason}",
+                    lockfile.display()
+                )));
+            }
+            debug!("resolving compiler versions again: {reason}");
+        } else {
+            trace!("using locked compiler versions from {}", lockfile.display());
+        }
+
+        // only the versions of files which aren't locked are resolved, so that adding a file
+        // doesn't change the versions of the others
+        let versioned_nodes = self.get_node_versions(project, stale)?;
+        let mut nodes = self.resolve_settings(project, versioned_nodes)?;
+        for (idx, language, version, profile_idx) in locked {
+            nodes
+                .entry(language)
+                .or_default()
+                .entry(version)
+                .or_default()
+                .entry(profile_idx)
+                .or_default()
+                .push(idx);
+        }
+
+        let mut new_lock = self.create_lock(project, &nodes);
+        if let Some(lock) = &lock {
+            for (path, req) in &lock.requirements {
+                let file = project.paths.root.join(path);
+                if !self.files().contains_key(&file) && fs.exists(&file) {
+                    new_lock.requirements.insert(path.clone(), req.clone());
+                }
+            }
+            for group in &lock.groups {
+                for source in &group.sources {
+                    let file = project.paths.root.join(source);
+                    if !self.files().contains_key(&file) && fs.exists(&file) {
+                        new_lock.insert(
+                            group.language,
+                            &group.version,
+                            &group.profile,
+                            source.clone(),
+                        );
+                    }
+                }
+            }
+            new_lock.sort();
+        }
+
+        // the lock file is never modified in strict mode, e.g. to drop entries of deleted files
+        let changed = project.lock_mode != LockMode::Strict && lock.as_ref() != Some(&new_lock);
+        let new_lock = changed.then_some(new_lock);
+        Ok((nodes, new_lock))
+    }
+
+    /// Creates the lock for the given resolved versions.
+    #[allow(clippy::complexity)]
+    fn create_lock<
+        C: Compiler<Language = L<P>>,
+        T: ArtifactOutput<CompilerContract = C::CompilerContract>,
+    >(
+        &self,
+        project: &Project<C, T>,
+        versioned_nodes: &HashMap<L<P>, HashMap<Version, HashMap<usize, Vec<usize>>>>,
+    ) -> CompilerLock<L<P>> {
+        let root = &project.paths.root;
+        let profiles = project.settings_profiles().collect::<Vec<_>>();
+
+        let mut lock = CompilerLock::default();
+        for idx in 0..self.nodes.len() {
+            if let Some(req) = self.version_requirement(idx, project) {
+                let path = utils::strip_prefix(self.node(idx).path(), root).to_path_buf();
+                lock.requirements.insert(path, req);
+            }
+        }
+        for (language, versions) in versioned_nodes {
+            for (version, profile_to_nodes) in versions {
+                for (profile_idx, nodes) in profile_to_nodes {
+                    for idx in nodes {
+                        let path = utils::strip_prefix(self.node(*idx).path(), root).to_path_buf();
+                        lock.insert(*language, version, profiles[*profile_idx].0, path);
+                    }
+                }
+            }
+        }
+        lock.sort();
+        lock
+    }
+
+    /// Returns the locked version and settings profile of the given input node.
+    ///
+    /// Returns the reason why the lock can't be used for the node if it's out of date.
+    #[allow(clippy::complexity)]
+    fn apply_lock<
+        C: Compiler<Language = L<P>>,
+        T: ArtifactOutput<CompilerContract = C::CompilerContract>,
+    >(
+        &self,
+        project: &Project<C, T>,
+        lock: &CompilerLock<L<P>>,
+        idx: usize,
+    ) -> std::result::Result<(Version, usize), String> {
+        let root = &project.paths.root;
+        for dep in self.node_ids(idx) {
+            let path = utils::strip_prefix(self.node(dep).path(), root);
+            if self.version_requirement(dep, project).as_ref() != lock.requirements.get(path) {
+                return Err(format!("version requirement of {} changed", path.display()));
+            }
+        }
+
+        let node = self.node(idx);
+        let path = utils::strip_prefix(node.path(), root);
+        let Some(group) = lock.group(path) else {
+            return Err(format!("{} is not locked", path.display()));
+        };
+        if group.language != node.data.language() {
+            return Err(format!("{} is locked for {}", path.display(), group.language));
+        }
+
+        // the requirements are unchanged, but the lock file might have been edited manually
+        if let Some(req) = self
+            .node_ids(idx)
+            .filter_map(|dep| self.version_requirement(dep, project))
+            .find(|req| !req.matches(&group.version))
+        {
+            return Err(format!(
+                "locked version {} of {} does not satisfy {req}",
+                group.version,
+                path.display()
+            ));
+        }
+
+        let profiles = project.settings_profiles().collect::<Vec<_>>();
+        let Some(profile_idx) = profiles.iter().position(|(name, _)| *name == group.profile) else {
+            return Err(format!("settings profile `{}` does not exist", group.profile));
+        };
+        let mut candidates = vec![(profile_idx, profiles[profile_idx])];
+        self.retain_compatible_profiles(idx, project, &mut candidates)?;
+
+        Ok((group.version.clone(), profile_idx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ProjectBuilder, ProjectPathsConfig,
+        vyper::{Vyper, VyperCompiler, VyperLanguage},
+    };
+    use std::fs;
+
+    fn project(root: &Path, mode: LockMode) -> Project<VyperCompiler> {
+        let vyper = |version| Vyper { path: "vyper".into(), version };
+        ProjectBuilder::<VyperCompiler>::default()
+            .paths(ProjectPathsConfig::dapptools(root).unwrap())
+            .lockfile(root.join(COMPILER_LOCK_FILENAME))
+            .set_lock_mode(mode)
+            .build(VyperCompiler::new([vyper(Version::new(0, 4, 0)), vyper(Version::new(0, 4, 1))]))
+            .unwrap()
+    }
+
+    /// Resolves the versions of all input files and writes the updated lock, like a successful
+    /// compilation does.
+    fn resolved_versions(project: &Project<VyperCompiler>) -> Result<BTreeMap<PathBuf, Version>> {
+        let graph = Graph::resolve(&project.paths)?;
+        let resolved = graph.into_sources_by_version(project)?;
+        if let Some(lock) = &resolved.lock {
+            lock.write(project.lockfile.as_ref().unwrap())?;
+        }
+        Ok(resolved
+            .sources
+            .into_values()
+            .flatten()
+            .flat_map(|(version, sources, _)| {
+                sources.0.into_keys().map(move |file| {
+                    let file = file.strip_prefix(&project.paths.root).unwrap().to_path_buf();
+                    (file, version.clone())
+                })
+            })
+            .collect())
+    }
+
+    #[test]
+    fn can_lock_versions() {
+        let tmp = utils::tempdir("lock").unwrap();
+        let root = tmp.path();
+        let lockfile = root.join(COMPILER_LOCK_FILENAME);
+        let (v0, v1) = (Version::new(0, 4, 0), Version::new(0, 4, 1));
+        let a = || (PathBuf::from("src/A.vy"), v0.clone());
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/A.vy"), "# pragma version ^0.4.0\n").unwrap();
+
+        // a missing lock file is an error in strict mode
+        let err = resolved_versions(&project(root, LockMode::Strict)).unwrap_err();
+        assert!(err.to_string().contains("lock file does not exist"), "{err}");
+        assert!(!lockfile.exists());
+
+        let project = project(root, LockMode::Auto);
+        assert_eq!(resolved_versions(&project).unwrap()[Path::new("src/A.vy")], v1);
+        let mut lock = CompilerLock::<VyperLanguage>::read(&lockfile).unwrap();
+        assert_eq!(lock.groups.len(), 1);
+        assert_eq!(lock.group(Path::new("src/A.vy")).unwrap().profile, "default");
+
+        // locked versions are used even if another version would be picked
+        lock.groups[0].version = v0.clone();
+        lock.write(&lockfile).unwrap();
+        assert_eq!(resolved_versions(&project).unwrap(), BTreeMap::from([a()]));
+        assert_eq!(
+            resolved_versions(&self::project(root, LockMode::Strict)).unwrap(),
+            BTreeMap::from([a()])
+        );
+
+        // new files are locked without changing the versions of the others
+        fs::write(root.join("src/B.vy"), "# pragma version ^0.4.0\n").unwrap();
+        assert!(resolved_versions(&self::project(root, LockMode::Strict)).is_err());
+        assert_eq!(
+            resolved_versions(&project).unwrap(),
+            BTreeMap::from([a(), ("src/B.vy".into(), v1.clone())])
+        );
+        let lock = CompilerLock::<VyperLanguage>::read(&lockfile).unwrap();
+        assert_eq!(lock.group(Path::new("src/A.vy")).unwrap().version, v0);
+        assert_eq!(lock.group(Path::new("src/B.vy")).unwrap().version, v1);
+
+        // the lock is stale once a pragma changes
+        fs::write(root.join("src/A.vy"), "# pragma version >=0.4.1\n").unwrap();
+        let err = resolved_versions(&self::project(root, LockMode::Strict)).unwrap_err();
+        assert!(err.to_string().contains("version requirement of src/A.vy changed"), "{err}");
+
+        assert_eq!(resolved_versions(&project).unwrap()[Path::new("src/A.vy")], v1);
+        let lock = CompilerLock::<VyperLanguage>::read(&lockfile).unwrap();
+        assert_eq!(lock.group(Path::new("src/A.vy")).unwrap().version, v1);
+        assert_eq!(lock.requirements[Path::new("src/A.vy")], ">=0.4.1".parse().unwrap());
+    }
+
+    #[test]
+    fn cannot_use_unavailable_locked_version() {
+        let tmp = utils::tempdir("lock").unwrap();
+        let root = tmp.path();
+        let lockfile = root.join(COMPILER_LOCK_FILENAME);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/A.vy"), "# pragma version ^0.4.0\n").unwrap();
+
+        let project = project(root, LockMode::Auto);
+        resolved_versions(&project).unwrap();
+        let mut lock = CompilerLock::<VyperLanguage>::read(&lockfile).unwrap();
+        lock.groups[0].version = Version::new(0, 4, 2);
+        lock.write(&lockfile).unwrap();
+
+        let err = resolved_versions(&self::project(root, LockMode::Strict)).unwrap_err();
+        assert!(err.to_string().contains("locked version 0.4.2 of src/A.vy is not available"));
+
+        assert_eq!(
+            resolved_versions(&project).unwrap()[Path::new("src/A.vy")],
+            Version::new(0, 4, 1)
+        );
+        let lock = CompilerLock::<VyperLanguage>::read(&lockfile).unwrap();
+        assert_eq!(lock.groups[0].version, Version::new(0, 4, 1));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn can_lock_versions_after_successful_compilation() {
+        use crate::{
+            solc::{Solc, SolcCompiler},
+            test_utils::stub_solc,
+        };
+
+        let tmp = utils::tempdir("lock").unwrap();
+        let root = tmp.path();
+        let lockfile = root.join(COMPILER_LOCK_FILENAME);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/A.sol"), "pragma solidity ^0.8.0; contract A {}").unwrap();
+
+        // `solc` stub which fails to compile the sources
+        let solc = stub_solc(
+            root,
+            r#"{"errors":[{"type":"TypeError","component":"general","severity":"error","message":"invalid"}]}"#,
+        );
+
+        let project = ProjectBuilder::<SolcCompiler>::default()
+            .paths(ProjectPathsConfig::dapptools(root).unwrap())
+            .lockfile(lockfile.clone())
+            .build(SolcCompiler::Specific(Solc::new_with_version(&solc, Version::new(0, 8, 25))))
+            .unwrap();
+        assert!(project.compile().unwrap().has_compiler_errors());
+        assert!(!lockfile.exists());
+
+        stub_solc(
+            root,
+            r#"{"contracts":{"src/A.sol":{"A":{"abi":[]}}},"sources":{"src/A.sol":{"id":0}}}"#,
+        );
+        project.compile().unwrap().assert_success();
+        let lock = CompilerLock::<crate::solc::SolcLanguage>::read(&lockfile).unwrap();
+        assert_eq!(lock.group(Path::new("src/A.sol")).unwrap().version, Version::new(0, 8, 25));
+    }
+}
@@ -46,7 +46,7 @@
 //! which is defined on a per source file basis.
 
 use crate::{
-    ArtifactOutput, CompilerSettings, Project, ProjectPathsConfig, SourceParser,
+    ArtifactOutput, CompilerSettings, Project, ProjectPathsConfig, SourceOverlay, SourceParser,
     compilers::{Compiler, CompilerVersion, ParsedSource},
     project::VersionedSources,
     resolver::parse::SolParser,
@@ -54,22 +54,42 @@ use crate::{
 use core::fmt;
 use foundry_compilers_artifacts::sources::{Source, Sources};
 use foundry_compilers_core::{
-    error::{Result, SolcError},
+    error::{Result, SolcError, VersionConflict, VersionConstraint, VersionSuggestion},
     utils,
 };
 use semver::{Version, VersionReq};
 use std::{
-    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     io,
     path::{Path, PathBuf},
 };
 use yansi::{Color, Paint};
 
+mod case;
+mod conflict;
+mod import;
+mod lock;
 pub mod parse;
+mod remapping;
+mod selection;
+mod suggest;
 mod tree;
+mod unused;
 
+pub use case::CaseMismatch;
+use case::{CaseChecker, case_conflicts};
+pub use import::{DefaultImportResolver, ImportResolver};
+pub(crate) use import::{default_import_resolvers, resolve_import};
+pub use lock::{COMPILER_LOCK_FILENAME, CompilerLock, LockMode, LockedGroup};
 pub use parse::SolImportAlias;
+pub(crate) use remapping::check_solc_precedence;
+pub use remapping::{ImportExplanation, RemappingOverlap, RemappingOverlapKind, RemappingReport};
+pub use selection::{
+    AllowList, MinimizeVersions, Newest, Oldest, PreferInstalled, VersionSelection,
+};
+pub use suggest::ImportSuggester;
 pub use tree::{Charset, TreeOptions, print};
+pub use unused::{UnusedImport, UnusedReport, find_unused_imports};
 
 /// Container for result of version and profile resolution of sources contained in [`Graph`].
 #[derive(Debug)]
@@ -89,6 +109,10 @@ pub struct ResolvedSources<'a, C: Compiler> {
     pub primary_profiles: HashMap<PathBuf, &'a str>,
     /// Graph edges.
     pub edges: GraphEdges<C::Parser>,
+    /// The updated compiler lock if the project uses a lock file which is missing or out of date.
+    ///
+    /// It's not written by the resolver, but once the sources compiled successfully.
+    pub lock: Option<CompilerLock<C::Language>>,
 }
 
 /// The underlying edges of the graph which only contains the raw relationship data.
@@ -120,6 +144,8 @@ pub struct GraphEdges<P: SourceParser> {
     num_input_files: usize,
     /// tracks all imports that we failed to resolve for a file
     unresolved_imports: HashSet<(PathBuf, PathBuf)>,
+    /// imports and files whose case doesn't match the files on disk
+    case_mismatches: Vec<CaseMismatch>,
     /// tracks additional include paths resolved by scanning all imports of the graph
     ///
     /// Absolute imports, like `import "src/Contract.sol"` are possible, but this does not play
@@ -140,6 +166,7 @@ impl<P: SourceParser> Default for GraphEdges<P> {
             parser: Default::default(),
             num_input_files: Default::default(),
             unresolved_imports: Default::default(),
+            case_mismatches: Default::default(),
             resolved_solc_include_paths: Default::default(),
         }
     }
@@ -186,6 +213,12 @@ impl<P: SourceParser> GraphEdges<P> {
         &self.unresolved_imports
     }
 
+    /// Returns all imports which only resolve if case is ignored and all files whose paths only
+    /// differ by case.
+    pub fn case_mismatches(&self) -> &[CaseMismatch] {
+        &self.case_mismatches
+    }
+
     /// Returns a list of nodes the given node index points to for the given kind.
     pub fn imported_nodes(&self, from: usize) -> &[usize] {
         &self.edges[from]
@@ -214,6 +247,27 @@ impl<P: SourceParser> GraphEdges<P> {
         }
     }
 
+    /// Returns all files which are overlaid by the given overlay or import an overlaid file,
+    /// directly or transitively.
+    pub fn overlay_dependents(&self, overlay: &SourceOverlay) -> HashSet<PathBuf> {
+        self.dependents(overlay.files().map(PathBuf::as_path))
+    }
+
+    /// Returns the given files and all files which import them, directly or transitively.
+    ///
+    /// Files which are not part of the graph are ignored.
+    pub fn dependents<'b>(&self, files: impl IntoIterator<Item = &'b Path>) -> HashSet<PathBuf> {
+        let mut queue: VecDeque<_> =
+            files.into_iter().filter_map(|file| self.indices.get(file).copied()).collect();
+        let mut dependents = HashSet::new();
+        while let Some(idx) = queue.pop_front() {
+            if dependents.insert(idx) {
+                queue.extend(self.rev_edges[idx].iter().copied());
+            }
+        }
+        dependents.into_iter().map(|idx| self.rev_indices[&idx].clone()).collect()
+    }
+
     /// Returns the id of the given file
     pub fn node_id(&self, file: &Path) -> usize {
         self.indices[file]
@@ -271,6 +325,11 @@ impl<P: SourceParser> Graph<P> {
         self.edges.parser()
     }
 
+    /// Returns the parser used to parse the sources.
+    pub fn parser_mut(&mut self) -> &mut P {
+        self.edges.parser_mut()
+    }
+
     /// Print the graph to `StdOut`
     pub fn print(&self) {
         self.print_with_options(Default::default())
@@ -372,16 +431,27 @@ impl<P: SourceParser> Graph<P> {
     }
 
     /// Resolves a number of sources within the given config
-    #[instrument(name = "Graph::resolve_sources", skip_all)]
     pub fn resolve_sources(
+        paths: &ProjectPathsConfig<<P::ParsedSource as ParsedSource>::Language>,
+        sources: Sources,
+    ) -> Result<Self> {
+        Self::resolve_sources_with(paths, sources, P::new(paths.with_language_ref()))
+    }
+
+    /// Resolves a number of sources within the given config with the given parser, see
+    /// [`Compiler::parser`](crate::Compiler::parser)
+    #[instrument(name = "Graph::resolve_sources", skip_all)]
+    pub fn resolve_sources_with(
         paths: &ProjectPathsConfig<<P::ParsedSource as ParsedSource>::Language>,
         mut sources: Sources,
+        mut parser: P,
     ) -> Result<Self> {
         /// checks if the given target path was already resolved, if so it adds its id to the list
         /// of resolved imports. If it hasn't been resolved yet, it queues in the file for
         /// processing
-        fn add_node<P: SourceParser>(
+        fn add_node<P: SourceParser, L>(
             parser: &mut P,
+            paths: &ProjectPathsConfig<L>,
             unresolved: &mut VecDeque<(PathBuf, Node<P::ParsedSource>)>,
             index: &mut HashMap<PathBuf, usize>,
             resolved_imports: &mut Vec<usize>,
@@ -391,7 +461,15 @@ impl<P: SourceParser> Graph<P> {
                 resolved_imports.push(idx);
             } else {
                 // imported file is not part of the input files
-                let node = parser.read(&target)?;
+                let node = if paths.overlay.contains(&target) || paths.fs.is_virtual() {
+                    let mut sources =
+                        Sources::from_iter([(target.clone(), paths.read_file(&target)?)]);
+                    parser.parse_sources(&mut sources)?.pop().map(|(_, node)| node).ok_or_else(
+                        || SolcError::msg(format!("unsupported source file {}", target.display())),
+                    )?
+                } else {
+                    parser.read(&target)?
+                };
                 unresolved.push_back((target.clone(), node));
                 let idx = index.len();
                 index.insert(target, idx);
@@ -402,8 +480,7 @@ impl<P: SourceParser> Graph<P> {
 
         // The cache relies on the absolute paths relative to the project root as cache keys.
         sources.make_absolute(&paths.root);
-
-        let mut parser = P::new(paths.with_language_ref());
+        paths.overlay.apply(&mut sources);
 
         // we start off by reading all input files, which includes all solidity files from the
         // source and test folder
@@ -427,7 +504,12 @@ impl<P: SourceParser> Graph<P> {
 
         // keep track of all unique paths that we failed to resolve to not spam the reporter with
         // the same path
-        let mut unresolved_imports = HashSet::new();
+        let mut unresolved_imports = HashMap::new();
+
+        // imports whose case doesn't match the files on disk, detected by comparing directory
+        // listings so that this also works on case-sensitive file systems
+        let mut case_checker = CaseChecker::new(paths);
+        let mut case_mismatches = Vec::new();
 
         // now we need to resolve all imports for the source file and those imported from other
         // locations
@@ -440,13 +522,39 @@ impl<P: SourceParser> Graph<P> {
             };
 
             for import_path in node.data.resolve_imports(paths, &mut resolved_solc_include_paths)? {
-                if let Some(err) = match paths.resolve_import_and_include_paths(
+                let resolved = paths.resolve_import_and_include_paths(
                     cwd,
                     &import_path,
                     &mut resolved_solc_include_paths,
-                ) {
+                );
+
+                let actual = match &resolved {
+                    Ok(import) => case_checker.actual_path(import),
+                    Err(_) if import_path.starts_with(".") || import_path.starts_with("..") => {
+                        case_checker
+                            .actual_path(&utils::clean_solidity_path(&cwd.join(&import_path)))
+                    }
+                    // the remapped import, or the import in one of the library directories
+                    Err(_) => paths
+                        .resolve_library_import(cwd, &import_path)
+                        .into_iter()
+                        .chain(paths.libraries.iter().map(|lib| lib.join(&import_path)))
+                        .find_map(|target| {
+                            case_checker.actual_path(&utils::clean_solidity_path(&target))
+                        }),
+                };
+                if let Some(actual) = actual {
+                    case_mismatches.push(CaseMismatch::Import {
+                        file: node.path.clone(),
+                        import: import_path.to_path_buf(),
+                        actual,
+                    });
+                }
+
+                if let Some(err) = match resolved {
                     Ok(import) => add_node(
                         &mut parser,
+                        paths,
                         &mut unresolved,
                         &mut index,
                         &mut resolved_imports,
@@ -455,8 +563,9 @@ impl<P: SourceParser> Graph<P> {
                     .err(),
                     Err(err) => Some(err),
                 } {
-                    unresolved_imports.insert((import_path.to_path_buf(), node.path.clone()));
-                    trace!("failed to resolve import component \"{:?}\" for {:?}", err, node.path)
+                    trace!("failed to resolve import component \"{:?}\" for {:?}", err, node.path);
+                    unresolved_imports
+                        .insert((import_path.to_path_buf(), node.path.clone()), err.to_string());
                 }
             }
 
@@ -475,15 +584,36 @@ impl<P: SourceParser> Graph<P> {
 
         if !unresolved_imports.is_empty() {
             // notify on all unresolved imports
-            crate::report::unresolved_imports(
+            let extensions = unresolved_imports
+                .keys()
+                .filter_map(|(import, _)| import.extension()?.to_str())
+                .collect::<BTreeSet<_>>();
+            let suggester =
+                ImportSuggester::new(paths, &extensions.into_iter().collect::<Vec<_>>());
+            let suggestions = unresolved_imports
+                .keys()
+                .map(|(import, file)| suggester.suggest(file.parent().unwrap_or(file), import))
+                .collect::<Vec<_>>();
+            crate::report::unresolved_import_errors(
                 &unresolved_imports
                     .iter()
-                    .map(|(i, f)| (i.as_path(), f.as_path()))
+                    .zip(&suggestions)
+                    .map(|(((import, file), error), suggestions)| crate::report::UnresolvedImport {
+                        import,
+                        file,
+                        error,
+                        suggestions,
+                    })
                     .collect::<Vec<_>>(),
                 &paths.remappings,
             );
         }
 
+        case_mismatches.extend(case_conflicts(index.keys()));
+        if !case_mismatches.is_empty() {
+            crate::report::case_mismatches(&case_mismatches);
+        }
+
         parser.finalize_imports(&mut nodes, &resolved_solc_include_paths)?;
 
         let edges = GraphEdges {
@@ -499,7 +629,8 @@ impl<P: SourceParser> Graph<P> {
                 .collect(),
             data: Default::default(),
             parser: Some(parser),
-            unresolved_imports,
+            unresolved_imports: unresolved_imports.into_keys().collect(),
+            case_mismatches,
             resolved_solc_include_paths,
         };
         Ok(Self { nodes, edges, root: paths.root.clone() })
@@ -512,6 +643,14 @@ impl<P: SourceParser> Graph<P> {
         Self::resolve_sources(paths, paths.read_input_files()?)
     }
 
+    /// Resolves the dependencies of a project's source contracts with the given parser
+    pub fn resolve_with(
+        paths: &ProjectPathsConfig<<P::ParsedSource as ParsedSource>::Language>,
+        parser: P,
+    ) -> Result<Self> {
+        Self::resolve_sources_with(paths, paths.read_input_files()?, parser)
+    }
+
     /// Consumes the nodes of the graph and returns all input files together with their appropriate
     /// version and the edges of the graph
     ///
@@ -555,8 +694,12 @@ impl<P: SourceParser> Graph<P> {
             }
         }
 
-        let versioned_nodes = self.get_input_node_versions(project)?;
-        let versioned_nodes = self.resolve_settings(project, versioned_nodes)?;
+        let (versioned_nodes, lock) = if let Some(lockfile) = &project.lockfile {
+            self.resolve_locked(project, lockfile)?
+        } else {
+            let versioned_nodes = self.get_input_node_versions(project)?;
+            (self.resolve_settings(project, versioned_nodes)?, None)
+        };
         let (nodes, edges) = self.split();
 
         let mut all_nodes = nodes.into_iter().enumerate().collect::<HashMap<_, _>>();
@@ -601,7 +744,12 @@ impl<P: SourceParser> Graph<P> {
             resulted_sources.insert(language, versioned_sources);
         }
 
-        Ok(ResolvedSources { sources: resulted_sources, primary_profiles: default_profiles, edges })
+        Ok(ResolvedSources {
+            sources: resulted_sources,
+            primary_profiles: default_profiles,
+            edges,
+            lock,
+        })
     }
 
     /// Writes the list of imported files into the given formatter:
@@ -696,7 +844,7 @@ impl<P: SourceParser> Graph<P> {
     }
 
     /// Filters incompatible versions from the `candidates`. It iterates over node imports and in
-    /// case if there is no compatible version it returns the latest seen node id.
+    /// case if there is no compatible version it returns an error explaining the conflict.
     fn retain_compatible_versions<
         C: Compiler,
         T: ArtifactOutput<CompilerContract = C::CompilerContract>,
@@ -705,8 +853,8 @@ impl<P: SourceParser> Graph<P> {
         idx: usize,
         candidates: &mut Vec<&CompilerVersion>,
         project: &Project<C, T>,
-    ) -> Result<(), String> {
-        let mut all_versions = candidates.clone();
+    ) -> Result<(), IncompatibleVersions> {
+        let all_versions = candidates.clone();
 
         let nodes: Vec<_> = self.node_ids(idx).collect();
         let mut failed_node_idx = None;
@@ -726,46 +874,105 @@ impl<P: SourceParser> Graph<P> {
             return Ok(());
         };
 
-        // This now keeps data for the node which were the last one before we had no candidates
-        // left. It means that there is a node directly conflicting with it in `nodes` coming
-        // before.
-        let failed_node = self.node(failed_node_idx);
-
+        // check if the version is even valid
         if let Err(version_err) =
             self.check_available_version(failed_node_idx, &all_versions, project)
         {
-            // check if the version is even valid
-            let f = utils::source_name(&failed_node.path, &self.root).display();
-            return Err(format!("Encountered invalid solc version in {f}: {version_err}"));
-        } else {
-            // if the node requirement makes sense, it means that there is at least one node
-            // which requirement conflicts with it
+            let f = utils::source_name(&self.node(failed_node_idx).path, &self.root).display();
+            return Err(IncompatibleVersions::Invalid(format!(
+                "Encountered invalid compiler version in {f}: {version_err}"
+            )));
+        }
 
-            // retain only versions compatible with the `failed_node`
-            if let Some(req) = self.version_requirement(failed_node_idx, project) {
-                all_versions.retain(|v| req.matches(v.as_ref()));
-            }
+        // if the node requirement makes sense, it means that there is at least one node which
+        // requirement conflicts with it, find the smallest set of such nodes
+        let constrained = nodes
+            .iter()
+            .filter_map(|&node| self.version_requirement(node, project).map(|req| (node, req)))
+            .collect::<Vec<_>>();
+        let reqs = constrained.iter().map(|(_, req)| req).collect::<Vec<_>>();
+        let mut versions = all_versions.iter().map(|v| v.as_ref()).collect::<Vec<_>>();
+        versions.sort_unstable();
+        versions.dedup();
+        let set = conflict::minimal_conflict(&reqs, &versions).expect("no candidates left; qed");
+
+        if let [single] = set[..] {
+            // a requirement which matches no available version at all
+            let (node, _) = constrained[single];
+            let f = utils::source_name(&self.node(node).path, &self.root).display();
+            let version_err = self
+                .check_available_version(node, &all_versions, project)
+                .expect_err("requirement is not satisfiable; qed");
+            return Err(IncompatibleVersions::Invalid(format!(
+                "Encountered invalid compiler version in {f}: {version_err}"
+            )));
+        }
 
-            // iterate over all the nodes once again and find the one incompatible
-            for node in &nodes {
-                if self.check_available_version(*node, &all_versions, project).is_err() {
-                    let mut msg = "Found incompatible versions:\n".white().to_string();
+        let source_name = |node: usize| utils::source_name(&self.node(node).path, &self.root);
+        let constraints = set
+            .iter()
+            .map(|&i| {
+                let (node, req) = &constrained[i];
+                VersionConstraint {
+                    file: source_name(*node).to_path_buf(),
+                    requirement: req.clone(),
+                    import_path: self
+                        .import_chain(idx, *node)
+                        .into_iter()
+                        .map(|node| source_name(node).to_path_buf())
+                        .collect(),
+                    excluded: conflict::excluded_versions(i, &set, &reqs, &versions),
+                }
+            })
+            .collect();
+
+        // prefer fixes outside of libraries, those are usually not under the user's control
+        let suggestion = [
+            set.iter()
+                .copied()
+                .filter(|&i| !project.paths.has_library_ancestor(&self.node(constrained[i].0).path))
+                .collect::<Vec<_>>(),
+            set.clone(),
+        ]
+        .into_iter()
+        .find_map(|set| conflict::narrowest_fix(&set, &reqs, &versions))
+        .map(|(i, suggested)| {
+            let (node, current) = constrained[i].clone();
+            VersionSuggestion { file: source_name(node).to_path_buf(), current, suggested }
+        });
 
-                    self.format_imports_list(
-                        idx,
-                        [*node, failed_node_idx].into(),
-                        project,
-                        &mut msg,
-                    )
-                    .unwrap();
-                    return Err(msg);
+        Err(IncompatibleVersions::Conflict(VersionConflict {
+            file: source_name(idx).to_path_buf(),
+            constraints,
+            suggestion,
+        }))
+    }
+
+    /// Returns the shortest chain of imports from `from` to `to`, both inclusive.
+    fn import_chain(&self, from: usize, to: usize) -> Vec<usize> {
+        let mut parents = HashMap::from([(from, from)]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(node) = queue.pop_front() {
+            if node == to {
+                break;
+            }
+            for &dep in self.imported_nodes(node) {
+                if let std::collections::hash_map::Entry::Vacant(entry) = parents.entry(dep) {
+                    entry.insert(node);
+                    queue.push_back(dep);
                 }
             }
         }
 
-        let mut msg = "Found incompatible versions:\n".white().to_string();
-        self.format_imports_list(idx, nodes.into_iter().collect(), project, &mut msg).unwrap();
-        Err(msg)
+        let mut chain = vec![to];
+        let mut node = to;
+        while node != from {
+            let Some(&parent) = parents.get(&node) else { break };
+            chain.push(parent);
+            node = parent;
+        }
+        chain.reverse();
+        chain
     }
 
     /// Filters profiles incompatible with the given node and its imports.
@@ -839,6 +1046,40 @@ impl<P: SourceParser> Graph<P> {
         nodes
     }
 
+    /// Returns the versions of the project's compiler which can be used for the given language,
+    /// sorted by version.
+    ///
+    /// If the project is `offline`, only installed versions and versions available in its
+    /// [offline mirror](Project::offline_mirror) are returned. Versions in the mirror are usable
+    /// even if the compiler doesn't know about them, e.g. releases newer than its release list.
+    fn usable_versions<
+        C: Compiler<Language = L<P>>,
+        T: ArtifactOutput<CompilerContract = C::CompilerContract>,
+    >(
+        project: &Project<C, T>,
+        language: &L<P>,
+    ) -> Vec<CompilerVersion> {
+        let mirrored = project
+            .offline_mirror
+            .as_deref()
+            .map(|mirror| project.compiler.mirrored_versions(language, mirror))
+            .unwrap_or_default();
+        let mut versions = project
+            .compiler
+            .available_versions(language)
+            .into_iter()
+            .filter(|v| !project.offline || v.is_installed() || mirrored.contains(v.as_ref()))
+            .collect::<Vec<_>>();
+        for version in mirrored {
+            if !versions.iter().any(|v| v.as_ref() == &version) {
+                versions.push(CompilerVersion::Remote(version));
+            }
+        }
+        versions.retain(|v| project.version_selection.allows(v.as_ref()));
+        versions.sort_by(|a, b| a.as_ref().cmp(b.as_ref()));
+        versions
+    }
+
     /// Returns a map of versions together with the input nodes that are compatible with that
     /// version.
     ///
@@ -856,28 +1097,34 @@ impl<P: SourceParser> Graph<P> {
     >(
         &self,
         project: &Project<C, T>,
+    ) -> Result<HashMap<L<P>, HashMap<Version, Vec<usize>>>> {
+        self.get_node_versions(project, self.input_nodes_by_language())
+    }
+
+    /// Same as [`Self::get_input_node_versions`], but only resolves the versions of the given
+    /// input nodes.
+    #[allow(clippy::type_complexity)]
+    fn get_node_versions<
+        C: Compiler<Language = L<P>>,
+        T: ArtifactOutput<CompilerContract = C::CompilerContract>,
+    >(
+        &self,
+        project: &Project<C, T>,
+        nodes_by_language: HashMap<L<P>, Vec<usize>>,
     ) -> Result<HashMap<L<P>, HashMap<Version, Vec<usize>>>> {
         trace!("resolving input node versions");
 
         let mut resulted_nodes = HashMap::new();
 
-        for (language, nodes) in self.input_nodes_by_language() {
+        for (language, nodes) in nodes_by_language {
             // this is likely called by an application and will be eventually printed so we don't
             // exit on first error, instead gather all the errors and return a bundled
             // error message instead
             let mut errors = Vec::new();
+            let mut conflicts = Vec::new();
 
-            // the sorted list of all versions
-            let all_versions = if project.offline {
-                project
-                    .compiler
-                    .available_versions(&language)
-                    .into_iter()
-                    .filter(|v| v.is_installed())
-                    .collect()
-            } else {
-                project.compiler.available_versions(&language)
-            };
+            // the list of all usable versions, sorted by version
+            let all_versions = Self::usable_versions(project, &language);
 
             if all_versions.is_empty() && !nodes.is_empty() {
                 return Err(SolcError::msg(format!(
@@ -896,17 +1143,13 @@ impl<P: SourceParser> Graph<P> {
                 // remove all incompatible versions from the candidates list by checking the node
                 // and all its imports
                 if let Err(err) = self.retain_compatible_versions(idx, &mut candidates, project) {
-                    errors.push(err);
+                    match err {
+                        IncompatibleVersions::Invalid(err) => errors.push(err),
+                        IncompatibleVersions::Conflict(conflict) => conflicts.push(conflict),
+                    }
                 } else {
-                    // found viable candidates, pick the most recent version that's already
-                    // installed
-                    let candidate =
-                        if let Some(pos) = candidates.iter().rposition(|v| v.is_installed()) {
-                            candidates[pos]
-                        } else {
-                            candidates.last().expect("not empty; qed.")
-                        }
-                        .clone();
+                    // found viable candidates, pick one according to the selection strategy
+                    let candidate = project.version_selection.select(&candidates).clone();
 
                     // also store all possible candidates to optimize the set
                     all_candidates.push((idx, candidates.into_iter().collect::<HashSet<_>>()));
@@ -921,7 +1164,10 @@ impl<P: SourceParser> Graph<P> {
             // detected multiple versions but there might still exist a single version that
             // satisfies all sources
             if versioned_nodes.len() > 1 {
-                versioned_nodes = Self::resolve_multiple_versions(all_candidates);
+                versioned_nodes = Self::resolve_multiple_versions(
+                    all_candidates,
+                    project.version_selection.as_ref(),
+                );
             }
 
             if versioned_nodes.len() == 1 {
@@ -931,6 +1177,11 @@ impl<P: SourceParser> Graph<P> {
                 );
             }
 
+            if errors.is_empty() && !conflicts.is_empty() {
+                debug!("found {} version conflicts", conflicts.len());
+                return Err(SolcError::VersionConflicts(conflicts));
+            }
+
             if errors.is_empty() {
                 trace!("resolved {} versions {:?}", versioned_nodes.len(), versioned_nodes.keys());
                 resulted_nodes.insert(
@@ -941,6 +1192,7 @@ impl<P: SourceParser> Graph<P> {
                         .collect(),
                 );
             } else {
+                errors.extend(conflicts.iter().map(ToString::to_string));
                 let s = errors.join("\n");
                 debug!("failed to resolve versions: {s}");
                 return Err(SolcError::msg(s));
@@ -968,6 +1220,23 @@ impl<P: SourceParser> Graph<P> {
                 for idx in nodes {
                     let mut profile_candidates =
                         project.settings_profiles().enumerate().collect::<Vec<_>>();
+
+                    // prefer the default profile of the node's group
+                    let path = &self.node(idx).path;
+                    if let Some(profile) =
+                        project.paths.input_group(path).and_then(|group| group.profile.as_deref())
+                    {
+                        let Some(pos) =
+                            profile_candidates.iter().position(|(_, (name, _))| *name == profile)
+                        else {
+                            let f = utils::source_name(path, &self.root).display();
+                            errors.push(format!("Missing profile \"{profile}\" for {f}"));
+                            continue;
+                        };
+                        let preferred = profile_candidates.remove(pos);
+                        profile_candidates.insert(0, preferred);
+                    }
+
                     if let Err(err) =
                         self.retain_compatible_profiles(idx, project, &mut profile_candidates)
                     {
@@ -998,7 +1267,17 @@ impl<P: SourceParser> Graph<P> {
     /// a high chance that the number of source files is <50, even for larger projects.
     fn resolve_multiple_versions(
         all_candidates: Vec<(usize, HashSet<&CompilerVersion>)>,
+        selection: &dyn VersionSelection,
     ) -> HashMap<CompilerVersion, Vec<usize>> {
+        // returns the given versions sorted by version
+        fn sorted<'a>(
+            versions: impl IntoIterator<Item = &'a CompilerVersion>,
+        ) -> Vec<&'a CompilerVersion> {
+            let mut v = versions.into_iter().collect::<Vec<_>>();
+            v.sort_unstable_by(|a, b| a.as_ref().cmp(b.as_ref()));
+            v
+        }
+
         // returns the intersection as sorted set of nodes
         fn intersection<'a>(
             mut sets: Vec<&HashSet<&'a CompilerVersion>>,
@@ -1012,31 +1291,15 @@ impl<P: SourceParser> Graph<P> {
                 result.retain(|item| sets.iter().all(|set| set.contains(item)));
             }
 
-            let mut v = result.into_iter().collect::<Vec<_>>();
-            v.sort_unstable();
-            v
-        }
-
-        /// returns the highest version that is installed
-        /// if the candidates set only contains uninstalled versions then this returns the highest
-        /// uninstalled version
-        fn remove_candidate(candidates: &mut Vec<&CompilerVersion>) -> CompilerVersion {
-            debug_assert!(!candidates.is_empty());
-
-            if let Some(pos) = candidates.iter().rposition(|v| v.is_installed()) {
-                candidates.remove(pos)
-            } else {
-                candidates.pop().expect("not empty; qed.")
-            }
-            .clone()
+            sorted(result)
         }
 
         let all_sets = all_candidates.iter().map(|(_, versions)| versions).collect();
 
         // find all versions that satisfy all nodes
-        let mut intersection = intersection(all_sets);
+        let intersection = intersection(all_sets);
         if !intersection.is_empty() {
-            let exact_version = remove_candidate(&mut intersection);
+            let exact_version = selection.select(&intersection).clone();
             let all_nodes = all_candidates.into_iter().map(|(node, _)| node).collect();
             trace!("resolved solc version compatible with all sources  \"{}\"", exact_version);
             return HashMap::from([(exact_version, all_nodes)]);
@@ -1045,24 +1308,62 @@ impl<P: SourceParser> Graph<P> {
         // no version satisfies all nodes
         let mut versioned_nodes: HashMap<_, _> = HashMap::new();
 
-        // try to minimize the set of versions, this is guaranteed to lead to `versioned_nodes.len()
-        // > 1` as no solc version exists that can satisfy all sources
-        for (node, versions) in all_candidates {
-            // need to sort them again
-            let mut versions = versions.into_iter().collect::<Vec<_>>();
-            versions.sort_unstable();
+        if selection.minimize_versions() {
+            // group the versions by the nodes they can compile, preferring versions compatible
+            // with the most nodes
+            let mut compatible: HashMap<&CompilerVersion, BTreeSet<usize>> = HashMap::new();
+            for (pos, (_, versions)) in all_candidates.iter().enumerate() {
+                for version in versions {
+                    compatible.entry(*version).or_default().insert(pos);
+                }
+            }
+            let mut groups: BTreeMap<BTreeSet<usize>, Vec<&CompilerVersion>> = BTreeMap::new();
+            for (version, nodes) in compatible {
+                groups.entry(nodes).or_default().push(version);
+            }
+            let mut groups = groups.into_iter().collect::<Vec<_>>();
+            groups.sort_by_key(|(nodes, _)| std::cmp::Reverse(nodes.len()));
+
+            let sets = groups.iter().map(|(nodes, _)| nodes).collect::<Vec<_>>();
+            let mut cover = selection::minimum_cover(&sets, all_candidates.len());
+            cover.sort_unstable();
+
+            // nodes compatible with multiple of the versions are assigned to the first one
+            let mut assigned = vec![false; all_candidates.len()];
+            for idx in cover {
+                let (nodes, versions) = &groups[idx];
+                let candidate = selection.select(&sorted(versions.iter().copied())).clone();
+                let nodes = nodes
+                    .iter()
+                    .filter(|&&pos| !std::mem::replace(&mut assigned[pos], true))
+                    .map(|&pos| all_candidates[pos].0)
+                    .collect();
+                versioned_nodes.insert(candidate, nodes);
+            }
+        } else {
+            // try to minimize the set of versions, this is guaranteed to lead to
+            // `versioned_nodes.len() > 1` as no solc version exists that can satisfy all sources
+            for (node, versions) in all_candidates {
+                let versions = sorted(versions);
 
-            let candidate = if let Some(idx) =
-                versions.iter().rposition(|v| versioned_nodes.contains_key(*v))
-            {
-                // use a version that's already in the set
-                versions.remove(idx).clone()
-            } else {
-                // use the highest version otherwise
-                remove_candidate(&mut versions)
-            };
+                // use a version that's already in the set if possible
+                let used = versions
+                    .iter()
+                    .copied()
+                    .filter(|v| versioned_nodes.contains_key(*v))
+                    .collect::<Vec<_>>();
+                let candidate = if used.is_empty() {
+                    selection.select(&versions)
+                } else {
+                    selection.select_reused(&used)
+                }
+                .clone();
 
-            versioned_nodes.entry(candidate).or_insert_with(|| Vec::with_capacity(1)).push(node);
+                versioned_nodes
+                    .entry(candidate)
+                    .or_insert_with(|| Vec::with_capacity(1))
+                    .push(node);
+            }
         }
 
         trace!(
@@ -1166,15 +1467,24 @@ impl<S: ParsedSource> fmt::Display for DisplayNode<'_, S> {
     }
 }
 
+/// Reasons why no compiler version can be found for an input file and its imports.
+#[derive(Debug)]
+enum IncompatibleVersions {
+    /// A requirement can't be satisfied by any available version.
+    Invalid(String),
+    /// The requirements of multiple files can't be satisfied together.
+    Conflict(VersionConflict),
+}
+
 /// Errors thrown when checking the solc version of a file
 #[derive(Debug, thiserror::Error)]
 #[allow(dead_code)]
 enum SourceVersionError {
     #[error("Failed to parse solidity version {0}: {1}")]
     InvalidVersion(String, SolcError),
-    #[error("No solc version exists that matches the version requirement: {0}")]
+    #[error("No compiler version exists that matches the version requirement: {0}")]
     NoMatchingVersion(VersionReq),
-    #[error("No solc version installed that matches the version requirement: {0}")]
+    #[error("No compiler version installed that matches the version requirement: {0}")]
     NoMatchingVersionOffline(VersionReq),
 }
 
@@ -1281,7 +1591,7 @@ src/Dapp.t.sol >=0.6.6
             Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test-data/incompatible-pragmas");
         let paths = ProjectPathsConfig::dapptools(&root).unwrap();
         let graph = Graph::<SolParser>::resolve(&paths).unwrap();
-        let Err(SolcError::Message(err)) = graph.get_input_node_versions(
+        let Err(SolcError::VersionConflicts(conflicts)) = graph.get_input_node_versions(
             &ProjectBuilder::<SolcCompiler>::default()
                 .paths(paths)
                 .build(SolcCompiler::AutoDetect)
@@ -1290,17 +1600,81 @@ src/Dapp.t.sol >=0.6.6
             panic!("expected error");
         };
 
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].constraints.len(), 2);
+
         snapbox::assert_data_eq!(
-            err,
+            conflicts[0].to_string(),
             snapbox::str![[r#"
-[37mFound incompatible versions:
-[0m[31msrc/A.sol =0.8.25[0m imports:
-    [37msrc/B.sol[0m
-    [31msrc/C.sol =0.7.0[0m
+Found incompatible versions for src/A.sol:
+    src/A.sol =0.8.25 rules out 0.7.0
+    src/C.sol =0.7.0 rules out 0.8.25, imported via src/A.sol -> src/B.sol -> src/C.sol
+help: change the version requirement of src/A.sol from `=0.8.25` to `>=0.7.0 <=0.8.25`
 "#]]
         );
     }
 
+    #[test]
+    fn can_resolve_versioned_dependencies() {
+        use foundry_compilers_core::vfs::MemoryFileSystem;
+        use std::sync::Arc;
+
+        let root = Path::new("/project");
+        let deps = root.join("dependencies");
+        let a = root.join("src/A.sol");
+        let erc20 = deps.join("solmate-6.2.0/src/tokens/ERC20.sol");
+        let fs = Arc::new(MemoryFileSystem::from_iter([
+            (a.clone(), "import \"forge-std/Test.sol\";\nimport \"solmate/tokens/ERC20.sol\";\n"),
+            (erc20.clone(), "import \"forge-std/Test.sol\";\n"),
+            (deps.join("forge-std-1.9.2/src/Test.sol"), "contract Test {}\n"),
+            (deps.join("solmate-6.2.0/lib/forge-std/src/Test.sol"), "contract Test {}\n"),
+        ]));
+        let paths = ProjectPathsConfig::builder().fs(fs).libs([&deps]).build_with_root(root);
+
+        let sources = paths.read_input_files().unwrap();
+        let graph = Graph::<SolParser>::resolve_sources(&paths, sources).unwrap();
+        assert!(graph.edges.unresolved_imports().is_empty());
+        let direct_imports = |file: &Path| {
+            graph
+                .imported_nodes(graph.edges.node_id(file))
+                .iter()
+                .map(|&id| graph.edges.node_path(id).to_path_buf())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(direct_imports(&a), [deps.join("forge-std-1.9.2/src/Test.sol"), erc20.clone()]);
+        assert_eq!(direct_imports(&erc20), [deps.join("solmate-6.2.0/lib/forge-std/src/Test.sol")]);
+    }
+
+    #[test]
+    fn can_skip_overlaid_import_of_unknown_language() {
+        use crate::{
+            Compiler,
+            multi::MultiCompilerLanguage,
+            registry::CompilerRegistry,
+            solc::{Solc, SolcCompiler},
+        };
+
+        let tmp = utils::tempdir("overlay").unwrap();
+        let root = tmp.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        let mut paths = ProjectPathsConfig::dapptools(root).unwrap();
+        let a = paths.sources.join("A.sol");
+        paths.overlay = SourceOverlay::new()
+            .with(&a, "import \"./B.txt\";\ncontract A {}\n")
+            .with(paths.sources.join("B.txt"), "contract B {}\n");
+
+        // the parser of a registry doesn't parse files which no registered compiler recognizes
+        let solc = SolcCompiler::Specific(Solc::new_with_version("solc", Version::new(0, 8, 28)));
+        let compiler = CompilerRegistry::<MultiCompilerLanguage>::new().with("solc", solc).unwrap();
+        let sources = paths.read_input_files().unwrap();
+        let graph = Graph::resolve_sources_with(&paths, sources, compiler.parser(&paths)).unwrap();
+        assert_eq!(graph.files().len(), 1);
+        assert_eq!(
+            graph.edges.unresolved_imports().iter().collect::<Vec<_>>(),
+            [&(PathBuf::from("./B.txt"), a)]
+        );
+    }
+
     #[cfg(target_os = "linux")]
     #[test]
     fn can_read_different_case() {
@@ -0,0 +1,358 @@
+//! Diagnostics for conflicting, broken and unused remappings.
+
+use super::{Graph, L};
+use crate::{ProjectPathsConfig, SourceParser};
+use foundry_compilers_artifacts::{SolcVfs, remappings::Remapping};
+use foundry_compilers_core::{
+    error::{Result, SolcError},
+    utils,
+};
+use std::{
+    collections::BTreeSet,
+    fmt,
+    path::{Component, Path, PathBuf},
+};
+
+/// Problems found in the remappings of a project.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RemappingReport {
+    /// Pairs of remappings which match some of the same imports but remap them to different
+    /// targets.
+    pub overlaps: Vec<RemappingOverlap>,
+    /// Remappings whose target doesn't exist.
+    pub missing_targets: Vec<Remapping>,
+    /// Remappings which are not applied to any import of the graph.
+    pub unused: Vec<Remapping>,
+}
+
+impl RemappingReport {
+    /// Returns `true` if no problems were found.
+    pub fn is_empty(&self) -> bool {
+        self.overlaps.is_empty() && self.missing_targets.is_empty() && self.unused.is_empty()
+    }
+}
+
+/// How two overlapping remappings interact.
+///
+/// Remappings are applied in order: the first remapping whose context contains the importing
+/// file and whose name is a prefix of the import wins, even if a later one has a longer prefix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RemappingOverlapKind {
+    /// The earlier remapping matches every import the later one matches, so the later one is
+    /// never applied.
+    Shadowed,
+    /// The earlier remapping is context-specific and replaces the later, broader one for imports
+    /// in its context.
+    ContextOverride,
+    /// The remappings match some of the same imports, and the earlier one wins for those.
+    Partial,
+}
+
+/// Two remappings which match some of the same imports but remap them to different targets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemappingOverlap {
+    /// The remapping that comes first, and wins for the imports both match.
+    pub first: Remapping,
+    /// The remapping that comes later.
+    pub second: Remapping,
+    /// How the remappings interact.
+    pub kind: RemappingOverlapKind,
+}
+
+impl fmt::Display for RemappingOverlap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { first, second, kind } = self;
+        match kind {
+            RemappingOverlapKind::Shadowed => {
+                write!(f, "\"{second}\" is never applied because \"{first}\" comes first")
+            }
+            RemappingOverlapKind::ContextOverride => {
+                write!(f, "\"{first}\" overrides \"{second}\" in its context")
+            }
+            RemappingOverlapKind::Partial => {
+                write!(f, "\"{first}\" takes precedence over \"{second}\" for shared imports")
+            }
+        }
+    }
+}
+
+/// How an import is resolved, see [`ProjectPathsConfig::explain_import`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportExplanation {
+    /// The file containing the import.
+    pub file: PathBuf,
+    /// The import path as written.
+    pub import: PathBuf,
+    /// The resolved path of the imported file, if the import can be resolved.
+    pub resolved: Option<PathBuf>,
+    /// The remapping applied to the import.
+    pub remapping: Option<Remapping>,
+    /// Remappings which match the import as well, but are not applied because `remapping` comes
+    /// first.
+    pub shadowed: Vec<Remapping>,
+}
+
+impl fmt::Display for ImportExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\" in \"{}\"", self.import.display(), self.file.display())?;
+        match &self.resolved {
+            Some(resolved) => write!(f, " resolves to \"{}\"", resolved.display())?,
+            None => write!(f, " can't be resolved")?,
+        }
+        match &self.remapping {
+            Some(remapping) => write!(f, " via remapping \"{remapping}\"")?,
+            None => write!(f, " without remappings")?,
+        }
+        for (i, remapping) in self.shadowed.iter().enumerate() {
+            let sep = if i == 0 { ", shadowing " } else { ", " };
+            write!(f, "{sep}\"{remapping}\"")?;
+        }
+        Ok(())
+    }
+}
+
+impl<L> ProjectPathsConfig<L> {
+    /// Explains how the `import` in `file` is resolved, and which remapping is applied to it.
+    ///
+    /// The remapping is determined the way the
+    /// [`DefaultImportResolver`](crate::resolver::DefaultImportResolver) resolves imports, while
+    /// the resolved path is determined by all configured [`Self::import_resolvers`].
+    pub fn explain_import(&self, file: &Path, import: &Path) -> ImportExplanation {
+        let cwd = file.parent().unwrap_or(file);
+        let resolved = self.resolve_import(cwd, import).ok();
+        let remapping = self.applied_remapping(cwd, import).cloned();
+
+        let shadowed = if is_relative_import(import) {
+            Vec::new()
+        } else {
+            self.remappings
+                .iter()
+                .filter(|r| Some(*r) != remapping.as_ref())
+                .filter(|r| self.is_in_remapping_context(r, cwd))
+                .filter(|r| import.starts_with(&r.name))
+                .cloned()
+                .collect()
+        };
+
+        ImportExplanation {
+            file: file.to_path_buf(),
+            import: import.to_path_buf(),
+            resolved,
+            remapping,
+            shadowed,
+        }
+    }
+
+    /// Returns the remapping which is applied to the `import` from `cwd`, if any.
+    fn applied_remapping(&self, cwd: &Path, import: &Path) -> Option<&Remapping> {
+        if is_relative_import(import) {
+            let resolved = utils::clean_solidity_path(&cwd.join(import));
+            self.remap_resolved_path(cwd, &resolved).map(|(r, _)| r)
+        } else {
+            self.remap_library_import(cwd, import).map(|(r, _)| r)
+        }
+    }
+}
+
+impl<P: SourceParser> Graph<P> {
+    /// Analyzes the configured remappings of `paths` against the imports of the graph.
+    ///
+    /// Reports remappings with overlapping names and contexts which remap the same imports to
+    /// different targets, remappings whose target doesn't exist, and remappings that are not
+    /// applied to any import of a Solidity file in the graph.
+    pub fn analyze_remappings(&self, paths: &ProjectPathsConfig<L<P>>) -> RemappingReport {
+        let remappings = &paths.remappings;
+
+        let mut overlaps = Vec::new();
+        for (i, first) in remappings.iter().enumerate() {
+            for second in &remappings[i + 1..] {
+                if let Some(kind) = overlap_kind(first, second) {
+                    overlaps.push(RemappingOverlap {
+                        first: first.clone(),
+                        second: second.clone(),
+                        kind,
+                    });
+                }
+            }
+        }
+
+        let missing_targets = remappings
+            .iter()
+            .filter(|r| !paths.file_exists(&paths.root.join(&r.path)))
+            .cloned()
+            .collect();
+
+        let mut used = BTreeSet::new();
+        for node in &self.nodes {
+            let file = node.path();
+            if file.extension().is_none_or(|ext| ext != "sol") {
+                continue;
+            }
+            let cwd = file.parent().unwrap_or(file);
+            for import in utils::find_import_paths(node.content()) {
+                if let Some(remapping) = paths.applied_remapping(cwd, Path::new(import.as_str())) {
+                    used.insert(remapping);
+                }
+            }
+        }
+        let unused = remappings.iter().filter(|r| !used.contains(r)).cloned().collect();
+
+        RemappingReport { overlaps, missing_targets, unused }
+    }
+}
+
+/// Returns an error if solc applies a later remapping to imports which an earlier one matches as
+/// well, see [`SolcVfs::remapping_precedence`].
+///
+/// Imports are resolved with the first matching remapping, so the resolved files would differ from
+/// the ones solc reads for these imports.
+pub(crate) fn check_solc_precedence(remappings: &[Remapping], vfs: &SolcVfs) -> Result<()> {
+    let converted = remappings.iter().map(|r| vfs.remapping(r)).collect::<Vec<_>>();
+    for (i, first) in converted.iter().enumerate() {
+        for (j, second) in converted.iter().enumerate().skip(i + 1) {
+            if overlap_kind(first, second).is_some()
+                && SolcVfs::remapping_precedence(second) >= SolcVfs::remapping_precedence(first)
+            {
+                return Err(SolcError::msg(format!(
+                    "solc applies remapping \"{}\" before \"{}\" which comes first, reorder \
+                     them so that the more specific one comes first",
+                    remappings[j], remappings[i]
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_relative_import(import: &Path) -> bool {
+    matches!(import.components().next(), Some(Component::CurDir | Component::ParentDir))
+}
+
+/// Returns how `first` and `second` interact if some import is matched by both and they remap it
+/// to different targets.
+fn overlap_kind(first: &Remapping, second: &Remapping) -> Option<RemappingOverlapKind> {
+    let contains_context =
+        |outer: &Remapping, inner: &Remapping| match (&outer.context, &inner.context) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(outer), Some(inner)) => Path::new(inner).starts_with(outer),
+        };
+    if !contains_context(first, second) && !contains_context(second, first) {
+        return None;
+    }
+
+    let (first_name, second_name) = (Path::new(&first.name), Path::new(&second.name));
+    let (short, long) = if second_name.starts_with(first_name) {
+        (first, second)
+    } else if first_name.starts_with(second_name) {
+        (second, first)
+    } else {
+        return None;
+    };
+
+    // Both remap the imports of the longer name to the same target.
+    let rest = Path::new(&long.name).strip_prefix(&short.name).unwrap();
+    if Path::new(&short.path).join(rest) == Path::new(&long.path) {
+        return None;
+    }
+
+    let kind = if contains_context(first, second) && second_name.starts_with(first_name) {
+        RemappingOverlapKind::Shadowed
+    } else if first.context.is_some() && first.context != second.context {
+        RemappingOverlapKind::ContextOverride
+    } else {
+        RemappingOverlapKind::Partial
+    };
+    Some(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolver::parse::SolParser;
+    use std::fs;
+
+    fn remapping(s: &str) -> Remapping {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn can_classify_overlaps() {
+        let kind = |first: &str, second: &str| overlap_kind(&remapping(first), &remapping(second));
+
+        assert_eq!(kind("a/=lib/a/", "b/=lib/b/"), None);
+        assert_eq!(kind("a/=lib/a/", "a/src/=lib/a/src/"), None);
+        assert_eq!(kind("a/=lib/a/", "a/=lib/b/"), Some(RemappingOverlapKind::Shadowed));
+        assert_eq!(kind("a/=lib/a/", "a/src/=lib/b/"), Some(RemappingOverlapKind::Shadowed));
+        assert_eq!(kind("a/src/=lib/b/", "a/=lib/a/"), Some(RemappingOverlapKind::Partial));
+        assert_eq!(
+            kind("lib/x:a/=lib/x/a/", "a/=lib/a/"),
+            Some(RemappingOverlapKind::ContextOverride)
+        );
+        assert_eq!(kind("a/=lib/a/", "lib/x:a/=lib/x/a/"), Some(RemappingOverlapKind::Shadowed));
+        assert_eq!(kind("lib/x:a/=lib/x/a/", "lib/y:a/=lib/y/a/"), None);
+    }
+
+    #[test]
+    fn can_check_solc_precedence() {
+        let vfs = SolcVfs::new("/", "/project");
+        let check = |remappings: &[&str]| {
+            check_solc_precedence(
+                &remappings.iter().map(|r| remapping(r)).collect::<Vec<_>>(),
+                &vfs,
+            )
+        };
+
+        assert!(check(&["a/src/=lib/b/", "a/=lib/a/", "b/=lib/b/"]).is_ok());
+        assert!(check(&["/project/lib/x:a/=lib/x/a/", "a/=lib/a/"]).is_ok());
+        assert!(check(&["a/=lib/a/", "a/src/=lib/a/src/"]).is_ok());
+        let err = check(&["a/=lib/a/", "a/src/=lib/b/"]).unwrap_err();
+        assert!(err.to_string().contains("\"a/src/=lib/b/\" before \"a/=lib/a/\""), "{err}");
+        assert!(check(&["a/=lib/a/", "lib/x:a/=lib/x/a/"]).is_err());
+        // the last one wins ties
+        assert!(check(&["a/=lib/a/", "a/=lib/b/"]).is_err());
+    }
+
+    #[test]
+    fn can_analyze_remappings() {
+        let tmp = utils::tempdir("remappings").unwrap();
+        let root = tmp.path();
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write("src/A.sol", "import 'dep/Dep.sol'; contract A {}");
+        write("lib/dep/src/Dep.sol", "import './Util.sol'; contract Dep {}");
+        write("lib/dep/src/Util.sol", "contract Util {}");
+        write("lib/other/src/Other.sol", "contract Other {}");
+
+        let mut paths = ProjectPathsConfig::dapptools(root).unwrap();
+        paths.remappings = ["dep/=lib/dep/src/", "dep/=lib/other/src/", "missing/=lib/missing/"]
+            .map(remapping)
+            .to_vec();
+        let graph = Graph::<SolParser>::resolve(&paths).unwrap();
+
+        let report = graph.analyze_remappings(&paths);
+        assert_eq!(
+            report.overlaps,
+            vec![RemappingOverlap {
+                first: paths.remappings[0].clone(),
+                second: paths.remappings[1].clone(),
+                kind: RemappingOverlapKind::Shadowed,
+            }]
+        );
+        assert_eq!(report.missing_targets, vec![paths.remappings[2].clone()]);
+        assert_eq!(report.unused, paths.remappings[1..].to_vec());
+
+        let explanation = paths.explain_import(&root.join("src/A.sol"), Path::new("dep/Dep.sol"));
+        assert_eq!(explanation.resolved, Some(root.join("lib/dep/src/Dep.sol")));
+        assert_eq!(explanation.remapping, Some(paths.remappings[0].clone()));
+        assert_eq!(explanation.shadowed, vec![paths.remappings[1].clone()]);
+
+        let explanation =
+            paths.explain_import(&root.join("lib/dep/src/Dep.sol"), Path::new("./Util.sol"));
+        assert_eq!(explanation.resolved, Some(root.join("lib/dep/src/Util.sol")));
+        assert_eq!(explanation.remapping, None);
+    }
+}
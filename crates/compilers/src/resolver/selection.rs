@@ -0,0 +1,297 @@
+//! Strategies for choosing among the compiler versions that satisfy the version requirements of
+//! a source file.
+
+use crate::CompilerVersion;
+use semver::Version;
+use std::{collections::BTreeSet, fmt};
+
+/// Decides which compiler versions are used to compile the project's sources.
+///
+/// Candidates passed to a strategy always satisfy the version requirements of the files they're
+/// selected for, and are sorted by version in ascending order.
+pub trait VersionSelection: dyn_clone::DynClone + fmt::Debug + Send + Sync {
+    /// Returns whether the given version may be used at all.
+    fn allows(&self, _version: &Version) -> bool {
+        true
+    }
+
+    /// Picks one of the given non-empty candidates.
+    fn select<'a>(&self, candidates: &[&'a CompilerVersion]) -> &'a CompilerVersion;
+
+    /// Picks one of the given non-empty candidates, which are all already selected for other
+    /// files, if no single version can compile all sources.
+    ///
+    /// Defaults to [`Self::select`].
+    fn select_reused<'a>(&self, candidates: &[&'a CompilerVersion]) -> &'a CompilerVersion {
+        self.select(candidates)
+    }
+
+    /// Whether to use as few distinct compiler versions as possible if no single version can
+    /// compile all sources, rather than selecting a version for each file in turn.
+    fn minimize_versions(&self) -> bool {
+        false
+    }
+}
+
+dyn_clone::clone_trait_object!(VersionSelection);
+
+/// Prefers the newest installed version, and the newest release if none of the candidates is
+/// installed.
+///
+/// This is the default strategy.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PreferInstalled;
+
+impl VersionSelection for PreferInstalled {
+    fn select<'a>(&self, candidates: &[&'a CompilerVersion]) -> &'a CompilerVersion {
+        candidates
+            .iter()
+            .rev()
+            .find(|v| v.is_installed())
+            .or_else(|| candidates.last())
+            .expect("not empty; qed.")
+    }
+
+    /// Picks the greatest candidate according to the ordering of [`CompilerVersion`], which
+    /// prefers versions that aren't installed yet since they have to be installed anyway.
+    fn select_reused<'a>(&self, candidates: &[&'a CompilerVersion]) -> &'a CompilerVersion {
+        candidates.iter().max().expect("not empty; qed.")
+    }
+}
+
+/// Prefers the newest release, regardless of whether it's installed.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Newest;
+
+impl VersionSelection for Newest {
+    fn select<'a>(&self, candidates: &[&'a CompilerVersion]) -> &'a CompilerVersion {
+        candidates.last().expect("not empty; qed.")
+    }
+}
+
+/// Prefers the oldest release, regardless of whether it's installed.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Oldest;
+
+impl VersionSelection for Oldest {
+    fn select<'a>(&self, candidates: &[&'a CompilerVersion]) -> &'a CompilerVersion {
+        candidates.first().expect("not empty; qed.")
+    }
+}
+
+/// Uses as few distinct versions as possible if no single version can compile all sources.
+///
+/// Among the smallest sets of versions which can compile all sources, versions compatible with
+/// the most files are preferred. Ties between versions compatible with the same files are broken
+/// like [`PreferInstalled`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MinimizeVersions;
+
+impl VersionSelection for MinimizeVersions {
+    fn select<'a>(&self, candidates: &[&'a CompilerVersion]) -> &'a CompilerVersion {
+        PreferInstalled.select(candidates)
+    }
+
+    fn minimize_versions(&self) -> bool {
+        true
+    }
+}
+
+/// Only uses the given versions.
+///
+/// Among the allowed candidates, versions are selected like [`PreferInstalled`].
+#[derive(Clone, Debug, Default)]
+pub struct AllowList {
+    pub versions: Vec<Version>,
+}
+
+impl AllowList {
+    /// Creates a new strategy which only uses the given versions.
+    pub fn new(versions: impl IntoIterator<Item = Version>) -> Self {
+        Self { versions: versions.into_iter().collect() }
+    }
+}
+
+impl VersionSelection for AllowList {
+    fn allows(&self, version: &Version) -> bool {
+        self.versions.contains(version)
+    }
+
+    fn select<'a>(&self, candidates: &[&'a CompilerVersion]) -> &'a CompilerVersion {
+        PreferInstalled.select(candidates)
+    }
+
+    fn select_reused<'a>(&self, candidates: &[&'a CompilerVersion]) -> &'a CompilerVersion {
+        PreferInstalled.select_reused(candidates)
+    }
+}
+
+/// Returns the indices of a smallest subset of `sets` whose union contains all elements from `0`
+/// to `len`, exclusive.
+///
+/// The search is exhaustive, which is fine for the few distinct sets of files that can be
+/// compiled by the same compiler version. Earlier sets are preferred if multiple smallest subsets
+/// exist.
+///
+/// # Panics
+///
+/// Panics if the union of all sets doesn't contain all elements.
+pub(crate) fn minimum_cover(sets: &[&BTreeSet<usize>], len: usize) -> Vec<usize> {
+    // a set which is part of another set is never needed
+    let candidates = (0..sets.len())
+        .filter(|&i| {
+            !sets.iter().enumerate().any(|(j, other)| {
+                i != j && sets[i].is_subset(other) && (sets[i].len() < other.len() || j < i)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let uncovered = (0..len).collect::<BTreeSet<_>>();
+    for limit in 1..=candidates.len() {
+        let mut cover = Vec::with_capacity(limit);
+        if find_cover(sets, &candidates, &uncovered, limit, &mut cover) {
+            return cover;
+        }
+    }
+    panic!("sets don't contain all elements");
+}
+
+/// Tries to cover the `uncovered` elements with at most `limit` of the `candidates`, which are
+/// added to `cover`.
+fn find_cover(
+    sets: &[&BTreeSet<usize>],
+    candidates: &[usize],
+    uncovered: &BTreeSet<usize>,
+    limit: usize,
+    cover: &mut Vec<usize>,
+) -> bool {
+    // branch on the element contained in the fewest sets
+    let containing =
+        |element: usize| candidates.iter().filter(move |&&i| sets[i].contains(&element));
+    let Some(element) = uncovered.iter().min_by_key(|&&element| containing(element).count()) else {
+        return true;
+    };
+    if limit == 0 {
+        return false;
+    }
+    for &i in containing(*element) {
+        cover.push(i);
+        if find_cover(sets, candidates, &(uncovered - sets[i]), limit - 1, cover) {
+            return true;
+        }
+        cover.pop();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Graph, resolver::parse::SolParser};
+    use std::collections::{HashMap, HashSet};
+
+    fn installed(v: &str) -> CompilerVersion {
+        CompilerVersion::Installed(v.parse().unwrap())
+    }
+
+    fn remote(v: &str) -> CompilerVersion {
+        CompilerVersion::Remote(v.parse().unwrap())
+    }
+
+    fn resolve(
+        candidates: &[(usize, Vec<&CompilerVersion>)],
+        selection: &dyn VersionSelection,
+    ) -> HashMap<String, Vec<usize>> {
+        let candidates = candidates
+            .iter()
+            .map(|(idx, versions)| (*idx, versions.iter().copied().collect::<HashSet<_>>()))
+            .collect();
+        Graph::<SolParser>::resolve_multiple_versions(candidates, selection)
+            .into_iter()
+            .map(|(version, mut nodes)| {
+                nodes.sort();
+                (version.to_string(), nodes)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn can_select_common_version() {
+        let (v1, v2, v3) = (installed("0.8.10"), remote("0.8.20"), installed("0.8.5"));
+        let candidates = [(0, vec![&v1, &v2, &v3]), (1, vec![&v1, &v2])];
+
+        assert_eq!(
+            resolve(&candidates, &PreferInstalled),
+            HashMap::from([("0.8.10".into(), vec![0, 1])])
+        );
+        assert_eq!(resolve(&candidates, &Newest), HashMap::from([("0.8.20".into(), vec![0, 1])]));
+        assert_eq!(resolve(&candidates, &Oldest), HashMap::from([("0.8.10".into(), vec![0, 1])]));
+    }
+
+    #[test]
+    fn can_minimize_versions() {
+        let versions = ["0.8.1", "0.8.2", "0.8.3", "0.8.4", "0.8.5"].map(installed);
+        let [v1, v2, v3, v4, v5] = versions.each_ref();
+        // every file but the last one can be compiled with 0.8.1, but each file prefers a newer
+        // version
+        let candidates = [(0, vec![v1, v2]), (1, vec![v1, v3]), (2, vec![v1, v4]), (3, vec![v5])];
+
+        assert_eq!(resolve(&candidates, &PreferInstalled).len(), 4);
+        assert_eq!(
+            resolve(&candidates, &MinimizeVersions),
+            HashMap::from([("0.8.1".into(), vec![0, 1, 2]), ("0.8.5".into(), vec![3])])
+        );
+    }
+
+    #[test]
+    fn can_find_minimum_number_of_versions() {
+        let versions = ["0.8.1", "0.8.2", "0.8.3"].map(installed);
+        let [v1, v2, v3] = versions.each_ref();
+        // 0.8.2 is compatible with the most files, but the files it can't compile need different
+        // versions, so picking it first would need three versions
+        let candidates = [
+            (0, vec![v1]),
+            (1, vec![v1, v2]),
+            (2, vec![v1, v2]),
+            (3, vec![v2, v3]),
+            (4, vec![v2, v3]),
+            (5, vec![v3]),
+        ];
+
+        assert_eq!(
+            resolve(&candidates, &MinimizeVersions),
+            HashMap::from([("0.8.1".into(), vec![0, 1, 2]), ("0.8.3".into(), vec![3, 4, 5])])
+        );
+    }
+
+    #[test]
+    fn reuses_selected_versions() {
+        let versions = ["0.7.6", "0.8.1", "0.8.2"].map(installed);
+        let [v1, v2, v3] = versions.each_ref();
+        let candidates = [(0, vec![v1]), (1, vec![v1, v2, v3]), (2, vec![v2, v3])];
+
+        assert_eq!(
+            resolve(&candidates, &Oldest),
+            HashMap::from([("0.7.6".into(), vec![0, 1]), ("0.8.1".into(), vec![2])])
+        );
+        assert_eq!(
+            resolve(&candidates, &Newest),
+            HashMap::from([("0.7.6".into(), vec![0, 1]), ("0.8.2".into(), vec![2])])
+        );
+
+        // the default strategy reuses the version which has to be installed
+        let (v1, v2, v3) = (installed("0.8.10"), remote("0.8.20"), installed("0.8.5"));
+        let candidates = [(0, vec![&v1]), (1, vec![&v2]), (2, vec![&v1, &v2, &v3])];
+        assert_eq!(
+            resolve(&candidates, &PreferInstalled),
+            HashMap::from([("0.8.10".into(), vec![0]), ("0.8.20".into(), vec![1, 2])])
+        );
+    }
+
+    #[test]
+    fn can_filter_allowed_versions() {
+        let allow = AllowList::new(["0.8.10".parse().unwrap()]);
+        assert!(allow.allows(&"0.8.10".parse().unwrap()));
+        assert!(!allow.allows(&"0.8.11".parse().unwrap()));
+    }
+}
@@ -0,0 +1,239 @@
+//! Suggested fixes for imports which couldn't be resolved.
+
+use crate::ProjectPathsConfig;
+use foundry_compilers_artifacts::remappings::Remapping;
+use foundry_compilers_core::error::{ImportFix, ImportSuggestion, SolcError};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsStr,
+    path::{Component, Path, PathBuf},
+};
+
+/// The maximum number of suggestions returned for a single import.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Suggests fixes for unresolved imports.
+///
+/// The project tree, including the library directories and `node_modules`, is searched for files
+/// with the same file name as the import. For each match a remapping or a corrected relative
+/// import is proposed, which also covers relative imports whose case doesn't match the file, and
+/// the suggestions are ranked by how many trailing path components of the import match and by how
+/// similar the proposed fix is to the import.
+#[derive(Debug)]
+pub struct ImportSuggester<'a, L> {
+    paths: &'a ProjectPathsConfig<L>,
+    /// All candidate files, by lowercase file name.
+    files: BTreeMap<String, BTreeSet<PathBuf>>,
+}
+
+impl<'a, L> ImportSuggester<'a, L> {
+    /// Indexes all files in the project with one of the given extensions.
+    pub fn new(paths: &'a ProjectPathsConfig<L>, extensions: &[&str]) -> Self {
+        let dirs = std::iter::once(&paths.root)
+            .chain(paths.libraries.iter().filter(|lib| !lib.starts_with(&paths.root)));
+        let files = dirs
+            .flat_map(|dir| paths.fs.source_files(dir, extensions))
+            .chain(paths.overlay.files().cloned());
+
+        let mut index = BTreeMap::<_, BTreeSet<_>>::new();
+        for file in files {
+            if let Some(name) = file.file_name() {
+                index.entry(name.to_string_lossy().to_lowercase()).or_default().insert(file);
+            }
+        }
+        Self { paths, files: index }
+    }
+
+    /// Adds the suggested fixes for the given `import` to the error of its resolution, see
+    /// [`SolcError::UnresolvedImport`].
+    ///
+    /// The `cwd` path is the parent dir of the file that includes the `import`.
+    pub fn with_suggestions(&self, error: SolcError, cwd: &Path, import: &Path) -> SolcError {
+        let suggestions = self.suggest(cwd, import);
+        if suggestions.is_empty() {
+            error
+        } else {
+            SolcError::UnresolvedImport { error: Box::new(error), suggestions }
+        }
+    }
+
+    /// Returns the suggested fixes for the given `import`, best first.
+    ///
+    /// The `cwd` path is the parent dir of the file that includes the `import`.
+    pub fn suggest(&self, cwd: &Path, import: &Path) -> Vec<ImportSuggestion> {
+        let Some(file_name) = import.file_name() else { return Vec::new() };
+        let Some(candidates) = self.files.get(&file_name.to_string_lossy().to_lowercase()) else {
+            return Vec::new();
+        };
+        let is_relative =
+            matches!(import.components().next(), Some(Component::CurDir | Component::ParentDir));
+        let components = normal_components(import);
+
+        let mut ranked = candidates
+            .iter()
+            .filter_map(|file| {
+                let matched = normal_components(file)
+                    .iter()
+                    .rev()
+                    .zip(components.iter().rev())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+
+                let (fix, distance) = if is_relative || components.len() < 2 {
+                    let relative = relative_import(cwd, file);
+                    let distance =
+                        levenshtein(&import.to_string_lossy(), &relative.to_string_lossy());
+                    (ImportFix::Import(relative), distance)
+                } else {
+                    // a remapping can't fix the case of the file name
+                    if file.file_name() != Some(file_name) {
+                        return None;
+                    }
+                    // keep at least the first component of the import as the remapping name
+                    let kept = matched.min(components.len() - 1);
+                    let name = components[..components.len() - kept].iter().collect::<PathBuf>();
+                    let target = file.ancestors().nth(kept).unwrap_or(file);
+                    let target = target.strip_prefix(&self.paths.root).unwrap_or(target);
+                    let distance = levenshtein(
+                        name.to_string_lossy().trim_start_matches('@'),
+                        &target.file_name().unwrap_or_default().to_string_lossy(),
+                    );
+                    let remapping = Remapping {
+                        context: None,
+                        name: format!("{}/", name.display()),
+                        path: format!("{}/", target.display()),
+                    };
+                    (ImportFix::Remapping(remapping.to_string()), distance)
+                };
+
+                Some(((Reverse(matched), distance), ImportSuggestion { fix, file: file.clone() }))
+            })
+            .collect::<Vec<_>>();
+        ranked.sort_by(|(a, a_suggestion), (b, b_suggestion)| {
+            a.cmp(b).then_with(|| a_suggestion.file.cmp(&b_suggestion.file))
+        });
+
+        let mut suggestions = Vec::<ImportSuggestion>::new();
+        for (_, suggestion) in ranked {
+            if !suggestions.iter().any(|s| s.fix == suggestion.fix) {
+                suggestions.push(suggestion);
+            }
+        }
+        suggestions.truncate(MAX_SUGGESTIONS);
+        suggestions
+    }
+}
+
+fn normal_components(path: &Path) -> Vec<&OsStr> {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(c) => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the relative import of `file` from the `cwd` directory.
+fn relative_import(cwd: &Path, file: &Path) -> PathBuf {
+    let cwd = cwd.components().collect::<Vec<_>>();
+    let file = file.components().collect::<Vec<_>>();
+    let common = cwd.iter().zip(&file).take_while(|(a, b)| a == b).count();
+
+    let mut import = PathBuf::new();
+    if common == cwd.len() {
+        import.push(".");
+    }
+    for _ in common..cwd.len() {
+        import.push("..");
+    }
+    import.extend(&file[common..]);
+    import
+}
+
+/// Returns the number of single character edits needed to turn `a` into `b`.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if a == *b { prev } else { 1 + prev.min(cur).min(row[j]) };
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_compilers_core::utils::{self, mkdir_or_touch};
+
+    #[test]
+    fn can_suggest_import_fixes() {
+        let tmp = utils::tempdir("suggest").unwrap();
+        let root = tmp.path();
+        mkdir_or_touch(
+            root,
+            &[
+                "src/Vault.sol",
+                "src/utils/Math.sol",
+                "lib/openzeppelin-contracts/contracts/token/ERC20/IERC20.sol",
+                "lib/forge-std/src/interfaces/IERC20.sol",
+                "node_modules/solmate/src/utils/SafeTransferLib.sol",
+            ],
+        );
+        let paths = ProjectPathsConfig::<()>::dapptools(root).unwrap();
+        let root = &paths.root;
+        let suggester = ImportSuggester::new(&paths, &["sol"]);
+
+        let suggestions = suggester.suggest(
+            &root.join("src"),
+            Path::new("@openzeppelin/contracts/token/ERC20/IERC20.sol"),
+        );
+        assert_eq!(
+            suggestions,
+            [
+                ImportSuggestion {
+                    fix: ImportFix::Remapping(
+                        "@openzeppelin/=lib/openzeppelin-contracts/".to_string()
+                    ),
+                    file: root.join("lib/openzeppelin-contracts/contracts/token/ERC20/IERC20.sol"),
+                },
+                ImportSuggestion {
+                    fix: ImportFix::Remapping(
+                        "@openzeppelin/contracts/token/ERC20/=lib/forge-std/src/interfaces/"
+                            .to_string()
+                    ),
+                    file: root.join("lib/forge-std/src/interfaces/IERC20.sol"),
+                },
+            ]
+        );
+
+        let suggestions =
+            suggester.suggest(&root.join("src"), Path::new("solmate/utils/SafeTransferLib.sol"));
+        assert_eq!(
+            suggestions[0].fix,
+            ImportFix::Remapping("solmate/=node_modules/solmate/src/".to_string())
+        );
+
+        let suggestions = suggester.suggest(&root.join("src/utils"), Path::new("./Vault.sol"));
+        assert_eq!(suggestions[0].fix, ImportFix::Import("../Vault.sol".into()));
+        let suggestions = suggester.suggest(&root.join("src"), Path::new("../Math.sol"));
+        assert_eq!(suggestions[0].fix, ImportFix::Import("./utils/Math.sol".into()));
+
+        let suggestions = suggester.suggest(&root.join("src"), Path::new("./vault.sol"));
+        assert_eq!(suggestions[0].fix, ImportFix::Import("./Vault.sol".into()));
+        assert!(suggester.suggest(&root.join("src"), Path::new("./Missing.sol")).is_empty());
+    }
+
+    #[test]
+    fn can_compute_levenshtein_distance() {
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("openzeppelin", "openzeppelin-contracts"), 10);
+    }
+}
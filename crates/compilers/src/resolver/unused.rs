@@ -0,0 +1,232 @@
+//! Detection of source files and imported symbols that don't contribute to the build.
+
+use super::{Graph, L};
+use crate::{ProjectPathsConfig, SourceParser, parse_one_source};
+use solar::parse::ast::{self, Visit};
+use std::{
+    collections::{HashSet, VecDeque},
+    ops::{ControlFlow, Range},
+    path::{Path, PathBuf},
+};
+
+/// Files and imported symbols that are part of the graph but are not needed by it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UnusedReport {
+    /// Source and library files which are not reachable from any test, script or configured
+    /// entry point.
+    ///
+    /// These files are still parsed, hashed and compiled on every build.
+    pub unreachable_files: Vec<PathBuf>,
+    /// Symbols imported by a project file which are never referenced in that file.
+    pub unused_imports: Vec<UnusedImport>,
+}
+
+impl UnusedReport {
+    /// Returns `true` if nothing unused was found.
+    pub fn is_empty(&self) -> bool {
+        self.unreachable_files.is_empty() && self.unused_imports.is_empty()
+    }
+}
+
+/// A symbol which is imported but never referenced, e.g. `B` in
+/// `import {A, B} from "x.sol";` if only `A` is used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnusedImport {
+    /// The file containing the import directive.
+    pub file: PathBuf,
+    /// The import path as written in the import directive.
+    pub import: PathBuf,
+    /// The local name of the symbol which is never used.
+    pub symbol: String,
+    /// The byte range of the import directive in `file`.
+    pub span: Range<usize>,
+}
+
+impl<P: SourceParser> Graph<P> {
+    /// Finds files and imported symbols that are part of the graph but don't contribute to it.
+    ///
+    /// All test and script files, as well as the given `entry_points`, are treated as roots. Every
+    /// other file (i.e. sources and libraries) that is not transitively imported by a root is
+    /// reported as unreachable.
+    ///
+    /// Unused imported symbols are only reported for Solidity files that don't belong to a library,
+    /// and only for imports that bind names (`import {A} from ...`, `import * as A from ...`, or
+    /// `import ... as A`), since plain imports can't be attributed to a symbol without full name
+    /// resolution.
+    ///
+    /// Symbols are matched by name without resolving scopes, so an imported symbol is considered
+    /// used if any identifier in the file has the same name, e.g. a local variable or parameter
+    /// shadowing it. Unused imports can therefore be missed, but used ones are never reported.
+    pub fn find_unused(
+        &self,
+        paths: &ProjectPathsConfig<L<P>>,
+        entry_points: &[PathBuf],
+    ) -> UnusedReport {
+        let mut roots = self
+            .files()
+            .iter()
+            .filter(|(path, _)| paths.is_test_or_script(path) || entry_points.contains(path))
+            .map(|(_, idx)| *idx)
+            .collect::<VecDeque<_>>();
+
+        let mut reachable = HashSet::new();
+        while let Some(idx) = roots.pop_front() {
+            if reachable.insert(idx) {
+                roots.extend(self.imported_nodes(idx).iter().copied());
+            }
+        }
+
+        let mut unreachable_files = (0..self.nodes.len())
+            .filter(|idx| !reachable.contains(idx))
+            .map(|idx| self.node(idx).path().to_path_buf())
+            .collect::<Vec<_>>();
+        unreachable_files.sort();
+
+        let mut unused_imports = self
+            .nodes
+            .iter()
+            .filter(|node| {
+                node.path().extension().is_some_and(|ext| ext == "sol")
+                    && !paths.has_library_ancestor(node.path())
+            })
+            .flat_map(|node| find_unused_imports(node.path(), node.content()))
+            .collect::<Vec<_>>();
+        unused_imports.sort_by(|a, b| (&a.file, a.span.start).cmp(&(&b.file, b.span.start)));
+
+        UnusedReport { unreachable_files, unused_imports }
+    }
+}
+
+/// Returns all symbols bound by the import directives of the given Solidity source which are never
+/// referenced in the rest of the file.
+///
+/// Returns an empty list if the file can't be parsed.
+pub fn find_unused_imports(file: &Path, content: &str) -> Vec<UnusedImport> {
+    parse_one_source(content, file, |sess, ast| {
+        let mut collector = IdentCollector::default();
+        let _ = collector.visit_source_unit(ast);
+
+        let mut unused = Vec::new();
+        for item in ast.items.iter() {
+            let ast::ItemKind::Import(import) = &item.kind else { continue };
+            let locals = match &import.items {
+                ast::ImportItems::Plain(alias) => alias.iter().copied().collect(),
+                ast::ImportItems::Glob(alias) => vec![*alias],
+                ast::ImportItems::Aliases(aliases) => {
+                    aliases.iter().map(|(id, alias)| alias.unwrap_or(*id)).collect()
+                }
+            };
+            let Ok(span) = sess.source_map().span_to_range(item.span) else { continue };
+            for local in locals {
+                let symbol = local.as_str();
+                if !collector.idents.contains(symbol) {
+                    unused.push(UnusedImport {
+                        file: file.to_path_buf(),
+                        import: PathBuf::from(import.path.value.as_str()),
+                        symbol: symbol.to_string(),
+                        span: span.clone(),
+                    });
+                }
+            }
+        }
+        unused
+    })
+    .unwrap_or_default()
+}
+
+/// Collects the names of all identifiers referenced outside of import directives.
+///
+/// Identifiers are collected by name only, declarations shadowing an imported symbol are not
+/// distinguished from references to it.
+#[derive(Default)]
+struct IdentCollector {
+    idents: HashSet<String>,
+}
+
+impl<'ast> Visit<'ast> for IdentCollector {
+    type BreakValue = ();
+
+    fn visit_import_directive(
+        &mut self,
+        _import: &'ast ast::ImportDirective<'ast>,
+    ) -> ControlFlow<Self::BreakValue> {
+        ControlFlow::Continue(())
+    }
+
+    fn visit_ident(&mut self, ident: &'ast ast::Ident) -> ControlFlow<Self::BreakValue> {
+        self.idents.insert(ident.as_str().to_string());
+        ControlFlow::Continue(())
+    }
+
+    fn visit_doc_comment(
+        &mut self,
+        doc_comment: &'ast ast::DocComment,
+    ) -> ControlFlow<Self::BreakValue> {
+        // `@inheritdoc` is the only NatSpec tag which references a symbol.
+        let comment = doc_comment.symbol.as_str();
+        if let Some((_, rest)) = comment.split_once("@inheritdoc")
+            && let Some(name) = rest.split_whitespace().next()
+        {
+            self.idents.insert(name.to_string());
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolver::parse::SolParser;
+    use foundry_compilers_core::utils;
+    use std::fs;
+
+    #[test]
+    fn can_find_unused_imports() {
+        let content = r#"
+import {A, B as C, D} from "./A.sol";
+import * as Lib from "./Lib.sol";
+import "./Unused.sol" as U;
+import "./Plain.sol";
+
+/// @inheritdoc D
+contract E is A {
+    function f() public { Lib.g(); }
+}
+"#;
+        let unused = find_unused_imports(Path::new("E.sol"), content);
+        assert_eq!(
+            unused.iter().map(|i| (i.symbol.as_str(), i.import.as_path())).collect::<Vec<_>>(),
+            vec![("C", Path::new("./A.sol")), ("U", Path::new("./Unused.sol"))]
+        );
+    }
+
+    #[test]
+    fn can_find_unreachable_files() {
+        let tmp = utils::tempdir("unused").unwrap();
+        let root = tmp.path();
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write("src/Used.sol", "import {Dep} from 'dep/Dep.sol'; contract Used is Dep {}");
+        write("src/Dead.sol", "import 'dep/DeadDep.sol'; contract Dead {}");
+        write("src/Entry.sol", "contract Entry {}");
+        write("lib/dep/src/Dep.sol", "contract Dep {}");
+        write("lib/dep/src/DeadDep.sol", "contract DeadDep {}");
+        write("test/Used.t.sol", "import {Used} from '../src/Used.sol'; contract T is Used {}");
+
+        let paths = ProjectPathsConfig::dapptools(root).unwrap();
+        let graph = Graph::<SolParser>::resolve(&paths).unwrap();
+
+        let report = graph.find_unused(&paths, &[paths.sources.join("Entry.sol")]);
+        assert_eq!(
+            report.unreachable_files,
+            vec![paths.root.join("lib/dep/src/DeadDep.sol"), paths.sources.join("Dead.sol")]
+        );
+        assert!(report.unused_imports.is_empty());
+
+        let report = graph.find_unused(&paths, &[]);
+        assert!(report.unreachable_files.contains(&paths.sources.join("Entry.sol")));
+    }
+}
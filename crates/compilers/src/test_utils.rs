@@ -0,0 +1,37 @@
+//! Stub compilers for tests which run the compilation pipeline without a real compiler.
+
+use crate::{
+    multi::MultiCompiler,
+    solc::{Solc, SolcCompiler},
+};
+use semver::Version;
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+/// `solc` output with a contract for each of `src/A.sol`, `src/B.sol` and `src/C.sol`.
+pub(crate) const ABC_OUTPUT: &str = r#"{"contracts":{"src/A.sol":{"A":{"abi":[]}},"src/B.sol":{"B":{"abi":[]}},"src/C.sol":{"C":{"abi":[]}}},"sources":{"src/A.sol":{"id":0},"src/B.sol":{"id":1},"src/C.sol":{"id":2}}}"#;
+
+/// Writes an executable shell script with the given body to `path`.
+pub(crate) fn write_script(path: &Path, body: &str) {
+    fs::write(path, format!("#!/bin/sh\n{body}")).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+/// Writes a `solc` stub to `dir` which ignores its input and prints the given output.
+pub(crate) fn stub_solc(dir: &Path, output: &str) -> PathBuf {
+    let path = dir.join("solc");
+    write_script(&path, &format!("cat > /dev/null\necho '{output}'\n"));
+    path
+}
+
+/// Returns a [`MultiCompiler`] which compiles Solidity with the `solc` binary at the given path.
+pub(crate) fn multi_compiler(solc: &Path) -> MultiCompiler {
+    MultiCompiler {
+        solc: Some(SolcCompiler::Specific(Solc::new_with_version(solc, Version::new(0, 8, 28)))),
+        vyper: None,
+        vyper_installations: Default::default(),
+    }
+}
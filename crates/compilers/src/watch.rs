@@ -0,0 +1,451 @@
+//! Recompiling a project when its files change, see [`Project::watch`].
+
+use crate::{
+    ArtifactOutput, Project, ProjectCompileOutput,
+    artifact_output::Artifacts,
+    cache::ProjectInMemoryCache,
+    compilers::{Compiler, Language},
+    project::ProjectCompiler,
+    resolver::GraphEdges,
+};
+use foundry_compilers_artifacts::{
+    remappings::{RelativeRemapping, Remapping},
+    sources::Sources,
+};
+use foundry_compilers_core::error::{Result, SolcError, SolcIoError};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::{
+    collections::{BTreeSet, HashMap},
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+/// The name of the remappings file in the project root which is watched.
+const REMAPPINGS_FILE: &str = "remappings.txt";
+
+/// A recompilation of the project, passed to the callback of [`Project::watch`].
+#[derive(Debug)]
+pub struct WatchUpdate<'a, C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>> {
+    /// Files which were created or modified since the previous compilation.
+    pub changed: &'a BTreeSet<PathBuf>,
+    /// Files which were removed since the previous compilation, including the old paths of
+    /// renamed files.
+    pub removed: &'a BTreeSet<PathBuf>,
+    /// Input files affected by the changes: changed input files and all files which import a
+    /// changed or removed file. All input files are affected by the initial compilation and
+    /// changes to the remappings. The affected files of a failed compilation are affected again by
+    /// the next changes.
+    pub affected: &'a BTreeSet<PathBuf>,
+    /// The output of the compilation of the affected files.
+    ///
+    /// Only affected files which were dirty are freshly compiled, see
+    /// [`ProjectCompileOutput::compiled_artifacts`]. The artifacts of files which are not affected
+    /// are not part of the output.
+    pub output: Result<&'a ProjectCompileOutput<C, T>, &'a SolcError>,
+}
+
+/// The state of [`Project::watch`] which is kept in memory between compilations.
+struct WatchState<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>> {
+    /// All input files.
+    sources: Sources,
+    /// The direct imports of all resolved files, patched with the graph of every compilation.
+    imports: HashMap<PathBuf, BTreeSet<PathBuf>>,
+    /// Files with imports which couldn't be resolved.
+    unresolved: BTreeSet<PathBuf>,
+    /// The remappings read from the remappings file.
+    file_remappings: Vec<Remapping>,
+    /// The cache of the previous compilation.
+    in_memory: Option<ProjectInMemoryCache<T, C>>,
+    changed: BTreeSet<PathBuf>,
+    removed: BTreeSet<PathBuf>,
+    affected: BTreeSet<PathBuf>,
+    /// Affected files of a compilation which failed, compiled again with the next changes.
+    pending: BTreeSet<PathBuf>,
+}
+
+impl<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>> WatchState<C, T> {
+    /// Returns the given files and all files which import them, directly or transitively.
+    fn dependents(&self, files: impl IntoIterator<Item = PathBuf>) -> BTreeSet<PathBuf> {
+        let mut importers = HashMap::<&Path, Vec<&PathBuf>>::new();
+        for (file, imports) in &self.imports {
+            for import in imports {
+                importers.entry(import).or_default().push(file);
+            }
+        }
+        let mut queue = files.into_iter().collect::<Vec<_>>();
+        let mut dependents = BTreeSet::new();
+        while let Some(file) = queue.pop() {
+            if let Some(importers) = importers.get(file.as_path()) {
+                queue.extend(importers.iter().map(|&importer| importer.clone()));
+            }
+            dependents.insert(file);
+        }
+        dependents
+    }
+
+    /// Takes over the graph and artifacts of a successful compilation.
+    fn compiled(&mut self, output: ProjectCompileOutput<C, T>) {
+        let (artifacts, edges) = output.into_artifacts_and_edges();
+        // only the files of the graph were resolved again
+        for id in edges.files() {
+            let file = edges.node_path(id);
+            let imports = edges
+                .imported_nodes(id)
+                .iter()
+                .map(|&import| edges.node_path(import).to_path_buf())
+                .collect();
+            self.imports.insert(file.to_path_buf(), imports);
+            self.unresolved.remove(file);
+        }
+        self.unresolved
+            .extend(edges.unresolved_imports().iter().map(|(_, importer)| importer.clone()));
+        if let Some(in_memory) = &mut self.in_memory {
+            // the artifacts of the files which weren't compiled are already kept in memory
+            for (file, contracts) in artifacts.0 {
+                let entry = in_memory.artifacts.0.entry(file).or_default();
+                for (name, artifacts) in contracts {
+                    entry.entry(name).or_default().extend(artifacts);
+                }
+            }
+        }
+        self.pending.clear();
+    }
+
+    /// Forgets the cache after a failed compilation, so that it's read from disk again.
+    fn failed(&mut self) {
+        self.in_memory = None;
+        self.pending.extend(self.affected.iter().cloned());
+    }
+}
+
+impl<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>> Project<C, T> {
+    /// Compiles the project and recompiles it whenever its sources, tests, scripts, libraries or
+    /// remappings file change, until `f` returns [`ControlFlow::Break`].
+    ///
+    /// Filesystem events are debounced: a recompilation starts once no event was received for
+    /// `debounce`. Between compilations, the input files, the imports of all files and the cache
+    /// are kept in memory. Only the changed files are read again, and only the affected input
+    /// files and their imports are resolved and passed to the compiler.
+    ///
+    /// Failing compilations are passed to `f` and don't stop watching. Changes to the remappings
+    /// file replace the remappings previously read from it.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use foundry_compilers::Project;
+    /// use std::{ops::ControlFlow, time::Duration};
+    ///
+    /// let mut project = Project::builder().build(Default::default())?;
+    /// project.watch(Duration::from_millis(100), |update| {
+    ///     match update.output {
+    ///         Ok(output) => println!("{} files affected\n{output}", update.affected.len()),
+    ///         Err(err) => println!("{err}"),
+    ///     }
+    ///     ControlFlow::Continue(())
+    /// })?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn watch<F>(&mut self, debounce: Duration, mut f: F) -> Result<()>
+    where
+        F: FnMut(WatchUpdate<'_, C, T>) -> ControlFlow<()>,
+    {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx).map_err(watch_error)?;
+        let dirs = [&self.paths.sources, &self.paths.tests, &self.paths.scripts]
+            .into_iter()
+            .chain(&self.paths.libraries)
+            .filter(|dir| dir.is_dir())
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        for dir in &dirs {
+            watcher.watch(dir, RecursiveMode::Recursive).map_err(watch_error)?;
+        }
+        // the remappings file is watched through the root, to notice it being created
+        if !dirs.iter().any(|dir| self.paths.root.starts_with(dir)) {
+            watcher.watch(&self.paths.root, RecursiveMode::NonRecursive).map_err(watch_error)?;
+        }
+
+        let mut state = self.watch_state()?;
+        loop {
+            if !state.affected.is_empty() {
+                trace!(affected = state.affected.len(), "recompiling");
+                let result = self.compile_affected(&mut state);
+                let flow = f(WatchUpdate {
+                    changed: &state.changed,
+                    removed: &state.removed,
+                    affected: &state.affected,
+                    output: result.as_ref(),
+                });
+                match result {
+                    Ok(output) => state.compiled(output),
+                    Err(_) => state.failed(),
+                }
+                if flow.is_break() {
+                    return Ok(());
+                }
+            }
+
+            // wait for the first event and then until no more events are received
+            let Ok(event) = rx.recv() else { return Ok(()) };
+            let events = std::iter::once(event)
+                .chain(std::iter::from_fn(|| rx.recv_timeout(debounce).ok()))
+                .filter_map(|event| {
+                    event.map_err(|err| warn!("failed to watch files: {err}")).ok()
+                });
+            self.handle_events(&mut state, events)?;
+        }
+    }
+
+    /// Reads the input files and the remappings file for the initial compilation, which affects
+    /// all input files.
+    fn watch_state(&self) -> Result<WatchState<C, T>> {
+        let sources = self.paths.read_input_files()?;
+        let affected = sources.keys().cloned().collect::<BTreeSet<_>>();
+        Ok(WatchState {
+            file_remappings: self.read_remappings_file()?,
+            sources,
+            imports: HashMap::new(),
+            unresolved: BTreeSet::new(),
+            in_memory: None,
+            changed: affected.clone(),
+            removed: BTreeSet::new(),
+            affected,
+            pending: BTreeSet::new(),
+        })
+    }
+
+    /// Compiles the affected input files, reusing the cache kept in memory.
+    fn compile_affected(&self, state: &mut WatchState<C, T>) -> Result<ProjectCompileOutput<C, T>> {
+        let sources = state
+            .affected
+            .iter()
+            .filter_map(|file| Some((file.clone(), state.sources.get(file)?.clone())))
+            .collect();
+        ProjectCompiler::with_sources(self, sources)?
+            .with_in_memory_cache(&mut state.in_memory)
+            .compile()
+    }
+
+    /// Updates the state with the given filesystem events and determines the affected files.
+    fn handle_events(
+        &mut self,
+        state: &mut WatchState<C, T>,
+        events: impl IntoIterator<Item = notify::Event>,
+    ) -> Result<()> {
+        let fs = &*self.paths.fs;
+        let remappings_file = self.paths.root.join(REMAPPINGS_FILE);
+        let (changed, removed) = (&mut state.changed, &mut state.removed);
+        changed.clear();
+        removed.clear();
+        let mut remappings_changed = false;
+        for event in events {
+            if matches!(event.kind, EventKind::Access(_)) {
+                continue;
+            }
+            for path in event.paths {
+                if path == remappings_file {
+                    remappings_changed = true;
+                } else if fs.is_dir(&path) {
+                    // created or moved in directory
+                    changed.extend(fs.source_files(&path, C::Language::FILE_EXTENSIONS));
+                } else if is_source_file::<C::Language>(&path) {
+                    if fs.is_file(&path) { &mut *changed } else { &mut *removed }.insert(path);
+                } else if !fs.exists(&path) {
+                    // removed or moved out directory
+                    removed.extend(
+                        state
+                            .sources
+                            .keys()
+                            .chain(state.imports.keys())
+                            .filter(|file| file.starts_with(&path))
+                            .cloned(),
+                    );
+                }
+            }
+        }
+        // files which were changed and removed again
+        changed.retain(|file| fs.is_file(file));
+        removed.retain(|file| !changed.contains(file));
+
+        for file in &state.removed {
+            state.sources.remove(file);
+            state.imports.remove(file);
+            state.unresolved.remove(file);
+        }
+        for file in &state.changed {
+            if self.paths.is_input_file(file) {
+                state.sources.insert(file.clone(), self.paths.read_file(file)?);
+            }
+        }
+
+        let mut affected = state.dependents(state.changed.iter().chain(&state.removed).cloned());
+        affected.extend(state.pending.iter().cloned());
+        if !state.changed.is_empty() {
+            // created files may resolve previously unresolved imports
+            affected.extend(state.unresolved.iter().cloned());
+        }
+
+        if remappings_changed {
+            let remappings = self.read_remappings_file()?;
+            if remappings != state.file_remappings {
+                debug!("remappings changed");
+                self.paths
+                    .remappings
+                    .retain(|remapping| !state.file_remappings.contains(remapping));
+                self.paths.remappings.extend(remappings.iter().cloned());
+                state.file_remappings = remappings;
+                affected = state.sources.keys().cloned().collect();
+            }
+        }
+        affected.retain(|file| state.sources.contains_key(file));
+        state.affected = affected;
+        Ok(())
+    }
+
+    /// Reads the remappings from the remappings file in the root, if it exists, with paths
+    /// relative to the root.
+    fn read_remappings_file(&self) -> Result<Vec<Remapping>> {
+        let file = self.paths.root.join(REMAPPINGS_FILE);
+        if !self.paths.fs.is_file(&file) {
+            return Ok(Vec::new());
+        }
+        let content =
+            self.paths.fs.read_to_string(&file).map_err(|err| SolcIoError::new(err, &file))?;
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let remapping = line.parse::<Remapping>().map_err(|err| {
+                    SolcError::msg(format!("invalid remapping in {}: {err}", file.display()))
+                })?;
+                Ok(RelativeRemapping::new(remapping, &self.paths.root)
+                    .to_remapping(self.paths.root.clone()))
+            })
+            .collect()
+    }
+}
+
+impl<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>>
+    ProjectCompileOutput<C, T>
+{
+    /// Returns all artifacts, compiled and cached, and the source graph.
+    fn into_artifacts_and_edges(self) -> (Artifacts<T::Artifact>, GraphEdges<C::Parser>) {
+        let Self { compiled_artifacts, mut cached_artifacts, edges, .. } = self;
+        for (file, contracts) in compiled_artifacts.0 {
+            let entry = cached_artifacts.0.entry(file).or_default();
+            for (name, artifacts) in contracts {
+                entry.entry(name).or_default().extend(artifacts);
+            }
+        }
+        (cached_artifacts, edges)
+    }
+}
+
+/// Returns whether the given path has the extension of a source file of the language.
+fn is_source_file<L: Language>(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| L::FILE_EXTENSIONS.contains(&ext))
+}
+
+fn watch_error(err: notify::Error) -> SolcError {
+    SolcError::msg(format!("failed to watch files: {err}"))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::{
+        ProjectBuilder, ProjectPathsConfig,
+        multi::MultiCompiler,
+        test_utils::{ABC_OUTPUT, multi_compiler, stub_solc},
+    };
+    use foundry_compilers_core::utils;
+    use notify::{
+        Event,
+        event::{AccessKind, DataChange, ModifyKind, RenameMode},
+    };
+    use std::fs;
+
+    #[test]
+    fn can_recompile_affected_files() {
+        let tmp = utils::tempdir("watch").unwrap();
+        // `solc` stub which returns a contract for each source.
+        let solc = stub_solc(tmp.path(), ABC_OUTPUT);
+
+        let root = tmp.path().join("project");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/A.sol"), "pragma solidity ^0.8.0;\ncontract A {}\n").unwrap();
+        fs::write(
+            root.join("src/B.sol"),
+            "pragma solidity ^0.8.0;\nimport \"./A.sol\";\ncontract B is A {}\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/C.sol"), "pragma solidity ^0.8.0;\ncontract C {}\n").unwrap();
+
+        let compiler = multi_compiler(&solc);
+        let mut project = ProjectBuilder::<MultiCompiler>::default()
+            .paths(ProjectPathsConfig::dapptools(&root).unwrap())
+            .build(compiler)
+            .unwrap();
+        let [a, b, c, d] =
+            ["A", "B", "C", "D"].map(|name| project.paths.sources.join(format!("{name}.sol")));
+        let event = |kind, paths: &[&PathBuf]| {
+            paths.iter().fold(Event::new(kind), |event, &path| event.add_path(path.clone()))
+        };
+        let modified = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+
+        let mut state = project.watch_state().unwrap();
+        assert_eq!(state.affected, BTreeSet::from([a.clone(), b.clone(), c.clone()]));
+        let output = project.compile_affected(&mut state).unwrap();
+        assert!(!output.has_compiler_errors(), "{output}");
+        state.compiled(output);
+
+        // the cache is kept in memory
+        fs::remove_file(project.cache_path()).unwrap();
+        fs::write(&a, "pragma solidity ^0.8.0;\ncontract A { uint x; }\n").unwrap();
+        project.handle_events(&mut state, [event(modified, &[&a])]).unwrap();
+        assert_eq!(state.changed, BTreeSet::from([a.clone()]));
+        assert_eq!(state.affected, BTreeSet::from([a.clone(), b.clone()]));
+        let output = project.compile_affected(&mut state).unwrap();
+        assert!(output.compiled_artifacts().find_first("A").is_some());
+        assert!(output.compiled_artifacts().find_first("B").is_some());
+        assert!(output.find_first("C").is_none());
+        state.compiled(output);
+
+        // the artifacts of `C.sol` are kept in memory while it's not affected
+        fs::remove_file(project.cache_path()).unwrap();
+        project.handle_events(&mut state, [event(modified, &[&c])]).unwrap();
+        assert_eq!(state.affected, BTreeSet::from([c.clone()]));
+        let output = project.compile_affected(&mut state).unwrap();
+        assert!(output.compiled_artifacts().is_empty());
+        assert!(output.cached_artifacts().find_first("C").is_some());
+        state.compiled(output);
+
+        fs::rename(&b, &d).unwrap();
+        let renamed = EventKind::Modify(ModifyKind::Name(RenameMode::Both));
+        project.handle_events(&mut state, [event(renamed, &[&b, &d])]).unwrap();
+        assert_eq!(state.changed, BTreeSet::from([d.clone()]));
+        assert_eq!(state.removed, BTreeSet::from([b]));
+        assert_eq!(state.affected, BTreeSet::from([d.clone()]));
+        let output = project.compile_affected(&mut state).unwrap();
+        state.compiled(output);
+
+        // changed remappings affect all input files
+        let remappings_file = root.join(REMAPPINGS_FILE);
+        fs::write(&remappings_file, "lib/=lib/forge-std/src/\n").unwrap();
+        project.handle_events(&mut state, [event(modified, &[&remappings_file])]).unwrap();
+        assert!(state.changed.is_empty());
+        assert_eq!(state.affected, BTreeSet::from([a.clone(), c, d]));
+        assert!(project.paths.remappings.iter().any(|remapping| remapping.name == "lib/"));
+
+        // access events are ignored
+        project
+            .handle_events(&mut state, [event(EventKind::Access(AccessKind::Any), &[&a])])
+            .unwrap();
+        assert!(state.affected.is_empty());
+    }
+}
@@ -0,0 +1,375 @@
+//! Workspaces of several projects which are compiled together.
+
+use crate::{
+    ArtifactOutput, ConfigurableArtifacts, Graph, InputGroup, InputKind, Project, ProjectBuilder,
+    ProjectCompileOutput, ProjectPathsConfig,
+    compilers::{Compiler, multi::MultiCompilerLanguage},
+    multi::MultiCompiler,
+    project::ProjectCompiler,
+};
+use foundry_compilers_artifacts::{remappings::Remapping, sources::Sources};
+use foundry_compilers_core::error::{Result, SolcError, SolcIoError};
+use std::{collections::HashSet, path::Path};
+
+/// A member project of a [`Workspace`].
+#[derive(Clone, Debug)]
+pub struct WorkspaceMember<L = MultiCompilerLanguage> {
+    /// The name of the member, which is also the remapping the other members import its sources
+    /// with
+    pub name: String,
+    /// The layout of the member
+    pub paths: ProjectPathsConfig<L>,
+    /// The settings profile the files of the member are compiled with by default
+    pub profile: Option<String>,
+}
+
+impl<L> WorkspaceMember<L> {
+    /// Creates a new member with the given name and layout.
+    pub fn new(name: impl Into<String>, paths: ProjectPathsConfig<L>) -> Self {
+        Self { name: name.into(), paths, profile: None }
+    }
+
+    /// Sets the settings profile the files of the member are compiled with by default.
+    #[must_use]
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+}
+
+/// Several projects which import each other and are compiled as a single [`Project`].
+///
+/// The sources, tests and scripts of every member become [`InputGroup`]s of the workspace project,
+/// so that files shared by several members are resolved and compiled once per compiler version
+/// and profile, and are tracked by a single cache. The remappings of a member only apply to the
+/// files inside of its root, and every member can be imported by the others as `<name>/...`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use foundry_compilers::{Project, ProjectPathsConfig, Workspace, WorkspaceMember};
+/// use std::path::Path;
+///
+/// let root = Path::new("monorepo");
+/// let member = |name: &str| -> Result<_, Box<dyn std::error::Error>> {
+///     Ok(WorkspaceMember::new(name, ProjectPathsConfig::dapptools(&root.join(name))?))
+/// };
+/// let workspace = Workspace::builder(ProjectPathsConfig::builder().build_with_root(root))
+///     .member(member("core")?)
+///     .member(member("periphery")?)
+///     .build(Project::builder(), Default::default())?;
+/// let output = workspace.compile()?;
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Workspace<
+    C: Compiler = MultiCompiler,
+    T: ArtifactOutput<CompilerContract = C::CompilerContract> = ConfigurableArtifacts,
+> {
+    members: Vec<WorkspaceMember<C::Language>>,
+    shared_artifacts: bool,
+    project: Project<C, T>,
+}
+
+impl Workspace {
+    /// Convenience function to call [`WorkspaceBuilder::new`].
+    ///
+    /// The given `paths` are the layout of the workspace itself, which determine the location of
+    /// the shared cache, build infos and artifacts.
+    pub fn builder(paths: ProjectPathsConfig) -> WorkspaceBuilder {
+        WorkspaceBuilder::new(paths)
+    }
+}
+
+impl<C: Compiler, T: ArtifactOutput<CompilerContract = C::CompilerContract>> Workspace<C, T> {
+    /// Returns the members of the workspace.
+    pub fn members(&self) -> &[WorkspaceMember<C::Language>] {
+        &self.members
+    }
+
+    /// Returns the member with the given name.
+    pub fn member(&self, name: &str) -> Option<&WorkspaceMember<C::Language>> {
+        self.members.iter().find(|member| member.name == name)
+    }
+
+    /// Returns the member whose root contains the given file, preferring the innermost one.
+    pub fn member_of(&self, file: &Path) -> Option<&WorkspaceMember<C::Language>> {
+        self.members
+            .iter()
+            .filter(|member| file.starts_with(&member.paths.root))
+            .max_by_key(|member| member.paths.root.components().count())
+    }
+
+    /// Returns the project all members are compiled with.
+    pub fn project(&self) -> &Project<C, T> {
+        &self.project
+    }
+
+    /// Returns the project all members are compiled with.
+    pub fn project_mut(&mut self) -> &mut Project<C, T> {
+        &mut self.project
+    }
+
+    /// Resolves the combined dependency graph of all members.
+    pub fn graph(&self) -> Result<Graph<C::Parser>> {
+        Graph::resolve_with(&self.project.paths, self.project.parser())
+    }
+
+    /// Compiles all members.
+    pub fn compile(&self) -> Result<ProjectCompileOutput<C, T>> {
+        self.project.compile()
+    }
+
+    /// Compiles the sources, tests and scripts of the member with the given name, and the files
+    /// they import.
+    pub fn compile_member(&self, name: &str) -> Result<ProjectCompileOutput<C, T>> {
+        if self.member(name).is_none() {
+            return Err(SolcError::msg(format!("no workspace member {name}")));
+        }
+        let mut sources = Sources::new();
+        for group in self.project.paths.groups.iter().filter(|group| member_group(name, group)) {
+            sources.extend(self.project.paths.read_group(&group.name)?);
+        }
+        ProjectCompiler::with_sources(&self.project, sources)?.compile()
+    }
+
+    /// Removes the cache and artifacts of the workspace, and the artifacts directories of the
+    /// members.
+    pub fn cleanup(&self) -> std::result::Result<(), SolcIoError> {
+        self.project.cleanup()?;
+        if self.shared_artifacts {
+            return Ok(());
+        }
+        let fs = &*self.project.paths.fs;
+        for member in &self.members {
+            let artifacts = &member.paths.artifacts;
+            if fs.exists(artifacts) && member.paths.root != *artifacts {
+                fs.remove_dir_all(artifacts).map_err(|err| SolcIoError::new(err, artifacts))?;
+                trace!("removed artifacts dir \"{}\"", artifacts.display());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builder for a [`Workspace`].
+#[derive(Clone, Debug)]
+pub struct WorkspaceBuilder<L = MultiCompilerLanguage> {
+    paths: ProjectPathsConfig<L>,
+    members: Vec<WorkspaceMember<L>>,
+    shared_artifacts: bool,
+}
+
+impl<L> WorkspaceBuilder<L> {
+    /// Creates a new builder for a workspace with the given layout.
+    pub fn new(paths: ProjectPathsConfig<L>) -> Self {
+        Self { paths, members: Vec::new(), shared_artifacts: false }
+    }
+
+    /// Adds a member to the workspace.
+    #[must_use]
+    pub fn member(mut self, member: WorkspaceMember<L>) -> Self {
+        self.members.push(member);
+        self
+    }
+
+    /// Adds members to the workspace.
+    #[must_use]
+    pub fn members(mut self, members: impl IntoIterator<Item = WorkspaceMember<L>>) -> Self {
+        self.members.extend(members);
+        self
+    }
+
+    /// Writes the artifacts of all members into the artifacts directory of the workspace, instead
+    /// of the artifacts directories of the members.
+    #[must_use]
+    pub fn shared_artifacts(mut self) -> Self {
+        self.shared_artifacts = true;
+        self
+    }
+
+    /// Returns the combined layout of the workspace and its members.
+    ///
+    /// This contains an [`InputGroup`] for the sources, tests and scripts of each member, named
+    /// `<name>`, `<name>:test` and `<name>:script`, and for each of the member's own groups,
+    /// named `<name>:<group>`. The remappings of the members are restricted to their roots and
+    /// come first, followed by the remappings of the workspace and a `<name>/` remapping to the
+    /// sources of every member.
+    pub fn combined_paths(&self) -> Result<ProjectPathsConfig<L>>
+    where
+        L: Clone,
+    {
+        let mut names = HashSet::new();
+        if let Some(member) = self.members.iter().find(|member| !names.insert(&member.name)) {
+            return Err(SolcError::msg(format!("duplicate workspace member {}", member.name)));
+        }
+
+        let mut paths = self.paths.clone();
+        let mut member_remappings = Vec::new();
+        let mut cross_remappings = Vec::new();
+        for member in &self.members {
+            let member_paths = &member.paths;
+
+            let suffixed = |suffix: &str| format!("{}:{suffix}", member.name);
+            let groups = [
+                InputGroup::new(&member.name, InputKind::Source).path(&member_paths.sources),
+                InputGroup::new(suffixed("test"), InputKind::Test).path(&member_paths.tests),
+                InputGroup::new(suffixed("script"), InputKind::Script).path(&member_paths.scripts),
+            ]
+            .into_iter()
+            .chain(
+                member_paths
+                    .groups
+                    .iter()
+                    .map(|group| InputGroup { name: suffixed(&group.name), ..group.clone() }),
+            );
+            for mut group in groups {
+                if group.profile.is_none() {
+                    group.profile.clone_from(&member.profile);
+                }
+                if !self.shared_artifacts {
+                    group.artifacts_subdir = Some(match &group.artifacts_subdir {
+                        Some(subdir) => member_paths.artifacts.join(subdir),
+                        None => member_paths.artifacts.clone(),
+                    });
+                }
+                paths.groups.push(group);
+            }
+
+            // restrict the remappings of the member to the files inside of its root
+            let context = match member_paths.root.strip_prefix(&paths.root) {
+                Ok(rel) if rel.as_os_str().is_empty() => None,
+                Ok(rel) => Some(rel),
+                Err(_) => Some(member_paths.root.as_path()),
+            };
+            member_remappings.extend(member_paths.remappings.iter().map(|remapping| Remapping {
+                context: match (context, &remapping.context) {
+                    (None, ctx) => ctx.clone(),
+                    (Some(root), None) => Some(format!("{}/", root.display())),
+                    (Some(root), Some(ctx)) => Some(root.join(ctx).display().to_string()),
+                },
+                name: remapping.name.clone(),
+                path: member_paths.root.join(&remapping.path).display().to_string(),
+            }));
+
+            cross_remappings.push(Remapping {
+                context: None,
+                name: format!("{}/", member.name),
+                path: format!("{}/", member_paths.sources.display()),
+            });
+
+            for lib in &member_paths.libraries {
+                if !paths.libraries.contains(lib) {
+                    paths.libraries.push(lib.clone());
+                }
+            }
+            paths.include_paths.extend(member_paths.include_paths.iter().cloned());
+            paths.allowed_paths.insert(member_paths.root.clone());
+            paths.allowed_paths.extend(member_paths.allowed_paths.iter().cloned());
+        }
+
+        member_remappings.append(&mut paths.remappings);
+        member_remappings.append(&mut cross_remappings);
+        paths.remappings = member_remappings;
+
+        Ok(paths)
+    }
+
+    /// Builds the workspace, using the given builder for the configuration of the project all
+    /// members are compiled with.
+    pub fn build<C, T>(self, builder: ProjectBuilder<C, T>, compiler: C) -> Result<Workspace<C, T>>
+    where
+        L: Clone,
+        C: Compiler<Language = L>,
+        T: ArtifactOutput<CompilerContract = C::CompilerContract>,
+    {
+        let paths = self.combined_paths()?;
+        let project = builder.paths(paths).build(compiler)?;
+        Ok(Workspace { members: self.members, shared_artifacts: self.shared_artifacts, project })
+    }
+}
+
+/// Returns true if the group is one of the groups of the member with the given name.
+fn member_group(name: &str, group: &InputGroup) -> bool {
+    group.name.strip_prefix(name).is_some_and(|rest| rest.is_empty() || rest.starts_with(':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolver::parse::SolParser;
+    use foundry_compilers_core::utils;
+    use std::fs;
+
+    #[test]
+    fn can_combine_member_paths() {
+        let tmp = utils::tempdir("workspace").unwrap();
+        let root = utils::canonicalize(tmp.path()).unwrap();
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write("shared/src/Shared.sol", "import 'dep/Dep.sol'; contract Shared {}");
+        write("shared/lib/dep/src/Dep.sol", "contract Dep {}");
+        write(
+            "core/src/Core.sol",
+            "import 'shared/Shared.sol'; import 'dep/Dep.sol'; contract Core {}",
+        );
+        write("core/lib/dep/src/Dep.sol", "contract Dep {}");
+        write("core/test/Core.t.sol", "import '../src/Core.sol'; contract CoreTest {}");
+
+        let member = |name: &str| {
+            let paths = ProjectPathsConfig::dapptools(&root.join(name)).unwrap();
+            WorkspaceMember::new(name, paths)
+        };
+        let builder = WorkspaceBuilder::new(ProjectPathsConfig::builder().build_with_root(&root))
+            .member(member("shared"))
+            .member(member("core").profile("core"));
+        let paths = builder.combined_paths().unwrap();
+
+        assert_eq!(
+            paths.remappings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                format!("shared/:dep/={}/shared/lib/dep/src/", root.display()),
+                format!("core/:dep/={}/core/lib/dep/src/", root.display()),
+                format!("shared/={}/shared/src/", root.display()),
+                format!("core/={}/core/src/", root.display()),
+            ]
+        );
+
+        let group = paths.input_group(&root.join("core/test/Core.t.sol")).unwrap();
+        assert_eq!(group.name, "core:test");
+        assert_eq!(group.profile.as_deref(), Some("core"));
+        assert_eq!(group.artifacts_subdir.as_deref(), Some(root.join("core/out").as_path()));
+        assert!(paths.is_test(&root.join("core/test/Core.t.sol")));
+        assert!(paths.is_source_file(&root.join("shared/src/Shared.sol")));
+
+        let graph = Graph::<SolParser>::resolve(&paths).unwrap();
+        let mut files = graph.files().keys().cloned().collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(
+            files,
+            [
+                "core/lib/dep/src/Dep.sol",
+                "core/src/Core.sol",
+                "core/test/Core.t.sol",
+                "shared/lib/dep/src/Dep.sol",
+                "shared/src/Shared.sol",
+            ]
+            .map(|file| root.join(file))
+        );
+
+        let builder = builder.member(member("core"));
+        assert!(builder.combined_paths().is_err());
+    }
+
+    #[test]
+    fn can_match_member_groups() {
+        let group = |name: &str| InputGroup::new(name, InputKind::Source);
+        assert!(member_group("core", &group("core")));
+        assert!(member_group("core", &group("core:test")));
+        assert!(!member_group("core", &group("core-utils")));
+        assert!(!member_group("core", &group("periphery")));
+    }
+}
@@ -2,9 +2,9 @@
 
 use alloy_primitives::{Address, Bytes};
 use foundry_compilers::{
-    Artifact, ConfigurableArtifacts, ExtraOutputFiles, ExtraOutputValues, Graph, Project,
-    ProjectBuilder, ProjectCompileOutput, ProjectPathsConfig, RestrictionsWithVersion,
-    TestFileFilter,
+    Artifact, ConfigurableArtifacts, ExtraOutputFiles, ExtraOutputValues, Graph, InputGroup,
+    InputKind, Project, ProjectBuilder, ProjectCompileOutput, ProjectPathsConfig,
+    RestrictionsWithVersion, SourceUnitNames, TestFileFilter, Workspace, WorkspaceMember,
     buildinfo::BuildInfo,
     cache::{CompilerCache, SOLIDITY_FILES_CACHE_FILENAME},
     compilers::{
@@ -13,7 +13,7 @@ use foundry_compilers::{
         solc::{Solc, SolcCompiler, SolcLanguage},
         vyper::{Vyper, VyperLanguage, VyperSettings},
     },
-    flatten::Flattener,
+    flatten::{BytecodeKind, Flattener},
     info::ContractInfo,
     multi::{MultiCompilerInput, MultiCompilerParser, MultiCompilerRestrictions},
     project::{Preprocessor, ProjectCompiler},
@@ -433,6 +433,198 @@ contract B { }
     assert_eq!(build_info_count, 1);
 }
 
+#[test]
+fn can_compile_with_solc_source_unit_names() {
+    let mut project = TempProject::<MultiCompiler>::dapptools().unwrap();
+    let deps = tempfile::tempdir().unwrap();
+    let deps = canonicalize(deps.path()).unwrap();
+    let dep = deps.join("dep/Dep.sol");
+    fs::create_dir_all(dep.parent().unwrap()).unwrap();
+    fs::write(&dep, "pragma solidity ^0.8.10;\ncontract Dep {}\n").unwrap();
+
+    project.project_mut().build_info = true;
+    let paths = &mut project.project_mut().paths;
+    paths.source_unit_names = SourceUnitNames::Solc;
+    paths.include_paths.insert(deps.clone());
+    paths.allowed_paths.insert(deps.clone());
+    paths.remappings.push(Remapping {
+        context: None,
+        name: "dep/".to_string(),
+        path: format!("{}/", deps.join("dep").display()),
+    });
+    project
+        .add_source(
+            "A",
+            r#"
+pragma solidity ^0.8.10;
+import "dep/Dep.sol";
+contract A is Dep { }
+"#,
+        )
+        .unwrap();
+
+    let compiled = project.compile().unwrap();
+    compiled.assert_success();
+    assert!(compiled.find(&project.paths().sources.join("A.sol"), "A").is_some());
+    assert!(compiled.find(&dep, "Dep").is_some());
+
+    let info_dir = project.project().build_info_path();
+    let entry = fs::read_dir(info_dir).unwrap().next().unwrap().unwrap();
+    let info =
+        BuildInfo::<SolcInput, CompilerOutput<Error, Contract>>::read(&entry.path()).unwrap();
+    assert_eq!(
+        info.input.sources.keys().collect::<Vec<_>>(),
+        vec![Path::new("dep/Dep.sol"), Path::new("src/A.sol")]
+    );
+    assert!(info.input.settings.remappings.iter().any(|r| r.to_string() == "dep/=dep/"));
+}
+
+#[test]
+fn can_compile_input_groups() {
+    let mut project = TempProject::<MultiCompiler>::dapptools().unwrap();
+    let root = project.root().to_path_buf();
+
+    let mut harness_settings = project.project().settings.clone();
+    harness_settings.solc.optimizer.enabled = Some(true);
+    project.project_mut().additional_settings.insert("harness".to_string(), harness_settings);
+    project.project_mut().paths.groups = vec![
+        InputGroup::new("periphery", InputKind::Source).path(root.join("contracts/periphery")),
+        InputGroup::new("certora", InputKind::Source)
+            .path(root.join("certora/harness"))
+            .profile("harness")
+            .artifacts_subdir("certora"),
+    ];
+
+    let write = |file: &str, content: &str| {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    };
+    project
+        .add_source(
+            "Core",
+            r#"
+pragma solidity ^0.8.10;
+contract Core {}
+"#,
+        )
+        .unwrap();
+    let router = write(
+        "contracts/periphery/Router.sol",
+        r#"
+pragma solidity ^0.8.10;
+import "../../src/Core.sol";
+contract Router {}
+"#,
+    );
+    let harness = write(
+        "certora/harness/Harness.sol",
+        r#"
+pragma solidity ^0.8.10;
+contract Harness {}
+"#,
+    );
+
+    let compiled = project.compile().unwrap();
+    compiled.assert_success();
+    assert!(compiled.find(&router, "Router").is_some());
+
+    let (id, _) = compiled.artifact_ids().find(|(id, _)| id.source == harness).unwrap();
+    assert_eq!(id.profile, "harness");
+    assert!(id.path.starts_with(project.artifacts_path().join("certora")));
+
+    let compiled = project.compile().unwrap();
+    assert!(compiled.is_unchanged());
+
+    // adding a group invalidates the cache
+    project
+        .project_mut()
+        .paths
+        .groups
+        .push(InputGroup::new("invariants", InputKind::Test).path(root.join("invariants")));
+    let compiled = project.compile().unwrap();
+    compiled.assert_success();
+    assert!(!compiled.is_unchanged());
+}
+
+#[test]
+fn can_compile_workspace() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = canonicalize(tmp.path()).unwrap();
+    let write = |file: &str, content: &str| {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    };
+    let shared = write(
+        "shared/src/Shared.sol",
+        r#"
+pragma solidity ^0.8.10;
+contract Shared {}
+"#,
+    );
+    let core = write(
+        "core/src/Core.sol",
+        r#"
+pragma solidity ^0.8.10;
+import "shared/Shared.sol";
+contract Core is Shared {}
+"#,
+    );
+    let periphery = write(
+        "periphery/src/Router.sol",
+        r#"
+pragma solidity ^0.8.10;
+import "shared/Shared.sol";
+import "core/Core.sol";
+contract Router {}
+"#,
+    );
+
+    let member = |name: &str| {
+        WorkspaceMember::new(name, ProjectPathsConfig::dapptools(&root.join(name)).unwrap())
+    };
+    let workspace = Workspace::builder(ProjectPathsConfig::builder().build_with_root(&root))
+        .members(["shared", "core", "periphery"].map(member))
+        .build(Project::builder(), Default::default())
+        .unwrap();
+    assert_eq!(workspace.member_of(&periphery).unwrap().name, "periphery");
+
+    let compiled = workspace.compile().unwrap();
+    compiled.assert_success();
+    assert!(compiled.find(&core, "Core").is_some());
+    assert!(compiled.find(&periphery, "Router").is_some());
+
+    // the shared file is compiled once, and its artifact is written to its member's `out/`
+    let shared_ids =
+        compiled.artifact_ids().filter(|(id, _)| id.source == shared).collect::<Vec<_>>();
+    assert_eq!(shared_ids.len(), 1);
+    assert!(shared_ids[0].0.path.starts_with(root.join("shared/out")));
+    assert!(root.join("core/out/Core.sol/Core.json").exists());
+    assert!(root.join("periphery/out/Router.sol/Router.json").exists());
+    assert!(workspace.project().cache_path().starts_with(&root));
+    assert!(!root.join("core/cache").exists());
+
+    assert!(workspace.compile().unwrap().is_unchanged());
+    let compiled = workspace.compile_member("periphery").unwrap();
+    assert!(compiled.is_unchanged());
+    assert!(compiled.find(&periphery, "Router").is_some());
+
+    workspace.cleanup().unwrap();
+    assert!(!root.join("core/out").exists());
+
+    let workspace = Workspace::builder(ProjectPathsConfig::builder().build_with_root(&root))
+        .members(["shared", "core", "periphery"].map(member))
+        .shared_artifacts()
+        .build(Project::builder(), Default::default())
+        .unwrap();
+    workspace.compile().unwrap().assert_success();
+    assert!(root.join("out/Shared.sol/Shared.json").exists());
+    assert!(!root.join("shared/out").exists());
+}
+
 #[test]
 fn can_clean_build_info() {
     let mut project = TempProject::<MultiCompiler>::dapptools().unwrap();
@@ -573,13 +765,25 @@ fn test_flatteners(project: &TempProject, target: &Path, additional_checks: fn(&
     let target = canonicalize(target).unwrap();
     let result =
         project.project().paths.clone().with_language::<SolcLanguage>().flatten(&target).unwrap();
-    let solc_result = Flattener::new(project.project().clone(), &target).unwrap().flatten();
+    let solc_result = flatten_with_solc_and_solar(project, &target);
 
     assert_eq!(result, solc_result);
 
     additional_checks(&result);
 }
 
+// Flattens the target with both the solc and solar based analyses and asserts that they agree.
+fn flatten_with_solc_and_solar(project: &TempProject, target: &Path) -> String {
+    let solc_result =
+        Flattener::new_with_solc(project.project().clone(), target).unwrap().flatten();
+    let solar_result =
+        Flattener::new_with_solar(&project.project().paths, target).unwrap().flatten();
+
+    assert_eq!(solc_result, solar_result);
+
+    solc_result
+}
+
 #[test]
 fn can_flatten_file_with_external_lib() {
     let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test-data/hardhat-sample");
@@ -1157,7 +1361,7 @@ contract Bar is Foo {}
         )
         .unwrap();
 
-    let result = Flattener::new(project.project().clone(), &target).unwrap().flatten();
+    let result = flatten_with_solc_and_solar(&project, &target);
     assert_eq!(
         result,
         r"pragma solidity ^0.8.10;
@@ -1179,6 +1383,141 @@ contract Bar_1 is Foo {}
     );
 }
 
+#[test]
+fn can_flatten_multiple_targets() {
+    let project = TempProject::<MultiCompiler>::dapptools().unwrap();
+
+    project.add_source("Shared.sol", "pragma solidity ^0.8.10;\ncontract Shared {}\n").unwrap();
+    let targets = ["A", "B"].map(|name| {
+        project
+            .add_source(
+                &format!("{name}.sol"),
+                format!(
+                    r#"
+pragma solidity ^0.8.10;
+import {{Shared}} from "./Shared.sol";
+
+struct Config {{ uint256 value; }}
+
+contract {name} is Shared {{
+    Config config;
+}}
+"#
+                ),
+            )
+            .unwrap()
+    });
+
+    let solc_result =
+        Flattener::new_multi_with_solc(project.project().clone(), &targets).unwrap().flatten();
+    let solar_result =
+        Flattener::new_multi_with_solar(&project.project().paths, &targets).unwrap().flatten();
+    assert_eq!(solc_result, solar_result);
+
+    assert_eq!(solc_result.matches("contract Shared {}").count(), 1);
+    assert!(solc_result.contains("struct Config_0 { uint256 value; }"));
+    assert!(solc_result.contains("struct Config_1 { uint256 value; }"));
+}
+
+#[test]
+fn can_verify_flattened_output() {
+    let project = TempProject::<MultiCompiler>::dapptools().unwrap();
+
+    project
+        .add_source(
+            "Math.sol",
+            r"
+pragma solidity ^0.8.10;
+
+library Math {
+    function add(uint256 a, uint256 b) internal pure returns (uint256) {
+        return a + b;
+    }
+}
+",
+        )
+        .unwrap();
+
+    let target = project
+        .add_source(
+            "Counter.sol",
+            r#"
+pragma solidity ^0.8.10;
+import {Math as M} from "./Math.sol";
+
+contract Counter {
+    uint256 public count;
+
+    function increment() public {
+        count = M.add(count, 1);
+    }
+}
+"#,
+        )
+        .unwrap();
+
+    let (result, verification) = Flattener::new(project.project().clone(), &target)
+        .unwrap()
+        .flatten_and_verify(project.project().clone())
+        .unwrap();
+
+    assert!(verification.is_ok(), "{verification}");
+    assert_eq!(result, Flattener::new(project.project().clone(), &target).unwrap().flatten());
+}
+
+#[test]
+fn can_report_renamed_libraries_when_verifying_flattened_output() {
+    let project = TempProject::<MultiCompiler>::dapptools().unwrap();
+
+    for name in ["A.sol", "B.sol"] {
+        project
+            .add_source(
+                name,
+                r"
+pragma solidity ^0.8.10;
+
+library Lib {
+    function value() external pure returns (uint256) {
+        return 1;
+    }
+}
+",
+            )
+            .unwrap();
+    }
+
+    let target = project
+        .add_source(
+            "C.sol",
+            r#"
+pragma solidity ^0.8.10;
+import "./A.sol" as A;
+import "./B.sol" as B;
+
+contract C {
+    function value() public pure returns (uint256) {
+        return A.Lib.value() + B.Lib.value();
+    }
+}
+"#,
+        )
+        .unwrap();
+
+    let (_, verification) = Flattener::new(project.project().clone(), &target)
+        .unwrap()
+        .flatten_and_verify(project.project().clone())
+        .unwrap();
+
+    assert_eq!(
+        verification
+            .mismatches
+            .iter()
+            .map(|mismatch| (mismatch.name.as_str(), mismatch.kind))
+            .collect::<Vec<_>>(),
+        vec![("C", Some(BytecodeKind::Creation)), ("C", Some(BytecodeKind::Runtime))]
+    );
+}
+
 #[test]
 fn can_flatten_complex_aliases_setup_with_duplicates() {
     let project = TempProject::<MultiCompiler>::dapptools().unwrap();
@@ -1262,7 +1601,7 @@ contract D is C_File.B_File.A_File.A {
 }
 "#,).unwrap();
 
-    let result = Flattener::new(project.project().clone(), &target).unwrap().flatten();
+    let result = flatten_with_solc_and_solar(&project, &target);
     assert_eq!(
         result,
         r"pragma solidity ^0.8.10;
@@ -1357,7 +1696,7 @@ contract B {
         )
         .unwrap();
 
-    let result = Flattener::new(project.project().clone(), &target).unwrap().flatten();
+    let result = flatten_with_solc_and_solar(&project, &target);
     assert_eq!(
         result,
         r#"pragma solidity ^0.8.10;
@@ -1416,7 +1755,7 @@ contract B is A {}
         )
         .unwrap();
 
-    let result = Flattener::new(project.project().clone(), &target).unwrap().flatten();
+    let result = flatten_with_solc_and_solar(&project, &target);
     assert_eq!(
         result,
         r"pragma solidity =0.6.12;
@@ -1534,7 +1873,7 @@ contract B is A {
         )
         .unwrap();
 
-    let result = Flattener::new(project.project().clone(), &target).unwrap().flatten();
+    let result = flatten_with_solc_and_solar(&project, &target);
     assert_eq!(
         result,
         r"pragma solidity ^0.8.10;
@@ -1590,7 +1929,7 @@ contract B is Alias {
         )
         .unwrap();
 
-    let result = Flattener::new(project.project().clone(), &target).unwrap().flatten();
+    let result = flatten_with_solc_and_solar(&project, &target);
     assert_eq!(
         result,
         r"pragma solidity ^0.8.10;
@@ -1667,7 +2006,7 @@ contract Foo {
         )
         .unwrap();
 
-    let result = Flattener::new(project.project().clone(), &target).unwrap().flatten();
+    let result = flatten_with_solc_and_solar(&project, &target);
     assert_eq!(
         result,
         r"pragma solidity ^0.8.10;
@@ -1748,7 +2087,7 @@ contract Foo {
         )
         .unwrap();
 
-    let result = Flattener::new(project.project().clone(), &target).unwrap().flatten();
+    let result = flatten_with_solc_and_solar(&project, &target);
     assert_eq!(
         result,
         r"pragma solidity ^0.8.10;
@@ -1815,7 +2154,7 @@ contract Foo {
         )
         .unwrap();
 
-    let result = Flattener::new(project.project().clone(), &target).unwrap().flatten();
+    let result = flatten_with_solc_and_solar(&project, &target);
     assert_eq!(
         result,
         r"pragma solidity ^0.8.10;
@@ -2188,7 +2527,7 @@ fn can_detect_invalid_version() {
             assert_eq!(
                 err,
                 format!(
-                    "Encountered invalid solc version in src{MAIN_SEPARATOR}A.sol: No solc version exists that matches the version requirement: ^0.100.10"
+                    "Encountered invalid compiler version in src{MAIN_SEPARATOR}A.sol: No compiler version exists that matches the version requirement: ^0.100.10"
                 )
             );
         }
@@ -3906,6 +4245,7 @@ fn test_deterministic_metadata() {
             Solc::find_svm_installed_version(&Version::new(0, 8, 18)).unwrap().unwrap(),
         )),
         vyper: None,
+        vyper_installations: Default::default(),
     };
     let paths = ProjectPathsConfig::builder().root(root).build().unwrap();
     let project = Project::builder().paths(paths).build(compiler).unwrap();
@@ -4034,8 +4374,11 @@ fn test_can_compile_multi() {
         solc: Default::default(),
     };
 
-    let compiler =
-        MultiCompiler { solc: Some(SolcCompiler::default()), vyper: Some(VYPER.clone()) };
+    let compiler = MultiCompiler {
+        solc: Some(SolcCompiler::default()),
+        vyper: Some(VYPER.clone()),
+        vyper_installations: Default::default(),
+    };
 
     let project = ProjectBuilder::<MultiCompiler>::new(Default::default())
         .settings(settings)
@@ -1,6 +1,6 @@
-use semver::Version;
+use semver::{Version, VersionReq};
 use std::{
-    io,
+    fmt, io,
     path::{Path, PathBuf},
 };
 use thiserror::Error;
@@ -65,6 +65,13 @@ pub enum SolcError {
     #[cfg(feature = "svm-solc")]
     #[error(transparent)]
     SvmError(#[from] svm::SvmError),
+    /// The version requirements of one or more sources and their imports can't be satisfied by a
+    /// single compiler version.
+    #[error("{}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n"))]
+    VersionConflicts(Vec<VersionConflict>),
+    /// An import which couldn't be resolved, with suggested fixes ranked by similarity.
+    #[error("{error}{}", .suggestions.iter().map(|s| format!("\n{s}")).collect::<String>())]
+    UnresolvedImport { error: Box<Self>, suggestions: Vec<ImportSuggestion> },
     #[error("no contracts found at \"{0}\"")]
     NoContracts(String),
     /// General purpose message.
@@ -107,6 +114,143 @@ impl SolcError {
     }
 }
 
+/// A set of version requirements in the import graph of a source file which can't be satisfied by
+/// any available compiler version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionConflict {
+    /// The input file whose import graph contains the conflict, relative to the project root.
+    pub file: PathBuf,
+    /// The minimal set of conflicting requirements. Removing any one of them resolves the
+    /// conflict.
+    pub constraints: Vec<VersionConstraint>,
+    /// The narrowest change to a single requirement which would resolve the conflict, if any.
+    pub suggestion: Option<VersionSuggestion>,
+}
+
+/// A single version requirement which is part of a [`VersionConflict`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionConstraint {
+    /// The file declaring the requirement, relative to the project root.
+    pub file: PathBuf,
+    /// The version requirement of the file.
+    pub requirement: VersionReq,
+    /// The shortest chain of imports from [`VersionConflict::file`] to [`Self::file`], both
+    /// inclusive.
+    pub import_path: Vec<PathBuf>,
+    /// Available compiler versions which all other constraints of the conflict accept, but this
+    /// one rules out.
+    pub excluded: Vec<Version>,
+}
+
+/// A suggested change of a version requirement which resolves a [`VersionConflict`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionSuggestion {
+    /// The file whose requirement should be changed, relative to the project root.
+    pub file: PathBuf,
+    /// The current requirement of the file.
+    pub current: VersionReq,
+    /// The narrowest requirement which includes the current one and resolves the conflict.
+    pub suggested: VersionReq,
+}
+
+/// A suggested fix for an import which couldn't be resolved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportSuggestion {
+    /// The change which makes the import resolve.
+    pub fix: ImportFix,
+    /// The file the import resolves to once the fix is applied.
+    pub file: PathBuf,
+}
+
+/// The change proposed by an [`ImportSuggestion`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportFix {
+    /// Add a remapping, formatted as `name/=path/` with the path relative to the project root.
+    Remapping(String),
+    /// Replace the import path.
+    Import(PathBuf),
+}
+
+impl fmt::Display for ImportSuggestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.fix {
+            ImportFix::Remapping(remapping) => write!(f, "help: add the remapping `{remapping}`"),
+            ImportFix::Import(import) => write!(f, "help: import \"{}\" instead", import.display()),
+        }
+    }
+}
+
+impl fmt::Display for VersionConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Found incompatible versions for {}:", self.file.display())?;
+        for constraint in &self.constraints {
+            write!(f, "\n    {constraint}")?;
+        }
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n{suggestion}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for VersionConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} rules out ", self.file.display(), Pragma(&self.requirement))?;
+        match self.excluded.as_slice() {
+            [] => write!(f, "nothing")?,
+            [first, .., last] if self.excluded.len() > 3 => {
+                write!(f, "{first} to {last} ({} versions)", self.excluded.len())?
+            }
+            versions => {
+                for (i, version) in versions.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{version}")?;
+                }
+            }
+        }
+        if self.import_path.len() > 1 {
+            write!(f, ", imported via ")?;
+            for (i, path) in self.import_path.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " -> ")?;
+                }
+                write!(f, "{}", path.display())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for VersionSuggestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "help: change the version requirement of {} from `{}` to `{}`",
+            self.file.display(),
+            Pragma(&self.current),
+            Pragma(&self.suggested)
+        )
+    }
+}
+
+/// Formats a version requirement the way it's written in a version pragma, with space-separated
+/// comparators.
+struct Pragma<'a>(&'a VersionReq);
+
+impl fmt::Display for Pragma<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, comparator) in self.0.comparators.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{comparator}")?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Error)]
 #[error("\"{}\": {io}", self.path.display())]
 pub struct SolcIoError {
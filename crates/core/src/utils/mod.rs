@@ -1,6 +1,9 @@
 //! Utility functions
 
-use crate::error::{SolcError, SolcIoError};
+use crate::{
+    error::{SolcError, SolcIoError},
+    vfs::{FileSystem, OsFileSystem},
+};
 use alloy_primitives::{hex, keccak256};
 use cfg_if::cfg_if;
 use semver::{Version, VersionReq};
@@ -160,6 +163,15 @@ pub fn canonicalize(path: impl AsRef<Path>) -> Result<PathBuf, SolcIoError> {
 pub fn normalize_solidity_import_path(
     directory: &Path,
     import_path: &Path,
+) -> Result<PathBuf, SolcIoError> {
+    normalize_solidity_import_path_in(&OsFileSystem, directory, import_path)
+}
+
+/// Same as [`normalize_solidity_import_path`], but checks the given [`FileSystem`].
+pub fn normalize_solidity_import_path_in(
+    fs: &dyn FileSystem,
+    directory: &Path,
+    import_path: &Path,
 ) -> Result<PathBuf, SolcIoError> {
     let original = directory.join(import_path);
     let cleaned = clean_solidity_path(&original);
@@ -175,40 +187,40 @@ pub fn normalize_solidity_import_path(
     let normalized = PathBuf::from(normalized);
 
     // checks if the path exists without reading its content and obtains an io error if it doesn't.
-    let _ = normalized.metadata().map_err(|err| SolcIoError::new(err, original))?;
+    let _ = fs.file_kind(&normalized).map_err(|err| SolcIoError::new(err, original))?;
     Ok(normalized)
 }
 
-// This function lexically cleans the given path.
-//
-// It performs the following transformations for the path:
-//
-// * Resolves references (current directories (`.`) and parent (`..`) directories).
-// * Reduces repeated separators to a single separator (e.g., from `//` to `/`).
-//
-// This transformation is lexical, not involving the file system, which means it does not account
-// for symlinks. This approach has a caveat. For example, consider a filesystem-accessible path
-// `a/b/../c.sol` passed to this function. It returns `a/c.sol`. However, if `b` is a symlink,
-// `a/c.sol` might not be accessible in the filesystem in some environments. Despite this, it's
-// unlikely that this will pose a problem for our intended use.
-//
-// # How it works
-//
-// The function splits the given path into components, where each component roughly corresponds to a
-// string between separators. It then iterates over these components (starting from the leftmost
-// part of the path) to reconstruct the path. The following steps are applied to each component:
-//
-// * If the component is a current directory, it's removed.
-// * If the component is a parent directory, the following rules are applied:
-//     * If the preceding component is a normal, then both the preceding normal component and the
-//       parent directory component are removed. (Examples of normal components include `a` and `b`
-//       in `a/b`.)
-//     * Otherwise (if there is no preceding component, or if the preceding component is a parent,
-//       root, or prefix), it remains untouched.
-// * Otherwise, the component remains untouched.
-//
-// Finally, the processed components are reassembled into a path.
-fn clean_solidity_path(original_path: &Path) -> PathBuf {
+/// This function lexically cleans the given path.
+///
+/// It performs the following transformations for the path:
+///
+/// * Resolves references (current directories (`.`) and parent (`..`) directories).
+/// * Reduces repeated separators to a single separator (e.g., from `//` to `/`).
+///
+/// This transformation is lexical, not involving the file system, which means it does not account
+/// for symlinks. This approach has a caveat. For example, consider a filesystem-accessible path
+/// `a/b/../c.sol` passed to this function. It returns `a/c.sol`. However, if `b` is a symlink,
+/// `a/c.sol` might not be accessible in the filesystem in some environments. Despite this, it's
+/// unlikely that this will pose a problem for our intended use.
+///
+/// # How it works
+///
+/// The function splits the given path into components, where each component roughly corresponds to
+/// a string between separators. It then iterates over these components (starting from the leftmost
+/// part of the path) to reconstruct the path. The following steps are applied to each component:
+///
+/// * If the component is a current directory, it's removed.
+/// * If the component is a parent directory, the following rules are applied:
+///     * If the preceding component is a normal, then both the preceding normal component and the
+///       parent directory component are removed. (Examples of normal components include `a` and `b`
+///       in `a/b`.)
+///     * Otherwise (if there is no preceding component, or if the preceding component is a parent,
+///       root, or prefix), it remains untouched.
+/// * Otherwise, the component remains untouched.
+///
+/// Finally, the processed components are reassembled into a path.
+pub fn clean_solidity_path(original_path: &Path) -> PathBuf {
     let mut new_path = Vec::new();
 
     for component in original_path.components() {
@@ -248,6 +260,15 @@ pub fn canonicalized(path: impl Into<PathBuf>) -> PathBuf {
 /// and it exists.
 /// Note: this does not handle relative imports or remappings.
 pub fn resolve_library(libs: &[impl AsRef<Path>], source: impl AsRef<Path>) -> Option<PathBuf> {
+    resolve_library_in(&OsFileSystem, libs, source)
+}
+
+/// Same as [`resolve_library`], but checks the given [`FileSystem`].
+pub fn resolve_library_in(
+    fs: &dyn FileSystem,
+    libs: &[impl AsRef<Path>],
+    source: impl AsRef<Path>,
+) -> Option<PathBuf> {
     let source = source.as_ref();
     let comp = source.components().next()?;
     match comp {
@@ -257,7 +278,7 @@ pub fn resolve_library(libs: &[impl AsRef<Path>], source: impl AsRef<Path>) -> O
             for lib in libs {
                 let lib = lib.as_ref();
                 let contract = lib.join(source);
-                if contract.exists() {
+                if fs.exists(&contract) {
                     // contract exists in <lib>/<source>
                     return Some(contract);
                 }
@@ -266,7 +287,7 @@ pub fn resolve_library(libs: &[impl AsRef<Path>], source: impl AsRef<Path>) -> O
                     .join(first_dir)
                     .join("src")
                     .join(source.strip_prefix(first_dir).expect("is first component"));
-                if contract.exists() {
+                if fs.exists(&contract) {
                     return Some(contract);
                 }
             }
@@ -297,10 +318,20 @@ pub fn resolve_absolute_library(
     root: &Path,
     cwd: &Path,
     import: &Path,
+) -> Option<(PathBuf, PathBuf)> {
+    resolve_absolute_library_in(&OsFileSystem, root, cwd, import)
+}
+
+/// Same as [`resolve_absolute_library`], but checks the given [`FileSystem`].
+pub fn resolve_absolute_library_in(
+    fs: &dyn FileSystem,
+    root: &Path,
+    cwd: &Path,
+    import: &Path,
 ) -> Option<(PathBuf, PathBuf)> {
     let mut parent = cwd.parent()?;
     while parent != root {
-        if let Ok(import) = normalize_solidity_import_path(parent, import) {
+        if let Ok(import) = normalize_solidity_import_path_in(fs, parent, import) {
             return Some((parent.to_path_buf(), import));
         }
         parent = parent.parent()?;
@@ -400,10 +431,20 @@ pub fn common_ancestor(a: &Path, b: &Path) -> Option<PathBuf> {
 /// Returns `<root>/<fave>` if it exists or `<root>/<alt>` does not exist,
 /// Returns `<root>/<alt>` if it exists and `<root>/<fave>` does not exist.
 pub fn find_fave_or_alt_path(root: &Path, fave: &str, alt: &str) -> PathBuf {
+    find_fave_or_alt_path_in(&OsFileSystem, root, fave, alt)
+}
+
+/// Same as [`find_fave_or_alt_path`], but checks the given [`FileSystem`].
+pub fn find_fave_or_alt_path_in(
+    fs: &dyn FileSystem,
+    root: &Path,
+    fave: &str,
+    alt: &str,
+) -> PathBuf {
     let p = root.join(fave);
-    if !p.exists() {
+    if !fs.exists(&p) {
         let alt = root.join(alt);
-        if alt.exists() {
+        if fs.exists(&alt) {
             return alt;
         }
     }
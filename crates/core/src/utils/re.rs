@@ -32,7 +32,22 @@ pub static RE_TWO_OR_MORE_SPACES: Lazy<Regex> = Lazy::new(|| Regex::new(" {2,}")
 
 /// A regex that matches version pragma in a Vyper
 pub static RE_VYPER_VERSION: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"#(?:pragma version|@version)\s+(?P<version>.+)").unwrap());
+    Lazy::new(|| Regex::new(r"#\s*(?:pragma\s+version|@version)\s+(?P<version>.+)").unwrap());
+
+/// A regex that matches the path of a Huff `#include` directive with the named groups "p1", "p2".
+pub static RE_HUFF_INCLUDE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"#include\s+(?:"(?P<p1>[^"]*)"|'(?P<p2>[^']*)')"#).unwrap());
+
+/// A regex that matches the `MAIN` macro definition of a Huff contract.
+pub static RE_HUFF_MAIN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"#define\s+macro\s+MAIN\s*\(").unwrap());
+
+/// A regex that matches the ABI definitions of a Huff file, i.e. functions, events and errors,
+/// with the named group "item".
+pub static RE_HUFF_ABI_ITEM: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)#define\s+(?P<item>(?:function|event|error)\s+[^\n]*?)\s*(?://[^\n]*)?$")
+        .unwrap()
+});
 
 /// A regex that matches the contract names in a Solidity file.
 pub static RE_CONTRACT_NAMES: Lazy<Regex> = Lazy::new(|| {
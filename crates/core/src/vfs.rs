@@ -0,0 +1,400 @@
+//! File system abstraction, so that projects can be compiled without touching the disk.
+
+use crate::{error::SolcError, utils};
+use serde::{Serialize, de::DeserializeOwned};
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, PoisonError, RwLock},
+};
+
+/// The kind of a file system entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileKind {
+    File,
+    Dir,
+}
+
+/// An entry of a directory, see [`FileSystem::read_dir`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirEntry {
+    pub path: PathBuf,
+    /// The kind of the entry, or of its target if it's a symlink.
+    pub kind: FileKind,
+    pub is_symlink: bool,
+}
+
+/// The file system used to read sources and to write the cache and artifacts.
+///
+/// [`OsFileSystem`] is the disk, [`MemoryFileSystem`] keeps all files in memory.
+pub trait FileSystem: fmt::Debug + Send + Sync {
+    /// Reads the content of the file.
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Reads the content of the file as UTF-8.
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Writes the file, replacing its content if it exists. The parent directory must exist.
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Creates the directory and all its missing ancestors.
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Removes the file.
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Removes the directory and all its content.
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Returns the kind of the entry at the given path, following symlinks.
+    fn file_kind(&self, path: &Path) -> io::Result<FileKind>;
+
+    /// Returns the entries of the directory.
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<DirEntry>>;
+
+    /// Returns the canonical form of the path, resolving symlinks.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// Returns `true` if the files don't live on the disk, so that compilers which can only read
+    /// files from disk can't access them.
+    fn is_virtual(&self) -> bool {
+        true
+    }
+
+    /// Returns `true` if the path exists.
+    fn exists(&self, path: &Path) -> bool {
+        self.file_kind(path).is_ok()
+    }
+
+    /// Returns `true` if the path is a file.
+    fn is_file(&self, path: &Path) -> bool {
+        self.file_kind(path).is_ok_and(|kind| kind == FileKind::File)
+    }
+
+    /// Returns `true` if the path is a directory.
+    fn is_dir(&self, path: &Path) -> bool {
+        self.file_kind(path).is_ok_and(|kind| kind == FileKind::Dir)
+    }
+
+    /// Returns all files with one of the given extensions under the given dir, recursively.
+    fn source_files(&self, dir: &Path, extensions: &[&str]) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let mut dirs = vec![dir.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            for entry in self.read_dir(&dir).unwrap_or_default() {
+                match entry.kind {
+                    FileKind::Dir => dirs.push(entry.path),
+                    FileKind::File => {
+                        if entry
+                            .path
+                            .extension()
+                            .is_some_and(|ext| extensions.iter().any(|e| ext == *e))
+                        {
+                            files.push(entry.path);
+                        }
+                    }
+                }
+            }
+        }
+        files.sort();
+        files
+    }
+}
+
+/// Returns the [`OsFileSystem`], which is used by default.
+pub fn os() -> Arc<dyn FileSystem> {
+    Arc::new(OsFileSystem)
+}
+
+/// Reads the JSON file and deserializes it into the provided type.
+pub fn read_json_file<T: DeserializeOwned>(
+    fs: &dyn FileSystem,
+    path: &Path,
+) -> Result<T, SolcError> {
+    let s = fs.read_to_string(path).map_err(|err| SolcError::io(err, path))?;
+    serde_json::from_str(&s).map_err(Into::into)
+}
+
+/// Serializes the provided value to JSON and writes it to the file, creating all parent
+/// directories.
+pub fn write_json_file<T: Serialize>(
+    fs: &dyn FileSystem,
+    value: &T,
+    path: &Path,
+) -> Result<(), SolcError> {
+    if let Some(parent) = path.parent() {
+        fs.create_dir_all(parent).map_err(|err| SolcError::io(err, parent))?;
+    }
+    fs.write(path, &serde_json::to_vec(value)?).map_err(|err| SolcError::io(err, path))
+}
+
+/// The disk.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OsFileSystem;
+
+impl FileSystem for OsFileSystem {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir_all(path)
+    }
+
+    fn file_kind(&self, path: &Path) -> io::Result<FileKind> {
+        let metadata = fs::metadata(path)?;
+        Ok(if metadata.is_dir() { FileKind::Dir } else { FileKind::File })
+    }
+
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<DirEntry>> {
+        Ok(fs::read_dir(dir)?
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let path = entry.path();
+                let is_symlink = entry.file_type().ok()?.is_symlink();
+                let kind = self.file_kind(&path).ok()?;
+                Some(DirEntry { path, kind, is_symlink })
+            })
+            .collect())
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        utils::canonicalize(path).map_err(Into::into)
+    }
+
+    fn is_virtual(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "walkdir")]
+    fn source_files(&self, dir: &Path, extensions: &[&str]) -> Vec<PathBuf> {
+        utils::source_files_iter(dir, extensions).collect()
+    }
+}
+
+/// A file system which keeps all files in memory.
+///
+/// Paths are lexically cleaned, see [`utils::clean_solidity_path`], and all ancestors of a file
+/// are implicitly created as directories.
+///
+/// # Examples
+///
+/// ```
+/// use foundry_compilers_core::vfs::{FileSystem, MemoryFileSystem};
+/// use std::path::Path;
+///
+/// let fs = MemoryFileSystem::from_iter([("/project/src/A.sol", "contract A {}")]);
+/// assert!(fs.is_dir(Path::new("/project/src")));
+/// assert_eq!(fs.read_to_string(Path::new("/project/src/A.sol")).unwrap(), "contract A {}");
+/// ```
+#[derive(Debug, Default)]
+pub struct MemoryFileSystem {
+    entries: RwLock<BTreeMap<PathBuf, MemoryEntry>>,
+}
+
+#[derive(Debug)]
+enum MemoryEntry {
+    File(Arc<[u8]>),
+    Dir,
+}
+
+impl MemoryFileSystem {
+    /// Creates a new, empty file system.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes the file, creating all parent directories.
+    pub fn insert(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) {
+        let path = utils::clean_solidity_path(path.as_ref());
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        for ancestor in path.ancestors().skip(1) {
+            entries.insert(ancestor.to_path_buf(), MemoryEntry::Dir);
+        }
+        entries.insert(path, MemoryEntry::File(contents.as_ref().into()));
+    }
+
+    /// Returns the paths of all files.
+    pub fn files(&self) -> Vec<PathBuf> {
+        let entries = self.entries.read().unwrap_or_else(PoisonError::into_inner);
+        entries
+            .iter()
+            .filter(|(_, entry)| matches!(entry, MemoryEntry::File(_)))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    fn not_found(path: &Path) -> io::Error {
+        io::Error::new(io::ErrorKind::NotFound, format!("{} does not exist", path.display()))
+    }
+}
+
+impl<P: AsRef<Path>, C: AsRef<[u8]>> FromIterator<(P, C)> for MemoryFileSystem {
+    fn from_iter<I: IntoIterator<Item = (P, C)>>(iter: I) -> Self {
+        let fs = Self::new();
+        for (path, contents) in iter {
+            fs.insert(path, contents);
+        }
+        fs
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let entries = self.entries.read().unwrap_or_else(PoisonError::into_inner);
+        match entries.get(&utils::clean_solidity_path(path)) {
+            Some(MemoryEntry::File(contents)) => Ok(contents.to_vec()),
+            Some(MemoryEntry::Dir) => {
+                Err(io::Error::new(io::ErrorKind::IsADirectory, path.display().to_string()))
+            }
+            None => Err(Self::not_found(path)),
+        }
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let path = utils::clean_solidity_path(path);
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        if let Some(parent) = path.parent()
+            && !matches!(entries.get(parent), Some(MemoryEntry::Dir))
+        {
+            return Err(Self::not_found(parent));
+        }
+        if let Some(MemoryEntry::Dir) = entries.get(&path) {
+            return Err(io::Error::new(io::ErrorKind::IsADirectory, path.display().to_string()));
+        }
+        entries.insert(path, MemoryEntry::File(contents.into()));
+        Ok(())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let path = utils::clean_solidity_path(path);
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        for dir in path.ancestors() {
+            if let Some(MemoryEntry::File(_)) = entries.get(dir) {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} is a file", dir.display()),
+                ));
+            }
+        }
+        for dir in path.ancestors() {
+            entries.insert(dir.to_path_buf(), MemoryEntry::Dir);
+        }
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let path = utils::clean_solidity_path(path);
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        match entries.get(&path) {
+            Some(MemoryEntry::File(_)) => {
+                entries.remove(&path);
+                Ok(())
+            }
+            Some(MemoryEntry::Dir) => {
+                Err(io::Error::new(io::ErrorKind::IsADirectory, path.display().to_string()))
+            }
+            None => Err(Self::not_found(&path)),
+        }
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        let path = utils::clean_solidity_path(path);
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        if !matches!(entries.get(&path), Some(MemoryEntry::Dir)) {
+            return Err(Self::not_found(&path));
+        }
+        entries.retain(|entry, _| !entry.starts_with(&path));
+        Ok(())
+    }
+
+    fn file_kind(&self, path: &Path) -> io::Result<FileKind> {
+        let entries = self.entries.read().unwrap_or_else(PoisonError::into_inner);
+        match entries.get(&utils::clean_solidity_path(path)) {
+            Some(MemoryEntry::File(_)) => Ok(FileKind::File),
+            Some(MemoryEntry::Dir) => Ok(FileKind::Dir),
+            None => Err(Self::not_found(path)),
+        }
+    }
+
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<DirEntry>> {
+        let dir = utils::clean_solidity_path(dir);
+        let entries = self.entries.read().unwrap_or_else(PoisonError::into_inner);
+        if !matches!(entries.get(&dir), Some(MemoryEntry::Dir)) {
+            return Err(Self::not_found(&dir));
+        }
+        Ok(entries
+            .range(dir.clone()..)
+            .skip(1)
+            .take_while(|(path, _)| path.starts_with(&dir))
+            .filter(|(path, _)| path.parent() == Some(dir.as_path()))
+            .map(|(path, entry)| DirEntry {
+                path: path.clone(),
+                kind: match entry {
+                    MemoryEntry::File(_) => FileKind::File,
+                    MemoryEntry::Dir => FileKind::Dir,
+                },
+                is_symlink: false,
+            })
+            .collect())
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        let path = utils::clean_solidity_path(path);
+        if self.exists(&path) { Ok(path) } else { Err(Self::not_found(&path)) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_use_memory_file_system() {
+        let fs = MemoryFileSystem::from_iter([
+            ("/root/src/A.sol", "contract A {}"),
+            ("/root/src/nested/B.sol", "contract B {}"),
+            ("/root/src/C.vy", ""),
+        ]);
+        let root = Path::new("/root");
+
+        assert_eq!(fs.file_kind(&root.join("src/nested")).unwrap(), FileKind::Dir);
+        assert_eq!(fs.file_kind(&root.join("src/./A.sol")).unwrap(), FileKind::File);
+        assert_eq!(
+            fs.source_files(root, &["sol"]),
+            [root.join("src/A.sol"), root.join("src/nested/B.sol")]
+        );
+        let entries = fs.read_dir(&root.join("src")).unwrap();
+        assert_eq!(entries.len(), 3);
+
+        assert!(fs.write(&root.join("out/A.json"), b"{}").is_err());
+        write_json_file(&fs, &serde_json::json!({ "a": 1 }), &root.join("out/A.json")).unwrap();
+        let value: serde_json::Value = read_json_file(&fs, &root.join("out/A.json")).unwrap();
+        assert_eq!(value["a"], 1);
+
+        fs.remove_dir_all(&root.join("out")).unwrap();
+        assert!(!fs.exists(&root.join("out/A.json")));
+        fs.remove_file(&root.join("src/A.sol")).unwrap();
+        assert_eq!(fs.files().len(), 2);
+    }
+}